@@ -4,12 +4,8 @@ use std::fs;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let api_key =
-        std::env::var("CAPTURE_KEY").expect("CAPTURE_KEY environment variable is required");
-    let api_secret =
-        std::env::var("CAPTURE_SECRET").expect("CAPTURE_SECRET environment variable is required");
-
-    let capture = Capture::new(api_key, api_secret);
+    // Reads CAPTURE_KEY and CAPTURE_SECRET.
+    let capture = Capture::from_env()?;
 
     let mut options = HashMap::new();
     options.insert("full".to_string(), serde_json::Value::Bool(true));
@@ -51,9 +47,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .fetch_metadata("https://capture.page/", None)
         .await?;
     println!("Metadata success: {}", metadata.success);
+    println!("Title: {:?}", metadata.metadata.title);
     println!(
-        "Metadata keys: {:?}",
-        metadata.metadata.keys().collect::<Vec<_>>()
+        "Other metadata keys: {:?}",
+        metadata.metadata.extra.keys().collect::<Vec<_>>()
     );
 
     Ok(())
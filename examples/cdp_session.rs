@@ -2,12 +2,7 @@ use capture_rust::{Capture, CreateSessionOptions};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let api_key =
-        std::env::var("CAPTURE_KEY").expect("CAPTURE_KEY environment variable is required");
-    let api_secret =
-        std::env::var("CAPTURE_SECRET").expect("CAPTURE_SECRET environment variable is required");
-
-    let capture = Capture::new(api_key, api_secret);
+    let capture = Capture::from_env()?;
 
     let created = capture
         .create_session(Some(&CreateSessionOptions {
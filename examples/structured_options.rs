@@ -1,15 +1,13 @@
-use capture_rust::{Capture, ContentOptions, MetadataOptions, PdfOptions, ScreenshotOptions};
+use capture_rust::{
+    AnimatedFormat, AnimatedOptions, Capture, ContentOptions, MetadataOptions, PdfOptions,
+    ScreenshotOptions,
+};
 use std::collections::HashMap;
 use std::fs;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let api_key =
-        std::env::var("CAPTURE_KEY").expect("CAPTURE_KEY environment variable is required");
-    let api_secret =
-        std::env::var("CAPTURE_SECRET").expect("CAPTURE_SECRET environment variable is required");
-
-    let capture = Capture::new(api_key, api_secret);
+    let capture = Capture::from_env()?;
 
     // Example 1: Screenshot with structured options
     let screenshot_options = ScreenshotOptions {
@@ -18,7 +16,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         full: Some(true),
         delay: Some(3),
         dark_mode: Some(true),
-        image_type: Some("png".to_string()),
+        image_type: Some("jpeg".to_string()),
+        quality: Some(85),
         block_cookie_banners: Some(true),
         ..Default::default()
     };
@@ -28,16 +27,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         capture.build_screenshot_url("https://capture.page/", Some(&screenshot_options))?;
     println!("Screenshot URL: {screenshot_url}");
 
+    // Element screenshot with breathing room around the crop
+    let element_options = ScreenshotOptions {
+        selector: Some(".hero".to_string()),
+        selector_padding: Some(24),
+        ..Default::default()
+    };
+    let element_url =
+        capture.build_screenshot_url("https://capture.page/", Some(&element_options))?;
+    println!("Element screenshot URL: {element_url}");
+
     // Example 2: PDF with structured options
     let pdf_options = PdfOptions {
-        format: Some("A4".to_string()),
-        landscape: Some(true),
-        margin_top: Some("1cm".to_string()),
-        margin_bottom: Some("1cm".to_string()),
-        margin_left: Some("1cm".to_string()),
-        margin_right: Some("1cm".to_string()),
         delay: Some(2),
-        ..Default::default()
+        ..PdfOptions::new()
+            .format("A4")
+            .landscape(true)
+            .margins("1cm")
+            .print_background(true)
+            .prefer_css_page_size(true)
+            .tagged(true)
     };
 
     println!("Building PDF URL with structured options...");
@@ -48,6 +57,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let content_options = ContentOptions {
         delay: Some(1),
         wait_for: Some("#main-content".to_string()),
+        // Extract only the article body rather than the whole page.
+        selector: Some("main article".to_string()),
         ..Default::default()
     };
 
@@ -80,7 +91,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         capture.build_screenshot_url("https://capture.page/", Some(&screenshot_with_override))?;
     println!("Override URL: {override_url}");
 
-    // Example 5: Metadata with generic options only
+    // Example 5: Metadata with typed and generic options
     let mut metadata_additional = HashMap::new();
     metadata_additional.insert(
         "customParam".to_string(),
@@ -88,11 +99,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     let metadata_options = MetadataOptions {
+        user_agent: Some("Mozilla/5.0 (compatible; MetadataBot/1.0)".to_string()),
+        delay: Some(2),
+        wait_for: Some("head title".to_string()),
+        fresh: Some(true),
         additional_options: Some(metadata_additional),
         ..Default::default()
     };
 
-    println!("Building metadata URL with generic options...");
+    println!("Building metadata URL with typed and generic options...");
     let metadata_url =
         capture.build_metadata_url_structured("https://capture.page/", Some(&metadata_options))?;
     println!("Metadata URL: {metadata_url}");
@@ -102,8 +117,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let screenshot_data = capture
         .fetch_screenshot("https://capture.page/", Some(&screenshot_options))
         .await?;
-    fs::write("structured_screenshot.png", screenshot_data)?;
-    println!("Screenshot saved as structured_screenshot.png");
+    fs::write("structured_screenshot.jpg", screenshot_data)?;
+    println!("Screenshot saved as structured_screenshot.jpg");
+
+    // Light and dark variants of the same screenshot, fetched concurrently
+    let themes = capture
+        .fetch_screenshot_themes("https://capture.page/", &screenshot_options)
+        .await?;
+    fs::write("structured_screenshot_light.jpg", themes.light)?;
+    fs::write("structured_screenshot_dark.jpg", themes.dark)?;
+    println!("Theme screenshots saved");
 
     // Example 7: Fetch PDF with structured options
     println!("Fetching PDF with structured options...");
@@ -128,10 +151,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .fetch_metadata_structured("https://capture.page/", Some(&metadata_options))
         .await?;
     println!("Metadata success: {}", metadata.success);
+    println!("Title: {:?}", metadata.metadata.title);
     println!(
-        "Metadata keys: {:?}",
-        metadata.metadata.keys().collect::<Vec<_>>()
+        "Other metadata keys: {:?}",
+        metadata.metadata.extra.keys().collect::<Vec<_>>()
     );
 
+    // Example 10: Fetch a scrolling GIF with structured options
+    let animated_options = AnimatedOptions::new()
+        .viewport(1280, 720)
+        .duration(5)
+        .fps(10)
+        .format(AnimatedFormat::Gif)
+        .full(true)
+        .block_cookie_banners(true);
+
+    println!("Fetching animated capture with structured options...");
+    let animated_url =
+        capture.build_animated_url_structured("https://capture.page/", Some(&animated_options))?;
+    println!("Animated URL: {animated_url}");
+    let animated_data = capture
+        .fetch_animated_structured("https://capture.page/", Some(&animated_options))
+        .await?;
+    fs::write("structured_animated.gif", animated_data)?;
+    println!("Animated capture saved as structured_animated.gif");
+
     Ok(())
 }
@@ -0,0 +1,32 @@
+use capture_rust::{Capture, CaptureRequest, CaptureResponse, CaptureService};
+use std::time::Duration;
+use tower::{ServiceBuilder, ServiceExt};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let capture = Capture::from_env()?;
+
+    // At most four captures in flight, each bounded to 60 seconds.
+    let service = ServiceBuilder::new()
+        .concurrency_limit(4)
+        .timeout(Duration::from_secs(60))
+        .service(CaptureService::new(capture));
+
+    let response = service
+        .clone()
+        .oneshot(CaptureRequest::image("https://capture.page/"))
+        .await?;
+    if let CaptureResponse::Bytes(image) = response {
+        std::fs::write("tower_screenshot.png", image)?;
+        println!("Screenshot saved as tower_screenshot.png");
+    }
+
+    let response = service
+        .oneshot(CaptureRequest::metadata("https://capture.page/"))
+        .await?;
+    if let CaptureResponse::Metadata(metadata) = response {
+        println!("Metadata success: {}", metadata.success);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+#!/usr/bin/env bash
+# Checks that every cargo feature builds on its own, together with the
+# default features, and all at once. Run from the repository root.
+# "On its own" still includes a TLS backend for features that enable the
+# `http` client, which requires one: `native-tls` unless the feature is a
+# TLS backend itself. With no features at all the crate only signs URLs.
+# When the wasm32-unknown-unknown target is installed, also checks the
+# library for it with every feature that applies there.
+set -euo pipefail
+
+metadata=$(cargo metadata --no-deps --format-version 1)
+features=$(echo "$metadata" |
+    python3 -c 'import json, sys; print("\n".join(f for f in json.load(sys.stdin)["packages"][0]["features"] if f != "default"))')
+http_features=$(echo "$metadata" |
+    python3 -c 'import json, sys; print("\n".join(f for f, deps in json.load(sys.stdin)["packages"][0]["features"].items() if f == "http" or "http" in deps))')
+
+run() {
+    echo "+ cargo $*"
+    cargo "$@"
+}
+
+run check --all-targets --no-default-features
+run check --all-targets --no-default-features --features native-tls
+run check --all-targets
+run check --all-targets --all-features
+
+for feature in $features; do
+    if echo "$http_features" | grep -qx -- "$feature" && [[ $feature != *-tls ]]; then
+        alone="$feature,native-tls"
+    else
+        alone=$feature
+    fi
+    run check --all-targets --no-default-features --features "$alone"
+    run check --all-targets --features "$feature"
+done
+
+run test --no-default-features
+run test --test features --no-default-features --features native-tls
+run test --test features --no-default-features --features rustls-tls
+run test --test features --all-features
+run test --test runtime_agnostic --no-default-features --features runtime-agnostic,native-tls
+
+if rustup target list --installed 2>/dev/null | grep -qx wasm32-unknown-unknown; then
+    # `runtime-agnostic` and `blocking` need threads, which wasm32 lacks.
+    wasm_features=$(echo "$features" | grep -Ev '^(runtime-agnostic|blocking)$' | paste -sd, -)
+    run check --target wasm32-unknown-unknown --no-default-features
+    run check --target wasm32-unknown-unknown
+    run check --target wasm32-unknown-unknown --features "$wasm_features"
+    run check --target wasm32-unknown-unknown --test wasm
+fi
@@ -0,0 +1,245 @@
+//! Object-safe fetch API, for choosing a capture backend at runtime.
+
+use std::future::Future;
+use std::pin::Pin;
+#[cfg(feature = "http")]
+use std::sync::Arc;
+
+use crate::Result;
+#[cfg(feature = "http")]
+use crate::{Capture, ContentResponse, MetadataResponse, RequestOptions};
+
+/// Future returned by [`CaptureBackend`] methods.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Future returned by [`CaptureBackend`] methods. Not `Send` on wasm32,
+/// where the browser's fetch futures are tied to the main thread.
+#[cfg(target_arch = "wasm32")]
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + 'a>>;
+
+/// The fetch methods of [`Capture`] in a form that can be boxed, so the real
+/// API, fixtures and stubs can sit behind one `Box<dyn CaptureBackend>`.
+/// Arguments are owned and results are concrete types.
+#[cfg(feature = "http")]
+pub trait CaptureBackend: Send + Sync {
+    fn fetch_image(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>>;
+
+    fn fetch_pdf(&self, url: String, options: Option<RequestOptions>)
+        -> BackendFuture<'_, Vec<u8>>;
+
+    fn fetch_content(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, ContentResponse>;
+
+    fn fetch_metadata(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, MetadataResponse>;
+
+    fn fetch_animated(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>>;
+}
+
+#[cfg(feature = "http")]
+impl CaptureBackend for Capture {
+    fn fetch_image(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>> {
+        Box::pin(async move { Capture::fetch_image(self, &url, options.as_ref()).await })
+    }
+
+    fn fetch_pdf(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>> {
+        Box::pin(async move { Capture::fetch_pdf(self, &url, options.as_ref()).await })
+    }
+
+    fn fetch_content(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, ContentResponse> {
+        Box::pin(async move { Capture::fetch_content(self, &url, options.as_ref()).await })
+    }
+
+    fn fetch_metadata(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, MetadataResponse> {
+        Box::pin(async move { Capture::fetch_metadata(self, &url, options.as_ref()).await })
+    }
+
+    fn fetch_animated(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>> {
+        Box::pin(async move { Capture::fetch_animated(self, &url, options.as_ref()).await })
+    }
+}
+
+#[cfg(feature = "http")]
+impl<T: CaptureBackend + ?Sized> CaptureBackend for Arc<T> {
+    fn fetch_image(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>> {
+        (**self).fetch_image(url, options)
+    }
+
+    fn fetch_pdf(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>> {
+        (**self).fetch_pdf(url, options)
+    }
+
+    fn fetch_content(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, ContentResponse> {
+        (**self).fetch_content(url, options)
+    }
+
+    fn fetch_metadata(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, MetadataResponse> {
+        (**self).fetch_metadata(url, options)
+    }
+
+    fn fetch_animated(
+        &self,
+        url: String,
+        options: Option<RequestOptions>,
+    ) -> BackendFuture<'_, Vec<u8>> {
+        (**self).fetch_animated(url, options)
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::CaptureError;
+
+    /// Answers every call from canned data, as a demo or fixture backend
+    /// would.
+    struct Stub;
+
+    impl CaptureBackend for Stub {
+        fn fetch_image(
+            &self,
+            url: String,
+            _: Option<RequestOptions>,
+        ) -> BackendFuture<'_, Vec<u8>> {
+            Box::pin(async move { Ok(url.into_bytes()) })
+        }
+
+        fn fetch_pdf(&self, _: String, _: Option<RequestOptions>) -> BackendFuture<'_, Vec<u8>> {
+            Box::pin(async { Ok(b"%PDF-1.7".to_vec()) })
+        }
+
+        fn fetch_content(
+            &self,
+            url: String,
+            _: Option<RequestOptions>,
+        ) -> BackendFuture<'_, ContentResponse> {
+            Box::pin(async move {
+                let body = serde_json::json!({ "success": true, "textContent": url });
+                ContentResponse::from_slice(body.to_string().as_bytes(), &url)
+            })
+        }
+
+        fn fetch_metadata(
+            &self,
+            _: String,
+            _: Option<RequestOptions>,
+        ) -> BackendFuture<'_, MetadataResponse> {
+            Box::pin(async { Err(CaptureError::MissingUrl) })
+        }
+
+        fn fetch_animated(
+            &self,
+            _: String,
+            options: Option<RequestOptions>,
+        ) -> BackendFuture<'_, Vec<u8>> {
+            Box::pin(async move { Ok(vec![options.map_or(0, |options| options.len() as u8)]) })
+        }
+    }
+
+    async fn exercise(backend: &dyn CaptureBackend) -> Vec<String> {
+        let url = || "https://example.com".to_string();
+        let options = Some(RequestOptions::from([("full".to_string(), true.into())]));
+        vec![
+            format!(
+                "{:?}",
+                backend
+                    .fetch_image(url(), None)
+                    .await
+                    .map(|bytes| bytes.len())
+            ),
+            format!(
+                "{:?}",
+                backend
+                    .fetch_pdf(url(), None)
+                    .await
+                    .map(|bytes| bytes.len())
+            ),
+            format!(
+                "{:?}",
+                backend
+                    .fetch_content(url(), None)
+                    .await
+                    .map(|content| content.text_content)
+            ),
+            format!(
+                "{:?}",
+                backend
+                    .fetch_metadata(url(), None)
+                    .await
+                    .map(|metadata| metadata.success)
+            ),
+            format!("{:?}", backend.fetch_animated(url(), options).await),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_backends_are_interchangeable_behind_dyn() {
+        // Without credentials every call fails before any network access.
+        let capture = Capture::new(String::new(), String::new());
+        let backends: Vec<Box<dyn CaptureBackend>> = vec![Box::new(Stub), Box::new(capture)];
+
+        assert_eq!(
+            exercise(backends[0].as_ref()).await,
+            [
+                "Ok(19)",
+                "Ok(8)",
+                r#"Ok("https://example.com")"#,
+                "Err(MissingUrl)",
+                "Ok([1])",
+            ]
+        );
+        let shared: Arc<dyn CaptureBackend> = Arc::from(backends.into_iter().nth(1).unwrap());
+        assert_eq!(exercise(&shared).await, vec!["Err(MissingCredentials)"; 5]);
+    }
+}
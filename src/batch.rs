@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use futures_util::stream::{FuturesUnordered, StreamExt};
+
+use crate::signing::RequestType;
+use crate::{BackendFuture, Capture, CaptureRequest, CaptureResponse, Provenance, Result};
+
+/// Price per successful request for each request type, in whatever unit
+/// the caller budgets in (credits, cents, ...).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CostModel {
+    pub image: f64,
+    pub pdf: f64,
+    pub animated: f64,
+    pub content: f64,
+    pub metadata: f64,
+}
+
+impl CostModel {
+    pub fn price(&self, request_type: RequestType) -> f64 {
+        match request_type {
+            RequestType::Image => self.image,
+            RequestType::Pdf => self.pdf,
+            RequestType::Animated => self.animated,
+            RequestType::Content => self.content,
+            RequestType::Metadata => self.metadata,
+        }
+    }
+}
+
+/// Expected cost of running a batch if every request succeeds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostEstimate {
+    pub total: f64,
+    pub counts: HashMap<RequestType, usize>,
+}
+
+/// Pre-flight planning for batches.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchPlan;
+
+impl BatchPlan {
+    pub fn estimate(requests: &[CaptureRequest], cost_model: &CostModel) -> CostEstimate {
+        let mut estimate = CostEstimate::default();
+        for request in requests {
+            estimate.total += cost_model.price(request.request_type);
+            *estimate.counts.entry(request.request_type).or_default() += 1;
+        }
+        estimate
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub concurrency: usize,
+    pub cost_model: CostModel,
+    /// Stop issuing requests once successful requests have cost this much.
+    /// Requests already in flight still complete.
+    pub max_cost: Option<f64>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            cost_model: CostModel::default(),
+            max_cost: None,
+        }
+    }
+}
+
+impl BatchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_max_cost(mut self, cost_model: CostModel, max_cost: f64) -> Self {
+        self.cost_model = cost_model;
+        self.max_cost = Some(max_cost);
+        self
+    }
+}
+
+/// Outcome of a batch. Results are keyed by index into the request slice.
+/// Batches run into a [`CaptureSink`] report `()` in place of each response.
+#[derive(Debug)]
+pub struct BatchReport<T = CaptureResponse> {
+    pub results: Vec<(usize, Result<T>)>,
+    /// Requests not issued because the budget was reached.
+    pub skipped: Vec<usize>,
+    /// Cost of the successful requests under the batch's cost model.
+    pub spent: f64,
+    /// Records of every response received, keyed by request index, when
+    /// [`CaptureOptions::provenance`](crate::CaptureOptions::provenance) is
+    /// set.
+    pub provenance: Vec<(usize, Provenance)>,
+}
+
+impl<T> Default for BatchReport<T> {
+    fn default() -> Self {
+        Self {
+            results: Vec::new(),
+            skipped: Vec::new(),
+            spent: 0.0,
+            provenance: Vec::new(),
+        }
+    }
+}
+
+/// Receives each successful response of
+/// [`Capture::execute_batch_to_sink`] as soon as it completes, e.g. to write
+/// it to disk or object storage.
+pub trait CaptureSink: Send + Sync {
+    /// Takes the response to `requests[index]`. An error is recorded as the
+    /// result of that request; the batch goes on.
+    fn accept<'a>(
+        &'a self,
+        index: usize,
+        request: &'a CaptureRequest,
+        response: CaptureResponse,
+    ) -> BackendFuture<'a, ()>;
+}
+
+impl Capture {
+    /// Runs `requests` with at most `options.concurrency` in flight,
+    /// stopping early once `options.max_cost` is reached. Every response is
+    /// kept until the batch ends; see
+    /// [`execute_batch_to_sink`](Self::execute_batch_to_sink) for large
+    /// batches.
+    pub async fn execute_batch(
+        &self,
+        requests: &[CaptureRequest],
+        options: &BatchOptions,
+    ) -> BatchReport {
+        run_batch(requests, options, |request| self.execute_recorded(request)).await
+    }
+
+    /// Like [`execute_batch`](Self::execute_batch), handing each response to
+    /// `sink` and dropping it before further requests are issued. At most
+    /// `options.concurrency` responses are held at any time, however long
+    /// the batch.
+    pub async fn execute_batch_to_sink(
+        &self,
+        requests: &[CaptureRequest],
+        options: &BatchOptions,
+        sink: &dyn CaptureSink,
+    ) -> BatchReport<()> {
+        run_batch_to_sink(
+            requests,
+            options,
+            |request| self.execute_recorded(request),
+            sink,
+        )
+        .await
+    }
+
+    async fn execute_recorded(&self, request: &CaptureRequest) -> Result<Executed> {
+        if !self.options.provenance {
+            return self.execute(request).await.map(Executed::from);
+        }
+        let (response, provenance) = self.execute_with_provenance(request).await?;
+        Ok(Executed {
+            response,
+            provenance: Some(provenance),
+        })
+    }
+}
+
+/// A batch response, with its record when provenance is on.
+pub(crate) struct Executed {
+    response: CaptureResponse,
+    provenance: Option<Provenance>,
+}
+
+impl From<CaptureResponse> for Executed {
+    fn from(response: CaptureResponse) -> Self {
+        Self {
+            response,
+            provenance: None,
+        }
+    }
+}
+
+pub(crate) async fn run_batch<'a, F, Fut, R>(
+    requests: &'a [CaptureRequest],
+    options: &BatchOptions,
+    dispatch: F,
+) -> BatchReport
+where
+    F: Fn(&'a CaptureRequest) -> Fut,
+    Fut: Future<Output = Result<R>>,
+    R: Into<Executed>,
+{
+    let collected = Collect::default();
+    let report = run_batch_to_sink(requests, options, dispatch, &collected).await;
+    let mut responses = collected.0.into_inner().unwrap_or_else(|e| e.into_inner());
+
+    BatchReport {
+        results: report
+            .results
+            .into_iter()
+            .map(|(index, result)| {
+                let response = result.map(|()| {
+                    responses
+                        .remove(&index)
+                        .expect("collected every accepted response")
+                });
+                (index, response)
+            })
+            .collect(),
+        skipped: report.skipped,
+        spent: report.spent,
+        provenance: report.provenance,
+    }
+}
+
+/// Keeps every response, for [`run_batch`].
+#[derive(Default)]
+struct Collect(Mutex<HashMap<usize, CaptureResponse>>);
+
+impl CaptureSink for Collect {
+    fn accept<'a>(
+        &'a self,
+        index: usize,
+        _: &'a CaptureRequest,
+        response: CaptureResponse,
+    ) -> BackendFuture<'a, ()> {
+        let mut responses = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        responses.insert(index, response);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+pub(crate) async fn run_batch_to_sink<'a, F, Fut, R>(
+    requests: &'a [CaptureRequest],
+    options: &BatchOptions,
+    dispatch: F,
+    sink: &dyn CaptureSink,
+) -> BatchReport<()>
+where
+    F: Fn(&'a CaptureRequest) -> Fut,
+    Fut: Future<Output = Result<R>>,
+    R: Into<Executed>,
+{
+    let concurrency = options.concurrency.max(1);
+    let over_budget = |spent: f64| options.max_cost.is_some_and(|max_cost| spent >= max_cost);
+
+    let mut report = BatchReport::default();
+    let mut pending = requests.iter().enumerate();
+    let mut in_flight = FuturesUnordered::new();
+
+    loop {
+        while in_flight.len() < concurrency && !over_budget(report.spent) {
+            let Some((index, request)) = pending.next() else {
+                break;
+            };
+            let response = dispatch(request);
+            in_flight.push(async move { (index, request, response.await) });
+        }
+
+        let Some((index, request, result)) = in_flight.next().await else {
+            break;
+        };
+        // In-flight requests are not polled while the sink runs, so no
+        // further response is buffered until this one has been handed off.
+        let result = match result.map(Into::into) {
+            Ok(Executed {
+                response,
+                provenance,
+            }) => {
+                report.spent += options.cost_model.price(request.request_type);
+                report
+                    .provenance
+                    .extend(provenance.map(|provenance| (index, provenance)));
+                sink.accept(index, request, response).await
+            }
+            Err(error) => Err(error),
+        };
+        report.results.push((index, result));
+    }
+
+    report.skipped = pending.map(|(index, _)| index).collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CaptureError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn cost_model() -> CostModel {
+        CostModel {
+            image: 1.0,
+            pdf: 2.0,
+            animated: 5.0,
+            content: 0.5,
+            metadata: 0.25,
+        }
+    }
+
+    #[test]
+    fn test_estimate_sums_prices_by_type() {
+        let requests = [
+            CaptureRequest::image("https://a.example"),
+            CaptureRequest::image("https://b.example"),
+            CaptureRequest::pdf("https://a.example"),
+            CaptureRequest::animated("https://a.example"),
+            CaptureRequest::metadata("https://a.example"),
+        ];
+
+        let estimate = BatchPlan::estimate(&requests, &cost_model());
+        assert_eq!(estimate.total, 9.25);
+        assert_eq!(estimate.counts[&RequestType::Image], 2);
+        assert_eq!(estimate.counts[&RequestType::Pdf], 1);
+        assert!(!estimate.counts.contains_key(&RequestType::Content));
+    }
+
+    #[tokio::test]
+    async fn test_batch_stops_issuing_at_max_cost() {
+        let requests: Vec<_> = (0..10)
+            .map(|i| CaptureRequest::pdf(format!("https://example.com/{i}")))
+            .collect();
+        let options = BatchOptions::new()
+            .with_concurrency(1)
+            .with_max_cost(cost_model(), 5.0);
+        let issued = AtomicUsize::new(0);
+
+        let report = run_batch(&requests, &options, |_| {
+            issued.fetch_add(1, Ordering::SeqCst);
+            async { Ok(CaptureResponse::Bytes(b"%PDF".to_vec())) }
+        })
+        .await;
+
+        assert_eq!(issued.load(Ordering::SeqCst), 3);
+        assert_eq!(report.spent, 6.0);
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.skipped, (3..10).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_batch_sends_no_requests_past_max_cost() {
+        let (base_url, mut received) =
+            crate::mock_server::serve_recorded("200 OK", &[], b"%PDF-1.7".to_vec(), 10).await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            crate::CaptureOptions::new()
+                .with_base_url(&base_url)
+                .unwrap(),
+        );
+        let requests: Vec<_> = (0..10)
+            .map(|i| CaptureRequest::pdf(format!("https://example.com/{i}")))
+            .collect();
+        let options = BatchOptions::new()
+            .with_concurrency(1)
+            .with_max_cost(cost_model(), 5.0);
+
+        let report = capture.execute_batch(&requests, &options).await;
+        assert_eq!(report.spent, 6.0);
+        assert_eq!(report.skipped, (3..10).collect::<Vec<_>>());
+        assert!(report.results.iter().all(|(_, result)| result.is_ok()));
+
+        // Only the requests within budget reached the server.
+        let mut targets = Vec::new();
+        while let Ok(head) = received.try_recv() {
+            let path = head.split_whitespace().nth(1).unwrap();
+            let url = url::Url::parse(&format!("{base_url}{path}")).unwrap();
+            let (_, target) = url.query_pairs().find(|(key, _)| key == "url").unwrap();
+            targets.push(target.into_owned());
+        }
+        assert_eq!(
+            targets,
+            [
+                "https://example.com/0",
+                "https://example.com/1",
+                "https://example.com/2"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_requests_do_not_count_toward_budget() {
+        let requests: Vec<_> = (0..4)
+            .map(|i| CaptureRequest::image(format!("https://example.com/{i}")))
+            .collect();
+        let options = BatchOptions::new().with_max_cost(cost_model(), 1.0);
+
+        let report = run_batch(&requests, &options, |request| {
+            let fails = !request.url.ends_with('3');
+            async move {
+                if fails {
+                    Err(CaptureError::RequestFailed {
+                        status: 500,
+                        message: "boom".to_string(),
+                    })
+                } else {
+                    Ok(CaptureResponse::Bytes(Vec::new()))
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(report.results.len(), 4);
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.spent, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_report_carries_provenance_of_received_responses() {
+        let requests: Vec<_> = (0..3)
+            .map(|i| CaptureRequest::image(format!("https://example.com/{i}")))
+            .collect();
+        let info = crate::FetchInfo {
+            status: 200,
+            ..Default::default()
+        };
+
+        let report = run_batch(&requests, &BatchOptions::new(), |request| {
+            let body = request.url.clone().into_bytes();
+            let provenance =
+                Provenance::new(request, crate::Endpoint::Cdn.base_url(), &info, &body);
+            async move {
+                if request.url.ends_with('1') {
+                    return Err(CaptureError::MissingCredentials);
+                }
+                Ok(Executed {
+                    response: CaptureResponse::Bytes(body),
+                    provenance: Some(provenance),
+                })
+            }
+        })
+        .await;
+
+        let mut recorded: Vec<_> = report
+            .provenance
+            .iter()
+            .map(|(index, provenance)| (*index, provenance.url.as_str()))
+            .collect();
+        recorded.sort_unstable();
+        assert_eq!(
+            recorded,
+            [(0, "https://example.com/0"), (2, "https://example.com/2")]
+        );
+    }
+
+    /// Accounts for the payload bytes alive at once: produced by a
+    /// completed request, released once the sink has taken them.
+    #[derive(Default)]
+    struct Accounting {
+        live: AtomicUsize,
+        peak: AtomicUsize,
+        accepted: AtomicUsize,
+    }
+
+    impl Accounting {
+        fn produce(&self, bytes: usize) {
+            let live = self.live.fetch_add(bytes, Ordering::SeqCst) + bytes;
+            self.peak.fetch_max(live, Ordering::SeqCst);
+        }
+    }
+
+    impl CaptureSink for Accounting {
+        fn accept<'a>(
+            &'a self,
+            index: usize,
+            _: &'a CaptureRequest,
+            response: CaptureResponse,
+        ) -> BackendFuture<'a, ()> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                let CaptureResponse::Bytes(bytes) = response else {
+                    unreachable!()
+                };
+                self.live.fetch_sub(bytes.len(), Ordering::SeqCst);
+                self.accepted.fetch_add(1, Ordering::SeqCst);
+                if index == 7 {
+                    return Err(CaptureError::InvalidUrl);
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sink_bounds_buffered_payloads_by_concurrency() {
+        const PAYLOAD: usize = 1 << 20;
+        let requests: Vec<_> = (0..50)
+            .map(|i| CaptureRequest::image(format!("https://example.com/{i}")))
+            .collect();
+        let options = BatchOptions::new()
+            .with_concurrency(4)
+            .with_max_cost(cost_model(), 1000.0);
+        let accounting = Accounting::default();
+
+        let report = run_batch_to_sink(
+            &requests,
+            &options,
+            |request| {
+                let accounting = &accounting;
+                let fails = request.url.ends_with("/3");
+                async move {
+                    tokio::task::yield_now().await;
+                    if fails {
+                        return Err(CaptureError::MissingUrl);
+                    }
+                    accounting.produce(PAYLOAD);
+                    Ok(CaptureResponse::Bytes(vec![0; PAYLOAD]))
+                }
+            },
+            &accounting,
+        )
+        .await;
+
+        assert_eq!(report.results.len(), 50);
+        assert_eq!(accounting.accepted.load(Ordering::SeqCst), 49);
+        assert_eq!(accounting.live.load(Ordering::SeqCst), 0);
+        assert!(accounting.peak.load(Ordering::SeqCst) <= 4 * PAYLOAD);
+        assert_eq!(report.spent, 49.0);
+
+        let failed: Vec<_> = report
+            .results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(index, _)| *index)
+            .collect();
+        assert_eq!(failed.len(), 2);
+        assert!(failed.contains(&3) && failed.contains(&7));
+    }
+}
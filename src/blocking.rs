@@ -0,0 +1,657 @@
+//! A synchronous [`Capture`] on `reqwest::blocking`, enabled by the
+//! `blocking` feature, for build scripts and small tools that have no async
+//! runtime of their own.
+//!
+//! URLs are built by the same code as the async client, so both produce
+//! identical URLs for the same credentials and options. Timeouts, retries,
+//! response limits, payload validation, the redirect policy, host circuit
+//! breakers and rate limiters apply as there; shadow traffic, request slots
+//! and the disk cache are async-only. Like `reqwest::blocking`, a blocking client must
+//! not be used from within an async runtime.
+
+use std::io::Read;
+use std::time::Duration;
+
+use reqwest::blocking::{Client, ClientBuilder, Response};
+
+use crate::circuit;
+use crate::client::{request_failure, shared_client_settings};
+use crate::overrides::FetchSettings;
+use crate::rt::{Instant, SystemTime};
+use crate::signing::RequestType;
+use crate::{
+    payload, retry, AnimatedFormat, AnimatedOptions, CaptureConfig, CaptureError, CaptureOptions,
+    ContentOptions, ContentResponse, FetchInfo, MetadataOptions, MetadataResponse, PdfOptions,
+    RequestOptions, Result, ScreenshotOptions,
+};
+
+/// Blocking counterpart of [`crate::Capture`], with the same constructors
+/// and `build_*` methods and synchronous `fetch_*` methods. Cloning shares
+/// the HTTP client's connection pool and the credentials.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    /// Supplies the configuration, credentials and URL building.
+    inner: crate::Capture,
+    client: Client,
+}
+
+impl Capture {
+    /// See [`crate::Capture::try_new`].
+    pub fn try_new(key: String, secret: String) -> Result<Self> {
+        Self::builder().key(key).secret(secret).build()
+    }
+
+    pub fn new(key: String, secret: String) -> Self {
+        Self::from_async(crate::Capture::new(key, secret), None)
+    }
+
+    /// See [`crate::Capture::with_options`]. [`CaptureOptions::client`] is
+    /// an async client and is ignored; use [`CaptureBuilder::client`].
+    pub fn with_options(key: String, secret: String, options: CaptureOptions) -> Self {
+        Self::from_async(crate::Capture::with_options(key, secret, options), None)
+    }
+
+    /// Starts configuring a client; see [`CaptureBuilder`].
+    pub fn builder() -> CaptureBuilder {
+        CaptureBuilder::new()
+    }
+
+    /// See [`crate::Capture::url_builder_only`].
+    pub fn url_builder_only(key: String, secret: String) -> Self {
+        Self::from_async(crate::Capture::url_builder_only(key, secret), None)
+    }
+
+    /// See [`crate::Capture::from_env`].
+    pub fn from_env() -> Result<Self> {
+        Ok(Self::from_async(crate::Capture::from_env()?, None))
+    }
+
+    /// See [`crate::Capture::from_env_with_options`].
+    pub fn from_env_with_options(options: CaptureOptions) -> Result<Self> {
+        Ok(Self::from_async(
+            crate::Capture::from_env_with_options(options)?,
+            None,
+        ))
+    }
+
+    /// See [`crate::Capture::from_config`].
+    pub fn from_config(config: CaptureConfig) -> Result<Self> {
+        Ok(Self::from_async(crate::Capture::from_config(config)?, None))
+    }
+
+    fn from_async(inner: crate::Capture, client: Option<Client>) -> Self {
+        let client = client.unwrap_or_else(|| {
+            client_builder(&inner.options)
+                .build()
+                .unwrap_or_else(|_| Client::new())
+        });
+        Self { inner, client }
+    }
+
+    pub fn with_edge(mut self) -> Self {
+        self.inner = self.inner.with_edge();
+        self
+    }
+
+    /// See [`crate::Capture::with_timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.inner = self.inner.with_timeout(timeout);
+        self
+    }
+
+    /// See [`crate::Capture::with_timeout_for`].
+    pub fn with_timeout_for(mut self, request_type: RequestType, timeout: Duration) -> Self {
+        self.inner = self.inner.with_timeout_for(request_type, timeout);
+        self
+    }
+
+    pub fn build_image_url(&self, url: &str, options: Option<&RequestOptions>) -> Result<String> {
+        self.inner.build_image_url(url, options)
+    }
+
+    pub fn build_pdf_url(&self, url: &str, options: Option<&RequestOptions>) -> Result<String> {
+        self.inner.build_pdf_url(url, options)
+    }
+
+    pub fn build_content_url(&self, url: &str, options: Option<&RequestOptions>) -> Result<String> {
+        self.inner.build_content_url(url, options)
+    }
+
+    pub fn build_metadata_url(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.inner.build_metadata_url(url, options)
+    }
+
+    pub fn build_animated_url(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.inner.build_animated_url(url, options)
+    }
+
+    pub fn build_screenshot_url(
+        &self,
+        url: &str,
+        options: Option<&ScreenshotOptions>,
+    ) -> Result<String> {
+        self.inner.build_screenshot_url(url, options)
+    }
+
+    pub fn build_pdf_url_structured(
+        &self,
+        url: &str,
+        options: Option<&PdfOptions>,
+    ) -> Result<String> {
+        self.inner.build_pdf_url_structured(url, options)
+    }
+
+    pub fn build_content_url_structured(
+        &self,
+        url: &str,
+        options: Option<&ContentOptions>,
+    ) -> Result<String> {
+        self.inner.build_content_url_structured(url, options)
+    }
+
+    pub fn build_metadata_url_structured(
+        &self,
+        url: &str,
+        options: Option<&MetadataOptions>,
+    ) -> Result<String> {
+        self.inner.build_metadata_url_structured(url, options)
+    }
+
+    pub fn build_animated_url_structured(
+        &self,
+        url: &str,
+        options: Option<&AnimatedOptions>,
+    ) -> Result<String> {
+        self.inner.build_animated_url_structured(url, options)
+    }
+
+    pub fn fetch_image(&self, url: &str, options: Option<&RequestOptions>) -> Result<Vec<u8>> {
+        let capture_url = self.build_image_url(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Image)
+    }
+
+    pub fn fetch_pdf(&self, url: &str, options: Option<&RequestOptions>) -> Result<Vec<u8>> {
+        let capture_url = self.build_pdf_url(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Pdf)
+    }
+
+    pub fn fetch_content(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<ContentResponse> {
+        let capture_url = self.build_content_url(url, options)?;
+        let bytes = self.fetch_bytes(&capture_url, RequestType::Content)?;
+        ContentResponse::from_slice(&bytes, url)
+    }
+
+    pub fn fetch_metadata(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<MetadataResponse> {
+        let capture_url = self.build_metadata_url(url, options)?;
+        let bytes = self.fetch_bytes(&capture_url, RequestType::Metadata)?;
+        MetadataResponse::from_slice(&bytes)
+    }
+
+    pub fn fetch_animated(&self, url: &str, options: Option<&RequestOptions>) -> Result<Vec<u8>> {
+        let capture_url = self.build_animated_url(url, options)?;
+        let format = options
+            .and_then(|options| options.get("format"))
+            .and_then(|format| format.as_str())
+            .and_then(|format| format.parse().ok());
+        self.fetch_animated_bytes(&capture_url, format)
+    }
+
+    // Structured options fetch methods
+    pub fn fetch_screenshot(
+        &self,
+        url: &str,
+        options: Option<&ScreenshotOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_screenshot_url(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Image)
+    }
+
+    pub fn fetch_pdf_structured(&self, url: &str, options: Option<&PdfOptions>) -> Result<Vec<u8>> {
+        let capture_url = self.build_pdf_url_structured(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Pdf)
+    }
+
+    pub fn fetch_content_structured(
+        &self,
+        url: &str,
+        options: Option<&ContentOptions>,
+    ) -> Result<ContentResponse> {
+        let capture_url = self.build_content_url_structured(url, options)?;
+        let bytes = self.fetch_bytes(&capture_url, RequestType::Content)?;
+        ContentResponse::from_slice(&bytes, url)
+    }
+
+    pub fn fetch_metadata_structured(
+        &self,
+        url: &str,
+        options: Option<&MetadataOptions>,
+    ) -> Result<MetadataResponse> {
+        let capture_url = self.build_metadata_url_structured(url, options)?;
+        let bytes = self.fetch_bytes(&capture_url, RequestType::Metadata)?;
+        MetadataResponse::from_slice(&bytes)
+    }
+
+    pub fn fetch_animated_structured(
+        &self,
+        url: &str,
+        options: Option<&AnimatedOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_animated_url_structured(url, options)?;
+        let format = options.and_then(|options| options.animated_format);
+        self.fetch_animated_bytes(&capture_url, format)
+    }
+
+    fn fetch_animated_bytes(
+        &self,
+        capture_url: &str,
+        format: Option<AnimatedFormat>,
+    ) -> Result<Vec<u8>> {
+        let settings = self.inner.fetch_settings(None);
+        let (bytes, info) =
+            self.fetch_bytes_with_info(capture_url, &settings, RequestType::Animated)?;
+        if let Some(format) = format.filter(|_| settings.validate_payload) {
+            payload::check_animated(format, info.content_type.as_deref(), &bytes)?;
+        }
+        Ok(bytes)
+    }
+
+    fn fetch_bytes(&self, capture_url: &str, request_type: RequestType) -> Result<Vec<u8>> {
+        let settings = self.inner.fetch_settings(None);
+        let (bytes, _) = self.fetch_bytes_with_info(capture_url, &settings, request_type)?;
+        Ok(bytes)
+    }
+
+    fn fetch_bytes_with_info(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        if self.inner.fetch_disabled {
+            return Err(CaptureError::FetchDisabled);
+        }
+        retry::run_blocking(settings.retry_policy.as_ref(), || {
+            self.fetch_bytes_once(capture_url, settings, request_type)
+        })
+    }
+
+    /// Passes the host circuit breaker and rate limiter, then sends the
+    /// request, as [`crate::Capture`]'s fetches do.
+    fn fetch_bytes_once(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        let options = &self.inner.options;
+        let breaker = options
+            .host_circuit_breaker
+            .as_ref()
+            .and_then(|breaker| Some((breaker, circuit::target_host(capture_url)?)));
+        if let Some((breaker, host)) = &breaker {
+            breaker.check(host, Instant::now())?;
+        }
+        if let Some(rate_limiter) = &options.rate_limiter {
+            rate_limiter.acquire(SystemTime::now())?;
+        }
+
+        let result = self.send(capture_url, settings, request_type);
+
+        if let Some((breaker, host)) = &breaker {
+            let failed = result.as_ref().is_err_and(circuit::is_host_failure);
+            breaker.record(host, failed, Instant::now());
+        }
+        result
+    }
+
+    /// Sends the request and checks the response.
+    fn send(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        let mut request = self.client.get(capture_url);
+        if let Some(timeout) = settings.timeout_for(request_type) {
+            request = request.timeout(timeout);
+        }
+        if let Some(accept) = &settings.accept {
+            request = request.header(reqwest::header::ACCEPT, accept);
+        }
+        let response = request.send()?;
+        let status = response.status();
+        let info = FetchInfo {
+            labels: settings.labels.clone(),
+            ..FetchInfo::from_parts(status, response.headers())
+        };
+
+        if status.is_redirection() {
+            return Err(CaptureError::RedirectNotFollowed {
+                status: info.status,
+                location: info.location,
+            });
+        }
+        if !status.is_success() {
+            let body_text = response.text().unwrap_or_default();
+            return Err(request_failure(info.status, &body_text));
+        }
+
+        let bytes = read_body(response, settings.max_response_bytes)?;
+        if settings.validate_payload {
+            payload::check(request_type, info.content_type.as_deref(), &bytes)?;
+        }
+        Ok((bytes, info))
+    }
+}
+
+/// Reads the body of `response`, failing as soon as it exceeds `limit`.
+fn read_body(response: Response, limit: Option<usize>) -> Result<Vec<u8>> {
+    let Some(limit) = limit else {
+        return Ok(response.bytes()?.to_vec());
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(CaptureError::ResponseTooLarge { limit });
+    }
+
+    let mut body = Vec::new();
+    response.take(limit as u64 + 1).read_to_end(&mut body)?;
+    if body.len() > limit {
+        return Err(CaptureError::ResponseTooLarge { limit });
+    }
+    Ok(body)
+}
+
+/// The settings of the crate-built async client that `reqwest::blocking`
+/// supports; it has no read timeout or HTTP/2 keep-alive.
+fn client_builder(options: &CaptureOptions) -> ClientBuilder {
+    shared_client_settings!(Client::builder(), options)
+        // `reqwest::blocking` defaults to 30 seconds; the async client to none.
+        .timeout(options.timeout)
+}
+
+/// Builder for the blocking [`Capture`], with the settings of
+/// [`crate::CaptureBuilder`].
+#[derive(Debug, Clone, Default)]
+pub struct CaptureBuilder {
+    inner: crate::CaptureBuilder,
+    client: Option<Client>,
+}
+
+impl CaptureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.inner = self.inner.key(key);
+        self
+    }
+
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.inner = self.inner.secret(secret);
+        self
+    }
+
+    /// See [`crate::CaptureBuilder::options`].
+    pub fn options(mut self, options: CaptureOptions) -> Self {
+        self.inner = self.inner.options(options);
+        self
+    }
+
+    /// Sends requests to the edge endpoint instead of the CDN one.
+    pub fn edge(mut self, edge: bool) -> Self {
+        self.inner = self.inner.edge(edge);
+        self
+    }
+
+    /// See [`CaptureOptions::with_base_url`].
+    pub fn base_url(mut self, base_url: &str) -> Result<Self> {
+        self.inner = self.inner.base_url(base_url)?;
+        Ok(self)
+    }
+
+    /// See [`CaptureOptions::with_edge_base_url`].
+    pub fn edge_base_url(mut self, edge_base_url: &str) -> Result<Self> {
+        self.inner = self.inner.edge_base_url(edge_base_url)?;
+        Ok(self)
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner = self.inner.timeout(timeout);
+        self
+    }
+
+    /// See [`CaptureOptions::with_connect_timeout`].
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.inner = self.inner.connect_timeout(connect_timeout);
+        self
+    }
+
+    /// Sets the deadline of `request_type` requests, in place of `timeout`.
+    pub fn timeout_for(mut self, request_type: RequestType, timeout: Duration) -> Self {
+        self.inner = self.inner.timeout_for(request_type, timeout);
+        self
+    }
+
+    /// Sends all requests with `client` instead of one built from the
+    /// settings above.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Builds a client that only signs URLs; see [`Capture::url_builder_only`].
+    pub fn url_builder_only(mut self, url_builder_only: bool) -> Self {
+        self.inner = self.inner.url_builder_only(url_builder_only);
+        self
+    }
+
+    /// Builds the client, failing like [`crate::CaptureBuilder::build`].
+    pub fn build(self) -> Result<Capture> {
+        Ok(Capture::from_async(self.inner.build()?, self.client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::serve_once;
+    use crate::{ContentFormat, HostCircuitBreaker, RateLimiter, RetryPolicy};
+
+    const PAGE: &str = "https://example.com";
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    /// Starts a mock server on a runtime of its own, as the blocking client
+    /// must not run inside one. The runtime is returned to keep it alive.
+    fn server(
+        status: &str,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> (tokio::runtime::Runtime, String) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let url = runtime.block_on(serve_once(status, headers, body));
+        (runtime, url)
+    }
+
+    fn options_for(base_url: &str) -> CaptureOptions {
+        CaptureOptions::new().with_base_url(base_url).unwrap()
+    }
+
+    #[test]
+    fn test_urls_match_the_async_client() {
+        let options = CaptureOptions::new()
+            .with_edge()
+            .with_default_options(RequestOptions::from([(
+                "blockAds".to_string(),
+                true.into(),
+            )]));
+        let blocking = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".into(),
+            options.clone(),
+        );
+        let r#async =
+            crate::Capture::with_options("test_key".to_string(), "test_secret".into(), options);
+
+        let raw = RequestOptions::from([("vw".to_string(), 1440.into())]);
+        assert_eq!(
+            blocking.build_image_url(PAGE, Some(&raw)).unwrap(),
+            r#async.build_image_url(PAGE, Some(&raw)).unwrap()
+        );
+        assert_eq!(
+            blocking.build_pdf_url(PAGE, None).unwrap(),
+            r#async.build_pdf_url(PAGE, None).unwrap()
+        );
+        assert_eq!(
+            blocking.build_content_url(PAGE, None).unwrap(),
+            r#async.build_content_url(PAGE, None).unwrap()
+        );
+        assert_eq!(
+            blocking.build_metadata_url(PAGE, None).unwrap(),
+            r#async.build_metadata_url(PAGE, None).unwrap()
+        );
+        assert_eq!(
+            blocking.build_animated_url(PAGE, Some(&raw)).unwrap(),
+            r#async.build_animated_url(PAGE, Some(&raw)).unwrap()
+        );
+
+        let screenshot = ScreenshotOptions {
+            full: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            blocking
+                .build_screenshot_url(PAGE, Some(&screenshot))
+                .unwrap(),
+            r#async
+                .build_screenshot_url(PAGE, Some(&screenshot))
+                .unwrap()
+        );
+        let content = ContentOptions::new().format(ContentFormat::Markdown);
+        assert_eq!(
+            blocking
+                .build_content_url_structured(PAGE, Some(&content))
+                .unwrap(),
+            r#async
+                .build_content_url_structured(PAGE, Some(&content))
+                .unwrap()
+        );
+
+        let built = Capture::builder()
+            .key("test_key")
+            .secret("test_secret")
+            .edge(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            built.build_pdf_url(PAGE, None).unwrap(),
+            crate::Capture::new("test_key".to_string(), "test_secret".to_string())
+                .with_edge()
+                .build_pdf_url(PAGE, None)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fetches() {
+        let (_runtime, url) = server("200 OK", &[("content-type", "image/png")], PNG.to_vec());
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".into(),
+            options_for(&url),
+        );
+        assert_eq!(capture.fetch_image(PAGE, None).unwrap(), PNG);
+
+        let body = br#"{"success": true, "metadata": {"title": "Example"}}"#;
+        let (_runtime, url) = server(
+            "200 OK",
+            &[("content-type", "application/json")],
+            body.to_vec(),
+        );
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".into(),
+            options_for(&url),
+        );
+        let metadata = capture
+            .fetch_metadata_structured(PAGE, Some(&MetadataOptions::default()))
+            .unwrap();
+        assert_eq!(metadata.metadata.title.as_deref(), Some("Example"));
+    }
+
+    #[test]
+    fn test_errors_match_the_async_client() {
+        let (_runtime, url) = server("500 Internal Server Error", &[], b"boom".to_vec());
+        let options = options_for(&url).with_retry_policy(RetryPolicy::new().with_max_attempts(1));
+        let capture = Capture::with_options("test_key".to_string(), "test_secret".into(), options);
+        assert!(matches!(
+            capture.fetch_pdf(PAGE, None),
+            Err(CaptureError::RequestFailed { status: 500, .. })
+        ));
+
+        let (_runtime, url) = server("200 OK", &[], vec![0; 64]);
+        let options = options_for(&url).with_max_response_bytes(16);
+        let capture = Capture::with_options("test_key".to_string(), "test_secret".into(), options);
+        assert!(matches!(
+            capture.fetch_image(PAGE, None),
+            Err(CaptureError::ResponseTooLarge { limit: 16 })
+        ));
+
+        let capture = Capture::url_builder_only("test_key".to_string(), "test_secret".into());
+        assert!(matches!(
+            capture.fetch_image(PAGE, None),
+            Err(CaptureError::FetchDisabled)
+        ));
+        assert!(matches!(
+            Capture::try_new("test_key".to_string(), " ".to_string()),
+            Err(CaptureError::MissingCredentials)
+        ));
+    }
+
+    #[test]
+    fn test_circuit_breaker_and_rate_limiter_apply() {
+        let (_runtime, url) = server("424 Failed Dependency", &[], b"{}".to_vec());
+        let options = options_for(&url)
+            .with_host_circuit_breaker(HostCircuitBreaker::new(1, Duration::from_secs(60)));
+        let capture = Capture::with_options("test_key".to_string(), "test_secret".into(), options);
+        assert!(matches!(
+            capture.fetch_image(PAGE, None),
+            Err(CaptureError::TargetSiteError { .. })
+        ));
+        // The mock server only answers once; an open circuit sends nothing.
+        assert!(matches!(
+            capture.fetch_image(PAGE, None),
+            Err(CaptureError::HostCircuitOpen { ref host, .. }) if host == "example.com"
+        ));
+
+        let (_runtime, url) = server("200 OK", &[], PNG.to_vec());
+        let limiter = RateLimiter::daily(1);
+        let options = options_for(&url).with_rate_limiter(limiter.clone());
+        let capture = Capture::with_options("test_key".to_string(), "test_secret".into(), options);
+        assert_eq!(capture.fetch_image(PAGE, None).unwrap(), PNG);
+        assert!(matches!(
+            capture.fetch_image(PAGE, None),
+            Err(CaptureError::RateLimitExceeded { .. })
+        ));
+        assert_eq!(limiter.consumed(), 1);
+    }
+}
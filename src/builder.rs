@@ -0,0 +1,303 @@
+//! One place to configure and construct a [`Capture`].
+
+#[cfg(feature = "http")]
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "http")]
+use reqwest::Client;
+
+use crate::client::is_official_base_url;
+#[cfg(feature = "http")]
+use crate::client::{build_client, build_redirectless_client};
+#[cfg(feature = "http")]
+use crate::concurrency::RequestSlots;
+use crate::credentials::{Credentials, SharedCredentials};
+use crate::error::invalid_option;
+use crate::secret::Secret;
+use crate::signing::RequestType;
+use crate::{Capture, CaptureError, CaptureOptions, Result};
+
+/// Builder for [`Capture`], from [`Capture::builder`]. The HTTP client is
+/// built once, in [`build`](Self::build), from the final configuration.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureBuilder {
+    credentials: Credentials,
+    options: CaptureOptions,
+    url_builder_only: bool,
+}
+
+impl CaptureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.credentials.key = key.into();
+        self
+    }
+
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.credentials.secret = Secret::new(secret.into());
+        self
+    }
+
+    /// Sets the key and secret at once, without copying the secret.
+    pub(crate) fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Replaces every setting made so far except the credentials, e.g. to
+    /// start from a shared [`CaptureOptions`] before the calls below.
+    pub fn options(mut self, options: CaptureOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sends requests to the edge endpoint instead of the CDN one.
+    pub fn edge(mut self, edge: bool) -> Self {
+        self.options.use_edge = edge;
+        self
+    }
+
+    /// See [`CaptureOptions::with_base_url`].
+    pub fn base_url(mut self, base_url: &str) -> Result<Self> {
+        self.options = self.options.with_base_url(base_url)?;
+        Ok(self)
+    }
+
+    /// See [`CaptureOptions::with_edge_base_url`].
+    pub fn edge_base_url(mut self, edge_base_url: &str) -> Result<Self> {
+        self.options = self.options.with_edge_base_url(edge_base_url)?;
+        Ok(self)
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// See [`CaptureOptions::with_connect_timeout`].
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// See [`CaptureOptions::with_read_timeout`].
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.options.read_timeout = Some(read_timeout);
+        self
+    }
+
+    /// Sets the deadline of `request_type` requests, in place of `timeout`.
+    pub fn timeout_for(mut self, request_type: RequestType, timeout: Duration) -> Self {
+        self.options.type_timeouts.insert(request_type, timeout);
+        self
+    }
+
+    /// Sends all requests with `client`. See [`CaptureOptions::client`].
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn client(mut self, client: impl Into<Arc<Client>>) -> Self {
+        self.options.client = Some(client.into());
+        self
+    }
+
+    /// Builds a client that only signs URLs; see [`Capture::url_builder_only`].
+    pub fn url_builder_only(mut self, url_builder_only: bool) -> Self {
+        self.url_builder_only = url_builder_only;
+        self
+    }
+
+    /// Builds the client, failing with [`CaptureError::MissingCredentials`]
+    /// when the key or secret is empty or only whitespace. A URL-builder-only
+    /// client hands its URLs to others, so its base URLs must be the official
+    /// hosts; anything else fails with [`CaptureError::InvalidOption`].
+    pub fn build(self) -> Result<Capture> {
+        if self.credentials.is_blank() {
+            return Err(CaptureError::MissingCredentials);
+        }
+        if self.url_builder_only {
+            for (field, base_url) in [
+                ("base_url", &self.options.base_url),
+                ("edge_base_url", &self.options.edge_base_url),
+            ] {
+                if base_url
+                    .as_ref()
+                    .is_some_and(|url| !is_official_base_url(url))
+                {
+                    return Err(invalid_option(
+                        field,
+                        "must be an official capture host for a URL-builder-only client",
+                    ));
+                }
+            }
+        }
+        Ok(self.build_unchecked())
+    }
+
+    /// Builds the client without checking the credentials, for the
+    /// infallible constructors; their URL builders and fetches report
+    /// missing credentials instead.
+    pub(crate) fn build_unchecked(self) -> Capture {
+        let options = self.options;
+        #[cfg(feature = "http")]
+        let (client, redirectless_client, request_slots) = {
+            let client = options
+                .client
+                .clone()
+                .unwrap_or_else(|| Arc::new(build_client(&options)));
+            let redirectless_client = options
+                .client
+                .is_none()
+                .then(|| build_redirectless_client(&options));
+            let request_slots = options.max_concurrent_requests.map(RequestSlots::new);
+            (client, redirectless_client, request_slots)
+        };
+
+        Capture {
+            credentials: SharedCredentials::new(self.credentials),
+            credentials_provider: None,
+            options,
+            #[cfg(feature = "http")]
+            client,
+            #[cfg(feature = "http")]
+            redirectless_client,
+            #[cfg(feature = "http")]
+            request_slots,
+            fetch_disabled: self.url_builder_only,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Endpoint;
+
+    const PAGE: &str = "https://example.com";
+
+    #[test]
+    fn test_builder_matches_constructors() {
+        let built = Capture::builder()
+            .key("test_key")
+            .secret("test_secret")
+            .build()
+            .unwrap();
+        let constructed = Capture::new("test_key".to_string(), "test_secret".to_string());
+        assert_eq!(
+            built.build_image_url(PAGE, None).unwrap(),
+            constructed.build_image_url(PAGE, None).unwrap()
+        );
+        assert_eq!(built.endpoint(), Endpoint::Cdn);
+        assert_eq!(built.options.timeout, None);
+
+        let built = Capture::builder()
+            .key("test_key")
+            .secret("test_secret")
+            .edge(true)
+            .timeout(Duration::from_secs(20))
+            .timeout_for(RequestType::Animated, Duration::from_secs(180))
+            .build()
+            .unwrap();
+        let constructed = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_edge()
+                .with_timeout(Duration::from_secs(20))
+                .with_timeout_for(RequestType::Animated, Duration::from_secs(180)),
+        );
+        assert_eq!(
+            built.build_pdf_url(PAGE, None).unwrap(),
+            constructed.build_pdf_url(PAGE, None).unwrap()
+        );
+        assert!(built
+            .build_pdf_url(PAGE, None)
+            .unwrap()
+            .starts_with("https://edge.capture.page/"));
+        #[cfg(feature = "http")]
+        {
+            assert_eq!(built.fetch_settings(None), constructed.fetch_settings(None));
+            assert!(built.redirectless_client.is_some());
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_builder_keeps_the_given_client() {
+        let client = Arc::new(Client::new());
+        let capture = Capture::builder()
+            .key("test_key")
+            .secret("test_secret")
+            .client(client.clone())
+            .build()
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&capture.client, &client));
+        assert!(capture.redirectless_client.is_none());
+    }
+
+    #[test]
+    fn test_builder_requires_credentials() {
+        for builder in [
+            Capture::builder(),
+            Capture::builder().key("test_key"),
+            Capture::builder().secret("test_secret"),
+        ] {
+            assert!(matches!(
+                builder.build(),
+                Err(CaptureError::MissingCredentials)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_url_builder_only_accepts_official_hosts_only() {
+        let builder = || {
+            Capture::builder()
+                .key("test_key")
+                .secret("test_secret")
+                .url_builder_only(true)
+        };
+        assert!(builder().build().unwrap().is_url_builder_only());
+        assert!(builder()
+            .base_url("https://cdn.capture.page/")
+            .unwrap()
+            .edge_base_url("https://edge.capture.page")
+            .unwrap()
+            .build()
+            .is_ok());
+
+        let error = builder()
+            .edge_base_url("https://proxy.internal/capture")
+            .unwrap()
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            CaptureError::InvalidOption { ref field, .. } if field == "edge_base_url"
+        ));
+        assert!(builder()
+            .url_builder_only(false)
+            .base_url("https://proxy.internal/capture")
+            .unwrap()
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_options_replace_earlier_settings() {
+        let capture = Capture::builder()
+            .edge(true)
+            .options(CaptureOptions::new().with_timeout(Duration::from_secs(5)))
+            .key("test_key")
+            .secret("test_secret")
+            .build()
+            .unwrap();
+
+        assert_eq!(capture.endpoint(), Endpoint::Cdn);
+        assert_eq!(capture.options.timeout, Some(Duration::from_secs(5)));
+    }
+}
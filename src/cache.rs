@@ -0,0 +1,526 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::overrides::FetchSettings;
+use crate::rt::{self, SystemTime, UNIX_EPOCH};
+use crate::{Capture, FetchInfo, RequestType, Result};
+
+/// Keeps capture responses on disk, one file per capture URL, and answers
+/// repeated fetches from there. Set with
+/// [`CaptureOptions::with_disk_cache`](crate::CaptureOptions::with_disk_cache).
+///
+/// The cache follows the API's own cache controls:
+///
+/// - A call with `fresh` set skips the cached entry and replaces it with the
+///   new render.
+/// - An entry is kept for `cache_ttl`, but never longer than the response's
+///   `Cache-Control: max-age`.
+/// - An expired entry with an `ETag` is revalidated with `If-None-Match`; a
+///   `304 Not Modified` answer serves it again for another TTL.
+///
+/// Only successful responses are stored. The cache is best effort: an entry
+/// that cannot be read counts as missing, and failed writes are ignored.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    cache_ttl: Duration,
+}
+
+impl DiskCache {
+    /// Caches in `dir`, which is created on first write.
+    pub fn new(dir: impl Into<PathBuf>, cache_ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            cache_ttl,
+        }
+    }
+
+    /// The entry's file name: a digest of the capture URL, so clients with
+    /// other credentials or base URLs never share entries. Only the token
+    /// and `fresh` are left out, so a fresh call replaces the entry ordinary
+    /// calls read.
+    fn key(capture_url: &str) -> Option<(String, bool)> {
+        let url = url::Url::parse(capture_url).ok()?;
+        let mut segments: Vec<_> = url.path_segments()?.collect();
+        // `{base path}/{key}/{token}/{request type}`
+        let token = segments.len().checked_sub(2)?;
+        segments.remove(token);
+
+        let mut fresh = false;
+        let mut options = Vec::new();
+        for (name, value) in url.query_pairs() {
+            if name == "fresh" {
+                fresh = value == "true";
+            } else {
+                options.push(format!("{name}={value}"));
+            }
+        }
+        let digest = md5::compute(format!(
+            "{}/{}?{}",
+            url.origin().ascii_serialization(),
+            segments.join("/"),
+            options.join("&")
+        ));
+        Some((format!("{digest:x}"), fresh))
+    }
+
+    async fn read(&self, key: &str) -> Option<Entry> {
+        let path = self.dir.join(key);
+        let bytes = rt::blocking_io(move || fs::read(path)).await.ok()?;
+        let split = bytes.iter().position(|&byte| byte == b'\n')?;
+        let meta = serde_json::from_slice(&bytes[..split]).ok()?;
+        Some(Entry {
+            meta,
+            body: bytes[split + 1..].to_vec(),
+        })
+    }
+
+    /// Replaces the entry through a temporary file of its own, so readers
+    /// never see a partial write, nor concurrent writers a mixed one.
+    async fn write(&self, key: &str, entry: &Entry) -> io::Result<()> {
+        let mut bytes = serde_json::to_vec(&entry.meta)?;
+        bytes.push(b'\n');
+        bytes.extend_from_slice(&entry.body);
+
+        let dir = self.dir.clone();
+        let path = self.dir.join(key);
+        rt::blocking_io(move || {
+            fs::create_dir_all(dir)?;
+            let temporary = rt::temporary_path(&path);
+            fs::write(&temporary, bytes)?;
+            fs::rename(&temporary, &path).inspect_err(|_| {
+                let _ = fs::remove_file(&temporary);
+            })
+        })
+        .await
+    }
+
+    /// `cache_ttl`, bounded by the response's `max-age` when it has one.
+    fn ttl(&self, info: &FetchInfo) -> Duration {
+        match info.cache_control.as_deref().and_then(max_age) {
+            Some(max_age) => self.cache_ttl.min(max_age),
+            None => self.cache_ttl,
+        }
+    }
+
+    async fn store(&self, key: &str, bytes: &[u8], info: &FetchInfo, now: SystemTime) {
+        let entry = Entry {
+            meta: Meta {
+                expires_at: seconds(now + self.ttl(info)),
+                etag: info.etag.clone(),
+                content_type: info.content_type.clone(),
+            },
+            body: bytes.to_vec(),
+        };
+        let _ = self.write(key, &entry).await;
+    }
+}
+
+/// The `max-age` directive of a `Cache-Control` value.
+fn max_age(cache_control: &str) -> Option<Duration> {
+    cache_control.split(',').find_map(|directive| {
+        let (name, value) = directive.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("max-age") {
+            return None;
+        }
+        value.trim().parse().ok().map(Duration::from_secs)
+    })
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Meta {
+    /// Seconds since the Unix epoch.
+    expires_at: u64,
+    etag: Option<String>,
+    content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    meta: Meta,
+    body: Vec<u8>,
+}
+
+/// What a fetch does given the cached entry, if any.
+#[derive(Debug, PartialEq, Eq)]
+enum Plan {
+    /// Answer from the entry without a request.
+    Serve(Entry),
+    /// Ask whether the entry is still current.
+    Revalidate(Entry),
+    /// Fetch and store the response.
+    Fetch,
+}
+
+fn plan(entry: Option<Entry>, fresh: bool, now: SystemTime) -> Plan {
+    match entry {
+        _ if fresh => Plan::Fetch,
+        None => Plan::Fetch,
+        Some(entry) if entry.meta.expires_at > seconds(now) => Plan::Serve(entry),
+        Some(entry) if entry.meta.etag.is_some() => Plan::Revalidate(entry),
+        Some(_) => Plan::Fetch,
+    }
+}
+
+impl Capture {
+    pub(crate) async fn fetch_through_cache(
+        &self,
+        cache: &DiskCache,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        let Some((key, fresh)) = DiskCache::key(capture_url) else {
+            return self
+                .fetch_uncached(capture_url, settings, request_type)
+                .await;
+        };
+        let served = |entry: Entry| {
+            let info = FetchInfo {
+                status: 200,
+                content_type: entry.meta.content_type,
+                etag: entry.meta.etag,
+                labels: settings.labels.clone(),
+                ..FetchInfo::default()
+            };
+            (entry.body, info)
+        };
+
+        match plan(cache.read(&key).await, fresh, SystemTime::now()) {
+            Plan::Serve(entry) => Ok(served(entry)),
+            Plan::Revalidate(mut entry) => {
+                let revalidation = FetchSettings {
+                    if_none_match: entry.meta.etag.clone(),
+                    ..settings.clone()
+                };
+                let (bytes, info) = self
+                    .fetch_uncached(capture_url, &revalidation, request_type)
+                    .await?;
+                let now = SystemTime::now();
+                if info.status == 304 {
+                    entry.meta.expires_at = seconds(now + cache.ttl(&info));
+                    let _ = cache.write(&key, &entry).await;
+                    return Ok(served(entry));
+                }
+                if (200..300).contains(&info.status) {
+                    cache.store(&key, &bytes, &info, now).await;
+                }
+                Ok((bytes, info))
+            }
+            Plan::Fetch => {
+                let (bytes, info) = self
+                    .fetch_uncached(capture_url, settings, request_type)
+                    .await?;
+                if (200..300).contains(&info.status) {
+                    cache.store(&key, &bytes, &info, SystemTime::now()).await;
+                }
+                Ok((bytes, info))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server;
+    use crate::{CaptureOptions, RequestOptions};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CACHED: &[u8] = b"\x89PNG\r\n\x1a\ncached";
+    const RENDERED: &[u8] = b"\x89PNG\r\n\x1a\nrendered";
+    const TTL: Duration = Duration::from_secs(60 * 60);
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            Self(std::env::temp_dir().join(format!(
+                "capture-rust-cache-{name}-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            )))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum Local {
+        Miss,
+        Hit,
+        Expired,
+    }
+
+    fn options(fresh: bool) -> RequestOptions {
+        let mut options = RequestOptions::new();
+        options.insert("vw".to_string(), serde_json::json!(1280));
+        if fresh {
+            options.insert("fresh".to_string(), serde_json::json!(true));
+        }
+        options
+    }
+
+    fn cached_client(url: &str, cache: &DiskCache) -> Capture {
+        Capture::with_options(
+            "key".to_string(),
+            "secret".to_string(),
+            CaptureOptions::new()
+                .with_base_url(url)
+                .unwrap()
+                .with_disk_cache(cache.clone()),
+        )
+    }
+
+    async fn seed(cache: &DiskCache, capture: &Capture, local: Local, etag: Option<&str>) {
+        let now = seconds(SystemTime::now());
+        let expires_at = match local {
+            Local::Miss => return,
+            Local::Hit => now + 600,
+            Local::Expired => now - 600,
+        };
+        let url = capture
+            .build_url(
+                RequestType::Image,
+                "https://example.com",
+                Some(&options(false)),
+            )
+            .unwrap();
+        let entry = Entry {
+            meta: Meta {
+                expires_at,
+                etag: etag.map(ToOwned::to_owned),
+                content_type: Some("image/png".to_string()),
+            },
+            body: CACHED.to_vec(),
+        };
+        cache
+            .write(&DiskCache::key(&url).unwrap().0, &entry)
+            .await
+            .unwrap();
+    }
+
+    async fn stored(cache: &DiskCache, capture: &Capture) -> Entry {
+        let url = capture
+            .build_url(
+                RequestType::Image,
+                "https://example.com",
+                Some(&options(false)),
+            )
+            .unwrap();
+        cache.read(&DiskCache::key(&url).unwrap().0).await.unwrap()
+    }
+
+    /// Every combination of local state, `fresh` and `ETag`, against a mock
+    /// API that answers revalidations with `304` and fetches with a new
+    /// render. Requests the case should not make find no server listening.
+    #[tokio::test]
+    async fn test_cache_state_transitions() {
+        for local in [Local::Miss, Local::Hit, Local::Expired] {
+            for fresh in [false, true] {
+                for etag in [None, Some("\"v1\"")] {
+                    let case = format!("{local:?}, fresh {fresh}, etag {etag:?}");
+                    let revalidates = matches!(local, Local::Expired) && !fresh && etag.is_some();
+                    let serves_cached = matches!(local, Local::Hit) && !fresh;
+
+                    let not_modified: &[(&str, &str)] = &[("etag", "\"v1\"")];
+                    let rendered: &[(&str, &str)] =
+                        &[("content-type", "image/png"), ("etag", "\"v2\"")];
+                    let responses: Vec<mock_server::Response> = if serves_cached {
+                        vec![]
+                    } else if revalidates {
+                        vec![("304 Not Modified", not_modified, vec![])]
+                    } else {
+                        vec![("200 OK", rendered, RENDERED.to_vec())]
+                    };
+                    let (url, mut requests) = mock_server::serve_sequence(&responses).await;
+
+                    let dir = TempDir::new("transitions");
+                    let cache = DiskCache::new(&dir.0, TTL);
+                    let capture = cached_client(&url, &cache);
+                    seed(&cache, &capture, local, etag).await;
+
+                    let bytes = capture
+                        .fetch_image("https://example.com", Some(&options(fresh)))
+                        .await
+                        .unwrap_or_else(|error| panic!("{case}: {error}"));
+
+                    let expected = if serves_cached || revalidates {
+                        CACHED
+                    } else {
+                        RENDERED
+                    };
+                    assert_eq!(bytes, expected, "{case}");
+
+                    let mut heads = Vec::new();
+                    while let Ok(head) = requests.try_recv() {
+                        heads.push(head.to_ascii_lowercase());
+                    }
+                    assert_eq!(heads.len(), responses.len(), "{case}");
+                    let conditional = heads
+                        .iter()
+                        .any(|head| head.contains("if-none-match: \"v1\""));
+                    assert_eq!(conditional, revalidates, "{case}");
+
+                    let entry = stored(&cache, &capture).await;
+                    assert_eq!(entry.body, expected, "{case}");
+                    assert!(
+                        entry.meta.expires_at > seconds(SystemTime::now()),
+                        "{case}: entry still expired"
+                    );
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_max_age_bounds_the_local_ttl() {
+        let (url, _requests) = mock_server::serve_sequence(&[(
+            "200 OK",
+            &[
+                ("content-type", "image/png"),
+                ("cache-control", "public, max-age=60"),
+            ],
+            RENDERED.to_vec(),
+        )])
+        .await;
+        let dir = TempDir::new("max-age");
+        let cache = DiskCache::new(&dir.0, TTL);
+        let capture = cached_client(&url, &cache);
+
+        let before = seconds(SystemTime::now());
+        capture
+            .fetch_image("https://example.com", Some(&options(false)))
+            .await
+            .unwrap();
+
+        let expires_in = stored(&cache, &capture).await.meta.expires_at - before;
+        assert!((60..=61).contains(&expires_in), "expires in {expires_in}s");
+    }
+
+    #[tokio::test]
+    async fn test_failed_responses_are_not_stored() {
+        let (url, _requests) = mock_server::serve_sequence(&[(
+            "500 Internal Server Error",
+            &[],
+            b"{\"error\":\"boom\"}".to_vec(),
+        )])
+        .await;
+        let dir = TempDir::new("failure");
+        let cache = DiskCache::new(&dir.0, TTL);
+        let capture = cached_client(&url, &cache);
+
+        let result = capture
+            .fetch_image("https://example.com", Some(&options(false)))
+            .await;
+
+        assert!(result.is_err());
+        assert!(!dir.0.exists());
+    }
+
+    #[tokio::test]
+    async fn test_clients_with_other_credentials_do_not_share_entries() {
+        let png: &[(&str, &str)] = &[("content-type", "image/png")];
+        let (url, mut requests) = mock_server::serve_sequence(&[
+            ("200 OK", png, CACHED.to_vec()),
+            ("200 OK", png, RENDERED.to_vec()),
+        ])
+        .await;
+        let dir = TempDir::new("tenants");
+        let cache = DiskCache::new(&dir.0, TTL);
+        let tenant_a = cached_client(&url, &cache)
+            .with_credentials("key-a".to_string(), "secret-a".to_string());
+        let tenant_b = tenant_a.with_credentials("key-b".to_string(), "secret-b".to_string());
+
+        let fetch = |capture: &Capture| {
+            let capture = capture.clone();
+            async move {
+                capture
+                    .fetch_image("https://example.com", Some(&options(false)))
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(fetch(&tenant_a).await, CACHED);
+        assert_eq!(fetch(&tenant_b).await, RENDERED);
+        // Both are now answered from their own entries.
+        assert_eq!(fetch(&tenant_a).await, CACHED);
+        assert_eq!(fetch(&tenant_b).await, RENDERED);
+
+        let mut heads = Vec::new();
+        while let Ok(head) = requests.try_recv() {
+            heads.push(head);
+        }
+        assert_eq!(heads.len(), 2);
+        assert!(heads[0].contains("/key-a/"));
+        assert!(heads[1].contains("/key-b/"));
+    }
+
+    #[test]
+    fn test_key_ignores_only_fresh_and_token() {
+        let build = |base_url: &str, key: &str, secret: &str, fresh: bool| {
+            let capture = Capture::with_options(
+                key.to_string(),
+                secret.to_string(),
+                CaptureOptions::new().with_base_url(base_url).unwrap(),
+            );
+            let url = capture
+                .build_url(
+                    RequestType::Image,
+                    "https://example.com",
+                    Some(&options(fresh)),
+                )
+                .unwrap();
+            DiskCache::key(&url).unwrap()
+        };
+        let (plain, plain_fresh) = build("https://cdn.capture.page", "key", "secret", false);
+        assert!(!plain_fresh);
+
+        let (fresh, fresh_fresh) = build("https://cdn.capture.page", "key", "secret", true);
+        assert!(fresh_fresh);
+        assert_eq!(fresh, plain);
+        // Another secret only changes the token.
+        let (other_secret, _) = build("https://cdn.capture.page", "key", "rotated", false);
+        assert_eq!(other_secret, plain);
+
+        let (other_key, _) = build("https://cdn.capture.page", "other", "secret", false);
+        assert_ne!(other_key, plain);
+        let (other_base, _) = build("https://proxy.example/capture", "key", "secret", false);
+        assert_ne!(other_base, plain);
+
+        let pdf = Capture::new("key".to_string(), "secret".to_string())
+            .build_url(
+                RequestType::Pdf,
+                "https://example.com",
+                Some(&options(false)),
+            )
+            .unwrap();
+        assert_ne!(DiskCache::key(&pdf).unwrap().0, plain);
+    }
+
+    #[test]
+    fn test_max_age() {
+        assert_eq!(max_age("max-age=60"), Some(Duration::from_secs(60)));
+        assert_eq!(
+            max_age("public, Max-Age = 300, immutable"),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(max_age("no-cache"), None);
+        assert_eq!(max_age("s-maxage=60"), None);
+    }
+}
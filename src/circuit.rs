@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::rt::{Instant, SystemTime};
+use crate::{CaptureError, ErrorKind, Result};
+
+/// Per-target-host circuit breaker. After `failure_threshold` consecutive
+/// failures for a host, requests to it fail fast with
+/// [`CaptureError::HostCircuitOpen`] for `cool_down`; then one probe request
+/// is let through, and its outcome closes or re-opens the circuit. A probe
+/// that never reports back, e.g. because it was cancelled, is replaced by
+/// another one after a further `cool_down`.
+///
+/// Only failures of the target page ([`CaptureError::TargetSiteError`])
+/// count. Failures between this client and the capture service, such as a
+/// refused connection or a timeout, say nothing about any one target host,
+/// and neither do errors the service reports about the request itself.
+#[derive(Clone)]
+pub struct HostCircuitBreaker {
+    pub failure_threshold: u32,
+    pub cool_down: Duration,
+    hosts: Arc<Mutex<HashMap<String, HostState>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HostState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { until: Instant },
+}
+
+impl fmt::Debug for HostCircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostCircuitBreaker")
+            .field("failure_threshold", &self.failure_threshold)
+            .field("cool_down", &self.cool_down)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for HostCircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(60))
+    }
+}
+
+impl HostCircuitBreaker {
+    pub fn new(failure_threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Copy of this configuration with its own host state.
+    pub(crate) fn detached(&self) -> Self {
+        Self::new(self.failure_threshold, self.cool_down)
+    }
+
+    /// Fails fast if the circuit for `host` is open. An open circuit whose
+    /// cool-down has passed, or whose probe has not reported back within
+    /// one, lets this caller through as the probe.
+    pub(crate) fn check(&self, host: &str, now: Instant) -> Result<()> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = hosts.get_mut(host) else {
+            return Ok(());
+        };
+
+        match *state {
+            HostState::Closed { .. } => Ok(()),
+            HostState::Open { until } | HostState::HalfOpen { until } if now >= until => {
+                *state = HostState::HalfOpen {
+                    until: now + self.cool_down,
+                };
+                Ok(())
+            }
+            HostState::Open { until } | HostState::HalfOpen { until } => {
+                Err(CaptureError::HostCircuitOpen {
+                    host: host.to_string(),
+                    retry_at: SystemTime::now() + until.saturating_duration_since(now),
+                })
+            }
+        }
+    }
+
+    pub(crate) fn record(&self, host: &str, failed: bool, now: Instant) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        if !failed {
+            hosts.remove(host);
+            return;
+        }
+
+        let state = hosts
+            .entry(host.to_string())
+            .or_insert(HostState::Closed { failures: 0 });
+        *state = match *state {
+            HostState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                HostState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            _ => HostState::Open {
+                until: now + self.cool_down,
+            },
+        };
+    }
+}
+
+/// Whether `error` says something about the target host rather than about
+/// the connection to the capture service, credentials, quota or the request
+/// itself.
+pub(crate) fn is_host_failure(error: &CaptureError) -> bool {
+    error.kind() == ErrorKind::TargetSite
+}
+
+/// Host of the target page in a built capture URL.
+pub(crate) fn target_host(capture_url: &str) -> Option<String> {
+    let capture_url = url::Url::parse(capture_url).ok()?;
+    let (_, target) = capture_url.query_pairs().find(|(key, _)| key == "url")?;
+    let target = url::Url::parse(&target).ok()?;
+    target.host_str().map(str::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_open(result: Result<()>) -> bool {
+        matches!(result, Err(CaptureError::HostCircuitOpen { .. }))
+    }
+
+    #[test]
+    fn test_failing_host_trips_without_affecting_healthy_host() {
+        let breaker = HostCircuitBreaker::new(3, Duration::from_secs(30));
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            breaker.check("down.example", now).unwrap();
+            breaker.record("down.example", true, now);
+            breaker.check("up.example", now).unwrap();
+            breaker.record("up.example", false, now);
+        }
+
+        let result = breaker.check("down.example", now);
+        assert!(
+            matches!(result, Err(CaptureError::HostCircuitOpen { ref host, .. }) if host == "down.example")
+        );
+        assert!(breaker.check("up.example", now).is_ok());
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_or_reopens() {
+        let breaker = HostCircuitBreaker::new(1, Duration::from_secs(30));
+        let start = Instant::now();
+        breaker.record("flaky.example", true, start);
+        assert!(is_open(breaker.check("flaky.example", start)));
+
+        // After the cool-down a single probe goes through.
+        let later = start + Duration::from_secs(31);
+        assert!(breaker.check("flaky.example", later).is_ok());
+        assert!(is_open(breaker.check("flaky.example", later)));
+
+        // A probe that never reports back is replaced after another cool-down.
+        let stalled = later + Duration::from_secs(31);
+        assert!(breaker.check("flaky.example", stalled).is_ok());
+        assert!(is_open(breaker.check("flaky.example", stalled)));
+
+        // A failed probe re-opens the circuit.
+        breaker.record("flaky.example", true, stalled);
+        assert!(is_open(
+            breaker.check("flaky.example", stalled + Duration::from_secs(1))
+        ));
+
+        // A successful probe closes it.
+        let much_later = stalled + Duration::from_secs(31);
+        assert!(breaker.check("flaky.example", much_later).is_ok());
+        breaker.record("flaky.example", false, much_later);
+        assert!(breaker.check("flaky.example", much_later).is_ok());
+        assert!(breaker.check("flaky.example", much_later).is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_consecutive_failures() {
+        let breaker = HostCircuitBreaker::new(2, Duration::from_secs(30));
+        let now = Instant::now();
+
+        breaker.record("a.example", true, now);
+        breaker.record("a.example", false, now);
+        breaker.record("a.example", true, now);
+        assert!(breaker.check("a.example", now).is_ok());
+    }
+
+    #[test]
+    fn test_shared_and_detached_state() {
+        let breaker = HostCircuitBreaker::new(1, Duration::from_secs(30));
+        let now = Instant::now();
+        breaker.record("down.example", true, now);
+
+        assert!(is_open(breaker.clone().check("down.example", now)));
+        assert!(breaker.detached().check("down.example", now).is_ok());
+    }
+
+    #[test]
+    fn test_target_host_and_failure_classification() {
+        assert_eq!(
+            target_host("https://cdn.capture.page/k/t/image?url=https%3A%2F%2FWWW.Example.com%2Fa")
+                .as_deref(),
+            Some("www.example.com")
+        );
+        assert_eq!(target_host("https://cdn.capture.page/k/t/image"), None);
+
+        let failed = |status| CaptureError::RequestFailed {
+            status,
+            message: String::new(),
+        };
+        assert!(is_host_failure(&CaptureError::TargetSiteError {
+            target_status: Some(503),
+            message: String::new(),
+        }));
+        for status in [401, 404, 429, 502] {
+            assert!(!is_host_failure(&failed(status)), "{status}");
+        }
+        assert!(!is_host_failure(&CaptureError::MissingUrl));
+    }
+
+    #[tokio::test]
+    async fn test_failing_target_host_trips_only_its_circuit() {
+        let breaker = HostCircuitBreaker::new(2, Duration::from_secs(60));
+        let capture_at = |base_url: &str| {
+            crate::Capture::with_options(
+                "test_key".to_string(),
+                "test_secret".to_string(),
+                crate::CaptureOptions::new()
+                    .with_base_url(base_url)
+                    .unwrap()
+                    .with_host_circuit_breaker(breaker.clone()),
+            )
+        };
+        let (failing_url, mut failing_requests) = crate::mock_server::serve_recorded(
+            "424 Failed Dependency",
+            &[("content-type", "application/json")],
+            br#"{"error":"target unreachable","targetStatus":503}"#.to_vec(),
+            2,
+        )
+        .await;
+        let healthy_url = crate::mock_server::serve(
+            "200 OK",
+            &[("content-type", "image/png")],
+            b"\x89PNG\r\n\x1a\n".to_vec(),
+            3,
+        )
+        .await;
+        let service_error_url = crate::mock_server::serve(
+            "502 Bad Gateway",
+            &[("content-type", "application/json")],
+            br#"{"error":"renderer crashed"}"#.to_vec(),
+            3,
+        )
+        .await;
+        let (failing, healthy, service_error) = (
+            capture_at(&failing_url),
+            capture_at(&healthy_url),
+            capture_at(&service_error_url),
+        );
+
+        for _ in 0..2 {
+            let result = failing.fetch_image("https://down.example/", None).await;
+            assert!(matches!(result, Err(CaptureError::TargetSiteError { .. })));
+        }
+        let result = failing
+            .fetch_image("https://down.example/other", None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(CaptureError::HostCircuitOpen { ref host, .. }) if host == "down.example"
+        ));
+        failing_requests.recv().await.unwrap();
+        failing_requests.recv().await.unwrap();
+        assert!(failing_requests.try_recv().is_err());
+
+        for _ in 0..3 {
+            let image = healthy
+                .fetch_image("https://up.example/", None)
+                .await
+                .unwrap();
+            assert!(image.starts_with(b"\x89PNG"));
+            let result = service_error
+                .fetch_image("https://busy.example/", None)
+                .await;
+            assert!(matches!(
+                result,
+                Err(CaptureError::RequestFailed { status: 502, .. })
+            ));
+        }
+        assert!(breaker.check("up.example", Instant::now()).is_ok());
+        assert!(breaker.check("busy.example", Instant::now()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_transport_failures_leave_host_circuits_closed() {
+        let breaker = HostCircuitBreaker::new(1, Duration::from_secs(60));
+        // A port nothing listens on, so every request is refused.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let capture = crate::Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            crate::CaptureOptions::new()
+                .with_base_url(&base_url)
+                .unwrap()
+                .with_host_circuit_breaker(breaker.clone()),
+        );
+
+        for target in [
+            "https://a.example/",
+            "https://a.example/",
+            "https://b.example/",
+        ] {
+            let error = capture.fetch_image(target, None).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::Transport, "{error}");
+        }
+        assert!(breaker.check("a.example", Instant::now()).is_ok());
+        assert!(breaker.check("b.example", Instant::now()).is_ok());
+    }
+}
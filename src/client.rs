@@ -0,0 +1,3132 @@
+#[cfg(feature = "http")]
+use reqwest::Client;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(feature = "http")]
+use tokio::sync::oneshot;
+use url::Url;
+
+use crate::builder::CaptureBuilder;
+#[cfg(feature = "http")]
+use crate::cache::DiskCache;
+#[cfg(feature = "http")]
+use crate::circuit::{self, HostCircuitBreaker};
+#[cfg(feature = "http")]
+use crate::concurrency::RequestSlots;
+use crate::credentials::{Credentials, CredentialsProvider, SharedCredentials};
+use crate::error::invalid_option;
+#[cfg(feature = "http")]
+use crate::metrics::{FetchMetric, Metric, MetricsHook};
+#[cfg(feature = "http")]
+use crate::overrides::{self, FetchOverrides, FetchSettings};
+#[cfg(feature = "http")]
+use crate::payload;
+#[cfg(feature = "http")]
+use crate::rate_limit::RateLimiter;
+use crate::redirect::RedirectPolicy;
+#[cfg(feature = "http")]
+use crate::retry;
+use crate::retry::RetryPolicy;
+#[cfg(feature = "http")]
+use crate::rt::{self, Instant, SystemTime};
+#[cfg(feature = "http")]
+use crate::shadow::{self, Observation, ShadowConfig};
+use crate::signing::{self, RequestType};
+#[cfg(feature = "http")]
+use crate::{
+    AnimatedFormat, ContentFormat, ContentResponse, ErrorKind, FetchInfo, FullPagePlan,
+    MetadataResponse, ThemedScreenshots,
+};
+use crate::{
+    AnimatedOptions, CaptureError, CaptureProfile, ContentOptions, MetadataOptions, PdfOptions,
+    ProfileRegistry, RequestOptions, Result, ScreenshotOptions, ScreenshotPair,
+};
+
+/// Capture API host that requests are sent to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    #[default]
+    Cdn,
+    Edge,
+}
+
+impl Endpoint {
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            Endpoint::Cdn => Capture::API_URL,
+            Endpoint::Edge => Capture::EDGE_URL,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureOptions {
+    pub use_edge: bool,
+    /// Replaces `https://cdn.capture.page`, e.g. with a proxy or a
+    /// self-hosted compatible endpoint. See [`with_base_url`](Self::with_base_url).
+    pub base_url: Option<Url>,
+    /// Replaces `https://edge.capture.page`, for the edge endpoint and the
+    /// sessions API.
+    pub edge_base_url: Option<Url>,
+    pub timeout: Option<Duration>,
+    /// Deadlines for particular request types, e.g. a long one for animated
+    /// captures. Types without an entry use `timeout`.
+    pub type_timeouts: HashMap<RequestType, Duration>,
+    /// Deadline for connecting to the capture API, separate from `timeout`.
+    /// Applies to the crate-built HTTP client only, not one from `client`.
+    pub connect_timeout: Option<Duration>,
+    /// Deadline for each read of a response, restarted whenever data
+    /// arrives. Applies to the crate-built HTTP client only.
+    pub read_timeout: Option<Duration>,
+    /// Most idle connections kept open per host. Like the timeouts above,
+    /// the pool settings apply to the crate-built HTTP client only.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept open.
+    pub pool_idle_timeout: Option<Duration>,
+    /// Interval of TCP keepalive probes on open connections.
+    pub tcp_keepalive: Option<Duration>,
+    /// Speaks HTTP/2 from the first request instead of negotiating it, for
+    /// the crate-built HTTP client.
+    pub http2: bool,
+    /// Interval of HTTP/2 keep-alive pings on open connections.
+    pub http2_keep_alive: Option<Duration>,
+    /// Whether to disable Nagle's algorithm; reqwest's default is on.
+    pub tcp_nodelay: Option<bool>,
+    /// Addresses to connect to for these hosts instead of looking them up
+    /// in DNS, for the crate-built HTTP client. See
+    /// [`with_resolve`](Self::with_resolve).
+    pub resolve: HashMap<String, Vec<SocketAddr>>,
+    /// Redirects the crate-built HTTP client follows. A client from
+    /// `client` keeps its own policy.
+    pub redirect_policy: RedirectPolicy,
+    /// HTTP client to send requests with, e.g. one shared across an
+    /// application. It is used as is: never rebuilt, wrapped or replaced,
+    /// and shared by every clone of the [`Capture`].
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub client: Option<Arc<Client>>,
+    /// Mirrors a sample of fetches to another endpoint for comparison.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub shadow: Option<ShadowConfig>,
+    /// Fails fast for target hosts that keep failing.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub host_circuit_breaker: Option<HostCircuitBreaker>,
+    /// Caps requests per window on the client side.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub rate_limiter: Option<RateLimiter>,
+    /// Receives measurements of the client's requests, such as how long
+    /// each waited for a request slot.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub metrics: Option<MetricsHook>,
+    /// Keeps responses on disk and answers repeated fetches from there.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub disk_cache: Option<DiskCache>,
+    /// Retries failed fetches. Without one, each fetch is attempted once.
+    pub retry_policy: Option<RetryPolicy>,
+    /// Fails fetches whose body is longer than this many bytes.
+    pub max_response_bytes: Option<usize>,
+    /// Checks the leading bytes of image, PDF and animated responses and
+    /// fails with [`CaptureError::UnexpectedBody`] when they are not such a
+    /// file, e.g. a JSON error. On by default; turn it off for formats the
+    /// check does not know.
+    pub validate_payloads: bool,
+    /// Caps simultaneous requests of the client and its shared clones.
+    /// Further requests wait for a slot instead of being sent.
+    pub max_concurrent_requests: Option<usize>,
+    /// Named capture configurations for
+    /// [`Capture::capture_with_profile`].
+    pub profiles: ProfileRegistry,
+    /// Options sent with every capture URL, of any request type. See
+    /// [`with_default_options`](Self::with_default_options).
+    pub default_options: RequestOptions,
+    /// Records a [`Provenance`](crate::Provenance) for every response of
+    /// [`Capture::execute_batch`] in [`BatchReport::provenance`](crate::BatchReport::provenance).
+    pub provenance: bool,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            use_edge: false,
+            base_url: None,
+            edge_base_url: None,
+            timeout: None,
+            type_timeouts: HashMap::new(),
+            connect_timeout: None,
+            read_timeout: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            http2: false,
+            http2_keep_alive: None,
+            tcp_nodelay: None,
+            resolve: HashMap::new(),
+            redirect_policy: RedirectPolicy::default(),
+            #[cfg(feature = "http")]
+            client: None,
+            #[cfg(feature = "http")]
+            shadow: None,
+            #[cfg(feature = "http")]
+            host_circuit_breaker: None,
+            #[cfg(feature = "http")]
+            rate_limiter: None,
+            #[cfg(feature = "http")]
+            metrics: None,
+            #[cfg(feature = "http")]
+            disk_cache: None,
+            retry_policy: None,
+            max_response_bytes: None,
+            validate_payloads: true,
+            max_concurrent_requests: None,
+            profiles: ProfileRegistry::new(),
+            default_options: RequestOptions::new(),
+            provenance: false,
+        }
+    }
+}
+
+impl CaptureOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_edge(mut self) -> Self {
+        self.use_edge = true;
+        self
+    }
+
+    /// Sends CDN requests to `base_url` instead of the capture API host.
+    /// Signed URLs keep their path and token, so only the host part
+    /// changes. Fails with [`CaptureError::UrlError`] when `base_url` does
+    /// not parse and with [`CaptureError::InvalidOption`] when it is not an
+    /// http(s) URL without a query or fragment.
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self> {
+        self.base_url = Some(parse_base_url("base_url", base_url)?);
+        Ok(self)
+    }
+
+    /// Like [`with_base_url`](Self::with_base_url), for the edge endpoint.
+    pub fn with_edge_base_url(mut self, edge_base_url: &str) -> Result<Self> {
+        self.edge_base_url = Some(parse_base_url("edge_base_url", edge_base_url)?);
+        Ok(self)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the deadline of `request_type` requests, in place of `timeout`.
+    pub fn with_timeout_for(mut self, request_type: RequestType, timeout: Duration) -> Self {
+        self.type_timeouts.insert(request_type, timeout);
+        self
+    }
+
+    /// Fails requests that cannot connect within `connect_timeout`, however
+    /// long `timeout` allows for the whole request.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Fails requests whose response stalls for `read_timeout`, e.g. a large
+    /// PDF allowed a long `timeout` that stops arriving.
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    pub fn with_pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = Some(pool_max_idle_per_host);
+        self
+    }
+
+    pub fn with_pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    pub fn with_tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+
+    pub fn with_http2(mut self, http2: bool) -> Self {
+        self.http2 = http2;
+        self
+    }
+
+    pub fn with_http2_keep_alive(mut self, interval: Duration) -> Self {
+        self.http2_keep_alive = Some(interval);
+        self
+    }
+
+    pub fn with_tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = Some(tcp_nodelay);
+        self
+    }
+
+    /// Connects to `addrs` for `host`, e.g. `edge.capture.page`, instead
+    /// of resolving it; call once per host to pin several. A port of 0
+    /// keeps the port of the URL. Replaces earlier addresses of `host`.
+    pub fn with_resolve(
+        mut self,
+        host: impl Into<String>,
+        addrs: impl IntoIterator<Item = SocketAddr>,
+    ) -> Self {
+        self.resolve
+            .insert(host.into(), addrs.into_iter().collect());
+        self
+    }
+
+    pub fn with_redirect_policy(mut self, redirect_policy: RedirectPolicy) -> Self {
+        self.redirect_policy = redirect_policy;
+        self
+    }
+
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_client(mut self, client: impl Into<Arc<Client>>) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_shadow(mut self, shadow: ShadowConfig) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_host_circuit_breaker(mut self, breaker: HostCircuitBreaker) -> Self {
+        self.host_circuit_breaker = Some(breaker);
+        self
+    }
+
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_metrics(mut self, metrics: MetricsHook) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_disk_cache(mut self, disk_cache: DiskCache) -> Self {
+        self.disk_cache = Some(disk_cache);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = Some(max_response_bytes);
+        self
+    }
+
+    pub fn with_payload_validation(mut self, validate_payloads: bool) -> Self {
+        self.validate_payloads = validate_payloads;
+        self
+    }
+
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    pub fn with_profiles(mut self, profiles: ProfileRegistry) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    pub fn with_profile(mut self, name: impl Into<String>, profile: CaptureProfile) -> Self {
+        self.profiles.register(name, profile);
+        self
+    }
+
+    /// Sends `default_options` with every capture URL, whatever its request
+    /// type, e.g. `blockAds` or a standard viewport. Options of the call are
+    /// applied on top, so they win on the same key; for structured options
+    /// that includes their `additional_options`, which already take
+    /// precedence over the typed fields. A call can drop a default by
+    /// setting its key to `null`.
+    pub fn with_default_options(mut self, default_options: RequestOptions) -> Self {
+        self.default_options = default_options;
+        self
+    }
+
+    pub fn with_provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+}
+
+fn parse_base_url(field: &str, base_url: &str) -> Result<Url> {
+    let url = Url::parse(base_url)?;
+    if !matches!(url.scheme(), "http" | "https")
+        || url.query().is_some()
+        || url.fragment().is_some()
+    {
+        return Err(invalid_option(
+            field,
+            "must be an http or https URL without a query or fragment",
+        ));
+    }
+    Ok(url)
+}
+
+/// Whether `url` is the official host of either endpoint, the only base
+/// URLs a [`Capture::url_builder_only`] client may sign for.
+pub(crate) fn is_official_base_url(url: &Url) -> bool {
+    [Capture::API_URL, Capture::EDGE_URL]
+        .iter()
+        .any(|official| url.as_str().trim_end_matches('/') == *official)
+}
+
+/// Cloning shares transport state (the HTTP client's connection pool, the
+/// shadow sampler, host circuit breaker state, request slots and the
+/// credentials) while configuration is copied, so builder calls such as
+/// `with_edge` on a clone never affect the original. See
+/// [`Capture::shared`] and [`Capture::detached`].
+#[derive(Clone)]
+pub struct Capture {
+    /// Replaced by [`Capture::set_credentials`].
+    pub(crate) credentials: SharedCredentials,
+    /// Set by [`Capture::with_provider`], asked again on [`Capture::refresh`].
+    pub(crate) credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+    pub(crate) options: CaptureOptions,
+    #[cfg(feature = "http")]
+    pub(crate) client: Arc<Client>,
+    /// Crate-built client that does not follow redirects, used to read the
+    /// stored object URL of `s3Redirect` captures. `None` with a client from
+    /// `with_client`, which is then used for those calls too.
+    #[cfg(feature = "http")]
+    pub(crate) redirectless_client: Option<Client>,
+    #[cfg(feature = "http")]
+    pub(crate) request_slots: Option<RequestSlots>,
+    /// Set by [`Capture::url_builder_only`].
+    pub(crate) fetch_disabled: bool,
+}
+
+impl fmt::Debug for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let credentials = self.credentials.current();
+        f.debug_struct("Capture")
+            .field("key", &credentials.key)
+            .field("secret", &credentials.secret)
+            .field("endpoint", &self.endpoint())
+            .field("url_builder_only", &self.fetch_disabled)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Capture {
+    pub(crate) const API_URL: &'static str = "https://cdn.capture.page";
+    pub(crate) const EDGE_URL: &'static str = "https://edge.capture.page";
+
+    /// Like [`new`](Self::new), but fails with
+    /// [`CaptureError::MissingCredentials`] right away when the key or
+    /// secret is empty or only whitespace.
+    pub fn try_new(key: String, secret: String) -> Result<Self> {
+        Self::builder().key(key).secret(secret).build()
+    }
+
+    pub fn new(key: String, secret: String) -> Self {
+        Self::with_options(key, secret, CaptureOptions::default())
+    }
+
+    pub fn with_options(key: String, secret: String, options: CaptureOptions) -> Self {
+        Self::builder()
+            .key(key)
+            .secret(secret)
+            .options(options)
+            .build_unchecked()
+    }
+
+    /// Starts configuring a client; see [`CaptureBuilder`].
+    pub fn builder() -> CaptureBuilder {
+        CaptureBuilder::new()
+    }
+
+    /// A client that signs URLs but never sends a request, for code that
+    /// holds the secret only to hand out capture URLs. The `build_*`
+    /// methods work as usual; every fetch and sessions call fails with
+    /// [`CaptureError::FetchDisabled`] before anything leaves the process.
+    /// Clones, including [`detached`](Self::detached) ones, keep the mode.
+    pub fn url_builder_only(key: String, secret: String) -> Self {
+        Self::builder()
+            .key(key)
+            .secret(secret)
+            .url_builder_only(true)
+            .build_unchecked()
+    }
+
+    /// Creates a client whose credentials come from `provider`. They are
+    /// fetched once here and cached; [`refresh`](Self::refresh) fetches
+    /// them again. Fails with [`CaptureError::MissingCredentials`] when the
+    /// provider returns an empty key or secret.
+    pub async fn with_provider(
+        provider: Arc<dyn CredentialsProvider>,
+        options: CaptureOptions,
+    ) -> Result<Self> {
+        let capture = Self {
+            credentials_provider: Some(provider),
+            ..Self::builder().options(options).build_unchecked()
+        };
+        capture.refresh().await?;
+        Ok(capture)
+    }
+
+    pub fn is_url_builder_only(&self) -> bool {
+        self.fetch_disabled
+    }
+
+    /// Reads the credentials from the `CAPTURE_KEY` and `CAPTURE_SECRET`
+    /// environment variables. `CAPTURE_USE_EDGE` set to `1`, `true`, `yes`
+    /// or `on` selects the edge endpoint.
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with_options(CaptureOptions::default())
+    }
+
+    /// Like [`from_env`](Self::from_env), with `options` for everything the
+    /// environment does not set.
+    pub fn from_env_with_options(mut options: CaptureOptions) -> Result<Self> {
+        let key = env_var(KEY_ENV_VAR)?;
+        let secret = env_var(SECRET_ENV_VAR)?;
+        if std::env::var(USE_EDGE_ENV_VAR).is_ok_and(|value| is_truthy(&value)) {
+            options.use_edge = true;
+        }
+        Self::builder()
+            .key(key)
+            .secret(secret)
+            .options(options)
+            .build()
+    }
+
+    /// Returns a clone that shares this instance's transport state. Same as
+    /// [`Clone::clone`].
+    pub fn shared(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns a clone with its own transport state: a new connection pool,
+    /// shadow sampler, host circuit breaker state, request slots and a copy
+    /// of the credentials that later rotations of this one do not reach. A
+    /// client passed via `with_client` cannot be rebuilt and is still shared.
+    pub fn detached(&self) -> Self {
+        let options = self.options.clone();
+        #[cfg(feature = "http")]
+        let options = CaptureOptions {
+            shadow: options.shadow.map(|shadow| shadow.detached()),
+            host_circuit_breaker: options
+                .host_circuit_breaker
+                .map(|breaker| breaker.detached()),
+            ..options
+        };
+
+        Self {
+            fetch_disabled: self.fetch_disabled,
+            credentials: SharedCredentials::new((*self.credentials.current()).clone()),
+            credentials_provider: self.credentials_provider.clone(),
+            ..Self::with_options(String::new(), String::new(), options)
+        }
+    }
+
+    /// Returns a clone that signs with `key` and `secret`, e.g. one per
+    /// customer workspace. It shares the HTTP client, and so the connection
+    /// pool, and the rest of the transport state with this one;
+    /// [`set_credentials`](Self::set_credentials) on either does not reach
+    /// the other.
+    pub fn with_credentials(&self, key: String, secret: String) -> Self {
+        Self {
+            credentials: SharedCredentials::new(Credentials::new(key, secret)),
+            credentials_provider: None,
+            ..self.clone()
+        }
+    }
+
+    /// Replaces the key and secret of this client and of the clones sharing
+    /// its state, e.g. after a scheduled rotation, keeping the HTTP client
+    /// and its connections. URLs built from then on are signed with the new
+    /// pair; a build already in progress finishes with the old one. Fails
+    /// with [`CaptureError::MissingCredentials`] for an empty or
+    /// whitespace-only key or secret, leaving the current pair in place.
+    pub fn set_credentials(&self, key: String, secret: String) -> Result<()> {
+        self.replace_credentials(Credentials::new(key, secret))
+    }
+
+    /// Asks the provider of a [`with_provider`](Self::with_provider) client
+    /// for the credentials again, e.g. on a schedule, and signs with them
+    /// from then on, like [`set_credentials`](Self::set_credentials). Does
+    /// nothing for clients created with fixed credentials.
+    pub async fn refresh(&self) -> Result<()> {
+        let Some(provider) = &self.credentials_provider else {
+            return Ok(());
+        };
+        self.replace_credentials(provider.credentials().await?)
+    }
+
+    fn replace_credentials(&self, credentials: Credentials) -> Result<()> {
+        if credentials.is_blank() {
+            return Err(CaptureError::MissingCredentials);
+        }
+        self.credentials.replace(credentials);
+        Ok(())
+    }
+
+    /// The current credentials, as one consistent pair.
+    pub(crate) fn credentials(&self) -> Result<Arc<Credentials>> {
+        let credentials = self.credentials.current();
+        if credentials.is_missing() {
+            return Err(CaptureError::MissingCredentials);
+        }
+        Ok(credentials)
+    }
+
+    pub fn with_edge(mut self) -> Self {
+        self.options.use_edge = true;
+        self
+    }
+
+    /// Sets the total deadline of each request. It is applied per request,
+    /// so the HTTP client, including one from `with_client`, is kept.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the total deadline of `request_type` requests, in place of the
+    /// one from [`with_timeout`](Self::with_timeout). Applied per request
+    /// like it.
+    pub fn with_timeout_for(mut self, request_type: RequestType, timeout: Duration) -> Self {
+        self.options.type_timeouts.insert(request_type, timeout);
+        self
+    }
+
+    /// Sends all requests with `client`. See [`CaptureOptions::client`].
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn with_client(mut self, client: impl Into<Arc<Client>>) -> Self {
+        let client = client.into();
+        self.client = client.clone();
+        self.options.client = Some(client);
+        self.redirectless_client = None;
+        self
+    }
+
+    pub(crate) fn endpoint(&self) -> Endpoint {
+        if self.options.use_edge {
+            Endpoint::Edge
+        } else {
+            Endpoint::Cdn
+        }
+    }
+
+    /// Base URL of `endpoint`: the configured override, without trailing
+    /// slashes, or the official host.
+    pub(crate) fn base_url(&self, endpoint: Endpoint) -> &str {
+        let configured = match endpoint {
+            Endpoint::Cdn => &self.options.base_url,
+            Endpoint::Edge => &self.options.edge_base_url,
+        };
+        configured.as_ref().map_or(endpoint.base_url(), |url| {
+            url.as_str().trim_end_matches('/')
+        })
+    }
+
+    pub(crate) fn build_url(
+        &self,
+        request_type: RequestType,
+        url: &str,
+        request_options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url_at(self.endpoint(), request_type, url, request_options)
+    }
+
+    pub(crate) fn build_url_at(
+        &self,
+        endpoint: Endpoint,
+        request_type: RequestType,
+        url: &str,
+        request_options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        let credentials = self.credentials()?;
+        signing::sign_target(
+            self.base_url(endpoint),
+            &credentials.key,
+            credentials.secret.expose(),
+            request_type,
+            url,
+            self.with_default_options(request_options),
+        )
+    }
+
+    /// `request_options` applied on top of the client's default options.
+    fn with_default_options(&self, request_options: Option<&RequestOptions>) -> RequestOptions {
+        let mut options = self.options.default_options.clone();
+        if let Some(request_options) = request_options {
+            options.extend(
+                request_options
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+        }
+        options
+    }
+
+    /// Builds a capture URL that renders `html` instead of fetching a page.
+    /// The document travels in the signed `html` parameter, so it is covered
+    /// by the token like any other option.
+    pub(crate) fn build_url_from_html(
+        &self,
+        request_type: RequestType,
+        html: &str,
+        request_options: RequestOptions,
+    ) -> Result<String> {
+        let credentials = self.credentials()?;
+
+        if html.trim().is_empty() {
+            return Err(invalid_option("html", "must not be empty"));
+        }
+
+        let mut options = self.with_default_options(Some(&request_options));
+        options.remove("url");
+        options.insert(
+            "html".to_string(),
+            serde_json::Value::String(html.to_string()),
+        );
+
+        let capture_url = signing::sign(
+            self.base_url(self.endpoint()),
+            &credentials.key,
+            credentials.secret.expose(),
+            request_type,
+            &options,
+        );
+        if capture_url.len() > MAX_HTML_CAPTURE_URL_BYTES {
+            return Err(invalid_option(
+                "html",
+                &format!(
+                    "makes the capture URL {} bytes long, over the {MAX_HTML_CAPTURE_URL_BYTES} byte limit; host larger documents and capture them by URL",
+                    capture_url.len()
+                ),
+            ));
+        }
+
+        Ok(capture_url)
+    }
+
+    pub fn build_image_url(&self, url: &str, options: Option<&RequestOptions>) -> Result<String> {
+        self.build_url(RequestType::Image, url, options)
+    }
+
+    pub fn build_pdf_url(&self, url: &str, options: Option<&RequestOptions>) -> Result<String> {
+        self.build_url(RequestType::Pdf, url, options)
+    }
+
+    pub fn build_content_url(&self, url: &str, options: Option<&RequestOptions>) -> Result<String> {
+        self.build_url(RequestType::Content, url, options)
+    }
+
+    pub fn build_metadata_url(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url(RequestType::Metadata, url, options)
+    }
+
+    pub fn build_animated_url(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url(RequestType::Animated, url, options)
+    }
+
+    // Per-call endpoint variants; fetches take the endpoint from
+    // `FetchOverrides::with_endpoint`.
+    pub fn build_image_url_at(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url_at(endpoint, RequestType::Image, url, options)
+    }
+
+    pub fn build_pdf_url_at(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url_at(endpoint, RequestType::Pdf, url, options)
+    }
+
+    pub fn build_content_url_at(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url_at(endpoint, RequestType::Content, url, options)
+    }
+
+    pub fn build_metadata_url_at(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url_at(endpoint, RequestType::Metadata, url, options)
+    }
+
+    pub fn build_animated_url_at(
+        &self,
+        endpoint: Endpoint,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<String> {
+        self.build_url_at(endpoint, RequestType::Animated, url, options)
+    }
+
+    // Structured options methods
+    pub fn build_screenshot_url(
+        &self,
+        url: &str,
+        options: Option<&ScreenshotOptions>,
+    ) -> Result<String> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        self.build_url(RequestType::Image, url, request_options.as_ref())
+    }
+
+    pub fn build_pdf_url_structured(
+        &self,
+        url: &str,
+        options: Option<&PdfOptions>,
+    ) -> Result<String> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        self.build_url(RequestType::Pdf, url, request_options.as_ref())
+    }
+
+    pub fn build_content_url_structured(
+        &self,
+        url: &str,
+        options: Option<&ContentOptions>,
+    ) -> Result<String> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        self.build_url(RequestType::Content, url, request_options.as_ref())
+    }
+
+    pub fn build_metadata_url_structured(
+        &self,
+        url: &str,
+        options: Option<&MetadataOptions>,
+    ) -> Result<String> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        self.build_url(RequestType::Metadata, url, request_options.as_ref())
+    }
+
+    pub fn build_animated_url_structured(
+        &self,
+        url: &str,
+        options: Option<&AnimatedOptions>,
+    ) -> Result<String> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        self.build_url(RequestType::Animated, url, request_options.as_ref())
+    }
+
+    /// Builds a screenshot URL that renders `html` directly, e.g. a document
+    /// generated in memory. Documents whose encoded form would push the URL
+    /// past [`MAX_HTML_CAPTURE_URL_BYTES`] are rejected.
+    pub fn build_image_url_from_html(
+        &self,
+        html: &str,
+        options: &ScreenshotOptions,
+    ) -> Result<String> {
+        self.build_url_from_html(RequestType::Image, html, options.try_to_request_options()?)
+    }
+
+    /// Builds a PDF URL that renders `html` directly; see
+    /// [`build_image_url_from_html`](Self::build_image_url_from_html).
+    pub fn build_pdf_url_from_html(&self, html: &str, options: &PdfOptions) -> Result<String> {
+        self.build_url_from_html(RequestType::Pdf, html, options.try_to_request_options()?)
+    }
+
+    /// Builds the light and dark screenshot URLs for `url`. Both use `base`
+    /// with only `dark_mode` changed.
+    pub fn build_screenshot_pair_urls(
+        &self,
+        url: &str,
+        base: &ScreenshotOptions,
+    ) -> Result<ScreenshotPair<String>> {
+        Ok(ScreenshotPair {
+            light: self.build_screenshot_url(url, Some(&themed_options(base, false)))?,
+            dark: self.build_screenshot_url(url, Some(&themed_options(base, true)))?,
+        })
+    }
+}
+
+#[cfg(feature = "http")]
+impl Capture {
+    pub async fn fetch_image_from_html(
+        &self,
+        html: &str,
+        options: &ScreenshotOptions,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_image_url_from_html(html, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Image).await
+    }
+
+    pub async fn fetch_pdf_from_html(&self, html: &str, options: &PdfOptions) -> Result<Vec<u8>> {
+        let capture_url = self.build_pdf_url_from_html(html, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Pdf).await
+    }
+
+    pub async fn fetch_image(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_image_url(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Image).await
+    }
+
+    pub async fn fetch_pdf(&self, url: &str, options: Option<&RequestOptions>) -> Result<Vec<u8>> {
+        let capture_url = self.build_pdf_url(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Pdf).await
+    }
+
+    pub async fn fetch_content(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<ContentResponse> {
+        let capture_url = self.build_content_url(url, options)?;
+        let bytes = self.fetch_bytes(&capture_url, RequestType::Content).await?;
+        ContentResponse::from_slice(&bytes, url)
+    }
+
+    pub async fn fetch_metadata(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<MetadataResponse> {
+        let capture_url = self.build_metadata_url(url, options)?;
+        let bytes = self
+            .fetch_bytes(&capture_url, RequestType::Metadata)
+            .await?;
+        MetadataResponse::from_slice(&bytes)
+    }
+
+    pub async fn fetch_animated(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_animated_url(url, options)?;
+        let format = options
+            .and_then(|options| options.get("format"))
+            .and_then(|format| format.as_str())
+            .and_then(|format| format.parse().ok());
+        self.fetch_animated_bytes(&capture_url, format).await
+    }
+
+    // Structured options fetch methods
+    pub async fn fetch_screenshot(
+        &self,
+        url: &str,
+        options: Option<&ScreenshotOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_screenshot_url(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Image).await
+    }
+
+    pub async fn fetch_pdf_structured(
+        &self,
+        url: &str,
+        options: Option<&PdfOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_pdf_url_structured(url, options)?;
+        self.fetch_bytes(&capture_url, RequestType::Pdf).await
+    }
+
+    pub async fn fetch_animated_structured(
+        &self,
+        url: &str,
+        options: Option<&AnimatedOptions>,
+    ) -> Result<Vec<u8>> {
+        let capture_url = self.build_animated_url_structured(url, options)?;
+        let format = options.and_then(|options| options.animated_format);
+        self.fetch_animated_bytes(&capture_url, format).await
+    }
+
+    /// Fetches an animated capture and, with payload validation on, checks
+    /// that it is in the requested container.
+    async fn fetch_animated_bytes(
+        &self,
+        capture_url: &str,
+        format: Option<AnimatedFormat>,
+    ) -> Result<Vec<u8>> {
+        let settings = self.fetch_settings(None);
+        let (bytes, info) = self
+            .fetch_bytes_with_info(capture_url, &settings, RequestType::Animated)
+            .await?;
+        if let Some(format) = format.filter(|_| settings.validate_payload) {
+            payload::check_animated(format, info.content_type.as_deref(), &bytes)?;
+        }
+        Ok(bytes)
+    }
+
+    /// Fetches a PDF along with response details such as the upstream
+    /// `Content-Disposition`, for services that pass the file on to browsers.
+    pub async fn fetch_pdf_with_info(
+        &self,
+        url: &str,
+        options: Option<&PdfOptions>,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        let capture_url = self.build_pdf_url_structured(url, options)?;
+        let settings = self.fetch_settings(None);
+        self.fetch_bytes_with_info(&capture_url, &settings, RequestType::Pdf)
+            .await
+    }
+
+    /// Like [`fetch_screenshot`](Self::fetch_screenshot), with `overrides`
+    /// applied to this call only.
+    pub async fn fetch_screenshot_with_overrides(
+        &self,
+        url: &str,
+        options: Option<&ScreenshotOptions>,
+        overrides: &FetchOverrides,
+    ) -> Result<Vec<u8>> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        let (bytes, _) = self
+            .fetch_with_overrides(RequestType::Image, url, request_options.as_ref(), overrides)
+            .await?;
+        Ok(bytes)
+    }
+
+    /// Like [`fetch_pdf_structured`](Self::fetch_pdf_structured), with
+    /// `overrides` applied to this call only.
+    pub async fn fetch_pdf_with_overrides(
+        &self,
+        url: &str,
+        options: Option<&PdfOptions>,
+        overrides: &FetchOverrides,
+    ) -> Result<Vec<u8>> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        let (bytes, _) = self
+            .fetch_with_overrides(RequestType::Pdf, url, request_options.as_ref(), overrides)
+            .await?;
+        Ok(bytes)
+    }
+
+    /// Like [`fetch_content_structured`](Self::fetch_content_structured),
+    /// with `overrides` applied to this call only.
+    pub async fn fetch_content_with_overrides(
+        &self,
+        url: &str,
+        options: Option<&ContentOptions>,
+        overrides: &FetchOverrides,
+    ) -> Result<ContentResponse> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        let (bytes, _) = self
+            .fetch_with_overrides(
+                RequestType::Content,
+                url,
+                request_options.as_ref(),
+                overrides,
+            )
+            .await?;
+        ContentResponse::from_slice(&bytes, url)
+    }
+
+    /// Like [`fetch_metadata_structured`](Self::fetch_metadata_structured),
+    /// with `overrides` applied to this call only.
+    pub async fn fetch_metadata_with_overrides(
+        &self,
+        url: &str,
+        options: Option<&MetadataOptions>,
+        overrides: &FetchOverrides,
+    ) -> Result<MetadataResponse> {
+        let request_options = options.map(|o| o.try_to_request_options()).transpose()?;
+        let (bytes, _) = self
+            .fetch_with_overrides(
+                RequestType::Metadata,
+                url,
+                request_options.as_ref(),
+                overrides,
+            )
+            .await?;
+        MetadataResponse::from_slice(&bytes)
+    }
+
+    /// Fetches any capture type with `overrides` applied, returning the raw
+    /// body and response details, including the override labels.
+    pub async fn fetch_with_overrides(
+        &self,
+        request_type: RequestType,
+        url: &str,
+        options: Option<&RequestOptions>,
+        overrides: &FetchOverrides,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        let settings = self.fetch_settings(Some(overrides));
+        let capture_url = self.build_url_at(settings.endpoint, request_type, url, options)?;
+        self.fetch_bytes_with_info(&capture_url, &settings, request_type)
+            .await
+    }
+
+    /// Fetches the light and dark screenshots concurrently, failing if
+    /// either side fails.
+    pub async fn fetch_screenshot_pair(
+        &self,
+        url: &str,
+        base: &ScreenshotOptions,
+    ) -> Result<ScreenshotPair> {
+        let light_options = themed_options(base, false);
+        let dark_options = themed_options(base, true);
+        let (light, dark) = tokio::try_join!(
+            self.fetch_screenshot(url, Some(&light_options)),
+            self.fetch_screenshot(url, Some(&dark_options)),
+        )?;
+
+        Ok(ScreenshotPair { light, dark })
+    }
+
+    /// Fetches theme-aware screenshots of `url`: `options` as given with
+    /// `dark_mode` off and on, requested concurrently through
+    /// [`Capture::fetch_screenshot_pair`].
+    ///
+    /// Fails fast with the first error. Use
+    /// [`Capture::fetch_screenshot_pair_partial`] to get each side's result.
+    pub async fn fetch_screenshot_themes(
+        &self,
+        url: &str,
+        options: &ScreenshotOptions,
+    ) -> Result<ThemedScreenshots> {
+        self.fetch_screenshot_pair(url, options).await
+    }
+
+    /// Like [`Capture::fetch_screenshot_pair`], but waits for both requests
+    /// and reports each side's outcome separately.
+    pub async fn fetch_screenshot_pair_partial(
+        &self,
+        url: &str,
+        base: &ScreenshotOptions,
+    ) -> ScreenshotPair<Result<Vec<u8>>> {
+        let light_options = themed_options(base, false);
+        let dark_options = themed_options(base, true);
+        let (light, dark) = tokio::join!(
+            self.fetch_screenshot(url, Some(&light_options)),
+            self.fetch_screenshot(url, Some(&dark_options)),
+        );
+
+        ScreenshotPair { light, dark }
+    }
+
+    /// Fetches the page metadata and returns screenshot options sized for a
+    /// full-page capture of `url`.
+    ///
+    /// Full-page plans are capped with `max_height` at the reported height,
+    /// and never above 20000 pixels, so a runaway page cannot produce a
+    /// runaway capture.
+    ///
+    /// Configuration errors ([`ErrorKind::Config`](crate::ErrorKind::Config),
+    /// e.g. missing credentials or URL) are returned as errors. Any other
+    /// failure of the metadata request, including a `"success": false`
+    /// response, is not: the plan degrades to a full-page capture at a
+    /// 1280x800 viewport, capped at 20000 pixels, and carries the error as
+    /// its [`warning`](FullPagePlan::warning). The same defaults are used
+    /// when no height is reported.
+    pub async fn plan_full_page_capture(&self, url: &str) -> Result<FullPagePlan> {
+        let (metadata, warning) = match self.fetch_metadata(url, None).await {
+            Ok(metadata) => (Some(metadata), None),
+            Err(error) if error.kind() == ErrorKind::Config => return Err(error),
+            Err(error) => (None, Some(error)),
+        };
+
+        Ok(FullPagePlan {
+            options: plan_from_metadata(metadata.as_ref()),
+            warning,
+        })
+    }
+
+    pub async fn fetch_content_structured(
+        &self,
+        url: &str,
+        options: Option<&ContentOptions>,
+    ) -> Result<ContentResponse> {
+        let capture_url = self.build_content_url_structured(url, options)?;
+        let bytes = self.fetch_bytes(&capture_url, RequestType::Content).await?;
+        ContentResponse::from_slice(&bytes, url)
+    }
+
+    /// Fetches only the markdown of `url`, with `format` set to
+    /// [`ContentFormat::Markdown`] so the
+    /// HTML and text are not transferred.
+    pub async fn fetch_markdown(&self, url: &str, options: &ContentOptions) -> Result<String> {
+        let options = options.clone().format(ContentFormat::Markdown);
+        let content = self.fetch_content_structured(url, Some(&options)).await?;
+        Ok(content.markdown)
+    }
+
+    pub async fn fetch_metadata_structured(
+        &self,
+        url: &str,
+        options: Option<&MetadataOptions>,
+    ) -> Result<MetadataResponse> {
+        let capture_url = self.build_metadata_url_structured(url, options)?;
+        let bytes = self
+            .fetch_bytes(&capture_url, RequestType::Metadata)
+            .await?;
+        MetadataResponse::from_slice(&bytes)
+    }
+
+    /// Sends a capture GET request and turns non-2xx responses into
+    /// [`CaptureError::RequestFailed`], or [`CaptureError::TargetSiteError`]
+    /// when the target page is what failed, e.g. with `failOnStatus`.
+    /// Redirects left by the redirect policy fail with
+    /// [`CaptureError::RedirectNotFollowed`].
+    async fn check_capture_response(
+        response: reqwest::Response,
+        settings: &FetchSettings,
+    ) -> Result<reqwest::Response> {
+        let status = response.status();
+
+        if status == reqwest::StatusCode::NOT_MODIFIED && settings.if_none_match.is_some() {
+            return Ok(response);
+        }
+        if status.is_redirection() {
+            if !settings.follow_redirects {
+                return Ok(response);
+            }
+            return Err(CaptureError::RedirectNotFollowed {
+                status: status.as_u16(),
+                location: FetchInfo::from_response(&response).location,
+            });
+        }
+        if !status.is_success() {
+            let body_text = response.text().await.unwrap_or_default();
+            return Err(request_failure(status.as_u16(), &body_text));
+        }
+
+        Ok(response)
+    }
+
+    pub(crate) fn request(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> reqwest::RequestBuilder {
+        let client = match &self.redirectless_client {
+            Some(redirectless_client) if !settings.follow_redirects => redirectless_client,
+            _ => &*self.client,
+        };
+        let mut request = client.get(capture_url);
+        if let Some(timeout) = settings.timeout_for(request_type) {
+            request = request.timeout(timeout);
+        }
+        if let Some(accept) = &settings.accept {
+            request = request.header(reqwest::header::ACCEPT, accept);
+        }
+        if let Some(etag) = &settings.if_none_match {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        request
+    }
+
+    async fn fetch_bytes(&self, capture_url: &str, request_type: RequestType) -> Result<Vec<u8>> {
+        let settings = self.fetch_settings(None);
+        let (bytes, _) = self
+            .fetch_bytes_with_info(capture_url, &settings, request_type)
+            .await?;
+        Ok(bytes)
+    }
+
+    pub(crate) async fn fetch_bytes_with_info(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        if self.fetch_disabled {
+            return Err(CaptureError::FetchDisabled);
+        }
+        match &self.options.disk_cache {
+            Some(cache) => {
+                self.fetch_through_cache(cache, capture_url, settings, request_type)
+                    .await
+            }
+            None => {
+                self.fetch_uncached(capture_url, settings, request_type)
+                    .await
+            }
+        }
+    }
+
+    pub(crate) async fn fetch_uncached(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        retry::run(settings.retry_policy.as_ref(), || {
+            self.fetch_bytes_once(capture_url, settings, request_type)
+        })
+        .await
+    }
+
+    async fn fetch_bytes_once(
+        &self,
+        capture_url: &str,
+        settings: &FetchSettings,
+        request_type: RequestType,
+    ) -> Result<(Vec<u8>, FetchInfo)> {
+        let (_slot, queue_time) = match &self.request_slots {
+            Some(slots) => {
+                let (slot, queue_time) = rt::compat(slots.acquire()).await;
+                (Some(slot), queue_time)
+            }
+            None => (None, Duration::ZERO),
+        };
+        let breaker = self
+            .options
+            .host_circuit_breaker
+            .as_ref()
+            .and_then(|breaker| Some((breaker, circuit::target_host(capture_url)?)));
+        if let Some((breaker, host)) = &breaker {
+            breaker.check(host, Instant::now())?;
+        }
+        if let Some(rate_limiter) = &self.options.rate_limiter {
+            rate_limiter.acquire_async(SystemTime::now()).await?;
+        }
+
+        let shadow = self.start_shadow(capture_url, settings.endpoint);
+        let started = Instant::now();
+        let mut status = None;
+
+        let result = rt::compat(async {
+            let response = self
+                .request(capture_url, settings, request_type)
+                .send()
+                .await?;
+            status = Some(response.status().as_u16());
+            if !settings.follow_redirects
+                && Url::parse(capture_url).ok().as_ref() != Some(response.url())
+            {
+                return Err(CaptureError::RedirectFollowed {
+                    location: response.url().to_string(),
+                });
+            }
+            let response = Self::check_capture_response(response, settings).await?;
+            let info = FetchInfo {
+                labels: settings.labels.clone(),
+                queue_time,
+                ..FetchInfo::from_response(&response)
+            };
+            let bytes = overrides::read_body(response, settings.max_response_bytes).await?;
+            if settings.validate_payload && (200..300).contains(&info.status) {
+                payload::check(request_type, info.content_type.as_deref(), &bytes)?;
+            }
+            Ok((bytes, info))
+        })
+        .await;
+
+        if let Some(shadow) = shadow {
+            let compare_bodies = self
+                .options
+                .shadow
+                .as_ref()
+                .is_some_and(|config| config.compare_bodies);
+            let body = match &result {
+                Ok((bytes, _)) => Some(bytes.as_slice()).filter(|_| compare_bodies),
+                Err(_) => None,
+            };
+            let observation = Observation::new(status, body, started.elapsed());
+            let _ = shadow.send(observation);
+        }
+
+        if let Some((breaker, host)) = &breaker {
+            let failed = result.as_ref().is_err_and(circuit::is_host_failure);
+            breaker.record(host, failed, Instant::now());
+        }
+        if let Some(metrics) = &self.options.metrics {
+            metrics.record(&Metric::Fetch(&FetchMetric {
+                request_type,
+                status,
+                queue_time,
+                latency: started.elapsed(),
+            }));
+        }
+
+        result
+    }
+
+    /// Starts a shadow request for a sampled fetch. The shadow URL reuses
+    /// the primary URL's path and token, which do not depend on the base URL.
+    /// Like the primary request, it waits for a request slot and counts
+    /// against the rate limiter.
+    fn start_shadow(
+        &self,
+        capture_url: &str,
+        endpoint: Endpoint,
+    ) -> Option<oneshot::Sender<Observation>> {
+        let config = self.options.shadow.as_ref()?;
+        if config.target == endpoint || !config.should_sample() {
+            return None;
+        }
+
+        let path = capture_url.strip_prefix(self.base_url(endpoint))?;
+        shadow::spawn(
+            config,
+            self,
+            format!("{}{path}", self.base_url(config.target)),
+        )
+    }
+}
+
+const KEY_ENV_VAR: &str = "CAPTURE_KEY";
+const SECRET_ENV_VAR: &str = "CAPTURE_SECRET";
+const USE_EDGE_ENV_VAR: &str = "CAPTURE_USE_EDGE";
+
+/// The value of `name` without surrounding whitespace, treating an empty
+/// value as unset.
+fn env_var(name: &str) -> Result<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| CaptureError::MissingEnvVar {
+            name: name.to_string(),
+        })
+}
+
+fn is_truthy(value: &str) -> bool {
+    ["1", "true", "yes", "on"]
+        .iter()
+        .any(|truthy| value.trim().eq_ignore_ascii_case(truthy))
+}
+
+#[cfg(feature = "http")]
+pub(crate) fn build_client(options: &CaptureOptions) -> Client {
+    client_builder(options)
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// Redirects cannot be turned off per request, so redirect-sensitive calls
+/// use this separate client when the crate owns the HTTP client.
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) fn build_redirectless_client(options: &CaptureOptions) -> Client {
+    client_builder(options)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+/// The browser follows redirects whatever the client asks, so on wasm32 this
+/// is an ordinary client.
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+pub(crate) fn build_redirectless_client(options: &CaptureOptions) -> Client {
+    build_client(options)
+}
+
+/// The browser's fetch owns connections, TLS and redirects on wasm32, so only
+/// the per-request timeout of [`CaptureOptions::timeout`] applies there.
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+fn client_builder(_options: &CaptureOptions) -> reqwest::ClientBuilder {
+    Client::builder()
+}
+
+/// Applies the connection settings of `options` that `reqwest`'s async and
+/// blocking client builders both support. They are distinct types with the
+/// same methods, so this is a macro rather than a function.
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+macro_rules! shared_client_settings {
+    ($builder:expr, $options:expr) => {{
+        let options: &$crate::CaptureOptions = $options;
+        let mut builder = $builder.redirect(options.redirect_policy.to_reqwest());
+        #[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+        {
+            builder = builder.use_rustls_tls();
+        }
+        if let Some(connect_timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = options.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+        if let Some(pool_idle_timeout) = options.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(tcp_keepalive) = options.tcp_keepalive {
+            builder = builder.tcp_keepalive(tcp_keepalive);
+        }
+        if options.http2 {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(tcp_nodelay) = options.tcp_nodelay {
+            builder = builder.tcp_nodelay(tcp_nodelay);
+        }
+        for (host, addrs) in &options.resolve {
+            builder = builder.resolve_to_addrs(host, addrs);
+        }
+        builder
+    }};
+}
+#[cfg(feature = "blocking")]
+pub(crate) use shared_client_settings;
+
+/// The settings shared by every client the crate builds.
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+fn client_builder(options: &CaptureOptions) -> reqwest::ClientBuilder {
+    let mut builder = shared_client_settings!(Client::builder(), options);
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(read_timeout) = options.read_timeout {
+        builder = builder.read_timeout(read_timeout);
+    }
+    if let Some(interval) = options.http2_keep_alive {
+        builder = builder
+            .http2_keep_alive_interval(interval)
+            .http2_keep_alive_while_idle(true);
+    }
+    builder
+}
+
+/// Classifies a non-2xx capture response. The API reports a failing target
+/// page with `424 Failed Dependency`, or with the target's status in the
+/// error body as `{"error": "...", "targetStatus": 503}` or
+/// `{"error": "...", "target": {"status": 503}}`.
+#[cfg(feature = "http")]
+pub(crate) fn request_failure(status: u16, body_text: &str) -> CaptureError {
+    let message = capture_error_message(body_text);
+    let target_status = serde_json::from_str::<serde_json::Value>(body_text)
+        .ok()
+        .and_then(|body| {
+            body.get("targetStatus")
+                .or_else(|| body.get("target").and_then(|target| target.get("status")))
+                .and_then(serde_json::Value::as_u64)
+        })
+        .and_then(|target_status| u16::try_from(target_status).ok());
+
+    if status == 424 || target_status.is_some() {
+        CaptureError::TargetSiteError {
+            target_status,
+            message,
+        }
+    } else {
+        CaptureError::RequestFailed { status, message }
+    }
+}
+
+#[cfg(feature = "http")]
+fn capture_error_message(body_text: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body_text)
+        .ok()
+        .and_then(|body| {
+            body.get("error")
+                .or_else(|| body.get("message"))
+                .and_then(|value| value.as_str())
+                .map(ToOwned::to_owned)
+        })
+        .unwrap_or_else(|| body_text.chars().take(200).collect())
+}
+
+/// Longest capture URL built from an HTML document. Proxies and CDNs
+/// commonly refuse longer request lines.
+pub const MAX_HTML_CAPTURE_URL_BYTES: usize = 16 * 1024;
+
+#[cfg(feature = "http")]
+const PLAN_DEFAULT_VIEWPORT_WIDTH: u32 = 1280;
+#[cfg(feature = "http")]
+const PLAN_DEFAULT_VIEWPORT_HEIGHT: u32 = 800;
+#[cfg(feature = "http")]
+const PLAN_MAX_VIEWPORT_WIDTH: u32 = 3840;
+#[cfg(feature = "http")]
+const PLAN_MAX_PAGE_HEIGHT: u32 = 20_000;
+
+/// Picks screenshot options for a full-page capture from reported page
+/// dimensions. Full-page plans always set `max_height`: the reported height,
+/// capped at `PLAN_MAX_PAGE_HEIGHT`, which is also used when no height was
+/// reported. An implausible width falls back to the default viewport.
+#[cfg(feature = "http")]
+fn plan_from_metadata(metadata: Option<&MetadataResponse>) -> ScreenshotOptions {
+    let width = metadata
+        .and_then(MetadataResponse::content_width)
+        .filter(|width| (1..=PLAN_MAX_VIEWPORT_WIDTH).contains(width))
+        .unwrap_or(PLAN_DEFAULT_VIEWPORT_WIDTH);
+    let height = metadata
+        .and_then(MetadataResponse::content_height)
+        .filter(|height| *height > 0);
+
+    match height {
+        Some(height) if height <= PLAN_DEFAULT_VIEWPORT_HEIGHT => ScreenshotOptions {
+            vw: Some(width),
+            vh: Some(height),
+            full: Some(false),
+            ..Default::default()
+        },
+        _ => ScreenshotOptions {
+            vw: Some(width),
+            vh: Some(PLAN_DEFAULT_VIEWPORT_HEIGHT),
+            full: Some(true),
+            max_height: Some(height.map_or(PLAN_MAX_PAGE_HEIGHT, |height| {
+                height.min(PLAN_MAX_PAGE_HEIGHT)
+            })),
+            ..Default::default()
+        },
+    }
+}
+
+fn themed_options(base: &ScreenshotOptions, dark_mode: bool) -> ScreenshotOptions {
+    ScreenshotOptions {
+        dark_mode: Some(dark_mode),
+        ..base.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "http")]
+    use crate::{RateLimitState, RateLimitStore, ShadowReport};
+    #[cfg(feature = "http")]
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_capture_new() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let credentials = capture.credentials().unwrap();
+        assert_eq!(credentials.key, "test_key");
+        assert_eq!(credentials.secret.expose(), "test_secret");
+        assert!(!capture.options.use_edge);
+    }
+
+    #[test]
+    fn test_capture_with_edge() {
+        let options = CaptureOptions::new().with_edge();
+        let capture =
+            Capture::with_options("test_key".to_string(), "test_secret".to_string(), options);
+        assert!(capture.options.use_edge);
+    }
+
+    #[test]
+    fn test_build_image_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let url = capture
+            .build_image_url("https://example.com", None)
+            .unwrap();
+        assert!(url.contains("test_key"));
+        assert!(url.contains("image"));
+        assert!(url.contains("https://cdn.capture.page"));
+    }
+
+    #[test]
+    fn test_build_image_url_with_edge() {
+        let options = CaptureOptions::new().with_edge();
+        let capture =
+            Capture::with_options("test_key".to_string(), "test_secret".to_string(), options);
+        let url = capture
+            .build_image_url("https://example.com", None)
+            .unwrap();
+        assert!(url.contains("https://edge.capture.page"));
+    }
+
+    #[test]
+    fn test_missing_credentials() {
+        let capture = Capture::new("".to_string(), "".to_string());
+        let result = capture.build_image_url("https://example.com", None);
+        assert!(matches!(result, Err(CaptureError::MissingCredentials)));
+    }
+
+    #[test]
+    fn test_missing_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let result = capture.build_image_url("", None);
+        assert!(matches!(result, Err(CaptureError::MissingUrl)));
+    }
+
+    #[test]
+    fn test_html_is_encoded_and_signed() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let html = "<h1 class=\"title\">Tom & Jerry — 100%</h1>";
+        let options = ScreenshotOptions {
+            vw: Some(800),
+            ..Default::default()
+        };
+
+        let url = capture.build_image_url_from_html(html, &options).unwrap();
+        let query = url.split_once('?').unwrap().1;
+        let token = url.split('/').nth(4).unwrap();
+
+        assert!(!query.contains("url="));
+        assert!(query.contains("vw=800"));
+        assert_eq!(token, signing::generate_token("test_secret", query));
+        let encoded = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("html="))
+            .unwrap();
+        assert_eq!(urlencoding::decode(encoded).unwrap(), html);
+    }
+
+    #[test]
+    fn test_html_size_limits() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = PdfOptions::default();
+
+        let section = "<section><h2>Invoice line</h2><p>Qty 1, unit price 10.00</p></section>";
+        let document = format!("<html><body>{}</body></html>", section.repeat(60));
+        assert!(document.len() > 4 * 1024);
+        let url = capture
+            .build_pdf_url_from_html(&document, &options)
+            .unwrap();
+        assert!(url.len() <= MAX_HTML_CAPTURE_URL_BYTES);
+
+        let oversized = "<p>x</p>".repeat(MAX_HTML_CAPTURE_URL_BYTES / 4);
+        let result = capture.build_pdf_url_from_html(&oversized, &options);
+        assert!(matches!(
+            result,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "html"
+        ));
+
+        let result = capture.build_pdf_url_from_html("  \n", &options);
+        assert!(matches!(
+            result,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "html"
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_capture_error_message() {
+        assert_eq!(
+            capture_error_message(r#"{"error":"Target returned 404"}"#),
+            "Target returned 404"
+        );
+        assert_eq!(
+            capture_error_message(r#"{"message":"Bad token"}"#),
+            "Bad token"
+        );
+        assert_eq!(capture_error_message("Not Found"), "Not Found");
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_target_site_failures_are_classified() {
+        for (status, body, expected_target_status) in [
+            (
+                502,
+                r#"{"error":"Target returned 503","targetStatus":503}"#,
+                Some(503),
+            ),
+            (
+                400,
+                r#"{"message":"Page not found","target":{"status":404}}"#,
+                Some(404),
+            ),
+            (424, r#"{"error":"Navigation timed out"}"#, None),
+            (424, "upstream failed", None),
+        ] {
+            let error = request_failure(status, body);
+            assert!(
+                matches!(
+                    error,
+                    CaptureError::TargetSiteError { target_status, .. }
+                        if target_status == expected_target_status
+                ),
+                "{body}: {error:?}"
+            );
+            assert_eq!(error.kind(), crate::ErrorKind::TargetSite);
+        }
+
+        let error = request_failure(503, r#"{"error":"Service unavailable"}"#);
+        assert!(matches!(
+            error,
+            CaptureError::RequestFailed { status: 503, ref message } if message == "Service unavailable"
+        ));
+        assert_eq!(error.kind(), crate::ErrorKind::Server);
+        assert_eq!(
+            request_failure(401, "Unauthorized").kind(),
+            crate::ErrorKind::Rejected
+        );
+    }
+
+    #[test]
+    fn test_screenshot_pair_urls_differ_only_in_dark_mode() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let base = ScreenshotOptions {
+            vw: Some(1280),
+            full: Some(true),
+            dark_mode: Some(true),
+            ..Default::default()
+        };
+
+        let urls = capture
+            .build_screenshot_pair_urls("https://example.com", &base)
+            .unwrap();
+        let query_params = |url: &str| {
+            let mut params: Vec<String> = url
+                .split_once('?')
+                .unwrap()
+                .1
+                .split('&')
+                .map(ToOwned::to_owned)
+                .collect();
+            params.sort();
+            params
+        };
+        let light = query_params(&urls.light);
+        let dark = query_params(&urls.dark);
+
+        assert!(light.contains(&"darkMode=false".to_string()));
+        assert!(dark.contains(&"darkMode=true".to_string()));
+        assert_eq!(
+            light
+                .iter()
+                .filter(|p| !p.starts_with("darkMode="))
+                .collect::<Vec<_>>(),
+            dark.iter()
+                .filter(|p| !p.starts_with("darkMode="))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_clone_configuration_is_independent() {
+        let original = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for clone in [original.shared(), original.detached()] {
+            let clone = clone.with_edge().with_timeout(Duration::from_secs(5));
+            assert_eq!(clone.endpoint(), Endpoint::Edge);
+            assert_eq!(clone.options.timeout, Some(Duration::from_secs(5)));
+        }
+
+        assert_eq!(original.endpoint(), Endpoint::Cdn);
+        assert_eq!(original.options.timeout, None);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_shared_clone_shares_shadow_sampler() {
+        let shadow = ShadowConfig::new(Endpoint::Edge, 0.5);
+        let original = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_shadow(shadow),
+        );
+        let sampler = |capture: &Capture| capture.options.shadow.as_ref().unwrap().should_sample();
+
+        // The first of every two fetches is skipped at a 0.5 rate.
+        assert!(!sampler(&original));
+        let shared = original.shared();
+        assert!(sampler(&shared));
+        let detached = original.detached();
+        assert!(!sampler(&detached));
+        assert!(!sampler(&original));
+    }
+
+    /// A client whose CDN requests go to `cdn_url` and whose shadow
+    /// requests, with bodies compared, go to `edge_url`. Every shadow report
+    /// is sent on the returned channel, once from the divergence callback
+    /// when it diverged and once from the metrics hook.
+    #[cfg(feature = "http")]
+    fn shadowed(
+        cdn_url: &str,
+        edge_url: &str,
+        options: CaptureOptions,
+    ) -> (
+        Capture,
+        tokio::sync::mpsc::UnboundedReceiver<(&'static str, ShadowReport)>,
+    ) {
+        let (sender, reports) = tokio::sync::mpsc::unbounded_channel();
+        let divergences = sender.clone();
+        let shadow = ShadowConfig::new(Endpoint::Edge, 1.0)
+            .with_compare_bodies(true)
+            .on_divergence(move |report| {
+                let _ = divergences.send(("divergence", report.clone()));
+            });
+        let metrics = MetricsHook::new(move |metric| {
+            if let Metric::Shadow(report) = metric {
+                let _ = sender.send(("metrics", (*report).clone()));
+            }
+        });
+        let options = options
+            .with_base_url(cdn_url)
+            .unwrap()
+            .with_edge_base_url(edge_url)
+            .unwrap()
+            .with_shadow(shadow)
+            .with_metrics(metrics);
+
+        let capture =
+            Capture::with_options("test_key".to_string(), "test_secret".to_string(), options);
+        (capture, reports)
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_shadow_divergence_on_differing_bodies() {
+        let png = |tag: &[u8]| [b"\x89PNG\r\n\x1a\n".as_slice(), tag].concat();
+        let cdn_url = crate::mock_server::serve("200 OK", &[], png(b"cdn"), 1).await;
+        let (edge_url, mut shadow_requests) =
+            crate::mock_server::serve_recorded("200 OK", &[], png(b"edge"), 1).await;
+        let limiter = RateLimiter::daily(10);
+        let (capture, mut reports) = shadowed(
+            &cdn_url,
+            &edge_url,
+            CaptureOptions::new()
+                .with_rate_limiter(limiter.clone())
+                .with_max_concurrent_requests(1),
+        );
+
+        let bytes = capture
+            .fetch_image("https://example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(bytes, png(b"cdn"));
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let report = tokio::time::timeout(Duration::from_secs(5), reports.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            received.push(report);
+        }
+        received.sort_by_key(|(source, _)| *source);
+        assert_eq!(received[0].0, "divergence");
+        assert_eq!(received[1].0, "metrics");
+        let report = &received[0].1;
+        assert_eq!(&received[1].1, report);
+        assert_eq!(report.primary_status, Some(200));
+        assert_eq!(report.shadow_status, Some(200));
+        assert_eq!(report.bodies_match, Some(false));
+
+        // The shadow request was sent to the edge endpoint, without
+        // counting against the caller's rate limiter.
+        assert!(shadow_requests.recv().await.is_some());
+        assert_eq!(limiter.consumed(), 1);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_shadow_bodies_are_read_up_to_max_response_bytes() {
+        let png = |tag: &[u8]| [b"\x89PNG\r\n\x1a\n".as_slice(), tag].concat();
+        let cdn_url = crate::mock_server::serve("200 OK", &[], png(b"cdn"), 1).await;
+        let edge_url = crate::mock_server::serve("200 OK", &[], png(&[0; 64]), 1).await;
+        let (capture, mut reports) = shadowed(
+            &cdn_url,
+            &edge_url,
+            CaptureOptions::new().with_max_response_bytes(16),
+        );
+
+        capture
+            .fetch_image("https://example.com", None)
+            .await
+            .unwrap();
+
+        let (source, report) = tokio::time::timeout(Duration::from_secs(5), reports.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(source, "metrics");
+        assert_eq!(report.shadow_status, Some(200));
+        assert_eq!(report.bodies_match, None);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_shadows_neither_queue_callers_nor_spend_their_budget() {
+        let png = b"\x89PNG\r\n\x1a\nok".to_vec();
+        let cdn_url = crate::mock_server::serve("200 OK", &[], png.clone(), 3).await;
+        let (edge_url, edge) = crate::mock_server::serve_held(png).await;
+        let limiter = RateLimiter::daily(3);
+        let shadow_budget = RateLimiter::daily(10);
+        let shadow = ShadowConfig::new(Endpoint::Edge, 1.0)
+            .with_max_in_flight(1)
+            .with_rate_limiter(shadow_budget.clone());
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_base_url(&cdn_url)
+                .unwrap()
+                .with_edge_base_url(&edge_url)
+                .unwrap()
+                .with_shadow(shadow)
+                .with_rate_limiter(limiter.clone())
+                .with_max_concurrent_requests(1),
+        );
+
+        // The first shadow request is held for the whole test, yet every
+        // caller gets its one request slot and its budget.
+        for _ in 0..3 {
+            tokio::time::timeout(
+                Duration::from_secs(5),
+                capture.fetch_image("https://example.com", None),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        }
+        assert_eq!(limiter.consumed(), 3);
+
+        // Later fetches found the single shadow slot taken and were not
+        // mirrored.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(edge.accepted(), 1);
+        assert_eq!(shadow_budget.consumed(), 1);
+        edge.release(1);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_shadow_records_the_status_of_a_failed_target() {
+        let body = br#"{"error":"Target returned 503","targetStatus":503}"#.to_vec();
+        let cdn_url =
+            crate::mock_server::serve("424 Failed Dependency", &[], body.clone(), 1).await;
+        let edge_url = crate::mock_server::serve("424 Failed Dependency", &[], body, 1).await;
+        let (capture, mut reports) = shadowed(&cdn_url, &edge_url, CaptureOptions::new());
+
+        let error = capture
+            .fetch_image("https://example.com", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            CaptureError::TargetSiteError {
+                target_status: Some(503),
+                ..
+            }
+        ));
+
+        // Both sides failed alike, so only the metrics hook hears of it.
+        let (source, report) = tokio::time::timeout(Duration::from_secs(5), reports.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(source, "metrics");
+        assert_eq!(report.primary_status, Some(424));
+        assert_eq!(report.shadow_status, Some(424));
+        assert!(!report.diverged());
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_open_host_circuit_fails_fast_across_shared_clones() {
+        let breaker = HostCircuitBreaker::new(1, Duration::from_secs(60));
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_host_circuit_breaker(breaker.clone()),
+        );
+        breaker.record("down.example", true, Instant::now());
+
+        for capture in [&capture, &capture.shared()] {
+            let result = capture.fetch_image("https://down.example/page", None).await;
+            assert!(matches!(
+                result,
+                Err(CaptureError::HostCircuitOpen { ref host, retry_at })
+                    if host == "down.example" && retry_at > SystemTime::now()
+            ));
+        }
+        let detached = capture.detached();
+        let breaker = detached.options.host_circuit_breaker.as_ref().unwrap();
+        assert!(breaker.check("down.example", Instant::now()).is_ok());
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_screenshot_themes_fails_fast() {
+        let capture = Capture::new(String::new(), String::new());
+
+        let result = capture
+            .fetch_screenshot_themes("https://example.com", &ScreenshotOptions::default())
+            .await;
+        assert!(matches!(result, Err(CaptureError::MissingCredentials)));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_screenshot_themes_requests_both_modes() {
+        let png = b"\x89PNG\r\n\x1a\nthemed".to_vec();
+        let (base_url, mut requests) =
+            crate::mock_server::serve_recorded("200 OK", &[], png.clone(), 2).await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_base_url(&base_url).unwrap(),
+        );
+        let options = ScreenshotOptions {
+            vw: Some(1280),
+            dark_mode: Some(true),
+            ..Default::default()
+        };
+
+        let themes = capture
+            .fetch_screenshot_themes("https://example.com", &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            themes,
+            ScreenshotPair {
+                light: png.clone(),
+                dark: png
+            }
+        );
+
+        let mut modes = Vec::new();
+        for _ in 0..2 {
+            let head = requests.recv().await.unwrap();
+            let path = head.split_whitespace().nth(1).unwrap().to_owned();
+            assert!(path.contains("vw=1280"), "{path}");
+            modes.push(
+                ["darkMode=false", "darkMode=true"]
+                    .into_iter()
+                    .find(|mode| path.contains(mode))
+                    .unwrap(),
+            );
+        }
+        modes.sort();
+        assert_eq!(modes, ["darkMode=false", "darkMode=true"]);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_screenshot_pair_partial_reports_both_sides() {
+        let capture = Capture::new("".to_string(), "".to_string());
+
+        let pair = capture
+            .fetch_screenshot_pair_partial("https://example.com", &ScreenshotOptions::default())
+            .await;
+        assert!(matches!(pair.light, Err(CaptureError::MissingCredentials)));
+        assert!(matches!(pair.dark, Err(CaptureError::MissingCredentials)));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_screenshot_pair_partial_keeps_the_side_that_succeeded() {
+        let png = b"\x89PNG\r\n\x1a\npartial".to_vec();
+        let (base_url, mut requests) = crate::mock_server::serve_sequence(&[
+            ("200 OK", &[], png.clone()),
+            ("400 Bad Request", &[], b"bad".to_vec()),
+        ])
+        .await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_base_url(&base_url).unwrap(),
+        );
+
+        let pair = capture
+            .fetch_screenshot_pair_partial("https://example.com", &ScreenshotOptions::default())
+            .await;
+        // The first request to arrive is answered with the screenshot.
+        let first = requests.recv().await.unwrap();
+        let (succeeded, failed) = if first.contains("darkMode=true") {
+            (pair.dark, pair.light)
+        } else {
+            (pair.light, pair.dark)
+        };
+        assert_eq!(succeeded.unwrap(), png);
+        assert!(matches!(
+            failed,
+            Err(CaptureError::RequestFailed { status: 400, .. })
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_screenshot_pair_requests_run_concurrently() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let (base_url, server) = crate::mock_server::serve_held(png.clone()).await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_base_url(&base_url).unwrap(),
+        );
+
+        let pair = tokio::spawn(async move {
+            capture
+                .fetch_screenshot_pair_partial("https://example.com", &ScreenshotOptions::default())
+                .await
+        });
+        // Neither request is answered until both have arrived.
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.accepted() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        server.release(2);
+
+        let pair = pair.await.unwrap();
+        assert_eq!(pair.light.unwrap(), png);
+        assert_eq!(pair.dark.unwrap(), png);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_markdown_only_content_response() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let url = capture
+            .build_content_url_structured(
+                "https://example.com",
+                Some(&ContentOptions::new().format(ContentFormat::Markdown)),
+            )
+            .unwrap();
+        assert!(url.contains("format=markdown"));
+
+        let body = br##"{"success":true,"markdown":"# Example"}"##.to_vec();
+        let url =
+            crate::mock_server::serve_once("200 OK", &[("content-type", "application/json")], body)
+                .await;
+        let bytes = capture
+            .fetch_bytes(&url, RequestType::Content)
+            .await
+            .unwrap();
+        let content = ContentResponse::from_slice(&bytes, "https://example.com").unwrap();
+        assert_eq!(content.markdown, "# Example");
+        assert!(content.html.is_empty() && content.text_content.is_empty());
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_animated_body_must_match_requested_format() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let gif = b"GIF89a\x01\0\x01\0".to_vec();
+
+        let url = crate::mock_server::serve_once("200 OK", &[], gif.clone()).await;
+        let bytes = capture
+            .fetch_animated_bytes(&url, Some(AnimatedFormat::Gif))
+            .await
+            .unwrap();
+        assert_eq!(bytes, gif);
+
+        let url = crate::mock_server::serve_once("200 OK", &[], gif.clone()).await;
+        let result = capture
+            .fetch_animated_bytes(&url, Some(AnimatedFormat::Mp4))
+            .await;
+        assert!(matches!(
+            result,
+            Err(CaptureError::UnexpectedBody { ref snippet, .. }) if snippet.starts_with("GIF89a")
+        ));
+
+        let unchecked = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_payload_validation(false),
+        );
+        let url = crate::mock_server::serve_once("200 OK", &[], gif).await;
+        assert!(unchecked
+            .fetch_animated_bytes(&url, Some(AnimatedFormat::Webm))
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_set_credentials_rotates_shared_clones() {
+        let capture = Capture::new("test_key".to_string(), "old_secret".to_string());
+        let shared = capture.shared();
+        let detached = capture.detached();
+        let before = capture
+            .build_image_url("https://example.com", None)
+            .unwrap();
+
+        capture
+            .set_credentials("test_key".to_string(), "new_secret".to_string())
+            .unwrap();
+        let after = capture
+            .build_image_url("https://example.com", None)
+            .unwrap();
+        assert_ne!(after, before);
+        assert_eq!(
+            after,
+            Capture::new("test_key".to_string(), "new_secret".to_string())
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+        assert_eq!(
+            shared.build_image_url("https://example.com", None).unwrap(),
+            after
+        );
+        assert_eq!(
+            detached
+                .build_image_url("https://example.com", None)
+                .unwrap(),
+            before
+        );
+        #[cfg(feature = "http")]
+        assert!(Arc::ptr_eq(&capture.client, &shared.client));
+
+        assert!(matches!(
+            capture.set_credentials("test_key".to_string(), " ".to_string()),
+            Err(CaptureError::MissingCredentials)
+        ));
+        assert_eq!(
+            capture
+                .build_image_url("https://example.com", None)
+                .unwrap(),
+            after
+        );
+    }
+
+    #[test]
+    fn test_with_credentials_shares_the_client() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let tenant_a = capture.with_credentials("key_a".to_string(), "secret_a".to_string());
+        let tenant_b = capture.with_credentials("key_b".to_string(), "secret_b".to_string());
+
+        let url_a = tenant_a
+            .build_image_url("https://example.com", None)
+            .unwrap();
+        let url_b = tenant_b
+            .build_image_url("https://example.com", None)
+            .unwrap();
+        assert!(url_a.starts_with("https://cdn.capture.page/key_a/"));
+        assert!(url_b.starts_with("https://cdn.capture.page/key_b/"));
+        assert_eq!(
+            url_a,
+            Capture::new("key_a".to_string(), "secret_a".to_string())
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+        assert_ne!(url_a.split('/').nth(4), url_b.split('/').nth(4));
+        #[cfg(feature = "http")]
+        {
+            assert!(Arc::ptr_eq(&tenant_a.client, &capture.client));
+            assert!(Arc::ptr_eq(&tenant_a.client, &tenant_b.client));
+        }
+
+        tenant_a
+            .set_credentials("key_c".to_string(), "secret_c".to_string())
+            .unwrap();
+        assert!(capture
+            .build_image_url("https://example.com", None)
+            .unwrap()
+            .starts_with("https://cdn.capture.page/test_key/"));
+    }
+
+    #[test]
+    fn test_try_new_rejects_blank_credentials() {
+        for (key, secret) in [
+            ("", ""),
+            ("test_key", ""),
+            ("", "test_secret"),
+            (" ", "\t\n"),
+        ] {
+            assert!(matches!(
+                Capture::try_new(key.to_string(), secret.to_string()),
+                Err(CaptureError::MissingCredentials)
+            ));
+        }
+
+        let capture = Capture::try_new("test_key".to_string(), "test_secret".to_string()).unwrap();
+        assert_eq!(
+            capture
+                .build_image_url("https://example.com", None)
+                .unwrap(),
+            Capture::new("test_key".to_string(), "test_secret".to_string())
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_connect_and_read_timeouts() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+        let (url, _server) = crate::mock_server::serve_held(png.clone()).await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_read_timeout(Duration::from_millis(100)),
+        );
+        let error = capture
+            .fetch_bytes(&url, RequestType::Image)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, CaptureError::HttpError(ref error) if error.is_timeout()));
+
+        // A short connect timeout still allows a slow response once
+        // connected.
+        let (url, server) = crate::mock_server::serve_held(png.clone()).await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_connect_timeout(Duration::from_millis(100))
+                .with_timeout(Duration::from_secs(10)),
+        );
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            server.release(1);
+        });
+        assert_eq!(
+            capture.fetch_bytes(&url, RequestType::Image).await.unwrap(),
+            png
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_pool_settings_survive_reconfiguration() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        async fn connections_for(capture: &Capture, png: &[u8]) -> usize {
+            let (url, connections) = crate::mock_server::serve_keep_alive(png.to_vec()).await;
+            for _ in 0..3 {
+                capture.fetch_bytes(&url, RequestType::Image).await.unwrap();
+            }
+            connections.load(Ordering::SeqCst)
+        }
+
+        let pooled = Capture::new("test_key".to_string(), "test_secret".to_string());
+        assert_eq!(connections_for(&pooled, &png).await, 1);
+
+        let unpooled = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_pool_max_idle_per_host(0)
+                .with_pool_idle_timeout(Duration::from_secs(30))
+                .with_tcp_keepalive(Duration::from_secs(60)),
+        );
+        assert_eq!(connections_for(&unpooled, &png).await, 3);
+
+        let reconfigured = unpooled.clone().with_timeout(Duration::from_secs(5));
+        assert!(Arc::ptr_eq(&reconfigured.client, &unpooled.client));
+        assert_eq!(connections_for(&reconfigured, &png).await, 3);
+        assert_eq!(connections_for(&reconfigured.detached(), &png).await, 3);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_http2_settings_survive_reconfiguration() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        // The mock server speaks HTTP/1.1 only, so a client that insists on
+        // HTTP/2 fails against it.
+        async fn fetches(capture: &Capture, png: &[u8]) -> bool {
+            let url = crate::mock_server::serve_once("200 OK", &[], png.to_vec()).await;
+            capture.fetch_bytes(&url, RequestType::Image).await.is_ok()
+        }
+
+        let http1 = Capture::new("test_key".to_string(), "test_secret".to_string());
+        assert!(fetches(&http1, &png).await);
+
+        let http2 = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_http2(true)
+                .with_http2_keep_alive(Duration::from_secs(15))
+                .with_tcp_nodelay(true)
+                .with_timeout(Duration::from_secs(2)),
+        );
+        assert!(!fetches(&http2, &png).await);
+
+        let reconfigured = http2
+            .clone()
+            .with_timeout(Duration::from_secs(3))
+            .with_edge();
+        assert!(!fetches(&reconfigured, &png).await);
+        assert!(!fetches(&reconfigured.detached(), &png).await);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_resolve_pins_hosts_to_addresses() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let (server, mut requests) = crate::mock_server::serve_recorded(
+            "200 OK",
+            &[("content-type", "image/png")],
+            png.clone(),
+            2,
+        )
+        .await;
+        let address: SocketAddr = server.trim_start_matches("http://").parse().unwrap();
+
+        // Plain HTTP so the mock server can answer; the host is resolved
+        // only through the pinned address.
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_edge()
+                .with_edge_base_url("http://edge.capture.page")
+                .unwrap()
+                .with_resolve("edge.capture.page", [address])
+                .with_resolve("cdn.capture.page", [address]),
+        )
+        .with_timeout(Duration::from_secs(5));
+        assert_eq!(capture.options.resolve.len(), 2);
+
+        let bytes = capture
+            .fetch_image("https://example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(bytes, png);
+        let head = requests.recv().await.unwrap();
+        assert!(head.starts_with("GET /test_key/"), "{head}");
+        assert!(
+            head.to_lowercase().contains("host: edge.capture.page"),
+            "{head}"
+        );
+
+        assert!(capture
+            .detached()
+            .fetch_image("https://example.com", None)
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_default_options_merge_under_call_options() {
+        let defaults = RequestOptions::from([
+            ("blockAds".to_string(), serde_json::json!(true)),
+            ("blockCookieBanners".to_string(), serde_json::json!(true)),
+            ("vw".to_string(), serde_json::json!(1440)),
+        ]);
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_default_options(defaults.clone()),
+        );
+        let plain = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let query = |url: String| url.split_once('?').unwrap().1.to_string();
+
+        assert_eq!(
+            query(
+                capture
+                    .build_content_url("https://example.com", None)
+                    .unwrap()
+            ),
+            query(
+                plain
+                    .build_content_url("https://example.com", Some(&defaults))
+                    .unwrap()
+            )
+        );
+
+        // Call options win, and a null drops a default.
+        let call = RequestOptions::from([
+            ("vw".to_string(), serde_json::json!(800)),
+            ("blockCookieBanners".to_string(), serde_json::Value::Null),
+        ]);
+        assert_eq!(
+            query(
+                capture
+                    .build_image_url("https://example.com", Some(&call))
+                    .unwrap()
+            ),
+            "blockAds=true&url=https%3A%2F%2Fexample.com&vw=800"
+        );
+
+        // Typed fields beat defaults, and additional_options beat both.
+        let screenshot = ScreenshotOptions {
+            vw: Some(1024),
+            block_ads: Some(false),
+            additional_options: Some(HashMap::from([(
+                "blockAds".to_string(),
+                serde_json::json!("auto"),
+            )])),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(
+                capture
+                    .build_screenshot_url("https://example.com", Some(&screenshot))
+                    .unwrap()
+            ),
+            "blockAds=auto&blockCookieBanners=true&url=https%3A%2F%2Fexample.com&vw=1024"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_per_call() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let metadata_url = capture
+            .build_metadata_url_at(Endpoint::Edge, "https://example.com", None)
+            .unwrap();
+        let pdf_url = capture
+            .build_pdf_url_at(Endpoint::Cdn, "https://example.com", None)
+            .unwrap();
+        assert!(metadata_url.starts_with("https://edge.capture.page/test_key/"));
+        assert!(pdf_url.starts_with("https://cdn.capture.page/test_key/"));
+        assert_eq!(
+            pdf_url,
+            capture.build_pdf_url("https://example.com", None).unwrap()
+        );
+
+        let edge = capture.clone().with_edge();
+        assert_eq!(
+            edge.build_image_url_at(Endpoint::Cdn, "https://example.com", None)
+                .unwrap(),
+            capture
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+
+        #[cfg(feature = "http")]
+        {
+            let settings =
+                capture.fetch_settings(Some(&FetchOverrides::new().with_endpoint(Endpoint::Edge)));
+            assert_eq!(settings.endpoint, Endpoint::Edge);
+            assert_eq!(capture.fetch_settings(None).endpoint, Endpoint::Cdn);
+        }
+    }
+
+    #[test]
+    fn test_base_url_overrides_keep_the_token() {
+        let official = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let official_url = official
+            .build_image_url("https://example.com", None)
+            .unwrap();
+
+        for base_url in [
+            "https://proxy.internal/capture",
+            "https://proxy.internal/capture/",
+        ] {
+            let proxied = Capture::with_options(
+                "test_key".to_string(),
+                "test_secret".to_string(),
+                CaptureOptions::new().with_base_url(base_url).unwrap(),
+            );
+            let url = proxied
+                .build_image_url("https://example.com", None)
+                .unwrap();
+            assert_eq!(
+                url.strip_prefix("https://proxy.internal/capture"),
+                official_url.strip_prefix(Capture::API_URL)
+            );
+        }
+
+        let edge = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_edge()
+                .with_edge_base_url("http://localhost:8080")
+                .unwrap(),
+        );
+        assert!(edge
+            .build_image_url("https://example.com", None)
+            .unwrap()
+            .starts_with("http://localhost:8080/test_key/"));
+
+        assert!(matches!(
+            CaptureOptions::new().with_base_url("not a url"),
+            Err(CaptureError::UrlError(_))
+        ));
+        for base_url in ["ftp://proxy.internal", "https://proxy.internal/?via=1"] {
+            assert!(matches!(
+                CaptureOptions::new().with_edge_base_url(base_url),
+                Err(CaptureError::InvalidOption { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_the_secret() {
+        let capture = Capture::new("key_5d1e".to_string(), "secret_c07f".to_string());
+        for debug in [
+            format!("{capture:?}"),
+            format!("{capture:#?}"),
+            format!("{:?}", capture.clone()),
+            format!(
+                "{:?}",
+                Capture::builder().key("key_5d1e").secret("secret_c07f")
+            ),
+        ] {
+            assert!(debug.contains("key_5d1e"), "{debug}");
+            assert!(debug.contains(r#"secret: "***""#), "{debug}");
+            assert!(!debug.contains("secret_c07f"), "{debug}");
+        }
+    }
+
+    /// Serializes tests that set the process-wide credential variables.
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn with_env<T>(vars: &[(&str, Option<&str>)], test: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let saved: Vec<_> = vars
+            .iter()
+            .map(|(name, _)| (*name, std::env::var_os(name)))
+            .collect();
+        for (name, value) in vars {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+        let result = test();
+        for (name, value) in saved {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_from_env() {
+        let capture = with_env(
+            &[
+                ("CAPTURE_KEY", Some(" env_key")),
+                ("CAPTURE_SECRET", Some("env_secret\n")),
+                ("CAPTURE_USE_EDGE", None),
+            ],
+            Capture::from_env,
+        )
+        .unwrap();
+        let expected = Capture::new("env_key".to_string(), "env_secret".to_string());
+        assert_eq!(
+            capture
+                .build_image_url("https://example.com", None)
+                .unwrap(),
+            expected
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+
+        let capture = with_env(
+            &[
+                ("CAPTURE_KEY", Some("env_key")),
+                ("CAPTURE_SECRET", Some("env_secret")),
+                ("CAPTURE_USE_EDGE", Some("1")),
+            ],
+            || {
+                Capture::from_env_with_options(
+                    CaptureOptions::new().with_timeout(Duration::from_secs(5)),
+                )
+            },
+        )
+        .unwrap();
+        assert_eq!(capture.endpoint(), Endpoint::Edge);
+        assert_eq!(capture.options.timeout, Some(Duration::from_secs(5)));
+
+        for (key, secret, missing) in [
+            (None, Some("env_secret"), "CAPTURE_KEY"),
+            (Some("env_key"), Some(""), "CAPTURE_SECRET"),
+            (Some("env_key"), Some("  "), "CAPTURE_SECRET"),
+        ] {
+            let result = with_env(
+                &[("CAPTURE_KEY", key), ("CAPTURE_SECRET", secret)],
+                Capture::from_env,
+            );
+            assert!(
+                matches!(&result, Err(CaptureError::MissingEnvVar { name }) if name == missing),
+                "{missing}"
+            );
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_url_builder_only_signs_but_never_sends() {
+        let capture = Capture::url_builder_only("test_key".to_string(), "test_secret".to_string());
+        assert!(capture.is_url_builder_only() && capture.detached().is_url_builder_only());
+
+        let signed = Capture::new("test_key".to_string(), "test_secret".to_string());
+        assert_eq!(
+            capture
+                .build_image_url("https://example.com", None)
+                .unwrap(),
+            signed.build_image_url("https://example.com", None).unwrap()
+        );
+        assert!(capture
+            .build_metadata_url_structured("https://example.com", None)
+            .is_ok());
+
+        let (url, mut requests) = crate::mock_server::serve_recorded(
+            "200 OK",
+            &[("content-type", "image/png")],
+            b"\x89PNG\r\n\x1a\n".to_vec(),
+            1,
+        )
+        .await;
+        let error = capture
+            .fetch_bytes(&url, RequestType::Image)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, CaptureError::FetchDisabled));
+        assert_eq!(error.kind(), ErrorKind::Config);
+        assert!(requests.try_recv().is_err());
+
+        assert!(matches!(
+            capture.fetch_content("https://example.com", None).await,
+            Err(CaptureError::FetchDisabled)
+        ));
+        assert!(matches!(
+            capture.get_session("session").await,
+            Err(CaptureError::FetchDisabled)
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_success_false_is_an_api_failure() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let fetch = |body: &'static [u8]| {
+            let capture = capture.shared();
+            async move {
+                let url = crate::mock_server::serve_once(
+                    "200 OK",
+                    &[("content-type", "application/json")],
+                    body.to_vec(),
+                )
+                .await;
+                capture.fetch_bytes(&url, RequestType::Content).await
+            }
+        };
+
+        let body = fetch(br#"{"success":true,"html":"<p>Hi</p>","metadata":{"title":"Hi"}}"#)
+            .await
+            .unwrap();
+        let content = ContentResponse::from_slice(&body, "https://example.com").unwrap();
+        assert_eq!(content.html, "<p>Hi</p>");
+        assert!(content.error.is_none());
+        let metadata = MetadataResponse::from_slice(&body).unwrap();
+        assert_eq!(metadata.metadata.title.as_deref(), Some("Hi"));
+
+        let body = fetch(br#"{"success":false,"error":"Navigation timeout"}"#)
+            .await
+            .unwrap();
+        for error in [
+            ContentResponse::from_slice(&body, "https://example.com").unwrap_err(),
+            MetadataResponse::from_slice(&body).unwrap_err(),
+        ] {
+            assert!(
+                matches!(&error, CaptureError::ApiFailure { message } if message == "Navigation timeout"),
+                "{error:?}"
+            );
+            assert_eq!(error.kind(), ErrorKind::Server);
+        }
+        let body = fetch(br#"{"success":false}"#).await.unwrap();
+        assert!(matches!(
+            MetadataResponse::from_slice(&body),
+            Err(CaptureError::ApiFailure { .. })
+        ));
+
+        let body = fetch(br#"{"success":true,"html":"<p>H"#).await.unwrap();
+        for error in [
+            ContentResponse::from_slice(&body, "https://example.com").unwrap_err(),
+            MetadataResponse::from_slice(&body).unwrap_err(),
+        ] {
+            assert!(matches!(error, CaptureError::JsonError(_)), "{error:?}");
+            assert_eq!(error.kind(), ErrorKind::Decode);
+        }
+    }
+
+    #[cfg(feature = "http")]
+    fn tagged_client() -> Client {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-client", "mine".parse().unwrap());
+        Client::builder().default_headers(headers).build().unwrap()
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_user_client_survives_builder_calls() {
+        let client = Arc::new(tagged_client());
+        let key = || "test_key".to_string();
+        let secret = || "test_secret".to_string();
+
+        let by_builder = Capture::new(key(), secret())
+            .with_timeout(Duration::from_secs(5))
+            .with_client(client.clone())
+            .with_timeout(Duration::from_secs(10))
+            .with_edge();
+        let by_options = Capture::with_options(
+            key(),
+            secret(),
+            CaptureOptions::new()
+                .with_client(client.clone())
+                .with_timeout(Duration::from_secs(10))
+                .with_retry_policy(RetryPolicy::new())
+                .with_max_concurrent_requests(2),
+        );
+        let captures = [
+            by_builder.detached(),
+            by_builder.shared().with_timeout(Duration::from_secs(1)),
+            by_options.detached(),
+            by_builder,
+            by_options,
+        ];
+
+        for capture in &captures {
+            assert!(Arc::ptr_eq(&capture.client, &client));
+            assert!(capture.redirectless_client.is_none());
+
+            let (url, mut requests) = crate::mock_server::serve_recorded(
+                "200 OK",
+                &[("content-type", "application/json")],
+                br#"{"success":true}"#.to_vec(),
+                2,
+            )
+            .await;
+            capture
+                .fetch_bytes(&url, RequestType::Content)
+                .await
+                .unwrap();
+            capture
+                .fetch_stored_url(&url, RequestType::Content)
+                .await
+                .unwrap();
+            for _ in 0..2 {
+                let request = requests.recv().await.unwrap();
+                assert!(request.contains("x-client: mine"), "{request}");
+            }
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_crate_owned_clients_without_user_client() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string())
+            .with_timeout(Duration::from_secs(5));
+        assert!(capture.redirectless_client.is_some());
+        assert!(capture.options.client.is_none());
+        assert!(Arc::ptr_eq(&capture.client, &capture.shared().client));
+    }
+
+    /// Counts loads, and makes them slow enough for concurrent first
+    /// acquires to race.
+    #[cfg(feature = "http")]
+    struct CountingStore(Arc<AtomicUsize>);
+
+    #[cfg(feature = "http")]
+    impl RateLimitStore for CountingStore {
+        fn load(&self) -> Result<Option<RateLimitState>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(None)
+        }
+
+        fn save(&self, _: &RateLimitState) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_concurrent_first_use_initializes_once() {
+        const TASKS: usize = 200;
+        let loads = Arc::new(AtomicUsize::new(0));
+        let limiter = RateLimiter::daily(TASKS as u64).with_store(CountingStore(loads.clone()));
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_rate_limiter(limiter.clone())
+                .with_host_circuit_breaker(HostCircuitBreaker::new(3, Duration::from_secs(60))),
+        );
+        // Building the client does not touch the store; the first acquires race
+        // to load it.
+        assert_eq!(loads.load(Ordering::SeqCst), 0);
+        let url = crate::mock_server::serve(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"success":true}"#.to_vec(),
+            TASKS,
+        )
+        .await;
+
+        let start = Arc::new(tokio::sync::Barrier::new(TASKS));
+        let tasks: Vec<_> = (0..TASKS)
+            .map(|_| {
+                let (capture, url, start) = (capture.shared(), url.clone(), start.clone());
+                tokio::spawn(async move {
+                    start.wait().await;
+                    capture.fetch_bytes(&url, RequestType::Content).await
+                })
+            })
+            .collect();
+        let results = tokio::time::timeout(
+            Duration::from_secs(30),
+            futures_util::future::join_all(tasks),
+        )
+        .await
+        .expect("concurrent first use deadlocked");
+
+        for result in results {
+            assert!(!result.unwrap().unwrap().is_empty());
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(limiter.consumed(), TASKS as u64);
+        assert!(matches!(
+            capture.fetch_bytes(&url, RequestType::Content).await,
+            Err(CaptureError::RateLimitExceeded { .. })
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    async fn plan_served(status: &str, body: serde_json::Value) -> FullPagePlan {
+        let base_url = crate::mock_server::serve(
+            status,
+            &[("content-type", "application/json")],
+            body.to_string().into_bytes(),
+            1,
+        )
+        .await;
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_base_url(&base_url).unwrap(),
+        );
+        capture
+            .plan_full_page_capture("https://example.com")
+            .await
+            .unwrap()
+    }
+
+    #[cfg(feature = "http")]
+    fn metadata_with(metadata: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "success": true, "metadata": metadata })
+    }
+
+    #[cfg(feature = "http")]
+    fn assert_default_plan(options: &ScreenshotOptions) {
+        assert_eq!(options.vw, Some(1280));
+        assert_eq!(options.vh, Some(800));
+        assert_eq!(options.full, Some(true));
+        assert_eq!(options.max_height, Some(20_000));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_plan_full_page_capture_uses_reported_dimensions() {
+        let plan = plan_served(
+            "200 OK",
+            metadata_with(serde_json::json!({
+                "contentWidth": 1440,
+                "contentHeight": 5200
+            })),
+        )
+        .await;
+        assert!(plan.warning.is_none());
+        assert_eq!(plan.options.vw, Some(1440));
+        assert_eq!(plan.options.vh, Some(800));
+        assert_eq!(plan.options.full, Some(true));
+        assert_eq!(plan.options.max_height, Some(5200));
+        assert!(plan.options.validate().is_ok());
+
+        let plan = plan_served(
+            "200 OK",
+            metadata_with(serde_json::json!({ "contentHeight": 600 })),
+        )
+        .await;
+        assert!(plan.warning.is_none());
+        assert_eq!(plan.options.vh, Some(600));
+        assert_eq!(plan.options.full, Some(false));
+        assert_eq!(plan.options.max_height, None);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_plan_full_page_capture_defaults_when_absent() {
+        let plan = plan_served(
+            "200 OK",
+            metadata_with(serde_json::json!({ "title": "Example" })),
+        )
+        .await;
+        assert!(plan.warning.is_none());
+        assert_default_plan(&plan.options);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_plan_full_page_capture_caps_absurd_heights() {
+        for height in [
+            serde_json::json!(0),
+            serde_json::json!(900_000),
+            serde_json::json!(-5),
+        ] {
+            let plan = plan_served(
+                "200 OK",
+                metadata_with(serde_json::json!({
+                    "contentWidth": 100_000,
+                    "contentHeight": height
+                })),
+            )
+            .await;
+            assert!(plan.warning.is_none());
+            assert_default_plan(&plan.options);
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_plan_full_page_capture_degrades_with_a_warning() {
+        let plan = plan_served("500 Internal Server Error", serde_json::json!({})).await;
+        assert!(matches!(
+            plan.warning,
+            Some(CaptureError::RequestFailed { status: 500, .. })
+        ));
+        assert_default_plan(&plan.options);
+
+        let plan = plan_served(
+            "424 Failed Dependency",
+            serde_json::json!({ "targetStatus": 503 }),
+        )
+        .await;
+        assert_eq!(
+            plan.warning.as_ref().map(CaptureError::kind),
+            Some(ErrorKind::TargetSite)
+        );
+        assert_default_plan(&plan.options);
+
+        let plan = plan_served("200 OK", serde_json::json!("not metadata")).await;
+        assert!(matches!(plan.warning, Some(CaptureError::JsonError(_))));
+        assert_default_plan(&plan.options);
+
+        let plan = plan_served(
+            "200 OK",
+            serde_json::json!({ "success": false, "error": "Navigation timeout" }),
+        )
+        .await;
+        assert!(matches!(
+            &plan.warning,
+            Some(CaptureError::ApiFailure { message }) if message == "Navigation timeout"
+        ));
+        assert_default_plan(&plan.options);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_plan_full_page_capture_requires_credentials() {
+        let capture = Capture::new("".to_string(), "".to_string());
+
+        let result = capture.plan_full_page_capture("https://example.com").await;
+        assert!(matches!(result, Err(CaptureError::MissingCredentials)));
+    }
+}
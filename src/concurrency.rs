@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::rt::Instant;
+use crate::Capture;
+
+/// Saturation of a client's request slots, from
+/// [`Capture::concurrency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyStats {
+    /// The configured `max_concurrent_requests`.
+    pub max: usize,
+    /// Requests holding a slot.
+    pub in_flight: usize,
+    /// Requests waiting for a slot.
+    pub queued: usize,
+}
+
+/// Slots for `max_concurrent_requests`, shared by a client and its shared
+/// clones. A request holds its slot until its body has been read.
+#[derive(Debug, Clone)]
+pub(crate) struct RequestSlots {
+    max: usize,
+    semaphore: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl RequestSlots {
+    pub(crate) fn new(max: usize) -> Self {
+        let max = max.max(1);
+        Self {
+            max,
+            semaphore: Arc::new(Semaphore::new(max)),
+            queued: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Waits for a free slot, first come first served. Returns the slot and
+    /// how long the wait took.
+    pub(crate) async fn acquire(&self) -> (OwnedSemaphorePermit, Duration) {
+        let started = Instant::now();
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let _queued = Queued(&self.queued);
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("request slots are never closed");
+
+        (permit, started.elapsed())
+    }
+
+    /// Takes a free slot without waiting, or returns `None` when all are
+    /// taken.
+    pub(crate) fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        self.semaphore.clone().try_acquire_owned().ok()
+    }
+
+    /// Slots of the same size that are not shared with these.
+    pub(crate) fn detached(&self) -> Self {
+        Self::new(self.max)
+    }
+
+    fn stats(&self) -> ConcurrencyStats {
+        ConcurrencyStats {
+            max: self.max,
+            in_flight: self.max - self.semaphore.available_permits(),
+            queued: self.queued.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Leaves the queue when the wait ends, including when it is cancelled.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Capture {
+    /// Current use of the request slots, or `None` without
+    /// `max_concurrent_requests`.
+    pub fn concurrency(&self) -> Option<ConcurrencyStats> {
+        self.request_slots.as_ref().map(RequestSlots::stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::serve_held;
+    use crate::{CaptureOptions, Metric, MetricsHook, RequestType};
+    use std::sync::Mutex;
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_the_limit_queue() {
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let metrics = MetricsHook::new({
+            let recorded = recorded.clone();
+            move |metric| {
+                if let Metric::Fetch(fetch) = metric {
+                    recorded.lock().unwrap().push(fetch.queue_time);
+                }
+            }
+        });
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new()
+                .with_max_concurrent_requests(10)
+                .with_metrics(metrics),
+        );
+        let (url, server) = serve_held(br#"{"success":true}"#.to_vec()).await;
+        let settings = capture.fetch_settings(None);
+
+        let calls: Vec<_> = (0..11)
+            .map(|_| {
+                let (capture, url, settings) = (capture.shared(), url.clone(), settings.clone());
+                tokio::spawn(async move {
+                    capture
+                        .fetch_bytes_with_info(&url, &settings, RequestType::Content)
+                        .await
+                })
+            })
+            .collect();
+
+        settle().await;
+        assert_eq!(server.accepted(), 10);
+        assert_eq!(
+            capture.concurrency(),
+            Some(ConcurrencyStats {
+                max: 10,
+                in_flight: 10,
+                queued: 1,
+            })
+        );
+
+        // Completing one request lets the eleventh through, after it has
+        // queued for at least another settle.
+        settle().await;
+        server.release(1);
+        settle().await;
+        assert_eq!(server.accepted(), 11);
+        assert_eq!(capture.concurrency().unwrap().queued, 0);
+
+        server.release(10);
+        let mut queue_times = Vec::new();
+        for call in calls {
+            let (_, info) = call.await.unwrap().unwrap();
+            queue_times.push(info.queue_time);
+        }
+        queue_times.sort();
+        assert!(queue_times[9] < Duration::from_millis(100));
+        assert!(queue_times[10] >= Duration::from_millis(200));
+        assert_eq!(capture.concurrency().unwrap().in_flight, 0);
+
+        // The metrics hook saw the same waits.
+        let mut recorded = recorded.lock().unwrap().clone();
+        recorded.sort();
+        assert_eq!(recorded, queue_times);
+    }
+
+    #[test]
+    fn test_slots_are_shared_by_shared_clones_only() {
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_max_concurrent_requests(2),
+        );
+        let slots = capture.request_slots.as_ref().unwrap();
+        assert!(Arc::ptr_eq(
+            &slots.semaphore,
+            &capture.shared().request_slots.unwrap().semaphore
+        ));
+        assert!(!Arc::ptr_eq(
+            &slots.semaphore,
+            &capture.detached().request_slots.unwrap().semaphore
+        ));
+        assert!(Capture::new(String::new(), String::new())
+            .concurrency()
+            .is_none());
+    }
+}
@@ -0,0 +1,239 @@
+//! Client configuration read from a file.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::credentials::Credentials;
+use crate::secret::Secret;
+use crate::{Capture, CaptureError, CaptureOptions, RequestOptions, Result};
+
+/// Credentials and client settings for [`Capture::from_config`], usually
+/// read with [`from_path`](Self::from_path) from a file such as:
+///
+/// ```toml
+/// key = "your_api_key"
+/// secret = "your_api_secret"
+/// use_edge = true
+/// timeout_secs = 30
+///
+/// [default_options]
+/// blockCookieBanners = true
+/// ```
+///
+/// Unset fields keep the [`CaptureOptions`] defaults. Unknown keys are
+/// rejected, so a misspelled setting fails instead of being ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaptureConfig {
+    pub key: String,
+    secret: Secret,
+    #[serde(default)]
+    pub use_edge: bool,
+    /// See [`CaptureOptions::with_base_url`].
+    pub base_url: Option<String>,
+    /// See [`CaptureOptions::with_edge_base_url`].
+    pub edge_base_url: Option<String>,
+    /// Total deadline of each request, in seconds.
+    pub timeout_secs: Option<u64>,
+    /// See [`CaptureOptions::with_connect_timeout`], in seconds.
+    pub connect_timeout_secs: Option<u64>,
+    /// See [`CaptureOptions::with_read_timeout`], in seconds.
+    pub read_timeout_secs: Option<u64>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout_secs: Option<u64>,
+    pub tcp_keepalive_secs: Option<u64>,
+    #[serde(default)]
+    pub http2: bool,
+    pub http2_keep_alive_secs: Option<u64>,
+    pub tcp_nodelay: Option<bool>,
+    /// See [`CaptureOptions::with_resolve`], e.g.
+    /// `{"edge.capture.page": ["203.0.113.7:443"]}`.
+    #[serde(default)]
+    pub resolve: HashMap<String, Vec<SocketAddr>>,
+    pub max_response_bytes: Option<usize>,
+    pub validate_payloads: Option<bool>,
+    pub max_concurrent_requests: Option<usize>,
+    pub provenance: Option<bool>,
+    /// See [`CaptureOptions::with_default_options`].
+    pub default_options: Option<RequestOptions>,
+}
+
+impl CaptureConfig {
+    pub fn new(key: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            secret: Secret::new(secret.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Reads a `.toml` file, which needs the `toml` feature, or a JSON file
+    /// for any other extension. Parse errors, including unknown keys, fail
+    /// with [`CaptureError::InvalidConfig`].
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let parsed = if is_toml {
+            parse_toml(&text)
+        } else {
+            serde_json::from_str(&text).map_err(|error| error.to_string())
+        };
+        parsed.map_err(|message| CaptureError::InvalidConfig {
+            path: path.display().to_string(),
+            message,
+        })
+    }
+
+    fn to_options(&self) -> Result<CaptureOptions> {
+        let mut options = CaptureOptions::new();
+        options.use_edge = self.use_edge;
+        if let Some(base_url) = &self.base_url {
+            options = options.with_base_url(base_url)?;
+        }
+        if let Some(edge_base_url) = &self.edge_base_url {
+            options = options.with_edge_base_url(edge_base_url)?;
+        }
+        options.timeout = self.timeout_secs.map(Duration::from_secs);
+        options.connect_timeout = self.connect_timeout_secs.map(Duration::from_secs);
+        options.read_timeout = self.read_timeout_secs.map(Duration::from_secs);
+        options.pool_max_idle_per_host = self.pool_max_idle_per_host;
+        options.pool_idle_timeout = self.pool_idle_timeout_secs.map(Duration::from_secs);
+        options.tcp_keepalive = self.tcp_keepalive_secs.map(Duration::from_secs);
+        options.http2 = self.http2;
+        options.http2_keep_alive = self.http2_keep_alive_secs.map(Duration::from_secs);
+        options.tcp_nodelay = self.tcp_nodelay;
+        options.resolve = self.resolve.clone();
+        options.max_response_bytes = self.max_response_bytes;
+        if let Some(validate_payloads) = self.validate_payloads {
+            options.validate_payloads = validate_payloads;
+        }
+        options.max_concurrent_requests = self.max_concurrent_requests;
+        if let Some(provenance) = self.provenance {
+            options.provenance = provenance;
+        }
+        options.default_options = self.default_options.clone().unwrap_or_default();
+        Ok(options)
+    }
+}
+
+#[cfg(feature = "toml")]
+fn parse_toml(text: &str) -> std::result::Result<CaptureConfig, String> {
+    toml::from_str(text).map_err(|error| error.message().to_string())
+}
+
+#[cfg(not(feature = "toml"))]
+fn parse_toml(_text: &str) -> std::result::Result<CaptureConfig, String> {
+    Err("reading TOML needs the `toml` feature".to_string())
+}
+
+impl Capture {
+    /// Creates a client from `config`, failing like
+    /// [`CaptureBuilder::build`](crate::CaptureBuilder::build) for missing
+    /// credentials and like [`CaptureOptions::with_base_url`] for invalid
+    /// base URLs.
+    pub fn from_config(config: CaptureConfig) -> Result<Self> {
+        let options = config.to_options()?;
+        Self::builder()
+            .credentials(Credentials {
+                key: config.key,
+                secret: config.secret,
+            })
+            .options(options)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Endpoint;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn assert_fixture_client(config: CaptureConfig) {
+        let capture = Capture::from_config(config).unwrap();
+        assert_eq!(capture.endpoint(), Endpoint::Edge);
+        assert!(capture
+            .build_image_url("https://example.com", None)
+            .unwrap()
+            .starts_with("https://edge.capture.page/config_key/"));
+        assert_eq!(capture.options.timeout, Some(Duration::from_secs(45)));
+        assert_eq!(capture.options.max_concurrent_requests, Some(4));
+        assert!(capture.options.validate_payloads);
+        assert_eq!(
+            capture.options.default_options.get("blockAds"),
+            Some(&serde_json::Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn test_from_json_file() {
+        let config = CaptureConfig::from_path(fixture("capture.json")).unwrap();
+        assert!(!format!("{config:?}").contains("config_secret"));
+        assert_fixture_client(config);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml_file() {
+        assert_fixture_client(CaptureConfig::from_path(fixture("capture.toml")).unwrap());
+    }
+
+    #[cfg(not(feature = "toml"))]
+    #[test]
+    fn test_toml_needs_the_feature() {
+        let error = CaptureConfig::from_path(fixture("capture.toml")).unwrap_err();
+        assert!(
+            matches!(&error, CaptureError::InvalidConfig { message, .. } if message.contains("`toml` feature")),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_are_named() {
+        let path =
+            std::env::temp_dir().join(format!("capture-rust-config-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"key": "config_key", "secret": "config_secret", "timeuot_secs": 45}"#,
+        )
+        .unwrap();
+        let result = CaptureConfig::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let error = result.unwrap_err();
+        assert!(
+            matches!(&error, CaptureError::InvalidConfig { message, .. } if message.contains("timeuot_secs")),
+            "{error}"
+        );
+        assert_eq!(error.kind(), crate::ErrorKind::Config);
+    }
+
+    #[test]
+    fn test_from_config_checks_credentials_and_urls() {
+        assert!(matches!(
+            Capture::from_config(CaptureConfig::default()),
+            Err(CaptureError::MissingCredentials)
+        ));
+
+        let config = CaptureConfig {
+            base_url: Some("not a url".to_string()),
+            ..CaptureConfig::new("config_key", "config_secret")
+        };
+        assert!(matches!(
+            Capture::from_config(config),
+            Err(CaptureError::UrlError(_))
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+//! The API key and secret, shared by a client and its clones so they can be
+//! rotated in place, and the providers they can be fetched from.
+
+use std::sync::{Arc, RwLock};
+
+use crate::secret::Secret;
+use crate::BackendFuture;
+
+/// An API key and secret. `Debug` output hides the secret.
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    pub(crate) key: String,
+    pub(crate) secret: Secret,
+}
+
+impl Credentials {
+    pub fn new(key: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            secret: Secret::new(secret.into()),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub(crate) fn is_missing(&self) -> bool {
+        self.key.is_empty() || self.secret.is_empty()
+    }
+
+    pub(crate) fn is_blank(&self) -> bool {
+        self.key.trim().is_empty() || self.secret.expose().trim().is_empty()
+    }
+}
+
+/// Source of the API credentials, e.g. a secrets manager, for
+/// [`Capture::with_provider`](crate::Capture::with_provider). The client
+/// caches what it returns and asks again only on
+/// [`Capture::refresh`](crate::Capture::refresh), so URL building stays
+/// synchronous.
+pub trait CredentialsProvider: Send + Sync {
+    fn credentials(&self) -> BackendFuture<'_, Credentials>;
+}
+
+/// Fixed credentials, as passed to [`Capture::new`](crate::Capture::new).
+#[derive(Debug, Clone)]
+pub struct StaticCredentials(Credentials);
+
+impl StaticCredentials {
+    pub fn new(key: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self(Credentials::new(key, secret))
+    }
+}
+
+impl CredentialsProvider for StaticCredentials {
+    fn credentials(&self) -> BackendFuture<'_, Credentials> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+/// Credentials behind a lock. Readers take a snapshot of the whole pair, so
+/// a URL is never signed with one credential's key and another's secret.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedCredentials(Arc<RwLock<Arc<Credentials>>>);
+
+impl SharedCredentials {
+    pub(crate) fn new(credentials: Credentials) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(credentials))))
+    }
+
+    pub(crate) fn current(&self) -> Arc<Credentials> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub(crate) fn replace(&self, credentials: Credentials) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(credentials);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Capture, CaptureError, CaptureOptions};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Hands out `secret_1`, `secret_2`, ... and counts the calls.
+    #[derive(Default)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    impl CredentialsProvider for CountingProvider {
+        fn credentials(&self) -> BackendFuture<'_, Credentials> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move { Ok(Credentials::new("test_key", format!("secret_{call}"))) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_is_asked_on_creation_and_refresh_only() {
+        let provider = Arc::new(CountingProvider::default());
+        let capture = Capture::with_provider(provider.clone(), CaptureOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+
+        let first = capture
+            .build_image_url("https://example.com", None)
+            .unwrap();
+        for _ in 0..3 {
+            assert_eq!(
+                capture
+                    .build_image_url("https://example.com", None)
+                    .unwrap(),
+                first
+            );
+        }
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            first,
+            Capture::new("test_key".to_string(), "secret_1".to_string())
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+
+        let shared = capture.shared();
+        capture.refresh().await.unwrap();
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+        let refreshed = shared.build_image_url("https://example.com", None).unwrap();
+        assert_eq!(
+            refreshed,
+            Capture::new("test_key".to_string(), "secret_2".to_string())
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_static_and_blank_credentials() {
+        let capture = Capture::with_provider(
+            Arc::new(StaticCredentials::new("test_key", "test_secret")),
+            CaptureOptions::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            capture
+                .build_image_url("https://example.com", None)
+                .unwrap(),
+            Capture::new("test_key".to_string(), "test_secret".to_string())
+                .build_image_url("https://example.com", None)
+                .unwrap()
+        );
+        assert!(capture.refresh().await.is_ok());
+
+        let blank = Capture::with_provider(
+            Arc::new(StaticCredentials::new("test_key", "")),
+            CaptureOptions::new(),
+        )
+        .await;
+        assert!(matches!(blank, Err(CaptureError::MissingCredentials)));
+    }
+}
@@ -0,0 +1,112 @@
+use reqwest::header::HeaderValue;
+
+/// Builds `Content-Disposition` header values for serving captured files.
+#[derive(Debug, Clone, Copy)]
+pub struct ContentDisposition;
+
+impl ContentDisposition {
+    /// Returns `inline` or `attachment` disposition for `name`.
+    ///
+    /// Names that are not plain printable ASCII get an ASCII `filename`
+    /// fallback plus an RFC 5987 `filename*` parameter carrying the UTF-8
+    /// name, so browsers that understand it show the original name.
+    pub fn for_filename(name: &str, inline: bool) -> HeaderValue {
+        let disposition = if inline { "inline" } else { "attachment" };
+        let fallback = ascii_fallback(name);
+        let mut value = format!("{disposition}; filename=\"{}\"", quote_escaped(&fallback));
+
+        if fallback != name {
+            value.push_str("; filename*=UTF-8''");
+            value.push_str(&ext_value_encoded(name));
+        }
+
+        HeaderValue::from_str(&value).expect("disposition value is printable ASCII")
+    }
+}
+
+/// Replaces characters that cannot appear in a quoted ASCII filename.
+fn ascii_fallback(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn quote_escaped(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Percent-encodes everything outside RFC 5987 `attr-char`.
+fn ext_value_encoded(name: &str) -> String {
+    let mut encoded = String::new();
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disposition(name: &str, inline: bool) -> String {
+        ContentDisposition::for_filename(name, inline)
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_ascii_filename() {
+        assert_eq!(
+            disposition("report.pdf", false),
+            "attachment; filename=\"report.pdf\""
+        );
+        assert_eq!(
+            disposition("report.pdf", true),
+            "inline; filename=\"report.pdf\""
+        );
+    }
+
+    #[test]
+    fn test_filename_with_spaces() {
+        assert_eq!(
+            disposition("Q3 report.pdf", false),
+            "attachment; filename=\"Q3 report.pdf\""
+        );
+    }
+
+    #[test]
+    fn test_unicode_filename() {
+        assert_eq!(
+            disposition("résumé €.pdf", false),
+            "attachment; filename=\"r_sum_ _.pdf\"; \
+             filename*=UTF-8''r%C3%A9sum%C3%A9%20%E2%82%AC.pdf"
+        );
+    }
+
+    #[test]
+    fn test_quote_and_backslash_filename() {
+        assert_eq!(
+            disposition("say \"hi\"\\.pdf", true),
+            "inline; filename=\"say \\\"hi\\\"\\\\.pdf\""
+        );
+    }
+
+    #[test]
+    fn test_control_characters_are_not_passed_through() {
+        assert_eq!(
+            disposition("a\r\nb.pdf", false),
+            "attachment; filename=\"a__b.pdf\"; filename*=UTF-8''a%0D%0Ab.pdf"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use thiserror::Error;
+
+use crate::rt::SystemTime;
+use crate::ParseUrlError;
+
+#[derive(Error, Debug)]
+pub enum CaptureError {
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    #[error("HTTP request failed: {0}")]
+    HttpError(#[from] reqwest::Error),
+    #[error("URL parsing failed: {0}")]
+    UrlError(#[from] url::ParseError),
+    #[error("Key and Secret are required")]
+    MissingCredentials,
+    /// A credential variable read by [`Capture::from_env`](crate::Capture::from_env)
+    /// is unset or empty.
+    #[error("Environment variable {name} is not set")]
+    MissingEnvVar { name: String },
+    /// A file read by [`CaptureConfig::from_path`](crate::CaptureConfig::from_path)
+    /// could not be parsed, e.g. because of an unknown key.
+    #[error("Invalid config file {path}: {message}")]
+    InvalidConfig { path: String, message: String },
+    #[error("URL is required")]
+    MissingUrl,
+    #[error("Session ID is required")]
+    MissingSessionId,
+    #[error("URL should be a string")]
+    InvalidUrl,
+    #[error("JSON parsing failed: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid option `{field}`: {reason}")]
+    InvalidOption { field: String, reason: String },
+    #[error("Capture request failed with status {status}: {message}")]
+    RequestFailed { status: u16, message: String },
+    /// The capture service worked, but the target page failed to load or
+    /// returned an error status.
+    #[error("Target site failed: {message}")]
+    TargetSiteError {
+        target_status: Option<u16>,
+        message: String,
+    },
+    #[error("Local rate limit reached; retry after {retry_at:?}")]
+    RateLimitExceeded { retry_at: SystemTime },
+    #[error("Response body exceeds {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    /// A 2xx response whose body is not what the request asked for, such
+    /// as a JSON error message where an image was expected.
+    #[error("Unexpected response body ({content_type:?}): {snippet}")]
+    UnexpectedBody {
+        content_type: Option<String>,
+        /// The start of the body, for debugging.
+        snippet: String,
+    },
+    /// A redirect from the capture API that the client's
+    /// [`RedirectPolicy`](crate::RedirectPolicy) did not follow.
+    #[error("Redirect ({status}) not followed to {}", location.as_deref().unwrap_or("an unknown location"))]
+    RedirectNotFollowed {
+        status: u16,
+        location: Option<String>,
+    },
+    /// A redirect the crate reads itself, such as the one answering an
+    /// `s3Redirect` capture, was followed to `location` by a client from
+    /// [`with_client`](crate::Capture::with_client) whose redirect policy
+    /// follows redirects.
+    #[error("Redirect was followed to {location}; the client must not follow redirects for this request")]
+    RedirectFollowed { location: String },
+    #[error("Circuit open for host {host}; retry after {retry_at:?}")]
+    HostCircuitOpen { host: String, retry_at: SystemTime },
+    #[error("Malformed capture URL: {0}")]
+    InvalidCaptureUrl(#[from] ParseUrlError),
+    #[error("Unknown capture profile `{name}`; available: {}", available.join(", "))]
+    UnknownProfile {
+        name: String,
+        /// Registered profile names, sorted.
+        available: Vec<String>,
+    },
+    /// A fetch on a client from [`Capture::url_builder_only`](crate::Capture::url_builder_only).
+    #[error("Fetching is disabled on this URL-builder-only client")]
+    FetchDisabled,
+    /// Background work, such as
+    /// [`Capture::monitor_screenshot`](crate::Capture::monitor_screenshot),
+    /// was started outside a Tokio runtime with the `runtime-tokio` feature.
+    #[error("No async runtime to spawn onto; call this within a Tokio runtime")]
+    NoRuntime,
+    /// A 2xx response reporting `"success": false`.
+    #[error("Capture API reported failure: {message}")]
+    ApiFailure { message: String },
+    #[error("{message}")]
+    SessionsApiError {
+        status: u16,
+        body: serde_json::Value,
+        message: String,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, CaptureError>;
+
+/// Broad category of a [`CaptureError`], for routing and retry decisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The request did not complete, e.g. a connection error or timeout.
+    Transport,
+    /// The capture service failed with a 5xx status.
+    Server,
+    /// The capture service rejected the request with 429.
+    RateLimited,
+    /// The capture service rejected the request with another 4xx status.
+    Rejected,
+    /// The target page failed, not the capture service.
+    TargetSite,
+    /// Missing credentials, invalid options and other caller mistakes.
+    Config,
+    /// A response body could not be decoded, was too large or was not the
+    /// requested format.
+    Decode,
+    Io,
+    /// Refused locally by the rate limiter or a host circuit breaker.
+    Local,
+}
+
+impl CaptureError {
+    /// The underlying HTTP error, e.g. to tell connect failures
+    /// ([`reqwest::Error::is_connect`]) from interrupted bodies.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn as_reqwest_error(&self) -> Option<&reqwest::Error> {
+        match self {
+            CaptureError::HttpError(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// The underlying JSON error, with the line and column where decoding
+    /// failed.
+    pub fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            CaptureError::JsonError(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        let status_kind = |status: u16| match status {
+            429 => ErrorKind::RateLimited,
+            500.. => ErrorKind::Server,
+            _ => ErrorKind::Rejected,
+        };
+
+        match self {
+            #[cfg(feature = "http")]
+            CaptureError::HttpError(error) if error.is_decode() => ErrorKind::Decode,
+            #[cfg(feature = "http")]
+            CaptureError::HttpError(_) => ErrorKind::Transport,
+            CaptureError::RequestFailed { status, .. }
+            | CaptureError::SessionsApiError { status, .. } => status_kind(*status),
+            CaptureError::TargetSiteError { .. } => ErrorKind::TargetSite,
+            CaptureError::ApiFailure { .. } => ErrorKind::Server,
+            CaptureError::UrlError(_)
+            | CaptureError::MissingCredentials
+            | CaptureError::MissingEnvVar { .. }
+            | CaptureError::InvalidConfig { .. }
+            | CaptureError::MissingUrl
+            | CaptureError::MissingSessionId
+            | CaptureError::InvalidUrl
+            | CaptureError::InvalidOption { .. }
+            | CaptureError::UnknownProfile { .. }
+            | CaptureError::FetchDisabled
+            | CaptureError::NoRuntime
+            | CaptureError::RedirectNotFollowed { .. }
+            | CaptureError::RedirectFollowed { .. }
+            | CaptureError::InvalidCaptureUrl(_) => ErrorKind::Config,
+            CaptureError::JsonError(_)
+            | CaptureError::ResponseTooLarge { .. }
+            | CaptureError::UnexpectedBody { .. } => ErrorKind::Decode,
+            CaptureError::IoError(_) => ErrorKind::Io,
+            CaptureError::RateLimitExceeded { .. } | CaptureError::HostCircuitOpen { .. } => {
+                ErrorKind::Local
+            }
+        }
+    }
+}
+
+pub(crate) fn invalid_option(field: &str, reason: &str) -> CaptureError {
+    CaptureError::InvalidOption {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::mock_server::serve_once;
+    use crate::{Capture, ContentResponse, RequestType};
+    use std::error::Error as _;
+
+    fn capture() -> Capture {
+        Capture::new("test_key".to_string(), "test_secret".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_connect_error_is_reachable() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        let capture = capture();
+        let error = capture
+            .fetch_bytes_with_info(&url, &capture.fetch_settings(None), RequestType::Content)
+            .await
+            .unwrap_err();
+        assert!(error.as_reqwest_error().unwrap().is_connect());
+        assert!(error.as_serde_error().is_none());
+        let source = error.source().unwrap();
+        assert!(source.downcast_ref::<reqwest::Error>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_json_error_is_reachable() {
+        let url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            b"{\n  \"success\": tru".to_vec(),
+        )
+        .await;
+        let capture = capture();
+        let (bytes, _) = capture
+            .fetch_bytes_with_info(&url, &capture.fetch_settings(None), RequestType::Content)
+            .await
+            .unwrap();
+
+        let error = ContentResponse::from_slice(&bytes, &url).unwrap_err();
+        let json = error.as_serde_error().unwrap();
+        assert!(json.is_syntax() || json.is_eof());
+        assert_eq!(json.line(), 2);
+        assert!(error.as_reqwest_error().is_none());
+        let source = error.source().unwrap();
+        assert!(source.downcast_ref::<serde_json::Error>().is_some());
+    }
+
+    #[test]
+    fn test_errors_without_a_cause_have_no_source() {
+        for error in [
+            CaptureError::MissingUrl,
+            CaptureError::ApiFailure {
+                message: "boom".to_string(),
+            },
+        ] {
+            assert!(error.source().is_none());
+            assert!(error.as_reqwest_error().is_none() && error.as_serde_error().is_none());
+        }
+    }
+}
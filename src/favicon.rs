@@ -0,0 +1,186 @@
+//! Site icons, located through the metadata endpoint.
+
+use url::Url;
+
+use crate::{overrides, rt, Capture, CaptureError, MetadataResponse, Result};
+
+/// Icon of a captured site, from [`Capture::fetch_favicon`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Favicon {
+    pub bytes: Vec<u8>,
+    /// `Content-Type` of the icon response, `application/octet-stream`
+    /// when the site sends none.
+    pub content_type: String,
+    /// Where the icon was downloaded from.
+    pub source_url: Url,
+}
+
+impl Capture {
+    /// Downloads the icon of the page at `url`: the one reported by the
+    /// metadata endpoint, resolved against `url` when relative, or
+    /// `/favicon.ico` on the page's origin when none is reported. The icon is
+    /// fetched from the site directly with this client's HTTP client.
+    pub async fn fetch_favicon(&self, url: &str) -> Result<Favicon> {
+        let metadata = self.fetch_metadata(url, None).await?;
+        self.favicon_from_metadata(&metadata, url).await
+    }
+
+    pub(crate) async fn favicon_from_metadata(
+        &self,
+        metadata: &MetadataResponse,
+        page_url: &str,
+    ) -> Result<Favicon> {
+        let source_url = favicon_url(metadata, page_url)?;
+        let mut request = self.client.get(source_url.as_str());
+        if let Some(timeout) = self.options.timeout {
+            request = request.timeout(timeout);
+        }
+
+        let (bytes, content_type) = rt::compat(async {
+            let response = request.send().await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(CaptureError::TargetSiteError {
+                    target_status: Some(status.as_u16()),
+                    message: format!("favicon {source_url} returned {status}"),
+                });
+            }
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("application/octet-stream")
+                .to_string();
+            let bytes = overrides::read_body(response, self.options.max_response_bytes).await?;
+            Ok((bytes, content_type))
+        })
+        .await?;
+
+        Ok(Favicon {
+            bytes,
+            content_type,
+            source_url,
+        })
+    }
+}
+
+/// The reported icon resolved against `page_url`, or `/favicon.ico` on its
+/// origin.
+fn favicon_url(metadata: &MetadataResponse, page_url: &str) -> Result<Url> {
+    let page_url = Url::parse(page_url)?;
+    let icon = metadata
+        .metadata
+        .favicon
+        .as_deref()
+        .map(str::trim)
+        .filter(|icon| !icon.is_empty());
+
+    Ok(match icon {
+        Some(icon) => page_url.join(icon)?,
+        None => page_url.join("/favicon.ico")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(favicon: Option<&str>) -> MetadataResponse {
+        serde_json::from_value(serde_json::json!({
+            "success": true,
+            "metadata": { "logo": favicon },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_favicon_url_resolution() {
+        let page = "https://example.com/blog/post?id=1";
+        let resolved = |favicon| favicon_url(&metadata(favicon), page).unwrap().to_string();
+
+        assert_eq!(
+            resolved(Some("https://cdn.example.net/icon.png")),
+            "https://cdn.example.net/icon.png"
+        );
+        assert_eq!(
+            resolved(Some("/assets/icon.svg")),
+            "https://example.com/assets/icon.svg"
+        );
+        assert_eq!(
+            resolved(Some("icons/32.png")),
+            "https://example.com/blog/icons/32.png"
+        );
+        assert_eq!(resolved(None), "https://example.com/favicon.ico");
+        assert_eq!(resolved(Some("  ")), "https://example.com/favicon.ico");
+        assert!(matches!(
+            favicon_url(&metadata(None), "example.com"),
+            Err(CaptureError::UrlError(_))
+        ));
+    }
+
+    async fn download(favicon: impl Fn(&str) -> Option<String>) -> (Result<Favicon>, String) {
+        let (base, mut requests) = crate::mock_server::serve_recorded(
+            "200 OK",
+            &[("content-type", "image/png")],
+            b"\x89PNG\r\n\x1a\n".to_vec(),
+            1,
+        )
+        .await;
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let page_url = format!("{base}/blog/post");
+        let favicon = capture
+            .favicon_from_metadata(&metadata(favicon(&base).as_deref()), &page_url)
+            .await;
+        let request_line = requests
+            .recv()
+            .await
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_string();
+        (favicon, request_line)
+    }
+
+    #[tokio::test]
+    async fn test_downloads_absolute_icon() {
+        let (favicon, request_line) =
+            download(|base| Some(format!("{base}/static/icon.png"))).await;
+        let favicon = favicon.unwrap();
+        assert_eq!(request_line, "GET /static/icon.png HTTP/1.1");
+        assert_eq!(favicon.bytes, b"\x89PNG\r\n\x1a\n");
+        assert_eq!(favicon.content_type, "image/png");
+        assert_eq!(favicon.source_url.path(), "/static/icon.png");
+    }
+
+    #[tokio::test]
+    async fn test_downloads_relative_icon() {
+        let (favicon, request_line) = download(|_| Some("../img/icon.png".to_string())).await;
+        assert_eq!(request_line, "GET /img/icon.png HTTP/1.1");
+        assert_eq!(favicon.unwrap().source_url.path(), "/img/icon.png");
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_favicon_ico() {
+        let (favicon, request_line) = download(|_| None).await;
+        assert_eq!(request_line, "GET /favicon.ico HTTP/1.1");
+        assert_eq!(favicon.unwrap().source_url.path(), "/favicon.ico");
+    }
+
+    #[tokio::test]
+    async fn test_missing_icon_is_a_target_site_error() {
+        let base = crate::mock_server::serve_once("404 Not Found", &[], Vec::new()).await;
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let error = capture
+            .favicon_from_metadata(&metadata(None), &base)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            CaptureError::TargetSiteError {
+                target_status: Some(404),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,518 @@
+//! A small, tolerant HTML parser for post-processing captured pages. It
+//! builds a tree good enough for content extraction; it is not a
+//! spec-compliant DOM and never fails, whatever the input.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Element {
+    /// Lowercase tag name.
+    pub(crate) name: String,
+    /// Attributes with lowercase names and decoded values, in source order.
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Text of all descendants, as written.
+    pub(crate) fn text(&self) -> String {
+        let mut text = String::new();
+        collect_text(&self.children, &mut text);
+        text
+    }
+}
+
+fn collect_text(nodes: &[Node], text: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(value) => text.push_str(value),
+            Node::Element(element) => collect_text(&element.children, text),
+        }
+    }
+}
+
+/// Elements that never have children.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is kept verbatim up to the matching end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Elements that implicitly end an open `<p>`.
+const CLOSES_PARAGRAPH: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "div",
+    "dl",
+    "fieldset",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Deepest element nesting kept in the tree. Elements opened below it are
+/// flattened into the innermost kept element, so code walking the tree
+/// recursively, and dropping it, needs a bounded stack whatever the input.
+/// Blink caps its DOM in the same way, at 512 levels.
+const MAX_DEPTH: usize = 256;
+
+/// Parses `html` into a list of top-level nodes. Unclosed elements are
+/// closed at the end of their parent, stray end tags are ignored, comments
+/// and doctypes are dropped. Elements nested more than [`MAX_DEPTH`] deep
+/// are kept without children, and their content follows them in the
+/// deepest element kept.
+pub(crate) fn parse(html: &str) -> Vec<Node> {
+    let mut parser = Parser {
+        stack: vec![Element::new("#document")],
+        flattened: Vec::new(),
+    };
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            parser.text(rest);
+            break;
+        };
+        parser.text(&rest[..start]);
+        rest = &rest[start..];
+
+        rest = if let Some(comment) = rest.strip_prefix("<!--") {
+            comment.find("-->").map_or("", |end| &comment[end + 3..])
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest.find('>').map_or("", |end| &rest[end + 1..])
+        } else if let Some(end_tag) = rest.strip_prefix("</") {
+            let end = end_tag.find('>').unwrap_or(end_tag.len());
+            parser.close(&end_tag[..end].trim().to_ascii_lowercase());
+            end_tag.get(end + 1..).unwrap_or("")
+        } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            parser.start_tag(&rest[1..])
+        } else {
+            parser.text("<");
+            &rest[1..]
+        };
+    }
+
+    while parser.stack.len() > 1 {
+        parser.pop();
+    }
+    parser
+        .stack
+        .pop()
+        .map(|root| root.children)
+        .unwrap_or_default()
+}
+
+struct Parser {
+    stack: Vec<Element>,
+    /// Names of the elements open below `MAX_DEPTH`, whose content goes to
+    /// the innermost element of `stack`.
+    flattened: Vec<String>,
+}
+
+impl Parser {
+    fn current(&mut self) -> &mut Element {
+        self.stack.last_mut().expect("the document is never popped")
+    }
+
+    fn text(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.current()
+                .children
+                .push(Node::Text(decode_entities(text)));
+        }
+    }
+
+    fn pop(&mut self) {
+        if let Some(element) = self.stack.pop() {
+            self.current().children.push(Node::Element(element));
+        }
+    }
+
+    /// Closes the innermost open `name` and everything opened inside it.
+    fn close(&mut self, name: &str) {
+        if let Some(index) = self.flattened.iter().rposition(|open| open == name) {
+            self.flattened.truncate(index);
+            return;
+        }
+        if let Some(index) = self.stack.iter().skip(1).rposition(|e| e.name == name) {
+            while self.stack.len() > index + 1 {
+                self.pop();
+            }
+        }
+    }
+
+    /// Parses the tag at the start of `input` (just after `<`) and returns
+    /// the input after it.
+    fn start_tag<'a>(&mut self, input: &'a str) -> &'a str {
+        let name_end = input
+            .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+            .unwrap_or(input.len());
+        let mut element = Element::new(&input[..name_end].to_ascii_lowercase());
+        let (rest, self_closing) = parse_attributes(&input[name_end..], &mut element.attributes);
+
+        let top = match self.flattened.last() {
+            Some(name) => name.clone(),
+            None => self.current().name.clone(),
+        };
+        let implicitly_closed = (top == "p" && CLOSES_PARAGRAPH.contains(&element.name.as_str()))
+            || (top == "li" && element.name == "li");
+        if implicitly_closed && self.flattened.pop().is_none() {
+            self.pop();
+        }
+
+        if self_closing || VOID_ELEMENTS.contains(&element.name.as_str()) {
+            self.current().children.push(Node::Element(element));
+            return rest;
+        }
+        if RAW_TEXT_ELEMENTS.contains(&element.name.as_str()) {
+            let end_tag = format!("</{}", element.name);
+            let end = find_ignore_ascii_case(rest, &end_tag).unwrap_or(rest.len());
+            if end > 0 {
+                element.children.push(Node::Text(rest[..end].to_string()));
+            }
+            self.current().children.push(Node::Element(element));
+            let rest = &rest[end..];
+            return rest.find('>').map_or("", |close| &rest[close + 1..]);
+        }
+
+        // The stack holds the document root besides the open elements.
+        if self.stack.len() > MAX_DEPTH {
+            self.flattened.push(element.name.clone());
+            self.current().children.push(Node::Element(element));
+        } else {
+            self.stack.push(element);
+        }
+        rest
+    }
+}
+
+/// Reads attributes up to the end of the tag. Returns the input after the
+/// tag and whether it was self-closing.
+fn parse_attributes<'a>(
+    mut input: &'a str,
+    attributes: &mut Vec<(String, String)>,
+) -> (&'a str, bool) {
+    loop {
+        input = input.trim_start();
+        if let Some(rest) = input.strip_prefix("/>") {
+            return (rest, true);
+        }
+        if let Some(rest) = input.strip_prefix('>') {
+            return (rest, false);
+        }
+        if let Some(rest) = input.strip_prefix('/') {
+            input = rest;
+            continue;
+        }
+        if input.is_empty() {
+            return (input, false);
+        }
+
+        let name_end = input
+            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(input.len())
+            .max(1);
+        let name = input[..name_end].to_ascii_lowercase();
+        input = input[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(rest) = input.strip_prefix('=') {
+            let rest = rest.trim_start();
+            let (raw, after) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let quoted = &rest[1..];
+                    match quoted.find(quote) {
+                        Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                        None => (quoted, ""),
+                    }
+                }
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            value = decode_entities(raw);
+            input = after;
+        }
+        attributes.push((name, value));
+    }
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Decodes character references. Unknown named references are kept as
+/// written.
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        match reference.and_then(decode_reference) {
+            Some(character) => {
+                decoded.push(character);
+                rest = &rest[reference.map_or(0, str::len) + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_reference(reference: &str) -> Option<char> {
+    if let Some(number) = reference.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    Some(match reference {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        _ => return None,
+    })
+}
+
+/// Serializes `nodes` back to HTML, escaping text and attribute values.
+#[cfg(feature = "readability")]
+pub(crate) fn to_html(nodes: &[Node]) -> String {
+    let mut html = String::new();
+    write_html(nodes, &mut html);
+    html
+}
+
+#[cfg(feature = "readability")]
+fn write_html(nodes: &[Node], html: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => escape(text, false, html),
+            Node::Element(element) => {
+                html.push('<');
+                html.push_str(&element.name);
+                for (name, value) in &element.attributes {
+                    html.push(' ');
+                    html.push_str(name);
+                    html.push_str("=\"");
+                    escape(value, true, html);
+                    html.push('"');
+                }
+                html.push('>');
+                if VOID_ELEMENTS.contains(&element.name.as_str()) {
+                    continue;
+                }
+                write_html(&element.children, html);
+                html.push_str("</");
+                html.push_str(&element.name);
+                html.push('>');
+            }
+        }
+    }
+}
+
+#[cfg(feature = "readability")]
+fn escape(text: &str, attribute: bool, html: &mut String) {
+    for character in text.chars() {
+        match character {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if attribute => html.push_str("&quot;"),
+            '\u{a0}' => html.push_str("&nbsp;"),
+            _ => html.push(character),
+        }
+    }
+}
+
+/// Replaces runs of whitespace, including non-breaking spaces, with a
+/// single space.
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_space = false;
+    for character in text.chars() {
+        if character.is_whitespace() {
+            if !in_space {
+                collapsed.push(' ');
+            }
+            in_space = true;
+        } else {
+            collapsed.push(character);
+            in_space = false;
+        }
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(nodes: &[Node], index: usize) -> &Element {
+        match &nodes[index] {
+            Node::Element(element) => element,
+            Node::Text(text) => panic!("expected an element, found text {text:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_is_tolerant() {
+        let nodes = parse(
+            "<!DOCTYPE html><!-- note --><div class=intro id='a'>\
+             <p>One<p>Two &amp; <b>three</div></span><ul><li>a<li>b</ul>",
+        );
+
+        let div = element(&nodes, 0);
+        assert_eq!(div.attribute("class"), Some("intro"));
+        assert_eq!(div.attribute("id"), Some("a"));
+        assert_eq!(div.children.len(), 2);
+        assert_eq!(element(&div.children, 0).text(), "One");
+        assert_eq!(element(&div.children, 1).text(), "Two & three");
+
+        let list = element(&nodes, 1);
+        assert_eq!(list.children.len(), 2);
+        assert_eq!(element(&list.children, 1).text(), "b");
+    }
+
+    #[test]
+    fn test_raw_text_and_void_elements() {
+        let nodes =
+            parse("<script>if (a < b) { x = '</p>'; }</SCRIPT><img src=a.png alt=\"A\"><br/>x");
+
+        assert_eq!(element(&nodes, 0).text(), "if (a < b) { x = '</p>'; }");
+        assert_eq!(element(&nodes, 1).attribute("alt"), Some("A"));
+        assert_eq!(element(&nodes, 2).name, "br");
+        assert_eq!(nodes[3], Node::Text("x".to_string()));
+    }
+
+    #[test]
+    fn test_entities() {
+        assert_eq!(
+            decode_entities("&lt;a&gt; &#39;&#x41;&quot; &nbsp;&unknown; & done"),
+            "<a> 'A\" \u{a0}&unknown; & done"
+        );
+    }
+
+    #[cfg(feature = "readability")]
+    #[test]
+    fn test_round_trip_escapes() {
+        let html = "<p class=\"a&quot;b\">1 &lt; 2 &amp;&amp; 3<br></p>";
+        assert_eq!(to_html(&parse(html)), html);
+    }
+
+    fn depth(nodes: &[Node]) -> usize {
+        let mut deepest = 0;
+        let mut pending: Vec<_> = nodes.iter().map(|node| (node, 1)).collect();
+        while let Some((node, level)) = pending.pop() {
+            if let Node::Element(element) = node {
+                deepest = deepest.max(level);
+                pending.extend(element.children.iter().map(|child| (child, level + 1)));
+            }
+        }
+        deepest
+    }
+
+    #[test]
+    fn test_deep_nesting_is_flattened() {
+        let html = format!(
+            "{}<p>deep</p>{}<p>after</p>",
+            "<div><span>".repeat(50_000),
+            "</span></div>".repeat(50_000)
+        );
+        let nodes = parse(&html);
+
+        assert_eq!(depth(&nodes), MAX_DEPTH + 1);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(element(&nodes, 0).text(), "deep");
+        assert_eq!(element(&nodes, 1).text(), "after");
+    }
+
+    #[cfg(feature = "readability")]
+    #[test]
+    fn test_deep_nesting_round_trips() {
+        let html = format!("{}text", "<div>".repeat(100_000));
+        let serialized = to_html(&parse(&html));
+
+        assert!(serialized.starts_with(&"<div>".repeat(MAX_DEPTH + 1)));
+        assert!(serialized.ends_with(&format!("text{}", "</div>".repeat(MAX_DEPTH))));
+        assert_eq!(serialized.matches("<div>").count(), 100_000);
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        assert_eq!(element(&parse("<p>text<a href=\"x"), 0).text(), "text");
+        assert!(parse("<").iter().all(|node| matches!(node, Node::Text(_))));
+        assert!(parse("<!-- open").is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Outbound links of a captured page, read from its HTML.
+
+use url::Url;
+
+use crate::html::{self, Element, Node};
+
+/// An `<a href>` on a captured page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The target, resolved against the page URL and any `<base href>`.
+    pub href: String,
+    /// The link text with whitespace collapsed; empty for image-only links.
+    pub text: String,
+    /// Lowercase tokens of the `rel` attribute, e.g. `nofollow`.
+    pub rel: Vec<String>,
+}
+
+/// Links in document order. Targets that cannot be resolved, such as
+/// `javascript:` URLs or malformed hosts, are left out. Relative targets are
+/// left out as well when `page_url` is not an absolute URL.
+pub(crate) fn extract(page: &str, page_url: &str) -> Vec<Link> {
+    let nodes = html::parse(page);
+    let page_url = Url::parse(page_url).ok();
+    let base = find_base(&nodes)
+        .and_then(|href| match &page_url {
+            Some(page_url) => page_url.join(href).ok(),
+            None => Url::parse(href).ok(),
+        })
+        .or(page_url);
+
+    let mut links = Vec::new();
+    collect(&nodes, base.as_ref(), &mut links);
+    links
+}
+
+fn find_base(nodes: &[Node]) -> Option<&str> {
+    nodes.iter().find_map(|node| match node {
+        Node::Element(element) if element.name == "base" => element.attribute("href"),
+        Node::Element(element) => find_base(&element.children),
+        Node::Text(_) => None,
+    })
+}
+
+fn collect(nodes: &[Node], base: Option<&Url>, links: &mut Vec<Link>) {
+    for node in nodes {
+        let Node::Element(element) = node else {
+            continue;
+        };
+        if element.name == "a" {
+            if let Some(link) = link(element, base) {
+                links.push(link);
+            }
+        }
+        collect(&element.children, base, links);
+    }
+}
+
+fn link(element: &Element, base: Option<&Url>) -> Option<Link> {
+    let href = element.attribute("href")?.trim();
+    if href.is_empty() {
+        return None;
+    }
+    let href = match base {
+        Some(base) => base.join(href),
+        None => Url::parse(href),
+    }
+    .ok()?;
+    if href.scheme() == "javascript" {
+        return None;
+    }
+
+    Some(Link {
+        href: href.into(),
+        text: html::collapse_whitespace(&element.text())
+            .trim()
+            .to_string(),
+        rel: element
+            .attribute("rel")
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body>
+        <a href="/about">About   us</a>
+        <a href="docs/guide.html#install" rel="Help NoFollow">Guide</a>
+        <a href="https://other.example/x?q=1">Other</a>
+        <a href="//cdn.example/logo"><img src="logo.png"></a>
+        <a href="mailto:team@example.com">Mail &amp; more</a>
+        <a href="javascript:void(0)">Menu</a>
+        <a href="http://[::1">Broken</a>
+        <a href="">Empty</a>
+        <a name="anchor">No href</a>
+    </body></html>"#;
+
+    fn hrefs(links: &[Link]) -> Vec<&str> {
+        links.iter().map(|link| link.href.as_str()).collect()
+    }
+
+    #[test]
+    fn test_links_are_resolved_against_the_page() {
+        let links = extract(PAGE, "https://example.com/blog/post");
+        assert_eq!(
+            hrefs(&links),
+            [
+                "https://example.com/about",
+                "https://example.com/blog/docs/guide.html#install",
+                "https://other.example/x?q=1",
+                "https://cdn.example/logo",
+                "mailto:team@example.com",
+            ]
+        );
+        assert_eq!(links[0].text, "About us");
+        assert_eq!(links[1].rel, ["help", "nofollow"]);
+        assert!(links[3].text.is_empty() && links[3].rel.is_empty());
+        assert_eq!(links[4].text, "Mail & more");
+    }
+
+    #[test]
+    fn test_base_href() {
+        let page = r#"<head><base href="/docs/"></head><a href="intro">Intro</a>"#;
+        let links = extract(page, "https://example.com/index.html");
+        assert_eq!(hrefs(&links), ["https://example.com/docs/intro"]);
+    }
+
+    #[test]
+    fn test_relative_links_need_an_absolute_page_url() {
+        let links = extract(PAGE, "not a url");
+        assert_eq!(
+            hrefs(&links),
+            ["https://other.example/x?q=1", "mailto:team@example.com"]
+        );
+        assert!(extract("", "https://example.com").is_empty());
+    }
+
+    #[test]
+    fn test_deeply_nested_links() {
+        let page = format!(
+            "{}<a href=\"/deep\">Deep</a>",
+            "<div><a href=\"/nested\">".repeat(50_000)
+        );
+
+        let links = extract(&page, "https://example.com/");
+        assert_eq!(links.len(), 50_001);
+        assert_eq!(links[0].href, "https://example.com/nested");
+        assert_eq!(links[50_000].href, "https://example.com/deep");
+    }
+}
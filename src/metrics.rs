@@ -0,0 +1,60 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{RequestType, ShadowReport};
+
+/// Receives measurements of a client's requests, e.g. to feed an
+/// application's metrics. Set with
+/// [`CaptureOptions::with_metrics`](crate::CaptureOptions::with_metrics).
+///
+/// The callback runs on the task that made the measurement, so it should
+/// return quickly.
+#[derive(Clone)]
+pub struct MetricsHook {
+    callback: Arc<dyn Fn(&Metric<'_>) + Send + Sync>,
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsHook").finish_non_exhaustive()
+    }
+}
+
+impl MetricsHook {
+    pub fn new(callback: impl Fn(&Metric<'_>) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+
+    pub(crate) fn record(&self, metric: &Metric<'_>) {
+        (self.callback)(metric);
+    }
+}
+
+/// One measurement passed to a [`MetricsHook`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Metric<'a> {
+    /// A capture request was sent and has finished. Recorded for every
+    /// attempt, so a retried fetch records several.
+    Fetch(&'a FetchMetric),
+    /// A shadow request was compared with its primary fetch.
+    Shadow(&'a ShadowReport),
+}
+
+/// Measurements of one capture request, passed in [`Metric::Fetch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchMetric {
+    pub request_type: RequestType,
+    /// HTTP status, or `None` when no response arrived.
+    pub status: Option<u16>,
+    /// Time spent waiting for a request slot under `max_concurrent_requests`,
+    /// as in [`FetchInfo::queue_time`](crate::FetchInfo::queue_time). Long
+    /// waits mean the limit is saturated.
+    pub queue_time: Duration,
+    /// Time from sending the request until the body was read or the request
+    /// failed.
+    pub latency: Duration,
+}
@@ -0,0 +1,181 @@
+//! A minimal HTTP responder for tests that need a real response.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Semaphore};
+
+/// Answers one request with `status` (e.g. `"302 Found"`), `headers` and
+/// `body`, and returns the server's base URL.
+pub(crate) async fn serve_once(status: &str, headers: &[(&str, &str)], body: Vec<u8>) -> String {
+    serve(status, headers, body, 1).await
+}
+
+/// Like [`serve_once`], but answers `requests` requests, concurrently.
+pub(crate) async fn serve(
+    status: &str,
+    headers: &[(&str, &str)],
+    body: Vec<u8>,
+    requests: usize,
+) -> String {
+    serve_recorded(status, headers, body, requests).await.0
+}
+
+/// Like [`serve`], also passing on the head of each request received.
+pub(crate) async fn serve_recorded(
+    status: &str,
+    headers: &[(&str, &str)],
+    body: Vec<u8>,
+    requests: usize,
+) -> (String, mpsc::UnboundedReceiver<String>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let mut head = format!("HTTP/1.1 {status}\r\n");
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "content-length: {}\r\nconnection: close\r\n\r\n",
+        body.len()
+    ));
+
+    tokio::spawn(async move {
+        for _ in 0..requests {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (head, body, sender) = (head.clone(), body.clone(), sender.clone());
+            tokio::spawn(async move {
+                let mut request = [0; 4096];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let _ = sender.send(String::from_utf8_lossy(&request[..read]).into_owned());
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(&body).await.unwrap();
+            });
+        }
+    });
+    (format!("http://{address}"), receiver)
+}
+
+/// A status such as `"200 OK"`, headers and body.
+pub(crate) type Response<'a> = (&'a str, &'a [(&'a str, &'a str)], Vec<u8>);
+
+/// Answers one request per entry of `responses`, in the order the requests
+/// arrive. Also passes on the head of each request received.
+pub(crate) async fn serve_sequence(
+    responses: &[Response<'_>],
+) -> (String, mpsc::UnboundedReceiver<String>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let responses: Vec<_> = responses
+        .iter()
+        .map(|(status, headers, body)| {
+            let mut response = format!("HTTP/1.1 {status}\r\n");
+            for (name, value) in *headers {
+                response.push_str(&format!("{name}: {value}\r\n"));
+            }
+            response.push_str(&format!(
+                "content-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            ));
+            let mut response = response.into_bytes();
+            response.extend_from_slice(body);
+            response
+        })
+        .collect();
+
+    tokio::spawn(async move {
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let read = socket.read(&mut request).await.unwrap_or(0);
+            let _ = sender.send(String::from_utf8_lossy(&request[..read]).into_owned());
+            socket.write_all(&response).await.unwrap();
+        }
+    });
+    (format!("http://{address}"), receiver)
+}
+
+/// Requests accepted by [`serve_held`], answered only once released.
+pub(crate) struct HeldServer {
+    accepted: Arc<AtomicUsize>,
+    released: Arc<Semaphore>,
+}
+
+impl HeldServer {
+    pub(crate) fn accepted(&self) -> usize {
+        self.accepted.load(Ordering::SeqCst)
+    }
+
+    /// Answers `requests` more of the held requests.
+    pub(crate) fn release(&self, requests: usize) {
+        self.released.add_permits(requests);
+    }
+}
+
+/// Accepts any number of requests, answering each with `200 OK` and `body`
+/// once [`HeldServer::release`] allows it.
+pub(crate) async fn serve_held(body: Vec<u8>) -> (String, HeldServer) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = HeldServer {
+        accepted: Arc::new(AtomicUsize::new(0)),
+        released: Arc::new(Semaphore::new(0)),
+    };
+    let head = format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+        body.len()
+    );
+
+    let (accepted, released) = (server.accepted.clone(), server.released.clone());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let _ = socket.read(&mut request).await;
+            accepted.fetch_add(1, Ordering::SeqCst);
+
+            let (head, body, released) = (head.clone(), body.clone(), released.clone());
+            tokio::spawn(async move {
+                released.acquire().await.unwrap().forget();
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(&body).await.unwrap();
+            });
+        }
+    });
+    (format!("http://{address}"), server)
+}
+
+/// Answers every request with `200 OK` and `body`, keeping connections open
+/// for more requests, and counts the connections accepted.
+pub(crate) async fn serve_keep_alive(body: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n", body.len());
+
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            accepted.fetch_add(1, Ordering::SeqCst);
+
+            let (head, body) = (head.clone(), body.clone());
+            tokio::spawn(async move {
+                let mut request = [0; 4096];
+                while matches!(socket.read(&mut request).await, Ok(read) if read > 0) {
+                    if socket.write_all(head.as_bytes()).await.is_err()
+                        || socket.write_all(&body).await.is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    (format!("http://{address}"), connections)
+}
@@ -0,0 +1,296 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::rt::{self, Instant};
+use crate::{Capture, CaptureError, Result, ScreenshotOptions};
+
+/// Extra delay added to each interval, as a fraction of it, so monitors
+/// started together do not stay in lockstep.
+const JITTER: f64 = 0.1;
+
+/// Failed checks back off exponentially up to this multiple of the interval.
+const MAX_BACKOFF_FACTOR: u32 = 8;
+
+/// Emitted by [`Capture::monitor_screenshot`].
+#[derive(Debug)]
+pub enum MonitorEvent {
+    /// The first screenshot, or one whose bytes differ from the last
+    /// successful check. `check` counts checks from 1, failures included.
+    Changed { screenshot: Vec<u8>, check: u64 },
+    /// A check failed; the next one runs after `retry_in`.
+    Failed {
+        error: CaptureError,
+        check: u64,
+        retry_in: Duration,
+    },
+}
+
+/// Handle to a running screenshot monitor. Stopping it, or dropping it,
+/// ends the monitor; no events are emitted afterwards.
+#[derive(Debug)]
+pub struct CaptureMonitor {
+    stop: watch::Sender<bool>,
+}
+
+impl CaptureMonitor {
+    pub fn stop(&self) {
+        let _ = self.stop.send(true);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.stop.borrow() || self.stop.is_closed()
+    }
+}
+
+impl Capture {
+    /// Captures `url` every `interval` (plus a little jitter) with `fresh`
+    /// set, and calls `on_event` when the screenshot changes or a check
+    /// fails. Failed checks back off exponentially. Screenshots are compared
+    /// byte for byte.
+    ///
+    /// With the `runtime-tokio` feature, must be called within a Tokio
+    /// runtime; elsewhere it fails with [`CaptureError::NoRuntime`].
+    pub fn monitor_screenshot(
+        &self,
+        url: &str,
+        options: &ScreenshotOptions,
+        interval: Duration,
+        on_event: impl Fn(MonitorEvent) + Send + 'static,
+    ) -> Result<CaptureMonitor> {
+        let options = ScreenshotOptions {
+            fresh: Some(true),
+            ..options.clone()
+        };
+        options.validate()?;
+
+        let (stop, stopped) = watch::channel(false);
+        let capture = self.clone();
+        let url = url.to_string();
+        let spawned = rt::spawn(async move {
+            let fetch = || capture.fetch_screenshot(&url, Some(&options));
+            run_monitor(fetch, interval, on_event, stopped).await;
+        });
+        if !spawned {
+            return Err(CaptureError::NoRuntime);
+        }
+
+        Ok(CaptureMonitor { stop })
+    }
+}
+
+async fn run_monitor<F, Fut>(
+    fetch: F,
+    interval: Duration,
+    on_event: impl Fn(MonitorEvent),
+    mut stopped: watch::Receiver<bool>,
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    let mut previous = None;
+    let mut failures = 0;
+    let mut check = 0;
+
+    loop {
+        let result = tokio::select! {
+            biased;
+            _ = stopped.changed() => return,
+            result = fetch() => result,
+        };
+        check += 1;
+
+        let wait = match result {
+            Ok(screenshot) => {
+                failures = 0;
+                let digest = md5::compute(&screenshot).0;
+                if previous != Some(digest) {
+                    previous = Some(digest);
+                    on_event(MonitorEvent::Changed { screenshot, check });
+                }
+                interval.mul_f64(1.0 + JITTER * random_fraction())
+            }
+            Err(error) => {
+                failures += 1;
+                let retry_in = interval * (1 << failures.min(MAX_BACKOFF_FACTOR.ilog2()));
+                on_event(MonitorEvent::Failed {
+                    error,
+                    check,
+                    retry_in,
+                });
+                retry_in
+            }
+        };
+
+        tokio::select! {
+            biased;
+            _ = stopped.changed() => return,
+            _ = rt::sleep(wait) => {}
+        }
+    }
+}
+
+/// A value in `[0, 1)` from the standard library's randomly keyed hasher.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().hash_one(Instant::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_server, CaptureOptions};
+    use tokio::sync::mpsc;
+
+    const INTERVAL: Duration = Duration::from_millis(50);
+    const IMAGE: &[(&str, &str)] = &[("content-type", "image/png")];
+    const JSON: &[(&str, &str)] = &[("content-type", "application/json")];
+
+    fn png(tag: u8) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.push(tag);
+        png
+    }
+
+    fn capture_at(base_url: &str) -> Capture {
+        Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_base_url(base_url).unwrap(),
+        )
+    }
+
+    /// Starts a monitor of `capture` whose events arrive on the returned
+    /// receiver.
+    fn monitor(
+        capture: &Capture,
+        interval: Duration,
+    ) -> (CaptureMonitor, mpsc::UnboundedReceiver<MonitorEvent>) {
+        let (sender, events) = mpsc::unbounded_channel();
+        let monitor = capture
+            .monitor_screenshot(
+                "https://example.com",
+                &ScreenshotOptions::default(),
+                interval,
+                move |event| {
+                    let _ = sender.send(event);
+                },
+            )
+            .unwrap();
+        (monitor, events)
+    }
+
+    async fn next_event(events: &mut mpsc::UnboundedReceiver<MonitorEvent>) -> MonitorEvent {
+        tokio::time::timeout(Duration::from_secs(10), events.recv())
+            .await
+            .expect("no monitor event")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_monitor_emits_changes_and_failures_until_stopped() {
+        let (url, mut requests) = mock_server::serve_sequence(&[
+            ("200 OK", IMAGE, png(b'a')),
+            ("200 OK", IMAGE, png(b'a')),
+            (
+                "502 Bad Gateway",
+                JSON,
+                br#"{"error":"bad gateway"}"#.to_vec(),
+            ),
+            ("200 OK", IMAGE, png(b'a')),
+            ("200 OK", IMAGE, png(b'b')),
+        ])
+        .await;
+        let (monitor, mut events) = monitor(&capture_at(&url), INTERVAL);
+
+        assert!(matches!(
+            next_event(&mut events).await,
+            MonitorEvent::Changed { ref screenshot, check: 1 } if *screenshot == png(b'a')
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            MonitorEvent::Failed {
+                error: CaptureError::RequestFailed { status: 502, .. },
+                check: 3,
+                retry_in,
+            } if retry_in == INTERVAL * 2
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            MonitorEvent::Changed { ref screenshot, check: 5 } if *screenshot == png(b'b')
+        ));
+
+        monitor.stop();
+        for _ in 0..5 {
+            assert!(requests.recv().await.unwrap().contains("fresh=true"));
+        }
+        tokio::time::sleep(INTERVAL * 4).await;
+        assert!(events.try_recv().is_err());
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_monitor_backoff_is_capped() {
+        let interval = Duration::from_millis(10);
+        let url = mock_server::serve(
+            "502 Bad Gateway",
+            JSON,
+            br#"{"error":"bad gateway"}"#.to_vec(),
+            6,
+        )
+        .await;
+        let (monitor, mut events) = monitor(&capture_at(&url), interval);
+
+        let mut retries = Vec::new();
+        for _ in 0..6 {
+            match next_event(&mut events).await {
+                MonitorEvent::Failed { retry_in, .. } => retries.push(retry_in.as_millis()),
+                MonitorEvent::Changed { .. } => panic!("the server only fails"),
+            }
+        }
+        monitor.stop();
+        assert_eq!(retries, [20, 40, 80, 80, 80, 80]);
+    }
+
+    #[tokio::test]
+    async fn test_monitor_screenshot_validates_options_and_stops() {
+        let url = mock_server::serve_once("200 OK", IMAGE, png(b'a')).await;
+        let capture = capture_at(&url);
+        let invalid = ScreenshotOptions {
+            vw: Some(0),
+            ..Default::default()
+        };
+        assert!(capture
+            .monitor_screenshot("https://example.com", &invalid, INTERVAL, |_| {})
+            .is_err());
+
+        let monitor = capture
+            .monitor_screenshot(
+                "https://example.com",
+                &ScreenshotOptions::default(),
+                INTERVAL,
+                |_| {},
+            )
+            .unwrap();
+        assert!(!monitor.is_stopped());
+        monitor.stop();
+        assert!(monitor.is_stopped());
+    }
+
+    #[cfg(feature = "runtime-tokio")]
+    #[test]
+    fn test_monitor_screenshot_needs_a_runtime() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        let result = capture.monitor_screenshot(
+            "https://example.com",
+            &ScreenshotOptions::default(),
+            INTERVAL,
+            |_| {},
+        );
+        assert!(matches!(result, Err(CaptureError::NoRuntime)));
+    }
+}
@@ -0,0 +1,428 @@
+use super::query::{decode_query, OptionReader};
+use super::registry::check_range;
+use super::{RequestOptions, UserAgentPreset};
+use crate::error::{invalid_option, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Container of an animated capture, sent as `format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimatedFormat {
+    Gif,
+    Mp4,
+    Webm,
+}
+
+impl AnimatedFormat {
+    pub const ALL: [AnimatedFormat; 3] = [
+        AnimatedFormat::Gif,
+        AnimatedFormat::Mp4,
+        AnimatedFormat::Webm,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnimatedFormat::Gif => "gif",
+            AnimatedFormat::Mp4 => "mp4",
+            AnimatedFormat::Webm => "webm",
+        }
+    }
+}
+
+impl fmt::Display for AnimatedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AnimatedFormat {
+    type Err = crate::CaptureError;
+
+    /// Parses a format name, ignoring case and surrounding whitespace.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| invalid_option("animated_format", &format!("unknown format `{value}`")))
+    }
+}
+
+/// Options for animated captures of a page, such as a scrolling GIF.
+#[derive(Debug, Clone, Default)]
+pub struct AnimatedOptions {
+    // Viewport Options
+    pub vw: Option<u32>,
+    pub vh: Option<u32>,
+    pub scale_factor: Option<f64>,
+
+    // Recording
+    /// Length of the recording in seconds.
+    pub duration: Option<u32>,
+    /// Frames per second of the recording.
+    pub fps: Option<u32>,
+    /// Container of the recording, sent as `format`. The API picks one
+    /// when unset.
+    pub animated_format: Option<AnimatedFormat>,
+    /// Scroll through the full page while recording.
+    pub full: Option<bool>,
+    /// Seconds to wait before recording.
+    pub delay: Option<u32>,
+
+    // Visual Modifications
+    pub dark_mode: Option<bool>,
+    pub block_ads: Option<bool>,
+    pub block_cookie_banners: Option<bool>,
+
+    // Additional Options
+    pub http_auth: Option<String>,
+    pub user_agent: Option<String>,
+    /// Record anew instead of returning the API's cached recording.
+    pub fresh: Option<bool>,
+
+    // Generic override for any future options
+    pub additional_options: Option<RequestOptions>,
+}
+
+impl AnimatedOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn viewport(mut self, vw: u32, vh: u32) -> Self {
+        self.vw = Some(vw);
+        self.vh = Some(vh);
+        self
+    }
+
+    pub fn scale_factor(mut self, scale_factor: f64) -> Self {
+        self.scale_factor = Some(scale_factor);
+        self
+    }
+
+    pub fn duration(mut self, duration: u32) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = Some(fps);
+        self
+    }
+
+    pub fn format(mut self, format: AnimatedFormat) -> Self {
+        self.animated_format = Some(format);
+        self
+    }
+
+    pub fn full(mut self, full: bool) -> Self {
+        self.full = Some(full);
+        self
+    }
+
+    pub fn delay(mut self, delay: u32) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn dark_mode(mut self, dark_mode: bool) -> Self {
+        self.dark_mode = Some(dark_mode);
+        self
+    }
+
+    pub fn block_ads(mut self, block_ads: bool) -> Self {
+        self.block_ads = Some(block_ads);
+        self
+    }
+
+    pub fn block_cookie_banners(mut self, block_cookie_banners: bool) -> Self {
+        self.block_cookie_banners = Some(block_cookie_banners);
+        self
+    }
+
+    pub fn http_auth(mut self, http_auth: impl Into<String>) -> Self {
+        self.http_auth = Some(http_auth.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets `user_agent` to the string for `preset`, replacing any earlier value.
+    pub fn user_agent_preset(mut self, preset: UserAgentPreset) -> Self {
+        self.user_agent = Some(preset.as_str().to_string());
+        self
+    }
+
+    pub fn fresh(mut self, fresh: bool) -> Self {
+        self.fresh = Some(fresh);
+        self
+    }
+
+    /// Parses a query string using the API's parameter names. Unknown keys are
+    /// rejected and the result is validated.
+    pub fn from_query_str(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, true)
+    }
+
+    /// Like [`from_query_str`](Self::from_query_str), but keeps unknown keys
+    /// in `additional_options` instead of rejecting them.
+    pub fn from_query_str_lenient(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, false)
+    }
+
+    fn from_request_options(options: RequestOptions, strict: bool) -> Result<Self> {
+        let mut reader = OptionReader::new(options);
+        let options = Self {
+            vw: reader.integer("vw")?,
+            vh: reader.integer("vh")?,
+            scale_factor: reader.float("scaleFactor")?,
+            duration: reader.integer("duration")?,
+            fps: reader.integer("fps")?,
+            animated_format: reader
+                .string("format")?
+                .map(|format| format.parse())
+                .transpose()?,
+            full: reader.bool("full")?,
+            delay: reader.integer("delay")?,
+            dark_mode: reader.bool("darkMode")?,
+            block_ads: reader.bool("blockAds")?,
+            block_cookie_banners: reader.bool("blockCookieBanners")?,
+            http_auth: reader.string("httpAuth")?,
+            user_agent: reader.string("userAgent")?,
+            fresh: reader.bool("fresh")?,
+            additional_options: reader.finish(strict)?,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Checks option values that the API would otherwise reject at render time.
+    pub fn validate(&self) -> Result<()> {
+        for (field, value) in [
+            ("vw", self.vw.map(f64::from)),
+            ("vh", self.vh.map(f64::from)),
+            ("scale_factor", self.scale_factor),
+            ("duration", self.duration.map(f64::from)),
+            ("fps", self.fps.map(f64::from)),
+            ("delay", self.delay.map(f64::from)),
+        ] {
+            if let Some(value) = value {
+                check_range(field, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the options and converts them into request options.
+    pub fn try_to_request_options(&self) -> Result<RequestOptions> {
+        self.validate()?;
+        Ok(self.to_request_options())
+    }
+
+    pub fn to_request_options(&self) -> RequestOptions {
+        let mut options = RequestOptions::new();
+
+        if let Some(vw) = self.vw {
+            options.insert("vw".to_string(), serde_json::Value::Number(vw.into()));
+        }
+        if let Some(vh) = self.vh {
+            options.insert("vh".to_string(), serde_json::Value::Number(vh.into()));
+        }
+        if let Some(scale_factor) = self.scale_factor {
+            if let Some(num) = serde_json::Number::from_f64(scale_factor) {
+                options.insert("scaleFactor".to_string(), serde_json::Value::Number(num));
+            }
+        }
+        if let Some(duration) = self.duration {
+            options.insert(
+                "duration".to_string(),
+                serde_json::Value::Number(duration.into()),
+            );
+        }
+        if let Some(fps) = self.fps {
+            options.insert("fps".to_string(), serde_json::Value::Number(fps.into()));
+        }
+        if let Some(animated_format) = self.animated_format {
+            options.insert(
+                "format".to_string(),
+                serde_json::Value::String(animated_format.to_string()),
+            );
+        }
+        if let Some(full) = self.full {
+            options.insert("full".to_string(), serde_json::Value::Bool(full));
+        }
+        if let Some(delay) = self.delay {
+            options.insert("delay".to_string(), serde_json::Value::Number(delay.into()));
+        }
+        if let Some(dark_mode) = self.dark_mode {
+            options.insert("darkMode".to_string(), serde_json::Value::Bool(dark_mode));
+        }
+        if let Some(block_ads) = self.block_ads {
+            options.insert("blockAds".to_string(), serde_json::Value::Bool(block_ads));
+        }
+        if let Some(block_cookie_banners) = self.block_cookie_banners {
+            options.insert(
+                "blockCookieBanners".to_string(),
+                serde_json::Value::Bool(block_cookie_banners),
+            );
+        }
+        if let Some(http_auth) = &self.http_auth {
+            options.insert(
+                "httpAuth".to_string(),
+                serde_json::Value::String(http_auth.clone()),
+            );
+        }
+        if let Some(user_agent) = &self.user_agent {
+            options.insert(
+                "userAgent".to_string(),
+                serde_json::Value::String(user_agent.clone()),
+            );
+        }
+        if let Some(fresh) = self.fresh {
+            options.insert("fresh".to_string(), serde_json::Value::Bool(fresh));
+        }
+
+        // Merge additional options, allowing overrides
+        if let Some(additional) = &self.additional_options {
+            for (key, value) in additional {
+                options.insert(key.clone(), value.clone());
+            }
+        }
+
+        options
+    }
+}
+
+impl TryFrom<RequestOptions> for AnimatedOptions {
+    type Error = crate::CaptureError;
+
+    /// Converts raw request options keyed by API parameter name, rejecting
+    /// unknown keys.
+    fn try_from(options: RequestOptions) -> Result<Self> {
+        Self::from_request_options(options, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Capture, CaptureError};
+
+    #[test]
+    fn test_animated_serialization() {
+        let mut additional = RequestOptions::new();
+        additional.insert("fps".to_string(), serde_json::json!(24));
+        let options = AnimatedOptions {
+            additional_options: Some(additional),
+            ..AnimatedOptions::new()
+                .viewport(1280, 720)
+                .scale_factor(1.5)
+                .duration(6)
+                .fps(12)
+                .format(AnimatedFormat::Gif)
+                .full(true)
+                .delay(2)
+                .dark_mode(true)
+                .block_cookie_banners(true)
+        };
+
+        let request_options = options.try_to_request_options().unwrap();
+        assert_eq!(request_options["vw"], serde_json::json!(1280));
+        assert_eq!(request_options["vh"], serde_json::json!(720));
+        assert_eq!(request_options["scaleFactor"], serde_json::json!(1.5));
+        assert_eq!(request_options["duration"], serde_json::json!(6));
+        assert_eq!(request_options["format"], serde_json::json!("gif"));
+        assert_eq!(request_options["full"], serde_json::json!(true));
+        assert_eq!(request_options["delay"], serde_json::json!(2));
+        assert_eq!(request_options["darkMode"], serde_json::json!(true));
+        assert_eq!(
+            request_options["blockCookieBanners"],
+            serde_json::json!(true)
+        );
+        // Additional options are merged last and win.
+        assert_eq!(request_options["fps"], serde_json::json!(24));
+        assert!(!request_options.contains_key("blockAds"));
+    }
+
+    #[test]
+    fn test_animated_url_round_trips_through_query() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = AnimatedOptions::new()
+            .viewport(800, 600)
+            .duration(4)
+            .format(AnimatedFormat::Mp4)
+            .block_ads(true)
+            .fresh(true);
+
+        let url = capture
+            .build_animated_url_structured("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("/animated?"));
+        assert!(url.contains("duration=4"));
+        assert!(url.contains("format=mp4"));
+        assert!(url.contains("blockAds=true"));
+
+        let unvalidated = capture
+            .build_animated_url("https://example.com", Some(&options.to_request_options()))
+            .unwrap();
+        assert_eq!(url, unvalidated);
+
+        let query = url.split_once('?').unwrap().1;
+        let parsed = AnimatedOptions::from_query_str_lenient(query).unwrap();
+        assert_eq!(parsed.vw, Some(800));
+        assert_eq!(parsed.duration, Some(4));
+        assert_eq!(parsed.animated_format, Some(AnimatedFormat::Mp4));
+        assert_eq!(parsed.fresh, Some(true));
+        assert!(parsed.additional_options.unwrap().contains_key("url"));
+    }
+
+    #[test]
+    fn test_animated_out_of_range_values_are_rejected() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for (field, options) in [
+            ("vw", AnimatedOptions::new().viewport(0, 600)),
+            (
+                "scale_factor",
+                AnimatedOptions::new().scale_factor(f64::NAN),
+            ),
+            ("delay", AnimatedOptions::new().delay(600)),
+            ("duration", AnimatedOptions::new().duration(0)),
+            ("duration", AnimatedOptions::new().duration(3600)),
+            ("fps", AnimatedOptions::new().fps(0)),
+            ("fps", AnimatedOptions::new().fps(240)),
+        ] {
+            let result =
+                capture.build_animated_url_structured("https://example.com", Some(&options));
+            assert!(
+                matches!(result, Err(CaptureError::InvalidOption { field: ref f, .. }) if f == field),
+                "{field}: {result:?}"
+            );
+        }
+        assert!(AnimatedOptions::from_query_str("duration=5&stealth=true").is_err());
+        assert!(AnimatedOptions::from_query_str("duration=600").is_err());
+    }
+
+    #[test]
+    fn test_animated_format() {
+        for format in AnimatedFormat::ALL {
+            let options = AnimatedOptions::new().format(format);
+            assert_eq!(
+                options.to_request_options()["format"],
+                serde_json::json!(format.as_str())
+            );
+            assert_eq!(format.as_str().parse::<AnimatedFormat>().unwrap(), format);
+        }
+
+        let options = AnimatedOptions::from_query_str("format=WebM").unwrap();
+        assert_eq!(options.animated_format, Some(AnimatedFormat::Webm));
+        assert!(matches!(
+            AnimatedOptions::from_query_str("format=avi"),
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "animated_format"
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+use super::query::{decode_query, OptionReader};
+use super::registry::check_range;
+use super::{
+    block_resources_value, comma_joined, delay_value, validate_wait_for_timeout, RequestOptions,
+    ResourceType, UserAgentPreset,
+};
+use crate::error::{invalid_option, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Which representations of the page the content endpoint returns. The
+/// others are left empty in the [`ContentResponse`](crate::ContentResponse).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentFormat {
+    Html,
+    Text,
+    Markdown,
+    All,
+}
+
+impl ContentFormat {
+    pub const ALL: [ContentFormat; 4] = [
+        ContentFormat::Html,
+        ContentFormat::Text,
+        ContentFormat::Markdown,
+        ContentFormat::All,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentFormat::Html => "html",
+            ContentFormat::Text => "text",
+            ContentFormat::Markdown => "markdown",
+            ContentFormat::All => "all",
+        }
+    }
+}
+
+impl fmt::Display for ContentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContentFormat {
+    type Err = crate::CaptureError;
+
+    /// Parses a format name, ignoring case and surrounding whitespace.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| invalid_option("content_format", &format!("unknown format `{value}`")))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContentOptions {
+    pub http_auth: Option<String>,
+    pub user_agent: Option<String>,
+    pub delay: Option<u32>,
+    /// Delay in milliseconds. Takes precedence over `delay` when both are set.
+    pub delay_ms: Option<u32>,
+    pub wait_for: Option<String>,
+    pub wait_for_id: Option<String>,
+    /// Maximum time in milliseconds to wait for `wait_for`/`wait_for_id`.
+    pub wait_for_timeout: Option<u32>,
+    pub stealth: Option<bool>,
+    /// Ask the API to report console and network diagnostics for the page.
+    pub diagnostics: Option<bool>,
+    /// Resource types the renderer should not load.
+    pub block_resources: Option<Vec<ResourceType>>,
+    /// Ignore TLS certificate errors on the target site, e.g. self-signed
+    /// staging hosts. Does not affect the connection to capture.page.
+    pub ignore_https_errors: Option<bool>,
+    /// Target status codes that fail the capture instead of rendering the
+    /// error page. Sent comma-separated as `failOnStatus`.
+    pub fail_on_status: Option<Vec<u16>>,
+    /// Representations to return, sent as `format`. Defaults to all of them.
+    pub content_format: Option<ContentFormat>,
+    /// CSS selector of the element to extract. `html`, `text_content` and
+    /// `markdown` of the response then cover that element only.
+    pub selector: Option<String>,
+    /// ID of the element to extract, like `selector`. Setting both is an
+    /// error.
+    pub selector_id: Option<String>,
+
+    // Performance/Detection
+    pub block_cookie_banners: Option<bool>,
+    pub block_ads: Option<bool>,
+    pub bypass_bot_detection: Option<bool>,
+    /// Extract from a new render instead of the API's cached one.
+    pub fresh: Option<bool>,
+
+    // Generic override for any future options
+    pub additional_options: Option<RequestOptions>,
+}
+
+impl ContentOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn http_auth(mut self, http_auth: impl Into<String>) -> Self {
+        self.http_auth = Some(http_auth.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets `user_agent` to the string for `preset`, replacing any earlier value.
+    pub fn user_agent_preset(mut self, preset: UserAgentPreset) -> Self {
+        self.user_agent = Some(preset.as_str().to_string());
+        self
+    }
+
+    pub fn delay_ms(mut self, delay_ms: u32) -> Self {
+        self.delay_ms = Some(delay_ms);
+        self
+    }
+
+    pub fn wait_for(mut self, selector: impl Into<String>) -> Self {
+        self.wait_for = Some(selector.into());
+        self
+    }
+
+    pub fn wait_for_id(mut self, id: impl Into<String>) -> Self {
+        self.wait_for_id = Some(id.into());
+        self
+    }
+
+    pub fn wait_for_timeout(mut self, wait_for_timeout: u32) -> Self {
+        self.wait_for_timeout = Some(wait_for_timeout);
+        self
+    }
+
+    pub fn stealth(mut self, stealth: bool) -> Self {
+        self.stealth = Some(stealth);
+        self
+    }
+
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    pub fn block_resources(mut self, resources: impl Into<Vec<ResourceType>>) -> Self {
+        self.block_resources = Some(resources.into());
+        self
+    }
+
+    pub fn ignore_https_errors(mut self, ignore_https_errors: bool) -> Self {
+        self.ignore_https_errors = Some(ignore_https_errors);
+        self
+    }
+
+    pub fn fail_on_status(mut self, statuses: impl Into<Vec<u16>>) -> Self {
+        self.fail_on_status = Some(statuses.into());
+        self
+    }
+
+    pub fn format(mut self, format: ContentFormat) -> Self {
+        self.content_format = Some(format);
+        self
+    }
+
+    pub fn selector(mut self, selector: impl Into<String>) -> Self {
+        self.selector = Some(selector.into());
+        self
+    }
+
+    pub fn selector_id(mut self, selector_id: impl Into<String>) -> Self {
+        self.selector_id = Some(selector_id.into());
+        self
+    }
+
+    pub fn block_cookie_banners(mut self, block_cookie_banners: bool) -> Self {
+        self.block_cookie_banners = Some(block_cookie_banners);
+        self
+    }
+
+    pub fn block_ads(mut self, block_ads: bool) -> Self {
+        self.block_ads = Some(block_ads);
+        self
+    }
+
+    pub fn bypass_bot_detection(mut self, bypass_bot_detection: bool) -> Self {
+        self.bypass_bot_detection = Some(bypass_bot_detection);
+        self
+    }
+
+    pub fn fresh(mut self, fresh: bool) -> Self {
+        self.fresh = Some(fresh);
+        self
+    }
+
+    /// Parses a query string using the API's parameter names. Unknown keys are
+    /// rejected and the result is validated.
+    pub fn from_query_str(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, true)
+    }
+
+    /// Like [`from_query_str`](Self::from_query_str), but keeps unknown keys
+    /// in `additional_options` instead of rejecting them.
+    pub fn from_query_str_lenient(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, false)
+    }
+
+    fn from_request_options(options: RequestOptions, strict: bool) -> Result<Self> {
+        let mut reader = OptionReader::new(options);
+        let (delay, delay_ms) = reader.delay()?;
+        let options = Self {
+            http_auth: reader.string("httpAuth")?,
+            user_agent: reader.string("userAgent")?,
+            delay,
+            delay_ms,
+            wait_for: reader.string("waitFor")?,
+            wait_for_id: reader.string("waitForId")?,
+            wait_for_timeout: reader.integer("waitForTimeout")?,
+            stealth: reader.bool("stealth")?,
+            diagnostics: reader.bool("diagnostics")?,
+            block_resources: reader.list("blockResources")?,
+            ignore_https_errors: reader.bool("ignoreHttpsErrors")?,
+            fail_on_status: reader.list("failOnStatus")?,
+            content_format: reader
+                .string("format")?
+                .map(|format| format.parse())
+                .transpose()?,
+            selector: reader.string("selector")?,
+            selector_id: reader.string("selectorId")?,
+            block_cookie_banners: reader.bool("blockCookieBanners")?,
+            block_ads: reader.bool("blockAds")?,
+            bypass_bot_detection: reader.bool("bypassBotDetection")?,
+            fresh: reader.bool("fresh")?,
+            additional_options: reader.finish(strict)?,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Checks option values that the API would otherwise reject at render time.
+    pub fn validate(&self) -> Result<()> {
+        validate_wait_for_timeout(self.wait_for_timeout, &self.wait_for, &self.wait_for_id)?;
+
+        if self.selector.is_some() && self.selector_id.is_some() {
+            return Err(invalid_option("selector_id", "conflicts with selector"));
+        }
+
+        if let Some(delay) = self.delay {
+            check_range("delay", f64::from(delay))?;
+        }
+        if let Some(delay_ms) = self.delay_ms {
+            check_range("delay_ms", f64::from(delay_ms))?;
+        }
+
+        Ok(())
+    }
+
+    /// Validates the options and converts them into request options.
+    pub fn try_to_request_options(&self) -> Result<RequestOptions> {
+        self.validate()?;
+        Ok(self.to_request_options())
+    }
+
+    pub fn to_request_options(&self) -> RequestOptions {
+        let mut options = RequestOptions::new();
+
+        if let Some(http_auth) = &self.http_auth {
+            options.insert(
+                "httpAuth".to_string(),
+                serde_json::Value::String(http_auth.clone()),
+            );
+        }
+        if let Some(user_agent) = &self.user_agent {
+            options.insert(
+                "userAgent".to_string(),
+                serde_json::Value::String(user_agent.clone()),
+            );
+        }
+        if let Some(delay) = delay_value(self.delay, self.delay_ms) {
+            options.insert("delay".to_string(), delay);
+        }
+        if let Some(wait_for) = &self.wait_for {
+            options.insert(
+                "waitFor".to_string(),
+                serde_json::Value::String(wait_for.clone()),
+            );
+        }
+        if let Some(wait_for_id) = &self.wait_for_id {
+            options.insert(
+                "waitForId".to_string(),
+                serde_json::Value::String(wait_for_id.clone()),
+            );
+        }
+        if let Some(wait_for_timeout) = self.wait_for_timeout {
+            options.insert(
+                "waitForTimeout".to_string(),
+                serde_json::Value::Number(wait_for_timeout.into()),
+            );
+        }
+        if let Some(stealth) = self.stealth {
+            options.insert("stealth".to_string(), serde_json::Value::Bool(stealth));
+        }
+        if let Some(diagnostics) = self.diagnostics {
+            options.insert(
+                "diagnostics".to_string(),
+                serde_json::Value::Bool(diagnostics),
+            );
+        }
+        if let Some(ignore_https_errors) = self.ignore_https_errors {
+            options.insert(
+                "ignoreHttpsErrors".to_string(),
+                serde_json::Value::Bool(ignore_https_errors),
+            );
+        }
+        if let Some(block_resources) = self
+            .block_resources
+            .as_deref()
+            .and_then(block_resources_value)
+        {
+            options.insert("blockResources".to_string(), block_resources);
+        }
+        if let Some(fail_on_status) = self.fail_on_status.as_deref().and_then(comma_joined) {
+            options.insert("failOnStatus".to_string(), fail_on_status);
+        }
+        if let Some(content_format) = self.content_format {
+            options.insert(
+                "format".to_string(),
+                serde_json::Value::String(content_format.to_string()),
+            );
+        }
+        if let Some(selector) = &self.selector {
+            options.insert(
+                "selector".to_string(),
+                serde_json::Value::String(selector.clone()),
+            );
+        }
+        if let Some(selector_id) = &self.selector_id {
+            options.insert(
+                "selectorId".to_string(),
+                serde_json::Value::String(selector_id.clone()),
+            );
+        }
+        if let Some(block_cookie_banners) = self.block_cookie_banners {
+            options.insert(
+                "blockCookieBanners".to_string(),
+                serde_json::Value::Bool(block_cookie_banners),
+            );
+        }
+        if let Some(block_ads) = self.block_ads {
+            options.insert("blockAds".to_string(), serde_json::Value::Bool(block_ads));
+        }
+        if let Some(bypass_bot_detection) = self.bypass_bot_detection {
+            options.insert(
+                "bypassBotDetection".to_string(),
+                serde_json::Value::Bool(bypass_bot_detection),
+            );
+        }
+        if let Some(fresh) = self.fresh {
+            options.insert("fresh".to_string(), serde_json::Value::Bool(fresh));
+        }
+
+        // Merge additional options, allowing overrides
+        if let Some(additional) = &self.additional_options {
+            for (key, value) in additional {
+                options.insert(key.clone(), value.clone());
+            }
+        }
+
+        options
+    }
+}
+
+impl TryFrom<RequestOptions> for ContentOptions {
+    type Error = crate::CaptureError;
+
+    /// Converts raw request options keyed by API parameter name, rejecting
+    /// unknown keys.
+    fn try_from(options: RequestOptions) -> Result<Self> {
+        Self::from_request_options(options, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Capture, CaptureError};
+
+    #[test]
+    fn test_content_wait_for_timeout_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ContentOptions {
+            wait_for_id: Some("main".to_string()),
+            wait_for_timeout: Some(5000),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_content_url_structured("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("waitForId=main"));
+        assert!(url.contains("waitForTimeout=5000"));
+    }
+
+    #[test]
+    fn test_content_selector_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        let options = ContentOptions::new().selector("main article");
+        let url = capture
+            .build_content_url_structured("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("selector=main%20article"));
+
+        let options = ContentOptions::new().selector_id("post");
+        let url = capture
+            .build_content_url_structured("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("selectorId=post"));
+    }
+
+    #[test]
+    fn test_content_selector_and_selector_id_conflict() {
+        let options = ContentOptions::new()
+            .selector("main article")
+            .selector_id("post");
+        assert!(matches!(
+            options.validate(),
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "selector_id"
+        ));
+        assert!(ContentOptions::from_query_str("selector=main&selectorId=post").is_err());
+    }
+
+    #[test]
+    fn test_content_format() {
+        for format in ContentFormat::ALL {
+            let options = ContentOptions::new().format(format);
+            assert_eq!(
+                options.to_request_options()["format"],
+                serde_json::json!(format.as_str())
+            );
+            assert_eq!(format.as_str().parse::<ContentFormat>().unwrap(), format);
+        }
+
+        let options = ContentOptions::from_query_str("format=Markdown").unwrap();
+        assert_eq!(options.content_format, Some(ContentFormat::Markdown));
+        assert!(matches!(
+            ContentOptions::from_query_str("format=pdf"),
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "content_format"
+        ));
+    }
+
+    #[test]
+    fn test_content_wait_for_timeout_requires_wait_for() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ContentOptions {
+            wait_for_timeout: Some(5000),
+            ..Default::default()
+        };
+
+        let result = capture.build_content_url_structured("https://example.com", Some(&options));
+        assert!(matches!(
+            result,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "wait_for_timeout"
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use super::query::{decode_query, OptionReader};
+use super::registry::check_range;
+use super::{RequestOptions, UserAgentPreset};
+use crate::error::{invalid_option, Result};
+
+#[derive(Debug, Clone, Default)]
+pub struct MetadataOptions {
+    pub http_auth: Option<String>,
+    pub user_agent: Option<String>,
+    /// Seconds to wait before reading the metadata.
+    pub delay: Option<u32>,
+    pub wait_for: Option<String>,
+    pub stealth: Option<bool>,
+    /// Read the metadata from a new render instead of the API's cached one.
+    pub fresh: Option<bool>,
+    /// Language of the page as a BCP 47 tag such as `en` or `pt-BR`, for
+    /// pages that do not declare one. Sent as `lang`.
+    pub language: Option<String>,
+    /// Return the page's JSON-LD blocks in
+    /// [`MetadataResponse::structured_data`](crate::MetadataResponse::structured_data).
+    pub include_structured_data: Option<bool>,
+
+    // Generic override for any future options
+    pub additional_options: Option<RequestOptions>,
+}
+
+impl MetadataOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn http_auth(mut self, http_auth: impl Into<String>) -> Self {
+        self.http_auth = Some(http_auth.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets `user_agent` to the string for `preset`, replacing any earlier value.
+    pub fn user_agent_preset(mut self, preset: UserAgentPreset) -> Self {
+        self.user_agent = Some(preset.as_str().to_string());
+        self
+    }
+
+    pub fn delay(mut self, delay: u32) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn wait_for(mut self, selector: impl Into<String>) -> Self {
+        self.wait_for = Some(selector.into());
+        self
+    }
+
+    pub fn stealth(mut self, stealth: bool) -> Self {
+        self.stealth = Some(stealth);
+        self
+    }
+
+    /// Sends `fresh`, so the API renders the page again instead of
+    /// answering from its cache. A [`DiskCache`](crate::DiskCache) entry is
+    /// skipped too, and replaced by the new response.
+    pub fn fresh(mut self, fresh: bool) -> Self {
+        self.fresh = Some(fresh);
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn include_structured_data(mut self, include: bool) -> Self {
+        self.include_structured_data = Some(include);
+        self
+    }
+
+    /// Parses a query string using the API's parameter names. Unknown keys are
+    /// rejected and the result is validated.
+    pub fn from_query_str(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, true)
+    }
+
+    /// Like [`from_query_str`](Self::from_query_str), but keeps unknown keys
+    /// in `additional_options` instead of rejecting them.
+    pub fn from_query_str_lenient(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, false)
+    }
+
+    fn from_request_options(options: RequestOptions, strict: bool) -> Result<Self> {
+        let mut reader = OptionReader::new(options);
+        let options = Self {
+            http_auth: reader.string("httpAuth")?,
+            user_agent: reader.string("userAgent")?,
+            delay: reader.integer("delay")?,
+            wait_for: reader.string("waitFor")?,
+            stealth: reader.bool("stealth")?,
+            fresh: reader.bool("fresh")?,
+            language: reader.string("lang")?,
+            include_structured_data: reader.bool("structuredData")?,
+            additional_options: reader.finish(strict)?,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Checks option values that the API would otherwise reject at render time.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(delay) = self.delay {
+            check_range("delay", f64::from(delay))?;
+        }
+        if let Some(language) = &self.language {
+            if !is_language_tag(language) {
+                return Err(invalid_option(
+                    "language",
+                    &format!("`{language}` is not a language tag such as `en` or `pt-BR`"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the options and converts them into request options.
+    pub fn try_to_request_options(&self) -> Result<RequestOptions> {
+        self.validate()?;
+        Ok(self.to_request_options())
+    }
+
+    pub fn to_request_options(&self) -> RequestOptions {
+        let mut options = RequestOptions::new();
+
+        if let Some(http_auth) = &self.http_auth {
+            options.insert(
+                "httpAuth".to_string(),
+                serde_json::Value::String(http_auth.clone()),
+            );
+        }
+        if let Some(user_agent) = &self.user_agent {
+            options.insert(
+                "userAgent".to_string(),
+                serde_json::Value::String(user_agent.clone()),
+            );
+        }
+        if let Some(delay) = self.delay {
+            options.insert("delay".to_string(), serde_json::Value::Number(delay.into()));
+        }
+        if let Some(wait_for) = &self.wait_for {
+            options.insert(
+                "waitFor".to_string(),
+                serde_json::Value::String(wait_for.clone()),
+            );
+        }
+        if let Some(stealth) = self.stealth {
+            options.insert("stealth".to_string(), serde_json::Value::Bool(stealth));
+        }
+        if let Some(fresh) = self.fresh {
+            options.insert("fresh".to_string(), serde_json::Value::Bool(fresh));
+        }
+        if let Some(language) = &self.language {
+            options.insert(
+                "lang".to_string(),
+                serde_json::Value::String(language.clone()),
+            );
+        }
+        if let Some(include) = self.include_structured_data {
+            options.insert(
+                "structuredData".to_string(),
+                serde_json::Value::Bool(include),
+            );
+        }
+
+        // Merge additional options, allowing overrides
+        if let Some(additional) = &self.additional_options {
+            for (key, value) in additional {
+                options.insert(key.clone(), value.clone());
+            }
+        }
+
+        options
+    }
+}
+
+impl TryFrom<RequestOptions> for MetadataOptions {
+    type Error = crate::CaptureError;
+
+    /// Converts raw request options keyed by API parameter name, rejecting
+    /// unknown keys.
+    fn try_from(options: RequestOptions) -> Result<Self> {
+        Self::from_request_options(options, true)
+    }
+}
+
+/// Checks the shape of a BCP 47 tag: a primary language of 2 to 8 letters,
+/// then subtags of 1 to 8 letters or digits, separated by hyphens. Whether
+/// the subtags are registered is left to the API.
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    (2..=8).contains(&primary.len())
+        && primary.bytes().all(|byte| byte.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len())
+                && subtag.bytes().all(|byte| byte.is_ascii_alphanumeric())
+        })
+}
@@ -0,0 +1,782 @@
+mod animated;
+mod content;
+mod metadata;
+mod pdf;
+mod query;
+mod registry;
+mod screenshot;
+
+pub use animated::{AnimatedFormat, AnimatedOptions};
+pub use content::{ContentFormat, ContentOptions};
+pub use metadata::MetadataOptions;
+pub use pdf::{PageRanges, PdfFormat, PdfOptions};
+pub use registry::{
+    describe, supported_options, supports, OptionDescriptor, OptionKind, MIN_API_FEATURES,
+};
+pub use screenshot::{ScreenshotOptions, UserAgentPreset};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{invalid_option, Result};
+
+pub type RequestOptions = HashMap<String, serde_json::Value>;
+
+/// Resource types the renderer can skip loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceType {
+    Image,
+    Font,
+    Media,
+    Stylesheet,
+    Script,
+}
+
+impl ResourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceType::Image => "image",
+            ResourceType::Font => "font",
+            ResourceType::Media => "media",
+            ResourceType::Stylesheet => "stylesheet",
+            ResourceType::Script => "script",
+        }
+    }
+}
+
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ResourceType {
+    type Err = crate::CaptureError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "image" => Ok(ResourceType::Image),
+            "font" => Ok(ResourceType::Font),
+            "media" => Ok(ResourceType::Media),
+            "stylesheet" => Ok(ResourceType::Stylesheet),
+            "script" => Ok(ResourceType::Script),
+            _ => Err(invalid_option(
+                "block_resources",
+                &format!("unknown resource type `{value}`"),
+            )),
+        }
+    }
+}
+
+/// Canned S3 ACLs for stored captures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum S3Acl {
+    Private,
+    PublicRead,
+    PublicReadWrite,
+    AuthenticatedRead,
+    BucketOwnerRead,
+    BucketOwnerFullControl,
+}
+
+impl S3Acl {
+    pub const ALL: [S3Acl; 6] = [
+        S3Acl::Private,
+        S3Acl::PublicRead,
+        S3Acl::PublicReadWrite,
+        S3Acl::AuthenticatedRead,
+        S3Acl::BucketOwnerRead,
+        S3Acl::BucketOwnerFullControl,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            S3Acl::Private => "private",
+            S3Acl::PublicRead => "public-read",
+            S3Acl::PublicReadWrite => "public-read-write",
+            S3Acl::AuthenticatedRead => "authenticated-read",
+            S3Acl::BucketOwnerRead => "bucket-owner-read",
+            S3Acl::BucketOwnerFullControl => "bucket-owner-full-control",
+        }
+    }
+}
+
+impl fmt::Display for S3Acl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for S3Acl {
+    type Err = crate::CaptureError;
+
+    /// Parses a canned ACL name, ignoring case and surrounding whitespace.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|acl| acl.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                invalid_option(
+                    "s3_acl",
+                    &format!("unknown ACL `{value}`; use additional_options for others"),
+                )
+            })
+    }
+}
+
+impl From<S3Acl> for String {
+    fn from(acl: S3Acl) -> Self {
+        acl.as_str().to_string()
+    }
+}
+
+/// Sends known ACLs in their canonical spelling and anything else as given.
+pub(crate) fn s3_acl_value(s3_acl: &str) -> serde_json::Value {
+    serde_json::Value::String(
+        s3_acl
+            .parse::<S3Acl>()
+            .map_or_else(|_| s3_acl.to_string(), String::from),
+    )
+}
+
+/// Longest file name the storage upload accepts, in bytes.
+pub(crate) const MAX_FILE_NAME_BYTES: usize = 255;
+
+/// Applies the server's `fileName` sanitization: NFC normalization, path
+/// separators and control characters removed, leading dots and surrounding
+/// whitespace trimmed, and overlong names cut to [`MAX_FILE_NAME_BYTES`]
+/// while keeping a short extension.
+pub(crate) fn sanitize_file_name(name: &str) -> Result<String> {
+    let name: String = name
+        .nfc()
+        .filter(|c| !matches!(c, '/' | '\\') && !c.is_control())
+        .collect();
+    let name = name.trim().trim_start_matches('.').trim_start();
+    if name.trim_end_matches('.').is_empty() {
+        return Err(invalid_option("file_name", "is empty after sanitization"));
+    }
+    if name.len() <= MAX_FILE_NAME_BYTES {
+        return Ok(name.to_string());
+    }
+
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if name.len() - dot <= 16 => name.split_at(dot),
+        _ => (name, ""),
+    };
+    let mut end = MAX_FILE_NAME_BYTES - extension.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    Ok(format!("{}{extension}", stem[..end].trim_end()))
+}
+
+/// Serializes resource types in a fixed order with duplicates removed, so
+/// the same set always signs to the same token.
+pub(crate) fn block_resources_value(resources: &[ResourceType]) -> Option<serde_json::Value> {
+    let mut resources = resources.to_vec();
+    resources.sort();
+    resources.dedup();
+    comma_joined(&resources)
+}
+
+/// The API takes `delay` in seconds, so millisecond delays are sent as
+/// fractional seconds. `delay_ms` wins when both are set.
+pub(crate) fn delay_value(delay: Option<u32>, delay_ms: Option<u32>) -> Option<serde_json::Value> {
+    match delay_ms {
+        Some(ms) if ms % 1000 == 0 => Some(serde_json::Value::Number((ms / 1000).into())),
+        Some(ms) => {
+            serde_json::Number::from_f64(f64::from(ms) / 1000.0).map(serde_json::Value::Number)
+        }
+        None => delay.map(|delay| serde_json::Value::Number(delay.into())),
+    }
+}
+
+/// Joins list options into the comma-separated form the API expects, or
+/// `None` for an empty list.
+pub(crate) fn comma_joined<T: ToString>(values: &[T]) -> Option<serde_json::Value> {
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(serde_json::Value::String(
+        values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+    ))
+}
+
+pub(crate) fn validate_wait_for_timeout(
+    wait_for_timeout: Option<u32>,
+    wait_for: &Option<String>,
+    wait_for_id: &Option<String>,
+) -> Result<()> {
+    if wait_for_timeout.is_some() && wait_for.is_none() && wait_for_id.is_none() {
+        return Err(invalid_option(
+            "wait_for_timeout",
+            "requires wait_for or wait_for_id",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Capture, CaptureError};
+
+    #[test]
+    fn test_sanitize_file_name() {
+        // "e" followed by a combining acute accent composes to "é".
+        assert_eq!(
+            sanitize_file_name("cafe\u{301}.pdf").unwrap(),
+            "caf\u{e9}.pdf"
+        );
+        assert_eq!(
+            sanitize_file_name("  reports/2024\\q1.pdf ").unwrap(),
+            "reports2024q1.pdf"
+        );
+        assert_eq!(sanitize_file_name("../../etc/passwd").unwrap(), "etcpasswd");
+        assert_eq!(
+            sanitize_file_name("tab\there\n.png").unwrap(),
+            "tabhere.png"
+        );
+
+        for name in ["", "..", "...", " / ", "\u{7}"] {
+            assert!(
+                matches!(
+                    sanitize_file_name(name),
+                    Err(CaptureError::InvalidOption { ref field, .. }) if field == "file_name"
+                ),
+                "{name:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sanitize_file_name_caps_length() {
+        let long = format!("{}.pdf", "a".repeat(300));
+        let sanitized = sanitize_file_name(&long).unwrap();
+        assert_eq!(sanitized.len(), MAX_FILE_NAME_BYTES);
+        assert!(sanitized.ends_with("a.pdf"));
+
+        // Multi-byte characters are never split.
+        let long = "\u{e9}".repeat(200);
+        let sanitized = sanitize_file_name(&long).unwrap();
+        assert!(sanitized.len() <= MAX_FILE_NAME_BYTES);
+        assert_eq!(sanitized.chars().count(), MAX_FILE_NAME_BYTES / 2);
+    }
+
+    #[test]
+    fn test_fail_on_status_encoding() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            fail_on_status: Some(vec![404, 500, 503]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.to_request_options()["failOnStatus"],
+            serde_json::json!("404,500,503")
+        );
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("failOnStatus=404%2C500%2C503"));
+
+        let options = PdfOptions {
+            fail_on_status: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(!options.to_request_options().contains_key("failOnStatus"));
+
+        let options = ContentOptions {
+            fail_on_status: Some(vec![410]),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.to_request_options()["failOnStatus"],
+            serde_json::json!("410")
+        );
+    }
+
+    #[test]
+    fn test_ignore_https_errors_structured_and_raw() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        let structured = capture
+            .build_screenshot_url(
+                "https://staging.internal",
+                Some(&ScreenshotOptions {
+                    ignore_https_errors: Some(true),
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        let mut raw_options = RequestOptions::new();
+        raw_options.insert(
+            "ignoreHttpsErrors".to_string(),
+            serde_json::Value::Bool(true),
+        );
+        let raw = capture
+            .build_image_url("https://staging.internal", Some(&raw_options))
+            .unwrap();
+
+        assert!(structured.contains("ignoreHttpsErrors=true"));
+        assert!(raw.contains("ignoreHttpsErrors=true"));
+
+        for options in [
+            PdfOptions {
+                ignore_https_errors: Some(false),
+                ..Default::default()
+            }
+            .to_request_options(),
+            ContentOptions {
+                ignore_https_errors: Some(false),
+                ..Default::default()
+            }
+            .to_request_options(),
+        ] {
+            assert_eq!(options["ignoreHttpsErrors"], serde_json::json!(false));
+        }
+    }
+
+    #[test]
+    fn test_block_resources_order_is_stable() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let content_url = |resources: Vec<ResourceType>| {
+            capture
+                .build_content_url_structured(
+                    "https://example.com",
+                    Some(&ContentOptions {
+                        block_resources: Some(resources),
+                        ..Default::default()
+                    }),
+                )
+                .unwrap()
+        };
+
+        let url = content_url(vec![
+            ResourceType::Script,
+            ResourceType::Image,
+            ResourceType::Font,
+        ]);
+        assert!(url.contains("blockResources=image%2Cfont%2Cscript"));
+        assert_eq!(
+            url,
+            content_url(vec![
+                ResourceType::Font,
+                ResourceType::Script,
+                ResourceType::Image,
+                ResourceType::Font,
+            ])
+        );
+
+        let options = ScreenshotOptions {
+            block_resources: Some(vec![ResourceType::Media, ResourceType::Stylesheet]),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.to_request_options()["blockResources"],
+            serde_json::json!("media,stylesheet")
+        );
+        let empty = ScreenshotOptions {
+            block_resources: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(!empty.to_request_options().contains_key("blockResources"));
+    }
+
+    #[test]
+    fn test_s3_acl_serialization() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for acl in S3Acl::ALL {
+            let options = ScreenshotOptions {
+                s3_acl: Some(acl.into()),
+                ..Default::default()
+            };
+            let url = capture
+                .build_screenshot_url("https://example.com", Some(&options))
+                .unwrap();
+            assert!(url.contains(&format!("s3Acl={}", acl.as_str())));
+        }
+
+        let options = PdfOptions::new().s3_acl("Public-Read ");
+        assert_eq!(
+            options.try_to_request_options().unwrap()["s3Acl"],
+            serde_json::json!("public-read")
+        );
+        assert!(matches!(
+            PdfOptions::new().s3_acl("public-reed").validate(),
+            Err(crate::CaptureError::InvalidOption { ref field, .. }) if field == "s3_acl"
+        ));
+    }
+
+    #[test]
+    fn test_delay_ms_conversion() {
+        let delay_for = |delay_ms| {
+            ScreenshotOptions {
+                delay_ms: Some(delay_ms),
+                ..Default::default()
+            }
+            .to_request_options()["delay"]
+                .clone()
+        };
+
+        assert_eq!(delay_for(0), serde_json::json!(0));
+        assert_eq!(delay_for(250), serde_json::json!(0.25));
+        assert_eq!(delay_for(1500), serde_json::json!(1.5));
+
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ContentOptions {
+            delay_ms: Some(250),
+            ..Default::default()
+        };
+        let url = capture
+            .build_content_url_structured("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("delay=0.25"));
+    }
+
+    #[test]
+    fn test_delay_ms_takes_precedence_over_delay() {
+        let options = PdfOptions {
+            delay: Some(3),
+            delay_ms: Some(500),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.to_request_options()["delay"],
+            serde_json::json!(0.5)
+        );
+
+        let options = PdfOptions {
+            delay: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(options.to_request_options()["delay"], serde_json::json!(3));
+    }
+
+    /// API keys a fully populated instance of each options struct must
+    /// produce. Adding a field means adding it to the struct literals below
+    /// (they list every field, so the compiler insists) and its key here.
+    /// `delay` and `delay_ms` share the `delay` key.
+    const SCREENSHOT_KEYS: &[&str] = &[
+        "vw",
+        "vh",
+        "scaleFactor",
+        "full",
+        "maxHeight",
+        "delay",
+        "waitFor",
+        "waitForId",
+        "waitForTimeout",
+        "darkMode",
+        "transparent",
+        "selector",
+        "selectorId",
+        "selectorPadding",
+        "clipX",
+        "clipY",
+        "clipWidth",
+        "clipHeight",
+        "blockCookieBanners",
+        "blockAds",
+        "bypassBotDetection",
+        "blockResources",
+        "blockUrls",
+        "stealth",
+        "type",
+        "quality",
+        "bestFormat",
+        "resizeWidth",
+        "resizeHeight",
+        "httpAuth",
+        "userAgent",
+        "fresh",
+        "diagnostics",
+        "ignoreHttpsErrors",
+        "failOnStatus",
+        "fileName",
+        "s3Acl",
+        "s3Redirect",
+        "timestamp",
+    ];
+    const PDF_KEYS: &[&str] = &[
+        "httpAuth",
+        "userAgent",
+        "width",
+        "height",
+        "format",
+        "marginTop",
+        "marginRight",
+        "marginBottom",
+        "marginLeft",
+        "scale",
+        "landscape",
+        "pageRanges",
+        "printBackground",
+        "preferCSSPageSize",
+        "tagged",
+        "delay",
+        "waitFor",
+        "waitForId",
+        "darkMode",
+        "blockCookieBanners",
+        "blockAds",
+        "bypassBotDetection",
+        "stealth",
+        "fresh",
+        "ignoreHttpsErrors",
+        "failOnStatus",
+        "fileName",
+        "s3Acl",
+        "s3Redirect",
+        "timestamp",
+    ];
+    const CONTENT_KEYS: &[&str] = &[
+        "httpAuth",
+        "userAgent",
+        "delay",
+        "waitFor",
+        "waitForId",
+        "waitForTimeout",
+        "stealth",
+        "diagnostics",
+        "blockResources",
+        "ignoreHttpsErrors",
+        "failOnStatus",
+        "format",
+        "selector",
+        "selectorId",
+        "blockCookieBanners",
+        "blockAds",
+        "bypassBotDetection",
+        "fresh",
+    ];
+    const METADATA_KEYS: &[&str] = &[
+        "httpAuth",
+        "userAgent",
+        "delay",
+        "waitFor",
+        "stealth",
+        "fresh",
+        "lang",
+        "structuredData",
+    ];
+    const ANIMATED_KEYS: &[&str] = &[
+        "vw",
+        "vh",
+        "scaleFactor",
+        "duration",
+        "fps",
+        "format",
+        "full",
+        "delay",
+        "darkMode",
+        "blockAds",
+        "blockCookieBanners",
+        "httpAuth",
+        "userAgent",
+        "fresh",
+    ];
+
+    fn assert_wire_keys(name: &str, options: &RequestOptions, expected: &[&str]) {
+        let mut actual: Vec<_> = options.keys().map(String::as_str).collect();
+        actual.sort_unstable();
+        let mut expected = expected.to_vec();
+        expected.sort_unstable();
+        assert_eq!(actual, expected, "{name} keys");
+    }
+
+    #[test]
+    fn test_supported_options_match_the_typed_structs() {
+        let mut typed: Vec<_> = [
+            SCREENSHOT_KEYS,
+            PDF_KEYS,
+            CONTENT_KEYS,
+            METADATA_KEYS,
+            ANIMATED_KEYS,
+        ]
+        .concat()
+        .into_iter()
+        .collect();
+        typed.sort_unstable();
+        typed.dedup();
+        let mut supported: Vec<_> = supported_options().iter().map(|d| d.key).collect();
+        supported.sort_unstable();
+        supported.dedup();
+
+        assert_eq!(supported, typed);
+        assert!(typed.iter().all(|key| supports(key)));
+
+        let mut min_api_features = MIN_API_FEATURES.to_vec();
+        min_api_features.sort_unstable();
+        assert_eq!(min_api_features, typed);
+    }
+
+    #[test]
+    fn test_every_field_reaches_the_wire() {
+        let screenshot = ScreenshotOptions {
+            vw: Some(1280),
+            vh: Some(720),
+            scale_factor: Some(2.0),
+            full: Some(true),
+            max_height: Some(5000),
+            delay: Some(1),
+            delay_ms: Some(1500),
+            wait_for: Some("main".to_string()),
+            wait_for_id: Some("app".to_string()),
+            wait_for_timeout: Some(3000),
+            dark_mode: Some(true),
+            transparent: Some(true),
+            selector: Some("#hero".to_string()),
+            selector_id: Some("hero".to_string()),
+            selector_padding: Some(8),
+            clip_x: Some(0),
+            clip_y: Some(100),
+            clip_width: Some(600),
+            clip_height: Some(300),
+            block_cookie_banners: Some(true),
+            block_ads: Some(true),
+            bypass_bot_detection: Some(true),
+            block_resources: Some(vec![ResourceType::Font]),
+            block_urls: Some(vec!["*.ads.example".to_string()]),
+            stealth: Some(true),
+            image_type: Some("jpeg".to_string()),
+            quality: Some(80),
+            best_format: Some(true),
+            resize_width: Some(640),
+            resize_height: Some(360),
+            http_auth: Some("dXNlcjpwYXNz".to_string()),
+            user_agent: Some("Bot/1.0".to_string()),
+            fresh: Some(true),
+            diagnostics: Some(true),
+            ignore_https_errors: Some(true),
+            fail_on_status: Some(vec![404]),
+            file_name: Some("home.jpg".to_string()),
+            s3_acl: Some("private".to_string()),
+            s3_redirect: Some(true),
+            timestamp: Some(true),
+            additional_options: Some(RequestOptions::new()),
+        };
+        let pdf = PdfOptions {
+            http_auth: Some("dXNlcjpwYXNz".to_string()),
+            user_agent: Some("Bot/1.0".to_string()),
+            width: Some("8.5in".to_string()),
+            height: Some("11in".to_string()),
+            format: Some("A4".to_string()),
+            margin_top: Some("1cm".to_string()),
+            margin_right: Some("1cm".to_string()),
+            margin_bottom: Some("1cm".to_string()),
+            margin_left: Some("1cm".to_string()),
+            scale: Some(0.8),
+            landscape: Some(true),
+            page_ranges: Some("1-3".to_string()),
+            print_background: Some(true),
+            prefer_css_page_size: Some(true),
+            tagged: Some(true),
+            delay: Some(1),
+            delay_ms: Some(1500),
+            wait_for: Some("main".to_string()),
+            wait_for_id: Some("app".to_string()),
+            dark_mode: Some(true),
+            block_cookie_banners: Some(true),
+            block_ads: Some(true),
+            bypass_bot_detection: Some(true),
+            stealth: Some(true),
+            fresh: Some(true),
+            ignore_https_errors: Some(true),
+            fail_on_status: Some(vec![404]),
+            file_name: Some("report.pdf".to_string()),
+            s3_acl: Some("private".to_string()),
+            s3_redirect: Some(true),
+            timestamp: Some(true),
+            additional_options: Some(RequestOptions::new()),
+        };
+        let content = ContentOptions {
+            http_auth: Some("dXNlcjpwYXNz".to_string()),
+            user_agent: Some("Bot/1.0".to_string()),
+            delay: Some(1),
+            delay_ms: Some(1500),
+            wait_for: Some("main".to_string()),
+            wait_for_id: Some("app".to_string()),
+            wait_for_timeout: Some(3000),
+            stealth: Some(true),
+            diagnostics: Some(true),
+            block_resources: Some(vec![ResourceType::Image]),
+            ignore_https_errors: Some(true),
+            fail_on_status: Some(vec![404]),
+            content_format: Some(ContentFormat::Markdown),
+            selector: Some("main article".to_string()),
+            selector_id: Some("post".to_string()),
+            block_cookie_banners: Some(true),
+            block_ads: Some(true),
+            bypass_bot_detection: Some(true),
+            fresh: Some(true),
+            additional_options: Some(RequestOptions::new()),
+        };
+        let metadata = MetadataOptions {
+            http_auth: Some("user:pass".to_string()),
+            user_agent: Some("bot/1.0".to_string()),
+            delay: Some(2),
+            wait_for: Some("main".to_string()),
+            stealth: Some(true),
+            fresh: Some(true),
+            language: Some("en-US".to_string()),
+            include_structured_data: Some(true),
+            additional_options: Some(RequestOptions::new()),
+        };
+        let animated = AnimatedOptions {
+            vw: Some(1280),
+            vh: Some(720),
+            scale_factor: Some(1.0),
+            duration: Some(5),
+            fps: Some(15),
+            animated_format: Some(AnimatedFormat::Webm),
+            full: Some(true),
+            delay: Some(1),
+            dark_mode: Some(true),
+            block_ads: Some(true),
+            block_cookie_banners: Some(true),
+            http_auth: Some("dXNlcjpwYXNz".to_string()),
+            user_agent: Some("Bot/1.0".to_string()),
+            fresh: Some(true),
+            additional_options: Some(RequestOptions::new()),
+        };
+
+        assert_wire_keys(
+            "ScreenshotOptions",
+            &screenshot.try_to_request_options().unwrap(),
+            SCREENSHOT_KEYS,
+        );
+        assert_wire_keys(
+            "PdfOptions",
+            &pdf.try_to_request_options().unwrap(),
+            PDF_KEYS,
+        );
+        // Not validated: `selector` and `selector_id` are mutually exclusive.
+        assert_wire_keys(
+            "ContentOptions",
+            &content.to_request_options(),
+            CONTENT_KEYS,
+        );
+        assert_wire_keys(
+            "MetadataOptions",
+            &metadata.try_to_request_options().unwrap(),
+            METADATA_KEYS,
+        );
+        assert_wire_keys(
+            "AnimatedOptions",
+            &animated.try_to_request_options().unwrap(),
+            ANIMATED_KEYS,
+        );
+    }
+}
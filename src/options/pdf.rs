@@ -0,0 +1,815 @@
+use super::query::{decode_query, OptionReader};
+use super::registry::check_range;
+use super::{comma_joined, delay_value, s3_acl_value, sanitize_file_name, RequestOptions, S3Acl};
+use crate::error::{invalid_option, Result};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Paper formats accepted by the PDF endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PdfFormat {
+    A0,
+    A1,
+    A2,
+    A3,
+    A4,
+    A5,
+    A6,
+    Letter,
+    Legal,
+    Tabloid,
+    Ledger,
+}
+
+impl PdfFormat {
+    pub const ALL: [PdfFormat; 11] = [
+        PdfFormat::A0,
+        PdfFormat::A1,
+        PdfFormat::A2,
+        PdfFormat::A3,
+        PdfFormat::A4,
+        PdfFormat::A5,
+        PdfFormat::A6,
+        PdfFormat::Letter,
+        PdfFormat::Legal,
+        PdfFormat::Tabloid,
+        PdfFormat::Ledger,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PdfFormat::A0 => "A0",
+            PdfFormat::A1 => "A1",
+            PdfFormat::A2 => "A2",
+            PdfFormat::A3 => "A3",
+            PdfFormat::A4 => "A4",
+            PdfFormat::A5 => "A5",
+            PdfFormat::A6 => "A6",
+            PdfFormat::Letter => "Letter",
+            PdfFormat::Legal => "Legal",
+            PdfFormat::Tabloid => "Tabloid",
+            PdfFormat::Ledger => "Ledger",
+        }
+    }
+}
+
+impl fmt::Display for PdfFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PdfFormat {
+    type Err = crate::CaptureError;
+
+    /// Parses a format name, ignoring case and surrounding whitespace.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                invalid_option(
+                    "format",
+                    &format!("unknown format `{value}`; use additional_options for others"),
+                )
+            })
+    }
+}
+
+impl From<PdfFormat> for String {
+    fn from(format: PdfFormat) -> Self {
+        format.as_str().to_string()
+    }
+}
+
+/// Pages to include in a PDF, formatted as the API's `pageRanges` value,
+/// e.g. `PageRanges::from(&[1..=3, 5..=5])` is `"1-3,5"`. Pages are
+/// numbered from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRanges(Vec<RangeInclusive<u32>>);
+
+impl PageRanges {
+    pub fn ranges(&self) -> &[RangeInclusive<u32>] {
+        &self.0
+    }
+}
+
+impl From<&[RangeInclusive<u32>]> for PageRanges {
+    fn from(ranges: &[RangeInclusive<u32>]) -> Self {
+        Self(ranges.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[RangeInclusive<u32>; N]> for PageRanges {
+    fn from(ranges: &[RangeInclusive<u32>; N]) -> Self {
+        Self(ranges.to_vec())
+    }
+}
+
+impl fmt::Display for PageRanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PageRanges {
+    type Err = crate::CaptureError;
+
+    /// Parses comma-separated pages and `start-end` ranges, e.g. `1-3,5`.
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = || {
+            invalid_option(
+                "page_ranges",
+                &format!("`{value}` is not a list of pages or ranges like `1-3,5`"),
+            )
+        };
+        let page = |page: &str| match page.parse::<u32>() {
+            Ok(number) if number > 0 && page.bytes().all(|b| b.is_ascii_digit()) => Ok(number),
+            _ => Err(invalid()),
+        };
+
+        value
+            .split(',')
+            .map(|range| {
+                let (start, end) = range.split_once('-').unwrap_or((range, range));
+                let (start, end) = (page(start)?, page(end)?);
+                if start > end {
+                    return Err(invalid());
+                }
+                Ok(start..=end)
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+impl From<PageRanges> for String {
+    fn from(ranges: PageRanges) -> Self {
+        ranges.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PdfOptions {
+    // Authentication
+    pub http_auth: Option<String>,
+    pub user_agent: Option<String>,
+
+    // Page Dimensions
+    pub width: Option<String>,
+    pub height: Option<String>,
+    /// Paper format. Known names are sent in their canonical spelling, see
+    /// [`PdfFormat`]; `format(PdfFormat::A4)` sets it from the enum.
+    pub format: Option<String>,
+
+    // Margins
+    pub margin_top: Option<String>,
+    pub margin_right: Option<String>,
+    pub margin_bottom: Option<String>,
+    pub margin_left: Option<String>,
+
+    // Rendering Options
+    pub scale: Option<f64>,
+    pub landscape: Option<bool>,
+    /// Pages to include, e.g. `1-3,5`; see [`PageRanges`].
+    pub page_ranges: Option<String>,
+    /// Print background colors and images.
+    pub print_background: Option<bool>,
+    /// Use the page size declared by CSS `@page` rules over `format`,
+    /// `width` and `height`.
+    pub prefer_css_page_size: Option<bool>,
+    /// Produce a tagged (accessible) PDF with a structure tree.
+    pub tagged: Option<bool>,
+    pub delay: Option<u32>,
+    /// Delay in milliseconds. Takes precedence over `delay` when both are set.
+    pub delay_ms: Option<u32>,
+    pub wait_for: Option<String>,
+    pub wait_for_id: Option<String>,
+    pub dark_mode: Option<bool>,
+
+    // Performance/Detection
+    pub block_cookie_banners: Option<bool>,
+    pub block_ads: Option<bool>,
+    pub bypass_bot_detection: Option<bool>,
+    pub stealth: Option<bool>,
+    /// Render anew instead of returning the API's cached PDF.
+    pub fresh: Option<bool>,
+    /// Ignore TLS certificate errors on the target site, e.g. self-signed
+    /// staging hosts. Does not affect the connection to capture.page.
+    pub ignore_https_errors: Option<bool>,
+    /// Target status codes that fail the capture instead of rendering the
+    /// error page. Sent comma-separated as `failOnStatus`.
+    pub fail_on_status: Option<Vec<u16>>,
+
+    // Storage/Output
+    pub file_name: Option<String>,
+    /// Canned ACL for the stored object; see [`S3Acl`](super::S3Acl).
+    pub s3_acl: Option<String>,
+    /// Respond with a redirect to the stored S3 object. Use
+    /// `fetch_pdf_stored` to get the object's URL rather than its bytes.
+    pub s3_redirect: Option<bool>,
+    pub timestamp: Option<bool>,
+
+    // Generic override for any future options
+    pub additional_options: Option<RequestOptions>,
+}
+
+impl PdfOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    pub fn width(mut self, width: impl Into<String>) -> Self {
+        self.width = Some(width.into());
+        self
+    }
+
+    pub fn height(mut self, height: impl Into<String>) -> Self {
+        self.height = Some(height.into());
+        self
+    }
+
+    pub fn landscape(mut self, landscape: bool) -> Self {
+        self.landscape = Some(landscape);
+        self
+    }
+
+    pub fn page_ranges(mut self, page_ranges: impl Into<String>) -> Self {
+        self.page_ranges = Some(page_ranges.into());
+        self
+    }
+
+    pub fn print_background(mut self, print_background: bool) -> Self {
+        self.print_background = Some(print_background);
+        self
+    }
+
+    pub fn prefer_css_page_size(mut self, prefer_css_page_size: bool) -> Self {
+        self.prefer_css_page_size = Some(prefer_css_page_size);
+        self
+    }
+
+    pub fn tagged(mut self, tagged: bool) -> Self {
+        self.tagged = Some(tagged);
+        self
+    }
+
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Sets all four margins to `margin`.
+    pub fn margins(self, margin: impl Into<String>) -> Self {
+        let margin = margin.into();
+        self.margin_top(margin.clone())
+            .margin_right(margin.clone())
+            .margin_bottom(margin.clone())
+            .margin_left(margin)
+    }
+
+    pub fn margin_top(mut self, margin: impl Into<String>) -> Self {
+        self.margin_top = Some(margin.into());
+        self
+    }
+
+    pub fn margin_right(mut self, margin: impl Into<String>) -> Self {
+        self.margin_right = Some(margin.into());
+        self
+    }
+
+    pub fn margin_bottom(mut self, margin: impl Into<String>) -> Self {
+        self.margin_bottom = Some(margin.into());
+        self
+    }
+
+    pub fn margin_left(mut self, margin: impl Into<String>) -> Self {
+        self.margin_left = Some(margin.into());
+        self
+    }
+
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    pub fn s3_acl(mut self, s3_acl: impl Into<String>) -> Self {
+        self.s3_acl = Some(s3_acl.into());
+        self
+    }
+
+    pub fn s3_redirect(mut self, s3_redirect: bool) -> Self {
+        self.s3_redirect = Some(s3_redirect);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: bool) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Parses a query string such as `format=A4&landscape=true` using the
+    /// API's parameter names. Unknown keys are rejected.
+    pub fn from_query_str(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, true)
+    }
+
+    /// Like [`from_query_str`](Self::from_query_str), but keeps unknown keys
+    /// in `additional_options` instead of rejecting them.
+    pub fn from_query_str_lenient(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, false)
+    }
+
+    fn from_request_options(options: RequestOptions, strict: bool) -> Result<Self> {
+        let mut reader = OptionReader::new(options);
+        let (delay, delay_ms) = reader.delay()?;
+        let options = Self {
+            http_auth: reader.string("httpAuth")?,
+            user_agent: reader.string("userAgent")?,
+            width: reader.string("width")?,
+            height: reader.string("height")?,
+            format: reader.string("format")?,
+            margin_top: reader.string("marginTop")?,
+            margin_right: reader.string("marginRight")?,
+            margin_bottom: reader.string("marginBottom")?,
+            margin_left: reader.string("marginLeft")?,
+            scale: reader.float("scale")?,
+            landscape: reader.bool("landscape")?,
+            page_ranges: reader.string("pageRanges")?,
+            print_background: reader.bool("printBackground")?,
+            prefer_css_page_size: reader.bool("preferCSSPageSize")?,
+            tagged: reader.bool("tagged")?,
+            delay,
+            delay_ms,
+            wait_for: reader.string("waitFor")?,
+            wait_for_id: reader.string("waitForId")?,
+            dark_mode: reader.bool("darkMode")?,
+            block_cookie_banners: reader.bool("blockCookieBanners")?,
+            block_ads: reader.bool("blockAds")?,
+            bypass_bot_detection: reader.bool("bypassBotDetection")?,
+            stealth: reader.bool("stealth")?,
+            fresh: reader.bool("fresh")?,
+            ignore_https_errors: reader.bool("ignoreHttpsErrors")?,
+            fail_on_status: reader.list("failOnStatus")?,
+            file_name: reader.string("fileName")?,
+            s3_acl: reader.string("s3Acl")?,
+            s3_redirect: reader.bool("s3Redirect")?,
+            timestamp: reader.bool("timestamp")?,
+            additional_options: reader.finish(strict)?,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Sets `file_name` to `name` as the server would sanitize it, and
+    /// returns the stored value.
+    pub fn set_file_name_sanitized(&mut self, name: &str) -> Result<&str> {
+        Ok(self.file_name.insert(sanitize_file_name(name)?))
+    }
+
+    /// Object key the upload is stored under, relative to the bucket's
+    /// configured prefix, or `None` when no usable `file_name` is set or
+    /// `timestamp` is on, since the server picks that suffix at upload time.
+    pub fn predicted_object_key(&self) -> Option<String> {
+        if self.timestamp == Some(true) {
+            return None;
+        }
+        sanitize_file_name(self.file_name.as_deref()?).ok()
+    }
+
+    /// Checks option values that the API would otherwise reject at render time.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(format) = &self.format {
+            format.parse::<PdfFormat>()?;
+        }
+        if let Some(page_ranges) = &self.page_ranges {
+            page_ranges.parse::<PageRanges>()?;
+        }
+        if let Some(s3_acl) = &self.s3_acl {
+            s3_acl.parse::<S3Acl>()?;
+        }
+
+        for (field, value) in [
+            ("scale", self.scale),
+            ("delay", self.delay.map(f64::from)),
+            ("delay_ms", self.delay_ms.map(f64::from)),
+        ] {
+            if let Some(value) = value {
+                check_range(field, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the options and converts them into request options.
+    pub fn try_to_request_options(&self) -> Result<RequestOptions> {
+        self.validate()?;
+        Ok(self.to_request_options())
+    }
+
+    /// Converts the options without validating them. Non-finite floats
+    /// cannot be represented and are left out; use
+    /// [`try_to_request_options`](Self::try_to_request_options) to reject them.
+    pub fn to_request_options(&self) -> RequestOptions {
+        let mut options = RequestOptions::new();
+
+        if let Some(http_auth) = &self.http_auth {
+            options.insert(
+                "httpAuth".to_string(),
+                serde_json::Value::String(http_auth.clone()),
+            );
+        }
+        if let Some(user_agent) = &self.user_agent {
+            options.insert(
+                "userAgent".to_string(),
+                serde_json::Value::String(user_agent.clone()),
+            );
+        }
+        if let Some(width) = &self.width {
+            options.insert(
+                "width".to_string(),
+                serde_json::Value::String(width.clone()),
+            );
+        }
+        if let Some(height) = &self.height {
+            options.insert(
+                "height".to_string(),
+                serde_json::Value::String(height.clone()),
+            );
+        }
+        if let Some(format) = &self.format {
+            let format = format
+                .parse::<PdfFormat>()
+                .map_or_else(|_| format.clone(), String::from);
+            options.insert("format".to_string(), serde_json::Value::String(format));
+        }
+        if let Some(margin_top) = &self.margin_top {
+            options.insert(
+                "marginTop".to_string(),
+                serde_json::Value::String(margin_top.clone()),
+            );
+        }
+        if let Some(margin_right) = &self.margin_right {
+            options.insert(
+                "marginRight".to_string(),
+                serde_json::Value::String(margin_right.clone()),
+            );
+        }
+        if let Some(margin_bottom) = &self.margin_bottom {
+            options.insert(
+                "marginBottom".to_string(),
+                serde_json::Value::String(margin_bottom.clone()),
+            );
+        }
+        if let Some(margin_left) = &self.margin_left {
+            options.insert(
+                "marginLeft".to_string(),
+                serde_json::Value::String(margin_left.clone()),
+            );
+        }
+        if let Some(scale) = self.scale {
+            if let Some(num) = serde_json::Number::from_f64(scale) {
+                options.insert("scale".to_string(), serde_json::Value::Number(num));
+            }
+        }
+        if let Some(landscape) = self.landscape {
+            options.insert("landscape".to_string(), serde_json::Value::Bool(landscape));
+        }
+        if let Some(page_ranges) = &self.page_ranges {
+            options.insert(
+                "pageRanges".to_string(),
+                serde_json::Value::String(page_ranges.clone()),
+            );
+        }
+        if let Some(print_background) = self.print_background {
+            options.insert(
+                "printBackground".to_string(),
+                serde_json::Value::Bool(print_background),
+            );
+        }
+        if let Some(prefer_css_page_size) = self.prefer_css_page_size {
+            options.insert(
+                "preferCSSPageSize".to_string(),
+                serde_json::Value::Bool(prefer_css_page_size),
+            );
+        }
+        if let Some(tagged) = self.tagged {
+            options.insert("tagged".to_string(), serde_json::Value::Bool(tagged));
+        }
+        if let Some(delay) = delay_value(self.delay, self.delay_ms) {
+            options.insert("delay".to_string(), delay);
+        }
+        if let Some(wait_for) = &self.wait_for {
+            options.insert(
+                "waitFor".to_string(),
+                serde_json::Value::String(wait_for.clone()),
+            );
+        }
+        if let Some(wait_for_id) = &self.wait_for_id {
+            options.insert(
+                "waitForId".to_string(),
+                serde_json::Value::String(wait_for_id.clone()),
+            );
+        }
+        if let Some(dark_mode) = self.dark_mode {
+            options.insert("darkMode".to_string(), serde_json::Value::Bool(dark_mode));
+        }
+        if let Some(block_cookie_banners) = self.block_cookie_banners {
+            options.insert(
+                "blockCookieBanners".to_string(),
+                serde_json::Value::Bool(block_cookie_banners),
+            );
+        }
+        if let Some(block_ads) = self.block_ads {
+            options.insert("blockAds".to_string(), serde_json::Value::Bool(block_ads));
+        }
+        if let Some(bypass_bot_detection) = self.bypass_bot_detection {
+            options.insert(
+                "bypassBotDetection".to_string(),
+                serde_json::Value::Bool(bypass_bot_detection),
+            );
+        }
+        if let Some(stealth) = self.stealth {
+            options.insert("stealth".to_string(), serde_json::Value::Bool(stealth));
+        }
+        if let Some(fresh) = self.fresh {
+            options.insert("fresh".to_string(), serde_json::Value::Bool(fresh));
+        }
+        if let Some(file_name) = &self.file_name {
+            options.insert(
+                "fileName".to_string(),
+                serde_json::Value::String(file_name.clone()),
+            );
+        }
+        if let Some(s3_acl) = &self.s3_acl {
+            options.insert("s3Acl".to_string(), s3_acl_value(s3_acl));
+        }
+        if let Some(s3_redirect) = self.s3_redirect {
+            options.insert(
+                "s3Redirect".to_string(),
+                serde_json::Value::Bool(s3_redirect),
+            );
+        }
+        if let Some(timestamp) = self.timestamp {
+            options.insert("timestamp".to_string(), serde_json::Value::Bool(timestamp));
+        }
+        if let Some(ignore_https_errors) = self.ignore_https_errors {
+            options.insert(
+                "ignoreHttpsErrors".to_string(),
+                serde_json::Value::Bool(ignore_https_errors),
+            );
+        }
+        if let Some(fail_on_status) = self.fail_on_status.as_deref().and_then(comma_joined) {
+            options.insert("failOnStatus".to_string(), fail_on_status);
+        }
+
+        // Merge additional options, allowing overrides
+        if let Some(additional) = &self.additional_options {
+            for (key, value) in additional {
+                options.insert(key.clone(), value.clone());
+            }
+        }
+
+        options
+    }
+}
+
+impl TryFrom<RequestOptions> for PdfOptions {
+    type Error = crate::CaptureError;
+
+    /// Converts raw request options keyed by API parameter name, rejecting
+    /// unknown keys.
+    fn try_from(options: RequestOptions) -> Result<Self> {
+        Self::from_request_options(options, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_matches_struct_literal() {
+        let built = PdfOptions::new()
+            .format("A4")
+            .landscape(true)
+            .scale(0.8)
+            .margins("1cm")
+            .margin_bottom("2cm")
+            .file_name("report.pdf")
+            .s3_acl("private")
+            .s3_redirect(false)
+            .timestamp(true);
+        let literal = PdfOptions {
+            format: Some("A4".to_string()),
+            landscape: Some(true),
+            scale: Some(0.8),
+            margin_top: Some("1cm".to_string()),
+            margin_right: Some("1cm".to_string()),
+            margin_bottom: Some("2cm".to_string()),
+            margin_left: Some("1cm".to_string()),
+            file_name: Some("report.pdf".to_string()),
+            s3_acl: Some("private".to_string()),
+            s3_redirect: Some(false),
+            timestamp: Some(true),
+            ..Default::default()
+        };
+
+        assert_eq!(built.to_request_options(), literal.to_request_options());
+    }
+
+    #[test]
+    fn test_print_options() {
+        let options = PdfOptions::new()
+            .print_background(true)
+            .prefer_css_page_size(false)
+            .to_request_options();
+        assert_eq!(options["printBackground"], serde_json::json!(true));
+        assert_eq!(options["preferCSSPageSize"], serde_json::json!(false));
+
+        assert!(!PdfOptions::new()
+            .to_request_options()
+            .contains_key("printBackground"));
+
+        let mut additional = RequestOptions::new();
+        additional.insert("printBackground".to_string(), serde_json::json!(false));
+        let options = PdfOptions {
+            additional_options: Some(additional),
+            ..PdfOptions::new().print_background(true)
+        };
+        assert_eq!(
+            options.to_request_options()["printBackground"],
+            serde_json::json!(false)
+        );
+    }
+
+    #[test]
+    fn test_tagged_combines_with_layout_options() {
+        let options = PdfOptions::new()
+            .format(PdfFormat::A4)
+            .landscape(true)
+            .margins("2cm")
+            .tagged(true)
+            .try_to_request_options()
+            .unwrap();
+
+        assert_eq!(options["tagged"], serde_json::json!(true));
+        assert_eq!(options["format"], serde_json::json!("A4"));
+        assert_eq!(options["landscape"], serde_json::json!(true));
+        assert_eq!(options["marginLeft"], serde_json::json!("2cm"));
+        assert!(!PdfOptions::new()
+            .to_request_options()
+            .contains_key("tagged"));
+    }
+
+    #[test]
+    fn test_builder_page_size() {
+        let options = PdfOptions::new().width("8.5in").height("11in");
+
+        let request_options = options.to_request_options();
+        assert_eq!(request_options["width"], serde_json::json!("8.5in"));
+        assert_eq!(request_options["height"], serde_json::json!("11in"));
+    }
+
+    #[test]
+    fn test_pdf_format_serializes_canonical_name() {
+        for (format, expected) in [(PdfFormat::A4, "A4"), (PdfFormat::Letter, "Letter")] {
+            let options = PdfOptions::new().format(format);
+            assert_eq!(
+                options.try_to_request_options().unwrap()["format"],
+                serde_json::json!(expected)
+            );
+        }
+
+        let options = PdfOptions::new().format(" letter");
+        assert_eq!(
+            options.try_to_request_options().unwrap()["format"],
+            serde_json::json!("Letter")
+        );
+    }
+
+    #[test]
+    fn test_pdf_format_from_str() {
+        assert_eq!("A4 ".parse::<PdfFormat>().unwrap(), PdfFormat::A4);
+        assert_eq!("LEDGER".parse::<PdfFormat>().unwrap(), PdfFormat::Ledger);
+        assert!(matches!(
+            "B5".parse::<PdfFormat>(),
+            Err(crate::CaptureError::InvalidOption { ref field, .. }) if field == "format"
+        ));
+    }
+
+    #[test]
+    fn test_non_finite_scale_is_rejected() {
+        let capture = crate::Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for scale in [f64::NAN, f64::INFINITY] {
+            let result = capture.build_pdf_url_structured(
+                "https://example.com",
+                Some(&PdfOptions::new().scale(scale)),
+            );
+            assert!(
+                matches!(
+                    result,
+                    Err(crate::CaptureError::InvalidOption { ref field, ref reason })
+                        if field == "scale" && reason == "must be a finite number"
+                ),
+                "{scale}: {result:?}"
+            );
+        }
+
+        let url = capture
+            .build_pdf_url_structured("https://example.com", Some(&PdfOptions::new().scale(0.8)))
+            .unwrap();
+        assert!(url.contains("scale=0.8"));
+    }
+
+    #[test]
+    fn test_set_file_name_sanitized() {
+        let mut options = PdfOptions::new();
+        assert_eq!(options.predicted_object_key(), None);
+
+        let stored = options.set_file_name_sanitized("Q1/Résumé.pdf").unwrap();
+        assert_eq!(stored, "Q1Résumé.pdf");
+        assert_eq!(options.file_name.as_deref(), Some("Q1Résumé.pdf"));
+        assert_eq!(
+            options.predicted_object_key().as_deref(),
+            Some("Q1Résumé.pdf")
+        );
+        assert!(options.set_file_name_sanitized("..").is_err());
+        assert_eq!(options.file_name.as_deref(), Some("Q1Résumé.pdf"));
+
+        let options = PdfOptions::new().file_name("a/b.pdf");
+        assert_eq!(options.predicted_object_key().as_deref(), Some("ab.pdf"));
+        assert_eq!(
+            options.timestamp(false).predicted_object_key().as_deref(),
+            Some("ab.pdf")
+        );
+
+        let options = PdfOptions::new().file_name("report.pdf").timestamp(true);
+        assert_eq!(options.predicted_object_key(), None);
+    }
+
+    #[test]
+    fn test_page_ranges_formatting() {
+        assert_eq!(PageRanges::from(&[1..=3, 5..=5]).to_string(), "1-3,5");
+        assert_eq!(PageRanges::from(&[7..=7]).to_string(), "7");
+
+        let options = PdfOptions::new().page_ranges(PageRanges::from(&[1..=3, 5..=5]));
+        assert_eq!(
+            options.try_to_request_options().unwrap()["pageRanges"],
+            serde_json::json!("1-3,5")
+        );
+        assert_eq!(
+            "2-4,9".parse::<PageRanges>().unwrap().ranges(),
+            &[2..=4, 9..=9]
+        );
+    }
+
+    #[test]
+    fn test_invalid_page_ranges_are_rejected() {
+        for page_ranges in ["", "1-", "-3", "0", "3-1", "1,,2", "1 - 3", "+2", "a-b"] {
+            let result = PdfOptions::new().page_ranges(page_ranges).validate();
+            assert!(
+                matches!(
+                    result,
+                    Err(crate::CaptureError::InvalidOption { ref field, .. }) if field == "page_ranges"
+                ),
+                "{page_ranges:?}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_format_is_rejected_but_expressible() {
+        assert!(PdfOptions::new().format("B5").validate().is_err());
+
+        let mut additional = RequestOptions::new();
+        additional.insert("format".to_string(), serde_json::json!("B5"));
+        let options = PdfOptions {
+            additional_options: Some(additional),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.try_to_request_options().unwrap()["format"],
+            serde_json::json!("B5")
+        );
+    }
+}
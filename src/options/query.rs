@@ -0,0 +1,264 @@
+use std::str::FromStr;
+
+use super::RequestOptions;
+use crate::error::{invalid_option, Result};
+
+/// Decodes an `a=1&b=two` query string into raw request options. Values stay
+/// strings; typed conversion happens in [`OptionReader`]. A leading `?` is
+/// ignored and `+` is read as a space, as in form-encoded queries.
+pub(crate) fn decode_query(query: &str) -> Result<RequestOptions> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let mut options = RequestOptions::new();
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode_component(key)?;
+        let value = decode_component(value)?;
+
+        if options
+            .insert(key.clone(), serde_json::Value::String(value))
+            .is_some()
+        {
+            return Err(invalid_option(&key, "is specified more than once"));
+        }
+    }
+
+    Ok(options)
+}
+
+fn decode_component(component: &str) -> Result<String> {
+    urlencoding::decode(&component.replace('+', " "))
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| invalid_option(component, "is not valid percent-encoded UTF-8"))
+}
+
+/// Takes typed values out of raw request options by API key, coercing the
+/// string forms found in query strings. Keys left over after all fields are
+/// read are unknown to the option struct.
+pub(crate) struct OptionReader {
+    options: RequestOptions,
+}
+
+impl OptionReader {
+    pub(crate) fn new(options: RequestOptions) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn string(&mut self, key: &str) -> Result<Option<String>> {
+        match self.options.remove(key) {
+            None => Ok(None),
+            Some(serde_json::Value::String(value)) => Ok(Some(value)),
+            Some(serde_json::Value::Number(value)) => Ok(Some(value.to_string())),
+            Some(serde_json::Value::Bool(value)) => Ok(Some(value.to_string())),
+            Some(_) => Err(invalid_option(key, "expected a string")),
+        }
+    }
+
+    pub(crate) fn bool(&mut self, key: &str) -> Result<Option<bool>> {
+        match self.options.remove(key) {
+            None => Ok(None),
+            Some(serde_json::Value::Bool(value)) => Ok(Some(value)),
+            Some(serde_json::Value::String(value)) => match value.as_str() {
+                "true" => Ok(Some(true)),
+                "false" => Ok(Some(false)),
+                _ => Err(invalid_option(key, "expected `true` or `false`")),
+            },
+            Some(_) => Err(invalid_option(key, "expected `true` or `false`")),
+        }
+    }
+
+    pub(crate) fn integer<T: TryFrom<u64>>(&mut self, key: &str) -> Result<Option<T>> {
+        let value = match self.options.remove(key) {
+            None => return Ok(None),
+            Some(serde_json::Value::Number(value)) => value.as_u64(),
+            Some(serde_json::Value::String(value)) => value.parse::<u64>().ok(),
+            Some(_) => None,
+        };
+
+        value
+            .ok_or_else(|| invalid_option(key, "expected a non-negative integer"))
+            .and_then(|value| {
+                T::try_from(value).map_err(|_| invalid_option(key, "is out of range"))
+            })
+            .map(Some)
+    }
+
+    pub(crate) fn float(&mut self, key: &str) -> Result<Option<f64>> {
+        let value = match self.options.remove(key) {
+            None => return Ok(None),
+            Some(serde_json::Value::Number(value)) => value.as_f64(),
+            Some(serde_json::Value::String(value)) => value.parse::<f64>().ok(),
+            Some(_) => None,
+        };
+
+        match value {
+            Some(value) if value.is_finite() => Ok(Some(value)),
+            _ => Err(invalid_option(key, "expected a finite number")),
+        }
+    }
+
+    /// Reads a comma-separated list, skipping empty entries.
+    pub(crate) fn list<T: FromStr>(&mut self, key: &str) -> Result<Option<Vec<T>>> {
+        let Some(value) = self.string(key)? else {
+            return Ok(None);
+        };
+
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                item.parse()
+                    .map_err(|_| invalid_option(key, &format!("contains invalid entry `{item}`")))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    /// Reads the seconds-based `delay` key back into `(delay, delay_ms)`.
+    /// Whole seconds map to `delay`, fractional ones to `delay_ms`.
+    pub(crate) fn delay(&mut self) -> Result<(Option<u32>, Option<u32>)> {
+        let Some(seconds) = self.float("delay")? else {
+            return Ok((None, None));
+        };
+        if seconds < 0.0 || seconds * 1000.0 > f64::from(u32::MAX) {
+            return Err(invalid_option("delay", "is out of range"));
+        }
+
+        if seconds.fract() == 0.0 {
+            Ok((Some(seconds as u32), None))
+        } else {
+            Ok((None, Some((seconds * 1000.0).round() as u32)))
+        }
+    }
+
+    /// Returns the unread keys as `additional_options`, or rejects them when
+    /// `strict` is set.
+    pub(crate) fn finish(self, strict: bool) -> Result<Option<RequestOptions>> {
+        if self.options.is_empty() {
+            return Ok(None);
+        }
+        if strict {
+            let mut unknown: Vec<_> = self.options.keys().map(String::as_str).collect();
+            unknown.sort_unstable();
+            return Err(invalid_option(unknown[0], "is not a known option"));
+        }
+
+        Ok(Some(self.options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::to_query_string;
+    use crate::{
+        CaptureError, ContentOptions, MetadataOptions, PdfOptions, ResourceType, ScreenshotOptions,
+    };
+
+    /// The canonical form of a query is what `to_query_string` produces for
+    /// its decoded values.
+    fn canonical(query: &str) -> String {
+        to_query_string(&decode_query(query).unwrap())
+    }
+
+    #[test]
+    fn test_screenshot_from_query_str_coerces_values() {
+        let options = ScreenshotOptions::from_query_str(
+            "vw=1280&full=true&darkMode=true&scaleFactor=1.5&delay=0.25&blockResources=font,script",
+        )
+        .unwrap();
+
+        assert_eq!(options.vw, Some(1280));
+        assert_eq!(options.full, Some(true));
+        assert_eq!(options.dark_mode, Some(true));
+        assert_eq!(options.scale_factor, Some(1.5));
+        assert_eq!(options.delay_ms, Some(250));
+        assert_eq!(
+            options.block_resources,
+            Some(vec![ResourceType::Font, ResourceType::Script])
+        );
+        assert!(options.additional_options.is_none());
+    }
+
+    #[test]
+    fn test_from_query_str_round_trips_to_canonical_query() {
+        let screenshot = "?vw=1280&full=true&darkMode=true&maxHeight=8000&type=jpeg&quality=80\
+                          &delay=2&failOnStatus=404%2C500&userAgent=My+Bot%2F1.0";
+        let pdf = "format=A4&landscape=true&marginTop=1cm&scale=0.8&delay=0.5&s3Redirect=false\
+                   &pageRanges=1-3%2C5";
+        let content = "waitFor=main&waitForTimeout=3000&blockResources=image%2Cfont&stealth=true";
+        let metadata = "stealth=true";
+
+        let query = to_query_string(
+            &ScreenshotOptions::from_query_str(screenshot)
+                .unwrap()
+                .to_request_options(),
+        );
+        assert_eq!(query, canonical(screenshot));
+        let query = to_query_string(
+            &PdfOptions::from_query_str(pdf)
+                .unwrap()
+                .to_request_options(),
+        );
+        assert_eq!(query, canonical(pdf));
+        let query = to_query_string(
+            &ContentOptions::from_query_str(content)
+                .unwrap()
+                .to_request_options(),
+        );
+        assert_eq!(query, canonical(content));
+        let query = to_query_string(
+            &MetadataOptions::from_query_str(metadata)
+                .unwrap()
+                .to_request_options(),
+        );
+        assert_eq!(query, canonical(metadata));
+    }
+
+    #[test]
+    fn test_from_query_str_rejects_unknown_keys() {
+        let result = ScreenshotOptions::from_query_str("vw=1280&darkmode=true");
+        assert!(matches!(
+            result,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "darkmode"
+        ));
+
+        let options = ScreenshotOptions::from_query_str_lenient("vw=1280&darkmode=true").unwrap();
+        assert_eq!(
+            options.additional_options.unwrap()["darkmode"],
+            serde_json::json!("true")
+        );
+    }
+
+    #[test]
+    fn test_from_query_str_rejects_bad_values() {
+        for query in ["vw=wide", "full=yes", "quality=300", "vw=1&vw=2"] {
+            assert!(matches!(
+                ScreenshotOptions::from_query_str(query),
+                Err(CaptureError::InvalidOption { .. })
+            ));
+        }
+        assert!(ContentOptions::from_query_str("blockResources=image,video").is_err());
+    }
+
+    #[test]
+    fn test_from_query_str_validates() {
+        let result = ScreenshotOptions::from_query_str("waitForTimeout=1000");
+        assert!(matches!(
+            result,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "wait_for_timeout"
+        ));
+    }
+
+    #[test]
+    fn test_try_from_request_options_accepts_json_values() {
+        let mut raw = RequestOptions::new();
+        raw.insert("vw".to_string(), serde_json::json!(1024));
+        raw.insert("full".to_string(), serde_json::json!(true));
+
+        let options = ScreenshotOptions::try_from(raw).unwrap();
+        assert_eq!(options.vw, Some(1024));
+        assert_eq!(options.full, Some(true));
+    }
+}
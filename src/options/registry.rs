@@ -0,0 +1,755 @@
+use crate::error::{invalid_option, Result};
+use crate::RequestType;
+
+/// Value type of an API option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionKind {
+    Bool,
+    Integer,
+    Float,
+    String,
+    /// A string restricted to [`OptionDescriptor::allowed_values`].
+    Enum,
+}
+
+/// Describes one typed option: its API key, the struct field that sets it,
+/// and the constraints the crate validates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionDescriptor {
+    /// Query parameter name sent to the API.
+    pub key: &'static str,
+    /// Field name on the options structs.
+    pub rust_field: &'static str,
+    pub kind: OptionKind,
+    /// Whether the value is a list, sent comma-separated.
+    pub list: bool,
+    /// Accepted values for [`OptionKind::Enum`] options.
+    pub allowed_values: &'static [&'static str],
+    /// Inclusive bounds enforced by `validate()`.
+    pub range: Option<(f64, f64)>,
+    pub applies_to: &'static [RequestType],
+    pub description: &'static str,
+}
+
+impl OptionDescriptor {
+    const fn new(
+        key: &'static str,
+        rust_field: &'static str,
+        kind: OptionKind,
+        applies_to: &'static [RequestType],
+        description: &'static str,
+    ) -> Self {
+        Self {
+            key,
+            rust_field,
+            kind,
+            list: false,
+            allowed_values: &[],
+            range: None,
+            applies_to,
+            description,
+        }
+    }
+
+    const fn list(mut self) -> Self {
+        self.list = true;
+        self
+    }
+
+    const fn allowed_values(mut self, allowed_values: &'static [&'static str]) -> Self {
+        self.allowed_values = allowed_values;
+        self
+    }
+
+    const fn range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Whether the option can be set for requests of `request_type`.
+    pub fn applies_to(&self, request_type: RequestType) -> bool {
+        self.applies_to.contains(&request_type)
+    }
+}
+
+use OptionKind::{Bool, Enum, Float, Integer};
+use RequestType::{Animated, Content, Image, Metadata, Pdf};
+
+const IMAGE: &[RequestType] = &[Image];
+const PDF: &[RequestType] = &[Pdf];
+const CONTENT: &[RequestType] = &[Content];
+const METADATA: &[RequestType] = &[Metadata];
+const ANIMATED: &[RequestType] = &[Animated];
+const IMAGE_ANIMATED: &[RequestType] = &[Image, Animated];
+const IMAGE_CONTENT: &[RequestType] = &[Image, Content];
+const IMAGE_PDF: &[RequestType] = &[Image, Pdf];
+const IMAGE_PDF_ANIMATED: &[RequestType] = &[Image, Pdf, Animated];
+const RENDERED: &[RequestType] = &[Image, Pdf, Content];
+const RENDERED_ANIMATED: &[RequestType] = &[Image, Pdf, Content, Animated];
+const ALL_BUT_ANIMATED: &[RequestType] = &[Image, Pdf, Content, Metadata];
+const ALL: &[RequestType] = &[Image, Pdf, Content, Metadata, Animated];
+
+/// Largest viewport or resize dimension, in pixels.
+const MAX_DIMENSION: f64 = 10_000.0;
+/// Longest render delay the API accepts.
+const MAX_DELAY_SECONDS: f64 = 30.0;
+/// Longest animated recording the API accepts.
+const MAX_ANIMATION_SECONDS: f64 = 60.0;
+/// Highest frame rate of an animated recording.
+const MAX_FPS: f64 = 60.0;
+
+const RESOURCE_TYPES: &[&str] = &["image", "font", "media", "stylesheet", "script"];
+
+/// Every typed option the crate knows about. Validation reads its bounds
+/// from here, so the descriptors and `validate()` cannot drift apart.
+const REGISTRY: &[OptionDescriptor] = &[
+    // Shared
+    OptionDescriptor::new(
+        "httpAuth",
+        "http_auth",
+        OptionKind::String,
+        ALL,
+        "Basic auth credentials for the target site",
+    ),
+    OptionDescriptor::new(
+        "userAgent",
+        "user_agent",
+        OptionKind::String,
+        ALL,
+        "User agent used to load the page",
+    ),
+    OptionDescriptor::new(
+        "delay",
+        "delay",
+        Integer,
+        ALL,
+        "Seconds to wait before capturing",
+    )
+    .range(0.0, MAX_DELAY_SECONDS),
+    OptionDescriptor::new(
+        "delay",
+        "delay_ms",
+        Integer,
+        RENDERED,
+        "Milliseconds to wait before capturing; wins over delay",
+    )
+    .range(0.0, MAX_DELAY_SECONDS * 1000.0),
+    OptionDescriptor::new(
+        "waitFor",
+        "wait_for",
+        OptionKind::String,
+        ALL_BUT_ANIMATED,
+        "CSS selector to wait for before capturing",
+    ),
+    OptionDescriptor::new(
+        "waitForId",
+        "wait_for_id",
+        OptionKind::String,
+        RENDERED,
+        "Element ID to wait for before capturing",
+    ),
+    OptionDescriptor::new(
+        "waitForTimeout",
+        "wait_for_timeout",
+        Integer,
+        IMAGE_CONTENT,
+        "Milliseconds to wait for waitFor/waitForId",
+    ),
+    OptionDescriptor::new(
+        "stealth",
+        "stealth",
+        Bool,
+        ALL_BUT_ANIMATED,
+        "Hide headless browser fingerprints",
+    ),
+    OptionDescriptor::new(
+        "blockResources",
+        "block_resources",
+        Enum,
+        IMAGE_CONTENT,
+        "Resource types the renderer should not load",
+    )
+    .list()
+    .allowed_values(RESOURCE_TYPES),
+    OptionDescriptor::new(
+        "ignoreHttpsErrors",
+        "ignore_https_errors",
+        Bool,
+        RENDERED,
+        "Ignore TLS certificate errors on the target site",
+    ),
+    OptionDescriptor::new(
+        "failOnStatus",
+        "fail_on_status",
+        Integer,
+        RENDERED,
+        "Target status codes that fail the capture",
+    )
+    .list(),
+    OptionDescriptor::new(
+        "format",
+        "content_format",
+        Enum,
+        CONTENT,
+        "Representations to return: html, text, markdown or all",
+    )
+    .allowed_values(&["html", "text", "markdown", "all"]),
+    OptionDescriptor::new(
+        "fileName",
+        "file_name",
+        OptionKind::String,
+        IMAGE_PDF,
+        "File name for the stored capture",
+    ),
+    OptionDescriptor::new(
+        "s3Acl",
+        "s3_acl",
+        Enum,
+        IMAGE_PDF,
+        "ACL applied to the stored S3 object",
+    )
+    .allowed_values(&[
+        "private",
+        "public-read",
+        "public-read-write",
+        "authenticated-read",
+        "bucket-owner-read",
+        "bucket-owner-full-control",
+    ]),
+    OptionDescriptor::new(
+        "s3Redirect",
+        "s3_redirect",
+        Bool,
+        IMAGE_PDF,
+        "Redirect to the stored S3 object",
+    ),
+    OptionDescriptor::new(
+        "timestamp",
+        "timestamp",
+        Bool,
+        IMAGE_PDF,
+        "Append a timestamp to the stored file name",
+    ),
+    OptionDescriptor::new(
+        "diagnostics",
+        "diagnostics",
+        Bool,
+        IMAGE_CONTENT,
+        "Report console and network diagnostics",
+    ),
+    // Screenshot
+    OptionDescriptor::new(
+        "vw",
+        "vw",
+        Integer,
+        IMAGE_ANIMATED,
+        "Viewport width in pixels",
+    )
+    .range(1.0, MAX_DIMENSION),
+    OptionDescriptor::new(
+        "vh",
+        "vh",
+        Integer,
+        IMAGE_ANIMATED,
+        "Viewport height in pixels",
+    )
+    .range(1.0, MAX_DIMENSION),
+    OptionDescriptor::new(
+        "scaleFactor",
+        "scale_factor",
+        Float,
+        IMAGE_ANIMATED,
+        "Device scale factor",
+    )
+    .range(0.1, 5.0),
+    OptionDescriptor::new(
+        "full",
+        "full",
+        Bool,
+        IMAGE_ANIMATED,
+        "Capture the full scrollable page",
+    ),
+    OptionDescriptor::new(
+        "maxHeight",
+        "max_height",
+        Integer,
+        IMAGE,
+        "Maximum height of a full-page capture",
+    ),
+    OptionDescriptor::new(
+        "darkMode",
+        "dark_mode",
+        Bool,
+        IMAGE_PDF_ANIMATED,
+        "Render with prefers-color-scheme: dark",
+    ),
+    OptionDescriptor::new(
+        "transparent",
+        "transparent",
+        Bool,
+        IMAGE,
+        "Transparent page background",
+    ),
+    OptionDescriptor::new(
+        "selector",
+        "selector",
+        OptionKind::String,
+        IMAGE_CONTENT,
+        "CSS selector of the element to capture",
+    ),
+    OptionDescriptor::new(
+        "selectorId",
+        "selector_id",
+        OptionKind::String,
+        IMAGE_CONTENT,
+        "ID of the element to capture",
+    ),
+    OptionDescriptor::new(
+        "selectorPadding",
+        "selector_padding",
+        Integer,
+        IMAGE,
+        "Pixels of padding around the element crop",
+    ),
+    OptionDescriptor::new(
+        "clipX",
+        "clip_x",
+        Integer,
+        IMAGE,
+        "Left edge of the clipped region",
+    ),
+    OptionDescriptor::new(
+        "clipY",
+        "clip_y",
+        Integer,
+        IMAGE,
+        "Top edge of the clipped region",
+    ),
+    OptionDescriptor::new(
+        "clipWidth",
+        "clip_width",
+        Integer,
+        IMAGE,
+        "Width of the clipped region",
+    )
+    .range(1.0, MAX_DIMENSION),
+    OptionDescriptor::new(
+        "clipHeight",
+        "clip_height",
+        Integer,
+        IMAGE,
+        "Height of the clipped region",
+    )
+    .range(1.0, MAX_DIMENSION),
+    OptionDescriptor::new(
+        "blockCookieBanners",
+        "block_cookie_banners",
+        Bool,
+        RENDERED_ANIMATED,
+        "Hide cookie consent banners",
+    ),
+    OptionDescriptor::new(
+        "blockAds",
+        "block_ads",
+        Bool,
+        RENDERED_ANIMATED,
+        "Block ads",
+    ),
+    OptionDescriptor::new(
+        "bypassBotDetection",
+        "bypass_bot_detection",
+        Bool,
+        RENDERED,
+        "Solve bot detection challenges",
+    ),
+    OptionDescriptor::new(
+        "blockUrls",
+        "block_urls",
+        OptionKind::String,
+        IMAGE,
+        "URL patterns the renderer should not load",
+    )
+    .list(),
+    OptionDescriptor::new("type", "image_type", Enum, IMAGE, "Image format")
+        .allowed_values(&["png", "jpeg", "webp"]),
+    OptionDescriptor::new("quality", "quality", Integer, IMAGE, "JPEG/WebP quality")
+        .range(1.0, 100.0),
+    OptionDescriptor::new(
+        "bestFormat",
+        "best_format",
+        Bool,
+        IMAGE,
+        "Let the API pick the smallest format",
+    ),
+    OptionDescriptor::new(
+        "resizeWidth",
+        "resize_width",
+        Integer,
+        IMAGE,
+        "Resize the capture to this width",
+    )
+    .range(1.0, MAX_DIMENSION),
+    OptionDescriptor::new(
+        "resizeHeight",
+        "resize_height",
+        Integer,
+        IMAGE,
+        "Resize the capture to this height",
+    )
+    .range(1.0, MAX_DIMENSION),
+    OptionDescriptor::new("fresh", "fresh", Bool, ALL, "Bypass the capture cache"),
+    OptionDescriptor::new(
+        "lang",
+        "language",
+        OptionKind::String,
+        METADATA,
+        "Language hint for extraction, a BCP 47 tag such as en-US",
+    ),
+    OptionDescriptor::new(
+        "structuredData",
+        "include_structured_data",
+        Bool,
+        METADATA,
+        "Return the page's JSON-LD blocks",
+    ),
+    // Animated
+    OptionDescriptor::new(
+        "duration",
+        "duration",
+        Integer,
+        ANIMATED,
+        "Length of the recording in seconds",
+    )
+    .range(1.0, MAX_ANIMATION_SECONDS),
+    OptionDescriptor::new("fps", "fps", Integer, ANIMATED, "Frames per second").range(1.0, MAX_FPS),
+    OptionDescriptor::new(
+        "format",
+        "animated_format",
+        Enum,
+        ANIMATED,
+        "Container of the recording: gif, mp4 or webm",
+    )
+    .allowed_values(&["gif", "mp4", "webm"]),
+    // PDF
+    OptionDescriptor::new(
+        "width",
+        "width",
+        OptionKind::String,
+        PDF,
+        "Paper width, e.g. 8.5in",
+    ),
+    OptionDescriptor::new(
+        "height",
+        "height",
+        OptionKind::String,
+        PDF,
+        "Paper height, e.g. 11in",
+    ),
+    OptionDescriptor::new("format", "format", Enum, PDF, "Paper format, e.g. A4").allowed_values(
+        &[
+            "A0", "A1", "A2", "A3", "A4", "A5", "A6", "Letter", "Legal", "Tabloid", "Ledger",
+        ],
+    ),
+    OptionDescriptor::new(
+        "marginTop",
+        "margin_top",
+        OptionKind::String,
+        PDF,
+        "Top margin, e.g. 1cm",
+    ),
+    OptionDescriptor::new(
+        "marginRight",
+        "margin_right",
+        OptionKind::String,
+        PDF,
+        "Right margin",
+    ),
+    OptionDescriptor::new(
+        "marginBottom",
+        "margin_bottom",
+        OptionKind::String,
+        PDF,
+        "Bottom margin",
+    ),
+    OptionDescriptor::new(
+        "marginLeft",
+        "margin_left",
+        OptionKind::String,
+        PDF,
+        "Left margin",
+    ),
+    OptionDescriptor::new("scale", "scale", Float, PDF, "Rendering scale of the page")
+        .range(0.1, 2.0),
+    OptionDescriptor::new("landscape", "landscape", Bool, PDF, "Landscape orientation"),
+    OptionDescriptor::new(
+        "pageRanges",
+        "page_ranges",
+        OptionKind::String,
+        PDF,
+        "Pages to include, e.g. 1-3,5",
+    ),
+    OptionDescriptor::new(
+        "printBackground",
+        "print_background",
+        Bool,
+        PDF,
+        "Print background colors and images",
+    ),
+    OptionDescriptor::new(
+        "preferCSSPageSize",
+        "prefer_css_page_size",
+        Bool,
+        PDF,
+        "Prefer the page size declared by CSS @page rules",
+    ),
+    OptionDescriptor::new(
+        "tagged",
+        "tagged",
+        Bool,
+        PDF,
+        "Produce a tagged, accessible PDF",
+    ),
+];
+
+/// API option keys this version of the crate has typed fields for,
+/// deduplicated and in registry order. A capture.page deployment has to
+/// understand all of them for every typed field to take effect; compare it
+/// against the keys your account's API version documents.
+pub const MIN_API_FEATURES: &[&str] = UNIQUE_KEYS.0.split_at(UNIQUE_KEYS.1).0;
+
+/// Registry keys without repeats, padded to the registry's length, and the
+/// number of distinct keys.
+const UNIQUE_KEYS: ([&str; REGISTRY.len()], usize) = {
+    let mut keys = [""; REGISTRY.len()];
+    let mut len = 0;
+    let mut i = 0;
+    while i < REGISTRY.len() {
+        let key = REGISTRY[i].key;
+        let mut j = 0;
+        while j < len && !str_eq(keys[j], key) {
+            j += 1;
+        }
+        if j == len {
+            keys[len] = key;
+            len += 1;
+        }
+        i += 1;
+    }
+    (keys, len)
+};
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Returns an owned copy of [`supported_options`].
+pub fn describe() -> Vec<OptionDescriptor> {
+    supported_options().to_vec()
+}
+
+/// Every API option this version of the crate has a typed field for, in
+/// registry order. Options outside this list can still be sent through
+/// `additional_options`, but are neither validated nor documented here.
+pub fn supported_options() -> &'static [OptionDescriptor] {
+    REGISTRY
+}
+
+/// Whether `key`, an API query parameter such as `blockAds`, has a typed
+/// field in this version of the crate. Use it to warn about options that
+/// reach the API only through `additional_options`.
+pub fn supports(key: &str) -> bool {
+    MIN_API_FEATURES.contains(&key)
+}
+
+pub(crate) fn descriptor(rust_field: &str) -> Option<&'static OptionDescriptor> {
+    REGISTRY
+        .iter()
+        .find(|descriptor| descriptor.rust_field == rust_field)
+}
+
+/// Checks `value` against the registered range for `rust_field`.
+pub(crate) fn check_range(rust_field: &str, value: f64) -> Result<()> {
+    if !value.is_finite() {
+        return Err(invalid_option(rust_field, "must be a finite number"));
+    }
+    let Some((min, max)) = descriptor(rust_field).and_then(|descriptor| descriptor.range) else {
+        return Ok(());
+    };
+    if !(min..=max).contains(&value) {
+        return Err(invalid_option(
+            rust_field,
+            &format!("must be between {min} and {max}"),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnimatedOptions, ContentOptions, MetadataOptions, PdfOptions, ScreenshotOptions};
+    use std::collections::HashSet;
+
+    /// Field names of an options struct, read from its derived `Debug`
+    /// output so new fields are picked up without touching this test.
+    fn debug_fields(debug: &str) -> Vec<String> {
+        let body = debug.split_once('{').unwrap().1.trim_end_matches('}');
+        body.split(',')
+            .filter_map(|field| field.split_once(':'))
+            .map(|(name, _)| name.trim().to_string())
+            .filter(|name| name != "additional_options")
+            .collect()
+    }
+
+    fn assert_registry_covers(debug: &str, request_type: RequestType) {
+        let fields = debug_fields(debug);
+        for field in &fields {
+            let matches = REGISTRY
+                .iter()
+                .filter(|descriptor| {
+                    descriptor.rust_field == field && descriptor.applies_to(request_type)
+                })
+                .count();
+            assert_eq!(matches, 1, "{field} for {request_type:?}");
+        }
+
+        let registered: HashSet<_> = REGISTRY
+            .iter()
+            .filter(|descriptor| descriptor.applies_to(request_type))
+            .map(|descriptor| descriptor.rust_field)
+            .collect();
+        let fields: HashSet<_> = fields.iter().map(String::as_str).collect();
+        assert_eq!(registered, fields, "{request_type:?}");
+    }
+
+    #[test]
+    fn test_every_struct_field_is_described_once() {
+        assert_registry_covers(&format!("{:?}", ScreenshotOptions::default()), Image);
+        assert_registry_covers(&format!("{:?}", PdfOptions::default()), Pdf);
+        assert_registry_covers(&format!("{:?}", ContentOptions::default()), Content);
+        assert_registry_covers(&format!("{:?}", MetadataOptions::default()), Metadata);
+        assert_registry_covers(&format!("{:?}", AnimatedOptions::default()), Animated);
+    }
+
+    #[test]
+    fn test_supports_known_keys_only() {
+        assert!(supports("blockAds"));
+        assert!(supports("pageRanges"));
+        assert!(!supports("blockChats"));
+        assert!(!supports("block_ads"));
+        assert!(!supports("url"));
+        assert_eq!(supported_options(), describe().as_slice());
+    }
+
+    #[test]
+    fn test_min_api_features_lists_each_key_once() {
+        let keys: HashSet<_> = REGISTRY.iter().map(|descriptor| descriptor.key).collect();
+        assert_eq!(MIN_API_FEATURES.len(), keys.len());
+        assert_eq!(
+            MIN_API_FEATURES.iter().copied().collect::<HashSet<_>>(),
+            keys
+        );
+        assert_eq!(MIN_API_FEATURES[0], REGISTRY[0].key);
+    }
+
+    #[test]
+    fn test_rust_fields_are_unique() {
+        let mut seen = HashSet::new();
+        for descriptor in REGISTRY {
+            assert!(
+                seen.insert(descriptor.rust_field),
+                "{}",
+                descriptor.rust_field
+            );
+        }
+    }
+
+    #[test]
+    fn test_validation_matches_descriptor_ranges() {
+        let (min, max) = descriptor("quality").unwrap().range.unwrap();
+        let with_quality = |quality: f64| ScreenshotOptions {
+            quality: Some(quality as u8),
+            ..Default::default()
+        };
+
+        assert!(with_quality(min).validate().is_ok());
+        assert!(with_quality(max).validate().is_ok());
+        assert!(with_quality(min - 1.0).validate().is_err());
+        assert!(with_quality(max + 1.0).validate().is_err());
+    }
+
+    #[test]
+    fn test_screenshot_ranges_match_descriptors() {
+        type Build = fn(f64) -> ScreenshotOptions;
+        let cases: [(&str, Build); 5] = [
+            ("vw", |value| ScreenshotOptions {
+                vw: Some(value as u32),
+                ..Default::default()
+            }),
+            ("vh", |value| ScreenshotOptions {
+                vh: Some(value as u32),
+                ..Default::default()
+            }),
+            ("scale_factor", |value| ScreenshotOptions {
+                scale_factor: Some(value),
+                ..Default::default()
+            }),
+            ("resize_width", |value| ScreenshotOptions {
+                resize_width: Some(value as u32),
+                ..Default::default()
+            }),
+            ("delay_ms", |value| ScreenshotOptions {
+                delay_ms: Some(value as u32),
+                ..Default::default()
+            }),
+        ];
+
+        for (field, options) in cases {
+            let (min, max) = descriptor(field).unwrap().range.unwrap();
+            let step = if field == "scale_factor" { 0.05 } else { 1.0 };
+            assert!(options(min).validate().is_ok(), "{field} at {min}");
+            assert!(options(max).validate().is_ok(), "{field} at {max}");
+            assert!(
+                options(max + step).validate().is_err(),
+                "{field} above {max}"
+            );
+            if min >= step {
+                assert!(
+                    options(min - step).validate().is_err(),
+                    "{field} below {min}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_enum_values_match_types() {
+        let block_resources = descriptor("block_resources").unwrap();
+        for value in block_resources.allowed_values {
+            assert_eq!(
+                value.parse::<crate::ResourceType>().unwrap().as_str(),
+                *value
+            );
+        }
+
+        let format = descriptor("format").unwrap();
+        let names: Vec<_> = crate::PdfFormat::ALL.iter().map(|f| f.as_str()).collect();
+        assert_eq!(format.allowed_values, names.as_slice());
+
+        let s3_acl = descriptor("s3_acl").unwrap();
+        let names: Vec<_> = crate::S3Acl::ALL.iter().map(|acl| acl.as_str()).collect();
+        assert_eq!(s3_acl.allowed_values, names.as_slice());
+    }
+}
@@ -0,0 +1,932 @@
+use super::query::{decode_query, OptionReader};
+use super::registry::check_range;
+use super::{
+    block_resources_value, comma_joined, delay_value, s3_acl_value, sanitize_file_name,
+    validate_wait_for_timeout, RequestOptions, ResourceType, S3Acl,
+};
+use crate::error::{invalid_option, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UserAgentPreset {
+    ChromeDesktop,
+    ChromeMobile,
+    Firefox,
+    Safari,
+    SafariMobile,
+    Edge,
+    Googlebot,
+    BingBot,
+}
+
+impl UserAgentPreset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserAgentPreset::ChromeDesktop => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+            UserAgentPreset::ChromeMobile => "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Mobile Safari/537.36",
+            UserAgentPreset::Firefox => "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0",
+            UserAgentPreset::Safari => "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
+            UserAgentPreset::SafariMobile => "Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Mobile/15E148 Safari/604.1",
+            UserAgentPreset::Edge => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0",
+            UserAgentPreset::Googlebot => "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+            UserAgentPreset::BingBot => "Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScreenshotOptions {
+    // Viewport Options
+    pub vw: Option<u32>,
+    pub vh: Option<u32>,
+    pub scale_factor: Option<f64>,
+
+    // Capture Customization
+    pub full: Option<bool>,
+    /// Upper bound in pixels for full-page captures. Must be at least `vh`.
+    /// Only sent when `full` is `Some(true)`; otherwise it is ignored.
+    pub max_height: Option<u32>,
+    pub delay: Option<u32>,
+    /// Delay in milliseconds. Takes precedence over `delay` when both are set.
+    pub delay_ms: Option<u32>,
+    pub wait_for: Option<String>,
+    pub wait_for_id: Option<String>,
+    /// Maximum time in milliseconds to wait for `wait_for`/`wait_for_id`.
+    pub wait_for_timeout: Option<u32>,
+
+    // Visual Modifications
+    pub dark_mode: Option<bool>,
+    pub transparent: Option<bool>,
+    pub selector: Option<String>,
+    pub selector_id: Option<String>,
+    /// Pixels of padding around a `selector`/`selector_id` crop.
+    pub selector_padding: Option<u32>,
+    /// Left edge of a clipped region in CSS pixels. Any clip field needs
+    /// both `clip_width` and `clip_height`.
+    pub clip_x: Option<u32>,
+    /// Top edge of a clipped region in CSS pixels.
+    pub clip_y: Option<u32>,
+    pub clip_width: Option<u32>,
+    pub clip_height: Option<u32>,
+
+    // Performance/Detection
+    pub block_cookie_banners: Option<bool>,
+    pub block_ads: Option<bool>,
+    pub bypass_bot_detection: Option<bool>,
+    /// Resource types the renderer should not load.
+    pub block_resources: Option<Vec<ResourceType>>,
+    /// URL patterns to block, such as `*.doubleclick.net` or
+    /// `https://example.com/widgets/*`. An empty list is the same as `None`.
+    pub block_urls: Option<Vec<String>>,
+    pub stealth: Option<bool>,
+
+    // Image Options
+    pub image_type: Option<String>,
+    /// JPEG/WebP quality from 1 to 100. Rejected for PNG output.
+    pub quality: Option<u8>,
+    pub best_format: Option<bool>,
+    pub resize_width: Option<u32>,
+    pub resize_height: Option<u32>,
+
+    // Additional Options
+    pub http_auth: Option<String>,
+    pub user_agent: Option<String>,
+    /// Render anew instead of returning the API's cached screenshot. Also
+    /// skips and replaces a [`DiskCache`](crate::DiskCache) entry.
+    pub fresh: Option<bool>,
+    /// Ask the API to report console and network diagnostics for the page.
+    pub diagnostics: Option<bool>,
+    /// Ignore TLS certificate errors on the target site, e.g. self-signed
+    /// staging hosts. Does not affect the connection to capture.page.
+    pub ignore_https_errors: Option<bool>,
+    /// Target status codes that fail the capture instead of rendering the
+    /// error page. Sent comma-separated as `failOnStatus`.
+    pub fail_on_status: Option<Vec<u16>>,
+
+    // Storage/Output
+    pub file_name: Option<String>,
+    /// Canned ACL for the stored object; see [`S3Acl`](super::S3Acl).
+    pub s3_acl: Option<String>,
+    /// Respond with a redirect to the stored S3 object. The HTTP client
+    /// follows redirects by default, so `fetch_screenshot` then returns the
+    /// stored object's bytes; use `fetch_screenshot_stored` to get the
+    /// object's URL, or `build_screenshot_url` to hand the redirect URL to a
+    /// browser instead.
+    pub s3_redirect: Option<bool>,
+    pub timestamp: Option<bool>,
+
+    // Generic override for any future options
+    pub additional_options: Option<RequestOptions>,
+}
+
+impl ScreenshotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets `user_agent` to the string for `preset`, replacing any earlier value.
+    pub fn user_agent_preset(mut self, preset: UserAgentPreset) -> Self {
+        self.user_agent = Some(preset.as_str().to_string());
+        self
+    }
+
+    /// Parses a query string such as `vw=1280&full=true&darkMode=true` using
+    /// the API's parameter names. Unknown keys are rejected and the result is
+    /// validated.
+    pub fn from_query_str(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, true)
+    }
+
+    /// Like [`from_query_str`](Self::from_query_str), but keeps unknown keys
+    /// in `additional_options` instead of rejecting them.
+    pub fn from_query_str_lenient(query: &str) -> Result<Self> {
+        Self::from_request_options(decode_query(query)?, false)
+    }
+
+    fn from_request_options(options: RequestOptions, strict: bool) -> Result<Self> {
+        let mut reader = OptionReader::new(options);
+        let (delay, delay_ms) = reader.delay()?;
+        let options = Self {
+            vw: reader.integer("vw")?,
+            vh: reader.integer("vh")?,
+            scale_factor: reader.float("scaleFactor")?,
+            full: reader.bool("full")?,
+            max_height: reader.integer("maxHeight")?,
+            delay,
+            delay_ms,
+            wait_for: reader.string("waitFor")?,
+            wait_for_id: reader.string("waitForId")?,
+            wait_for_timeout: reader.integer("waitForTimeout")?,
+            dark_mode: reader.bool("darkMode")?,
+            transparent: reader.bool("transparent")?,
+            selector: reader.string("selector")?,
+            selector_id: reader.string("selectorId")?,
+            selector_padding: reader.integer("selectorPadding")?,
+            clip_x: reader.integer("clipX")?,
+            clip_y: reader.integer("clipY")?,
+            clip_width: reader.integer("clipWidth")?,
+            clip_height: reader.integer("clipHeight")?,
+            block_cookie_banners: reader.bool("blockCookieBanners")?,
+            block_ads: reader.bool("blockAds")?,
+            bypass_bot_detection: reader.bool("bypassBotDetection")?,
+            block_resources: reader.list("blockResources")?,
+            block_urls: reader.list("blockUrls")?,
+            stealth: reader.bool("stealth")?,
+            image_type: reader.string("type")?,
+            quality: reader.integer("quality")?,
+            best_format: reader.bool("bestFormat")?,
+            resize_width: reader.integer("resizeWidth")?,
+            resize_height: reader.integer("resizeHeight")?,
+            http_auth: reader.string("httpAuth")?,
+            user_agent: reader.string("userAgent")?,
+            fresh: reader.bool("fresh")?,
+            diagnostics: reader.bool("diagnostics")?,
+            ignore_https_errors: reader.bool("ignoreHttpsErrors")?,
+            fail_on_status: reader.list("failOnStatus")?,
+            file_name: reader.string("fileName")?,
+            s3_acl: reader.string("s3Acl")?,
+            s3_redirect: reader.bool("s3Redirect")?,
+            timestamp: reader.bool("timestamp")?,
+            additional_options: reader.finish(strict)?,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Sets `file_name` to `name` as the server would sanitize it, and
+    /// returns the stored value.
+    pub fn set_file_name_sanitized(&mut self, name: &str) -> Result<&str> {
+        Ok(self.file_name.insert(sanitize_file_name(name)?))
+    }
+
+    /// Object key the upload is stored under, relative to the bucket's
+    /// configured prefix, or `None` when no usable `file_name` is set or
+    /// `timestamp` is on, since the server picks that suffix at upload time.
+    pub fn predicted_object_key(&self) -> Option<String> {
+        if self.timestamp == Some(true) {
+            return None;
+        }
+        sanitize_file_name(self.file_name.as_deref()?).ok()
+    }
+
+    /// Checks option values that the API would otherwise reject at render time.
+    pub fn validate(&self) -> Result<()> {
+        validate_wait_for_timeout(self.wait_for_timeout, &self.wait_for, &self.wait_for_id)?;
+
+        if let Some(s3_acl) = &self.s3_acl {
+            s3_acl.parse::<S3Acl>()?;
+        }
+
+        if self.selector_padding.is_some() && self.selector.is_none() && self.selector_id.is_none()
+        {
+            return Err(invalid_option(
+                "selector_padding",
+                "requires selector or selector_id",
+            ));
+        }
+
+        let clip = [self.clip_x, self.clip_y, self.clip_width, self.clip_height];
+        if clip.iter().any(Option::is_some)
+            && (self.clip_width.is_none() || self.clip_height.is_none())
+        {
+            return Err(invalid_option(
+                "clip",
+                "clip_width and clip_height are required",
+            ));
+        }
+
+        // maxHeight is only sent for full-page captures, so only then can it
+        // conflict with the viewport.
+        let max_height = self.max_height.filter(|_| self.full == Some(true));
+        if let (Some(max_height), Some(vh)) = (max_height, self.vh) {
+            if max_height < vh {
+                return Err(invalid_option(
+                    "max_height",
+                    "must be greater than or equal to vh",
+                ));
+            }
+        }
+
+        for (field, value) in [
+            ("vw", self.vw.map(f64::from)),
+            ("vh", self.vh.map(f64::from)),
+            ("scale_factor", self.scale_factor),
+            ("resize_width", self.resize_width.map(f64::from)),
+            ("resize_height", self.resize_height.map(f64::from)),
+            ("clip_width", self.clip_width.map(f64::from)),
+            ("clip_height", self.clip_height.map(f64::from)),
+            ("delay", self.delay.map(f64::from)),
+            ("delay_ms", self.delay_ms.map(f64::from)),
+        ] {
+            if let Some(value) = value {
+                check_range(field, value)?;
+            }
+        }
+
+        if let Some(quality) = self.quality {
+            check_range("quality", f64::from(quality))?;
+            if self
+                .image_type
+                .as_deref()
+                .is_some_and(|image_type| image_type.eq_ignore_ascii_case("png"))
+            {
+                return Err(invalid_option(
+                    "quality",
+                    "is not supported for png output; use jpeg or webp",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the options and converts them into request options.
+    pub fn try_to_request_options(&self) -> Result<RequestOptions> {
+        self.validate()?;
+        Ok(self.to_request_options())
+    }
+
+    /// Converts the options without validating them. Non-finite floats
+    /// cannot be represented and are left out; use
+    /// [`try_to_request_options`](Self::try_to_request_options) to reject them.
+    pub fn to_request_options(&self) -> RequestOptions {
+        let mut options = RequestOptions::new();
+
+        if let Some(vw) = self.vw {
+            options.insert("vw".to_string(), serde_json::Value::Number(vw.into()));
+        }
+        if let Some(vh) = self.vh {
+            options.insert("vh".to_string(), serde_json::Value::Number(vh.into()));
+        }
+        if let Some(scale_factor) = self.scale_factor {
+            if let Some(num) = serde_json::Number::from_f64(scale_factor) {
+                options.insert("scaleFactor".to_string(), serde_json::Value::Number(num));
+            }
+        }
+        if let Some(full) = self.full {
+            options.insert("full".to_string(), serde_json::Value::Bool(full));
+        }
+        if let Some(max_height) = self.max_height.filter(|_| self.full == Some(true)) {
+            options.insert(
+                "maxHeight".to_string(),
+                serde_json::Value::Number(max_height.into()),
+            );
+        }
+        if let Some(delay) = delay_value(self.delay, self.delay_ms) {
+            options.insert("delay".to_string(), delay);
+        }
+        if let Some(wait_for) = &self.wait_for {
+            options.insert(
+                "waitFor".to_string(),
+                serde_json::Value::String(wait_for.clone()),
+            );
+        }
+        if let Some(wait_for_id) = &self.wait_for_id {
+            options.insert(
+                "waitForId".to_string(),
+                serde_json::Value::String(wait_for_id.clone()),
+            );
+        }
+        if let Some(wait_for_timeout) = self.wait_for_timeout {
+            options.insert(
+                "waitForTimeout".to_string(),
+                serde_json::Value::Number(wait_for_timeout.into()),
+            );
+        }
+        if let Some(dark_mode) = self.dark_mode {
+            options.insert("darkMode".to_string(), serde_json::Value::Bool(dark_mode));
+        }
+        if let Some(transparent) = self.transparent {
+            options.insert(
+                "transparent".to_string(),
+                serde_json::Value::Bool(transparent),
+            );
+        }
+        if let Some(selector) = &self.selector {
+            options.insert(
+                "selector".to_string(),
+                serde_json::Value::String(selector.clone()),
+            );
+        }
+        if let Some(selector_id) = &self.selector_id {
+            options.insert(
+                "selectorId".to_string(),
+                serde_json::Value::String(selector_id.clone()),
+            );
+        }
+        if let Some(selector_padding) = self.selector_padding {
+            options.insert(
+                "selectorPadding".to_string(),
+                serde_json::Value::Number(selector_padding.into()),
+            );
+        }
+        for (key, value) in [
+            ("clipX", self.clip_x),
+            ("clipY", self.clip_y),
+            ("clipWidth", self.clip_width),
+            ("clipHeight", self.clip_height),
+        ] {
+            if let Some(value) = value {
+                options.insert(key.to_string(), serde_json::Value::Number(value.into()));
+            }
+        }
+        if let Some(block_cookie_banners) = self.block_cookie_banners {
+            options.insert(
+                "blockCookieBanners".to_string(),
+                serde_json::Value::Bool(block_cookie_banners),
+            );
+        }
+        if let Some(block_ads) = self.block_ads {
+            options.insert("blockAds".to_string(), serde_json::Value::Bool(block_ads));
+        }
+        if let Some(bypass_bot_detection) = self.bypass_bot_detection {
+            options.insert(
+                "bypassBotDetection".to_string(),
+                serde_json::Value::Bool(bypass_bot_detection),
+            );
+        }
+        if let Some(stealth) = self.stealth {
+            options.insert("stealth".to_string(), serde_json::Value::Bool(stealth));
+        }
+        if let Some(image_type) = &self.image_type {
+            options.insert(
+                "type".to_string(),
+                serde_json::Value::String(image_type.clone()),
+            );
+        }
+        if let Some(quality) = self.quality {
+            options.insert(
+                "quality".to_string(),
+                serde_json::Value::Number(quality.into()),
+            );
+        }
+        if let Some(best_format) = self.best_format {
+            options.insert(
+                "bestFormat".to_string(),
+                serde_json::Value::Bool(best_format),
+            );
+        }
+        if let Some(resize_width) = self.resize_width {
+            options.insert(
+                "resizeWidth".to_string(),
+                serde_json::Value::Number(resize_width.into()),
+            );
+        }
+        if let Some(resize_height) = self.resize_height {
+            options.insert(
+                "resizeHeight".to_string(),
+                serde_json::Value::Number(resize_height.into()),
+            );
+        }
+        if let Some(http_auth) = &self.http_auth {
+            options.insert(
+                "httpAuth".to_string(),
+                serde_json::Value::String(http_auth.clone()),
+            );
+        }
+        if let Some(user_agent) = &self.user_agent {
+            options.insert(
+                "userAgent".to_string(),
+                serde_json::Value::String(user_agent.clone()),
+            );
+        }
+        if let Some(fresh) = self.fresh {
+            options.insert("fresh".to_string(), serde_json::Value::Bool(fresh));
+        }
+        if let Some(diagnostics) = self.diagnostics {
+            options.insert(
+                "diagnostics".to_string(),
+                serde_json::Value::Bool(diagnostics),
+            );
+        }
+        if let Some(ignore_https_errors) = self.ignore_https_errors {
+            options.insert(
+                "ignoreHttpsErrors".to_string(),
+                serde_json::Value::Bool(ignore_https_errors),
+            );
+        }
+        if let Some(block_resources) = self
+            .block_resources
+            .as_deref()
+            .and_then(block_resources_value)
+        {
+            options.insert("blockResources".to_string(), block_resources);
+        }
+        if let Some(block_urls) = self.block_urls.as_deref().and_then(comma_joined) {
+            options.insert("blockUrls".to_string(), block_urls);
+        }
+        if let Some(fail_on_status) = self.fail_on_status.as_deref().and_then(comma_joined) {
+            options.insert("failOnStatus".to_string(), fail_on_status);
+        }
+        if let Some(file_name) = &self.file_name {
+            options.insert(
+                "fileName".to_string(),
+                serde_json::Value::String(file_name.clone()),
+            );
+        }
+        if let Some(s3_acl) = &self.s3_acl {
+            options.insert("s3Acl".to_string(), s3_acl_value(s3_acl));
+        }
+        if let Some(s3_redirect) = self.s3_redirect {
+            options.insert(
+                "s3Redirect".to_string(),
+                serde_json::Value::Bool(s3_redirect),
+            );
+        }
+        if let Some(timestamp) = self.timestamp {
+            options.insert("timestamp".to_string(), serde_json::Value::Bool(timestamp));
+        }
+
+        // Merge additional options, allowing overrides
+        if let Some(additional) = &self.additional_options {
+            for (key, value) in additional {
+                options.insert(key.clone(), value.clone());
+            }
+        }
+
+        options
+    }
+}
+
+impl TryFrom<RequestOptions> for ScreenshotOptions {
+    type Error = crate::CaptureError;
+
+    /// Converts raw request options keyed by API parameter name, rejecting
+    /// unknown keys.
+    fn try_from(options: RequestOptions) -> Result<Self> {
+        Self::from_request_options(options, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signing, Capture, CaptureError};
+
+    #[test]
+    fn test_user_agent_preset_in_signed_query() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions::new().user_agent_preset(UserAgentPreset::Googlebot);
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        let query = url.split_once('?').unwrap().1;
+        let token = url.split('/').nth(4).unwrap();
+
+        assert!(query.contains(&format!(
+            "userAgent={}",
+            urlencoding::encode(UserAgentPreset::Googlebot.as_str())
+        )));
+        assert_eq!(token, signing::generate_token("test_secret", query));
+    }
+
+    #[test]
+    fn test_set_file_name_sanitized() {
+        let mut options = ScreenshotOptions::default();
+        options
+            .set_file_name_sanitized("shots\\home\u{0}.png")
+            .unwrap();
+
+        assert_eq!(
+            options.to_request_options()["fileName"],
+            serde_json::json!("shotshome.png")
+        );
+        assert_eq!(
+            options.predicted_object_key().as_deref(),
+            Some("shotshome.png")
+        );
+
+        options.timestamp = Some(true);
+        assert_eq!(options.predicted_object_key(), None);
+    }
+
+    #[test]
+    fn test_user_agent_last_write_wins() {
+        let options = ScreenshotOptions::new()
+            .user_agent_preset(UserAgentPreset::Firefox)
+            .user_agent("MyBot/1.0");
+        assert_eq!(options.user_agent.as_deref(), Some("MyBot/1.0"));
+
+        let options = ScreenshotOptions::new()
+            .user_agent("MyBot/1.0")
+            .user_agent_preset(UserAgentPreset::ChromeMobile);
+        assert_eq!(
+            options.user_agent.as_deref(),
+            Some(UserAgentPreset::ChromeMobile.as_str())
+        );
+    }
+
+    #[test]
+    fn test_screenshot_wait_for_timeout_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            wait_for: Some(".hero".to_string()),
+            wait_for_timeout: Some(2500),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("waitFor=.hero"));
+        assert!(url.contains("waitForTimeout=2500"));
+
+        let options = ScreenshotOptions {
+            wait_for_timeout: Some(2500),
+            ..Default::default()
+        };
+        assert!(matches!(
+            capture.build_screenshot_url("https://example.com", Some(&options)),
+            Err(CaptureError::InvalidOption { .. })
+        ));
+    }
+
+    #[test]
+    fn test_block_urls_survive_encoding_and_are_signed() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            block_urls: Some(vec![
+                "*.doubleclick.net".to_string(),
+                "https://widget.intercom.io/*".to_string(),
+            ]),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        let query = url.split_once('?').unwrap().1;
+        let token = url.split('/').nth(4).unwrap();
+        let block_urls = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("blockUrls="))
+            .unwrap();
+
+        assert_eq!(
+            urlencoding::decode(block_urls).unwrap(),
+            "*.doubleclick.net,https://widget.intercom.io/*"
+        );
+        assert_eq!(token, signing::generate_token("test_secret", query));
+    }
+
+    #[test]
+    fn test_empty_block_urls_is_omitted() {
+        let options = ScreenshotOptions {
+            block_urls: Some(Vec::new()),
+            ..Default::default()
+        };
+
+        assert!(!options.to_request_options().contains_key("blockUrls"));
+    }
+
+    #[test]
+    fn test_screenshot_quality_serialization() {
+        let options = ScreenshotOptions {
+            image_type: Some("jpeg".to_string()),
+            quality: Some(80),
+            ..Default::default()
+        };
+
+        let request_options = options.try_to_request_options().unwrap();
+        assert_eq!(request_options["quality"], serde_json::json!(80));
+    }
+
+    #[test]
+    fn test_screenshot_quality_out_of_range() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for quality in [0, 101] {
+            let options = ScreenshotOptions {
+                quality: Some(quality),
+                ..Default::default()
+            };
+            let result = capture.build_screenshot_url("https://example.com", Some(&options));
+            assert!(matches!(
+                result,
+                Err(CaptureError::InvalidOption { ref field, .. }) if field == "quality"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_screenshot_quality_rejected_for_png() {
+        let options = ScreenshotOptions {
+            image_type: Some("PNG".to_string()),
+            quality: Some(90),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            options.try_to_request_options(),
+            Err(CaptureError::InvalidOption { .. })
+        ));
+    }
+
+    #[test]
+    fn test_max_height_caps_full_page_capture() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            vh: Some(800),
+            full: Some(true),
+            max_height: Some(10000),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("maxHeight=10000"));
+    }
+
+    #[test]
+    fn test_max_height_below_viewport_is_rejected() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            vh: Some(800),
+            full: Some(true),
+            max_height: Some(600),
+            ..Default::default()
+        };
+
+        let result = capture.build_screenshot_url("https://example.com", Some(&options));
+        assert!(matches!(
+            result,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "max_height"
+        ));
+    }
+
+    #[test]
+    fn test_max_height_without_full_is_ignored() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            max_height: Some(10000),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(!url.contains("maxHeight"));
+    }
+
+    #[test]
+    fn test_max_height_below_viewport_without_full_is_accepted() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            vh: Some(800),
+            max_height: Some(600),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("vh=800"));
+        assert!(!url.contains("maxHeight"));
+    }
+
+    #[test]
+    fn test_clip_requires_a_size() {
+        let options = ScreenshotOptions {
+            clip_x: Some(10),
+            clip_width: Some(600),
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.validate(),
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "clip"
+        ));
+
+        let options = ScreenshotOptions {
+            clip_width: Some(0),
+            clip_height: Some(300),
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.validate(),
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "clip_width"
+        ));
+    }
+
+    #[test]
+    fn test_storage_options_in_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options = ScreenshotOptions {
+            file_name: Some("home page".to_string()),
+            s3_acl: Some("public-read".to_string()),
+            s3_redirect: Some(true),
+            timestamp: Some(false),
+            ..Default::default()
+        };
+
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("fileName=home%20page"));
+        assert!(url.contains("s3Acl=public-read"));
+        assert!(url.contains("s3Redirect=true"));
+        assert!(url.contains("timestamp=false"));
+    }
+
+    #[test]
+    fn test_selector_padding_with_selector() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for options in [
+            ScreenshotOptions {
+                selector: Some(".pricing-card".to_string()),
+                selector_padding: Some(24),
+                ..Default::default()
+            },
+            ScreenshotOptions {
+                selector_id: Some("pricing".to_string()),
+                selector_padding: Some(24),
+                ..Default::default()
+            },
+        ] {
+            let url = capture
+                .build_screenshot_url("https://example.com", Some(&options))
+                .unwrap();
+            assert!(url.contains("selectorPadding=24"));
+        }
+    }
+
+    #[test]
+    fn test_selector_padding_requires_selector() {
+        let options = ScreenshotOptions {
+            selector_padding: Some(24),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            options.try_to_request_options(),
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "selector_padding"
+        ));
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_rejected() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for (field, options) in [
+            (
+                "vw",
+                ScreenshotOptions {
+                    vw: Some(0),
+                    ..Default::default()
+                },
+            ),
+            (
+                "resize_width",
+                ScreenshotOptions {
+                    resize_width: Some(0),
+                    ..Default::default()
+                },
+            ),
+            (
+                "scale_factor",
+                ScreenshotOptions {
+                    scale_factor: Some(-3.0),
+                    ..Default::default()
+                },
+            ),
+            (
+                "delay",
+                ScreenshotOptions {
+                    delay: Some(600),
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let result = capture.build_screenshot_url("https://example.com", Some(&options));
+            assert!(
+                matches!(result, Err(CaptureError::InvalidOption { field: ref f, .. }) if f == field),
+                "{field}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_finite_scale_factor_is_rejected() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+        for scale_factor in [f64::NAN, f64::INFINITY] {
+            let options = ScreenshotOptions {
+                scale_factor: Some(scale_factor),
+                ..Default::default()
+            };
+            let result = capture.build_screenshot_url("https://example.com", Some(&options));
+            assert!(
+                matches!(
+                    result,
+                    Err(CaptureError::InvalidOption { ref field, ref reason })
+                        if field == "scale_factor" && reason == "must be a finite number"
+                ),
+                "{scale_factor}: {result:?}"
+            );
+        }
+
+        let options = ScreenshotOptions {
+            scale_factor: Some(1.5),
+            ..Default::default()
+        };
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&options))
+            .unwrap();
+        assert!(url.contains("scaleFactor=1.5"));
+    }
+
+    #[test]
+    fn test_valid_options_produce_unchanged_urls() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        // URLs the client built for these options before validation existed.
+        let golden = [
+            (
+                ScreenshotOptions {
+                    vw: Some(1920),
+                    vh: Some(1080),
+                    scale_factor: Some(2.0),
+                    ..Default::default()
+                },
+                "https://cdn.capture.page/test_key/81ab9add2587f00730690611f9525ef8/image\
+                 ?scaleFactor=2.0&url=https%3A%2F%2Fexample.com&vh=1080&vw=1920",
+            ),
+            (
+                ScreenshotOptions {
+                    full: Some(true),
+                    delay: Some(3),
+                    resize_width: Some(640),
+                    resize_height: Some(360),
+                    ..Default::default()
+                },
+                "https://cdn.capture.page/test_key/6cb54e0e51ac5bc2d56aefa0d35cfb00/image\
+                 ?delay=3&full=true&resizeHeight=360&resizeWidth=640&url=https%3A%2F%2Fexample.com",
+            ),
+            (
+                ScreenshotOptions {
+                    image_type: Some("jpeg".to_string()),
+                    quality: Some(85),
+                    ..Default::default()
+                },
+                "https://cdn.capture.page/test_key/4bea28178a32635dac9735b26ad5518f/image\
+                 ?quality=85&type=jpeg&url=https%3A%2F%2Fexample.com",
+            ),
+        ];
+
+        for (options, expected) in golden {
+            let validated = capture
+                .build_screenshot_url("https://example.com", Some(&options))
+                .unwrap();
+            assert_eq!(validated, expected);
+            let unvalidated = capture
+                .build_image_url("https://example.com", Some(&options.to_request_options()))
+                .unwrap();
+            assert_eq!(unvalidated, expected);
+        }
+    }
+}
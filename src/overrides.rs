@@ -0,0 +1,321 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::client::Endpoint;
+use crate::signing::RequestType;
+use crate::{Capture, CaptureError, Result, RetryPolicy};
+
+/// One-off deviations from the client configuration for a single call,
+/// passed to the `*_with_overrides` methods. Unset fields keep the client's
+/// setting; the next call without overrides uses the client's again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetchOverrides {
+    /// Total deadline for the request, whatever its type.
+    pub timeout: Option<Duration>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub endpoint: Option<Endpoint>,
+    /// Fails with [`CaptureError::ResponseTooLarge`] for longer bodies.
+    pub max_response_bytes: Option<usize>,
+    /// Checks that image, PDF and animated responses really hold such a
+    /// file, failing with [`CaptureError::UnexpectedBody`] otherwise.
+    pub validate_payload: Option<bool>,
+    /// Media types sent in the `Accept` header, most preferred first.
+    pub accept: Option<Vec<String>>,
+    /// Free-form labels, e.g. a tenant or job id, copied into the
+    /// [`FetchInfo`](crate::FetchInfo) of the call.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl FetchOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn with_endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = Some(max_response_bytes);
+        self
+    }
+
+    pub fn with_payload_validation(mut self, validate_payload: bool) -> Self {
+        self.validate_payload = Some(validate_payload);
+        self
+    }
+
+    pub fn with_accept<I, S>(mut self, media_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.accept = Some(media_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Client configuration with a call's [`FetchOverrides`] applied.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FetchSettings {
+    pub(crate) endpoint: Endpoint,
+    pub(crate) timeout: Option<Duration>,
+    /// Per-type deadlines that win over `timeout`; empty when the call
+    /// overrides the timeout.
+    pub(crate) type_timeouts: HashMap<RequestType, Duration>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    pub(crate) max_response_bytes: Option<usize>,
+    pub(crate) validate_payload: bool,
+    pub(crate) accept: Option<String>,
+    pub(crate) labels: BTreeMap<String, String>,
+    /// Off for calls that read a redirect's `Location` themselves.
+    pub(crate) follow_redirects: bool,
+    /// Sent as `If-None-Match` to revalidate a cache entry; a
+    /// `304 Not Modified` answer is then a success with an empty body.
+    pub(crate) if_none_match: Option<String>,
+}
+
+impl Capture {
+    /// The single place where per-call overrides are merged over the client
+    /// configuration. `None` yields the client configuration unchanged.
+    pub(crate) fn fetch_settings(&self, overrides: Option<&FetchOverrides>) -> FetchSettings {
+        let overrides = overrides.cloned().unwrap_or_default();
+
+        FetchSettings {
+            endpoint: overrides.endpoint.unwrap_or(self.endpoint()),
+            timeout: overrides.timeout.or(self.options.timeout),
+            type_timeouts: if overrides.timeout.is_some() {
+                HashMap::new()
+            } else {
+                self.options.type_timeouts.clone()
+            },
+            retry_policy: overrides
+                .retry_policy
+                .or_else(|| self.options.retry_policy.clone()),
+            max_response_bytes: overrides
+                .max_response_bytes
+                .or(self.options.max_response_bytes),
+            validate_payload: overrides
+                .validate_payload
+                .unwrap_or(self.options.validate_payloads),
+            accept: overrides.accept.map(|media_types| media_types.join(", ")),
+            labels: overrides.labels,
+            follow_redirects: true,
+            if_none_match: None,
+        }
+    }
+}
+
+impl FetchSettings {
+    pub(crate) fn timeout_for(&self, request_type: RequestType) -> Option<Duration> {
+        self.type_timeouts
+            .get(&request_type)
+            .copied()
+            .or(self.timeout)
+    }
+}
+
+/// Reads the body of `response`, failing as soon as it exceeds `limit`.
+pub(crate) async fn read_body(
+    response: reqwest::Response,
+    limit: Option<usize>,
+) -> Result<Vec<u8>> {
+    let Some(limit) = limit else {
+        return Ok(response.bytes().await?.to_vec());
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(CaptureError::ResponseTooLarge { limit });
+    }
+
+    read_limited(response, limit).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(CaptureError::ResponseTooLarge { limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// The wasm response cannot be read in chunks, so the limit is checked once
+/// the whole body is in.
+#[cfg(target_arch = "wasm32")]
+async fn read_limited(response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    let body = response.bytes().await?;
+    if body.len() > limit {
+        return Err(CaptureError::ResponseTooLarge { limit });
+    }
+    Ok(body.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::serve_once;
+    use crate::CaptureOptions;
+
+    fn capture() -> Capture {
+        let options = CaptureOptions::new()
+            .with_timeout(Duration::from_secs(30))
+            .with_retry_policy(RetryPolicy::new());
+        Capture::with_options("test_key".to_string(), "test_secret".to_string(), options)
+    }
+
+    #[test]
+    fn test_overrides_apply_to_one_call_only() {
+        let capture = capture();
+        let defaults = capture.fetch_settings(None);
+
+        let overrides = FetchOverrides::new()
+            .with_timeout(Duration::from_secs(180))
+            .with_retry_policy(RetryPolicy::new().with_max_attempts(1))
+            .with_endpoint(Endpoint::Edge)
+            .with_max_response_bytes(1024)
+            .with_payload_validation(false)
+            .with_accept(["image/webp", "image/png"])
+            .with_label("tenant", "acme");
+        let settings = capture.fetch_settings(Some(&overrides));
+
+        assert_eq!(settings.timeout, Some(Duration::from_secs(180)));
+        assert_eq!(settings.retry_policy.unwrap().max_attempts, 1);
+        assert_eq!(settings.endpoint, Endpoint::Edge);
+        assert_eq!(settings.max_response_bytes, Some(1024));
+        assert!(!settings.validate_payload);
+        assert_eq!(settings.accept.as_deref(), Some("image/webp, image/png"));
+        assert_eq!(settings.labels["tenant"], "acme");
+
+        // The client itself is untouched.
+        assert_eq!(capture.fetch_settings(None), defaults);
+        assert_eq!(
+            capture.fetch_settings(Some(&FetchOverrides::new())),
+            defaults
+        );
+        assert_eq!(defaults.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(defaults.retry_policy.unwrap().max_attempts, 3);
+        assert_eq!(defaults.endpoint, Endpoint::Cdn);
+        assert!(defaults.validate_payload);
+        assert!(defaults.accept.is_none() && defaults.labels.is_empty());
+    }
+
+    #[test]
+    fn test_overrides_reach_the_request() {
+        let capture = capture();
+        let overrides = FetchOverrides::new()
+            .with_timeout(Duration::from_secs(5))
+            .with_accept(["application/pdf"])
+            .with_endpoint(Endpoint::Edge);
+
+        let capture_url = capture
+            .build_url_at(
+                Endpoint::Edge,
+                crate::RequestType::Pdf,
+                "https://example.com",
+                None,
+            )
+            .unwrap();
+        assert!(capture_url.starts_with("https://edge.capture.page/"));
+
+        let request = capture
+            .request(
+                &capture_url,
+                &capture.fetch_settings(Some(&overrides)),
+                crate::RequestType::Pdf,
+            )
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(5)));
+        assert_eq!(request.headers()["accept"], "application/pdf");
+
+        let request = capture
+            .request(
+                &capture_url,
+                &capture.fetch_settings(None),
+                crate::RequestType::Pdf,
+            )
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(30)));
+        assert!(request.headers().get("accept").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_max_response_bytes() {
+        let capture = capture();
+
+        let url = serve_once("200 OK", &[("content-type", "image/png")], vec![0; 2048]).await;
+        let response = capture.client.get(&url).send().await.unwrap();
+        assert!(matches!(
+            read_body(response, Some(1024)).await,
+            Err(CaptureError::ResponseTooLarge { limit: 1024 })
+        ));
+
+        let url = serve_once("200 OK", &[("content-type", "image/png")], vec![0; 2048]).await;
+        let response = capture.client.get(&url).send().await.unwrap();
+        assert_eq!(read_body(response, None).await.unwrap().len(), 2048);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_per_request_type() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string())
+            .with_timeout(Duration::from_millis(100))
+            .with_timeout_for(RequestType::Animated, Duration::from_secs(10));
+
+        // Neither server answers before the global deadline.
+        let (metadata_url, _metadata_server) = crate::mock_server::serve_held(b"{}".to_vec()).await;
+        let (animated_url, animated_server) =
+            crate::mock_server::serve_held(b"GIF89a\x01\0\x01\0".to_vec()).await;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            animated_server.release(1);
+        });
+
+        let settings = capture.fetch_settings(None);
+        let (metadata, animated) = tokio::join!(
+            capture.fetch_bytes_with_info(&metadata_url, &settings, RequestType::Metadata),
+            capture.fetch_bytes_with_info(&animated_url, &settings, RequestType::Animated),
+        );
+        assert!(matches!(
+            metadata,
+            Err(CaptureError::HttpError(ref error)) if error.is_timeout()
+        ));
+        assert_eq!(animated.unwrap().0, b"GIF89a\x01\0\x01\0");
+
+        // A per-call timeout wins over the per-type one.
+        let overrides = FetchOverrides::new().with_timeout(Duration::from_secs(1));
+        let settings = capture.fetch_settings(Some(&overrides));
+        assert_eq!(
+            settings.timeout_for(RequestType::Animated),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            capture
+                .fetch_settings(None)
+                .timeout_for(RequestType::Content),
+            Some(Duration::from_millis(100))
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::signing::RequestType;
+use crate::{AnimatedFormat, CaptureError, Result};
+
+/// Longest body excerpt kept in [`CaptureError::UnexpectedBody`].
+const SNIPPET_BYTES: usize = 300;
+
+/// File types recognised by their leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signature {
+    Pdf,
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+    /// ISO base media files: MP4, AVIF and HEIF.
+    IsoMedia,
+    WebM,
+}
+
+fn signature(body: &[u8]) -> Option<Signature> {
+    if body.starts_with(b"%PDF-") {
+        Some(Signature::Pdf)
+    } else if body.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(Signature::Png)
+    } else if body.starts_with(b"\xff\xd8\xff") {
+        Some(Signature::Jpeg)
+    } else if body.starts_with(b"GIF87a") || body.starts_with(b"GIF89a") {
+        Some(Signature::Gif)
+    } else if body.starts_with(b"RIFF") && body.get(8..12) == Some(b"WEBP") {
+        Some(Signature::WebP)
+    } else if body.get(4..8) == Some(b"ftyp") {
+        Some(Signature::IsoMedia)
+    } else if body.starts_with(b"\x1a\x45\xdf\xa3") {
+        Some(Signature::WebM)
+    } else {
+        None
+    }
+}
+
+/// Checks by its leading bytes that `body` is the kind of file
+/// `request_type` returns, so an error message or page served with a 2xx
+/// status is not mistaken for a capture. JSON responses are not checked;
+/// they fail to parse instead.
+pub(crate) fn check(
+    request_type: RequestType,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<()> {
+    let expected: &[Signature] = match request_type {
+        RequestType::Pdf => &[Signature::Pdf],
+        RequestType::Image => &[
+            Signature::Png,
+            Signature::Jpeg,
+            Signature::WebP,
+            Signature::Gif,
+            Signature::IsoMedia,
+        ],
+        RequestType::Animated => &[
+            Signature::Gif,
+            Signature::WebP,
+            Signature::IsoMedia,
+            Signature::WebM,
+        ],
+        RequestType::Content | RequestType::Metadata => return Ok(()),
+    };
+
+    expect(expected, content_type, body)
+}
+
+/// Checks that an animated capture is in the container that was asked for,
+/// e.g. an MP4 rather than a GIF.
+pub(crate) fn check_animated(
+    format: AnimatedFormat,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<()> {
+    let expected = match format {
+        AnimatedFormat::Gif => Signature::Gif,
+        AnimatedFormat::Mp4 => Signature::IsoMedia,
+        AnimatedFormat::Webm => Signature::WebM,
+    };
+    expect(&[expected], content_type, body)
+}
+
+fn expect(expected: &[Signature], content_type: Option<&str>, body: &[u8]) -> Result<()> {
+    match signature(body) {
+        Some(found) if expected.contains(&found) => Ok(()),
+        _ => Err(CaptureError::UnexpectedBody {
+            content_type: content_type.map(ToOwned::to_owned),
+            snippet: String::from_utf8_lossy(&body[..body.len().min(SNIPPET_BYTES)]).into_owned(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_matching_signatures_pass() {
+        assert!(check(RequestType::Pdf, Some("application/pdf"), b"%PDF-1.7\n%").is_ok());
+        assert!(check(RequestType::Image, Some("image/png"), PNG).is_ok());
+        assert!(check(RequestType::Image, None, b"\xff\xd8\xff\xe0\0\x10JFIF").is_ok());
+        assert!(check(RequestType::Image, None, b"RIFF\x24\0\0\0WEBPVP8 ").is_ok());
+        assert!(check(RequestType::Animated, None, b"GIF89a\x01\0").is_ok());
+        assert!(check(RequestType::Animated, None, b"\0\0\0\x20ftypisom").is_ok());
+        assert!(check(RequestType::Content, None, b"{}").is_ok());
+    }
+
+    #[test]
+    fn test_json_error_body_is_rejected() {
+        let body = br#"{"error":"Invalid token","success":false}"#;
+        let result = check(RequestType::Pdf, Some("application/json"), body);
+
+        assert!(matches!(
+            result,
+            Err(CaptureError::UnexpectedBody { ref content_type, ref snippet })
+                if content_type.as_deref() == Some("application/json")
+                    && snippet.contains("Invalid token")
+        ));
+    }
+
+    #[test]
+    fn test_html_error_page_is_rejected_with_a_bounded_snippet() {
+        let page = format!(
+            "<!DOCTYPE html><html><body>{}</body></html>",
+            "<p>Service unavailable</p>".repeat(100)
+        );
+        let result = check(RequestType::Image, Some("text/html"), page.as_bytes());
+
+        match result {
+            Err(CaptureError::UnexpectedBody { snippet, .. }) => {
+                assert!(snippet.starts_with("<!DOCTYPE html>"));
+                assert_eq!(snippet.len(), SNIPPET_BYTES);
+            }
+            other => panic!("expected UnexpectedBody, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_wrong_file_type_is_rejected() {
+        assert!(check(RequestType::Pdf, Some("image/png"), PNG).is_err());
+        assert!(check(RequestType::Image, None, b"%PDF-1.7").is_err());
+        assert!(check(RequestType::Pdf, None, b"").is_err());
+    }
+
+    #[test]
+    fn test_animated_container_must_match_format() {
+        let gif: &[u8] = b"GIF89a\x01\0\x01\0";
+        let mp4: &[u8] = b"\0\0\0\x20ftypisom\0\0\x02\0";
+        let webm: &[u8] = b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81";
+
+        for (format, body) in [
+            (AnimatedFormat::Gif, gif),
+            (AnimatedFormat::Mp4, mp4),
+            (AnimatedFormat::Webm, webm),
+        ] {
+            assert!(check_animated(format, None, body).is_ok(), "{format}");
+            for other in [gif, mp4, webm].into_iter().filter(|other| *other != body) {
+                assert!(check_animated(format, None, other).is_err(), "{format}");
+            }
+        }
+
+        let result = check_animated(AnimatedFormat::Mp4, Some("text/html"), b"<h1>Timeout</h1>");
+        assert!(matches!(
+            result,
+            Err(CaptureError::UnexpectedBody { ref snippet, .. }) if snippet == "<h1>Timeout</h1>"
+        ));
+    }
+}
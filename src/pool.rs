@@ -0,0 +1,510 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::rt::Instant;
+use crate::{
+    BackendFuture, Capture, CaptureError, CaptureRequest, CaptureResponse, ContentOptions,
+    ContentResponse, MetadataOptions, MetadataResponse, PdfOptions, RequestOptions, Result,
+    ScreenshotOptions,
+};
+
+/// How a [`CapturePool`] picks the endpoint for the next request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionStrategy {
+    /// Cycle through healthy endpoints in order.
+    #[default]
+    RoundRobin,
+    /// Prefer the healthy endpoint with the lowest rolling average latency.
+    /// Endpoints without timings yet are tried first.
+    LowestLatency,
+    /// Always use the first healthy endpoint, falling back down the list
+    /// while earlier ones are ejected.
+    PrimaryWithFailover,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    pub strategy: SelectionStrategy,
+    /// Consecutive endpoint failures before it is ejected.
+    pub failure_threshold: u32,
+    /// How long an ejected endpoint sits out before it is probed again. A
+    /// probe that has not reported back within another interval, such as a
+    /// cancelled one, is replaced by a new one.
+    pub probe_interval: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            strategy: SelectionStrategy::RoundRobin,
+            failure_threshold: 3,
+            probe_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PoolOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_strategy(mut self, strategy: SelectionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+
+    pub fn with_probe_interval(mut self, probe_interval: Duration) -> Self {
+        self.probe_interval = probe_interval;
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// When the endpoint was ejected, or last handed a probe request.
+    ejected_at: Option<Instant>,
+    average_latency: Option<Duration>,
+}
+
+struct PoolEndpoint {
+    capture: Capture,
+    health: Mutex<EndpointHealth>,
+}
+
+/// A set of [`Capture`] clients for different endpoints (for example CDN and
+/// edge) that share one interface, spreading requests between them and
+/// ejecting endpoints that keep failing.
+///
+/// Only transport failures, 429s and 5xx responses count against an
+/// endpoint; configuration errors and other API errors do not. An ejected
+/// endpoint is re-admitted through one probe request at a time: the other
+/// requests keep avoiding it until the probe succeeds.
+pub struct CapturePool {
+    endpoints: Vec<PoolEndpoint>,
+    options: PoolOptions,
+    next: AtomicUsize,
+}
+
+impl CapturePool {
+    /// Creates a pool from clients configured for different endpoints.
+    /// Earlier clients are preferred by [`SelectionStrategy::PrimaryWithFailover`].
+    pub fn new(captures: Vec<Capture>, options: PoolOptions) -> Result<Self> {
+        if captures.is_empty() {
+            return Err(CaptureError::InvalidOption {
+                field: "captures".to_string(),
+                reason: "a pool needs at least one endpoint".to_string(),
+            });
+        }
+
+        Ok(Self {
+            endpoints: captures
+                .into_iter()
+                .map(|capture| PoolEndpoint {
+                    capture,
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+                .collect(),
+            options,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Number of endpoints currently ejected for consecutive failures.
+    pub fn ejected_count(&self) -> usize {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.health.lock().unwrap().ejected_at.is_some())
+            .count()
+    }
+
+    pub async fn execute(&self, request: &CaptureRequest) -> Result<CaptureResponse> {
+        self.dispatch(|capture| Box::pin(capture.execute(request)))
+            .await
+    }
+
+    pub async fn fetch_image(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<Vec<u8>> {
+        self.dispatch(|capture| Box::pin(capture.fetch_image(url, options)))
+            .await
+    }
+
+    pub async fn fetch_pdf(&self, url: &str, options: Option<&RequestOptions>) -> Result<Vec<u8>> {
+        self.dispatch(|capture| Box::pin(capture.fetch_pdf(url, options)))
+            .await
+    }
+
+    pub async fn fetch_content(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<ContentResponse> {
+        self.dispatch(|capture| Box::pin(capture.fetch_content(url, options)))
+            .await
+    }
+
+    pub async fn fetch_metadata(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<MetadataResponse> {
+        self.dispatch(|capture| Box::pin(capture.fetch_metadata(url, options)))
+            .await
+    }
+
+    pub async fn fetch_animated(
+        &self,
+        url: &str,
+        options: Option<&RequestOptions>,
+    ) -> Result<Vec<u8>> {
+        self.dispatch(|capture| Box::pin(capture.fetch_animated(url, options)))
+            .await
+    }
+
+    pub async fn fetch_screenshot(
+        &self,
+        url: &str,
+        options: Option<&ScreenshotOptions>,
+    ) -> Result<Vec<u8>> {
+        self.dispatch(|capture| Box::pin(capture.fetch_screenshot(url, options)))
+            .await
+    }
+
+    pub async fn fetch_pdf_structured(
+        &self,
+        url: &str,
+        options: Option<&PdfOptions>,
+    ) -> Result<Vec<u8>> {
+        self.dispatch(|capture| Box::pin(capture.fetch_pdf_structured(url, options)))
+            .await
+    }
+
+    pub async fn fetch_content_structured(
+        &self,
+        url: &str,
+        options: Option<&ContentOptions>,
+    ) -> Result<ContentResponse> {
+        self.dispatch(|capture| Box::pin(capture.fetch_content_structured(url, options)))
+            .await
+    }
+
+    pub async fn fetch_metadata_structured(
+        &self,
+        url: &str,
+        options: Option<&MetadataOptions>,
+    ) -> Result<MetadataResponse> {
+        self.dispatch(|capture| Box::pin(capture.fetch_metadata_structured(url, options)))
+            .await
+    }
+
+    async fn dispatch<'a, T>(
+        &'a self,
+        call: impl FnOnce(&'a Capture) -> BackendFuture<'a, T>,
+    ) -> Result<T> {
+        let index = self.select(Instant::now());
+        let started = Instant::now();
+        let result = call(&self.endpoints[index].capture).await;
+        self.record(index, started.elapsed(), &result, Instant::now());
+        result
+    }
+
+    fn select(&self, now: Instant) -> usize {
+        loop {
+            let available: Vec<usize> = (0..self.endpoints.len())
+                .filter(|&index| self.is_available(index, now))
+                .collect();
+            // With every endpoint ejected, keep serving from all of them
+            // rather than failing outright.
+            if available.is_empty() {
+                return self.pick((0..self.endpoints.len()).collect());
+            }
+
+            let index = self.pick(available);
+            if self.claim(index, now) {
+                return index;
+            }
+        }
+    }
+
+    fn pick(&self, candidates: Vec<usize>) -> usize {
+        match self.options.strategy {
+            SelectionStrategy::RoundRobin => {
+                let turn = self.next.fetch_add(1, Ordering::Relaxed);
+                candidates[turn % candidates.len()]
+            }
+            SelectionStrategy::LowestLatency => candidates
+                .into_iter()
+                .min_by_key(|&index| {
+                    self.endpoints[index]
+                        .health
+                        .lock()
+                        .unwrap()
+                        .average_latency
+                        .unwrap_or_default()
+                })
+                .unwrap_or_default(),
+            SelectionStrategy::PrimaryWithFailover => candidates[0],
+        }
+    }
+
+    /// Healthy endpoints are always available; ejected ones become available
+    /// for a probe request once `probe_interval` has passed.
+    fn is_available(&self, index: usize, now: Instant) -> bool {
+        match self.endpoints[index].health.lock().unwrap().ejected_at {
+            Some(ejected_at) => now.duration_since(ejected_at) >= self.options.probe_interval,
+            None => true,
+        }
+    }
+
+    /// Takes endpoint `index` for one request. Taking an ejected endpoint
+    /// makes the request its probe and restarts the probe timer, so no other
+    /// request is sent there until the probe reports back or stalls. Fails
+    /// when another request took the probe since `index` was found available.
+    fn claim(&self, index: usize, now: Instant) -> bool {
+        let mut health = self.endpoints[index].health.lock().unwrap();
+        match health.ejected_at {
+            None => true,
+            Some(ejected_at) if now.duration_since(ejected_at) >= self.options.probe_interval => {
+                health.ejected_at = Some(now);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn record<T>(&self, index: usize, latency: Duration, result: &Result<T>, now: Instant) {
+        let mut health = self.endpoints[index].health.lock().unwrap();
+
+        match result {
+            Ok(_) => {
+                health.consecutive_failures = 0;
+                health.ejected_at = None;
+                health.average_latency = Some(match health.average_latency {
+                    // Rolling average weighted 3:1 towards history.
+                    Some(average) => (average * 3 + latency) / 4,
+                    None => latency,
+                });
+            }
+            Err(error) if is_endpoint_failure(error) => {
+                health.consecutive_failures += 1;
+                if health.consecutive_failures >= self.options.failure_threshold {
+                    // Re-arms the probe timer when a probe fails.
+                    health.ejected_at = Some(now);
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn is_endpoint_failure(error: &CaptureError) -> bool {
+    match error {
+        CaptureError::HttpError(_) => true,
+        CaptureError::RequestFailed { status, .. } => *status == 429 || *status >= 500,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(strategy: SelectionStrategy) -> CapturePool {
+        let captures = vec![
+            Capture::new("test_key".to_string(), "test_secret".to_string()),
+            Capture::new("test_key".to_string(), "test_secret".to_string()).with_edge(),
+        ];
+        let options = PoolOptions::new()
+            .with_strategy(strategy)
+            .with_failure_threshold(2)
+            .with_probe_interval(Duration::from_secs(10));
+        CapturePool::new(captures, options).unwrap()
+    }
+
+    fn server_error() -> Result<()> {
+        Err(CaptureError::RequestFailed {
+            status: 503,
+            message: "unavailable".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_round_robin_skips_ejected_endpoint_until_probe() {
+        let pool = pool(SelectionStrategy::RoundRobin);
+        let now = Instant::now();
+        assert_eq!(
+            (0..4).map(|_| pool.select(now)).collect::<Vec<_>>(),
+            vec![0, 1, 0, 1]
+        );
+
+        pool.record(1, Duration::from_millis(5), &server_error(), now);
+        pool.record(1, Duration::from_millis(5), &server_error(), now);
+        assert_eq!(pool.ejected_count(), 1);
+        assert!((0..4).all(|_| pool.select(now) == 0));
+
+        // Only one request probes the ejected endpoint.
+        let probe_time = now + Duration::from_secs(10);
+        let selected: Vec<usize> = (0..4).map(|_| pool.select(probe_time)).collect();
+        assert_eq!(selected.iter().filter(|&&index| index == 1).count(), 1);
+        pool.record(1, Duration::from_millis(5), &Ok(()), probe_time);
+        assert_eq!(pool.ejected_count(), 0);
+        assert_eq!(
+            (0..4).map(|_| pool.select(probe_time)).collect::<Vec<_>>(),
+            vec![0, 1, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_stalled_probe_is_replaced() {
+        let pool = pool(SelectionStrategy::PrimaryWithFailover);
+        let now = Instant::now();
+        pool.record(0, Duration::from_millis(5), &server_error(), now);
+        pool.record(0, Duration::from_millis(5), &server_error(), now);
+
+        let probe_time = now + Duration::from_secs(10);
+        assert_eq!(pool.select(probe_time), 0);
+        assert_eq!(pool.select(probe_time), 1);
+        assert_eq!(pool.select(probe_time + Duration::from_secs(5)), 1);
+        // The probe never reported back, so another request takes over.
+        assert_eq!(pool.select(probe_time + Duration::from_secs(10)), 0);
+    }
+
+    #[test]
+    fn test_primary_with_failover_shifts_and_recovers() {
+        let pool = pool(SelectionStrategy::PrimaryWithFailover);
+        let now = Instant::now();
+        assert_eq!(pool.select(now), 0);
+
+        pool.record(0, Duration::from_millis(5), &server_error(), now);
+        assert_eq!(pool.select(now), 0);
+        pool.record(0, Duration::from_millis(5), &server_error(), now);
+        assert_eq!(pool.select(now), 1);
+
+        let probe_time = now + Duration::from_secs(11);
+        assert_eq!(pool.select(probe_time), 0);
+        pool.record(0, Duration::from_millis(5), &Ok(()), probe_time);
+        assert_eq!(pool.select(probe_time), 0);
+    }
+
+    #[test]
+    fn test_lowest_latency_prefers_faster_endpoint() {
+        let pool = pool(SelectionStrategy::LowestLatency);
+        let now = Instant::now();
+
+        pool.record(0, Duration::from_millis(400), &Ok(()), now);
+        assert_eq!(pool.select(now), 1);
+        pool.record(1, Duration::from_millis(100), &Ok(()), now);
+        assert_eq!(pool.select(now), 1);
+        for _ in 0..4 {
+            pool.record(1, Duration::from_millis(900), &Ok(()), now);
+        }
+        assert_eq!(pool.select(now), 0);
+    }
+
+    #[test]
+    fn test_configuration_errors_do_not_eject() {
+        let pool = pool(SelectionStrategy::RoundRobin);
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            pool.record::<()>(0, Duration::ZERO, &Err(CaptureError::MissingUrl), now);
+            pool.record::<()>(
+                0,
+                Duration::ZERO,
+                &Err(CaptureError::RequestFailed {
+                    status: 404,
+                    message: "not found".to_string(),
+                }),
+                now,
+            );
+        }
+        assert_eq!(pool.ejected_count(), 0);
+    }
+
+    #[test]
+    fn test_empty_pool_is_rejected() {
+        assert!(matches!(
+            CapturePool::new(Vec::new(), PoolOptions::default()),
+            Err(CaptureError::InvalidOption { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_traffic_shifts_from_a_failing_endpoint_and_recovers() {
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let (healthy_url, mut healthy) =
+            crate::mock_server::serve_recorded("200 OK", &[], png.clone(), 16).await;
+        // Answers two requests with errors and one, the probe, with a
+        // screenshot; any further request is refused.
+        let (degraded_url, mut degraded) = crate::mock_server::serve_sequence(&[
+            ("503 Service Unavailable", &[], b"down".to_vec()),
+            ("503 Service Unavailable", &[], b"down".to_vec()),
+            ("200 OK", &[], png.clone()),
+        ])
+        .await;
+        let capture = |base_url: &str| {
+            Capture::with_options(
+                "test_key".to_string(),
+                "test_secret".to_string(),
+                crate::CaptureOptions::new()
+                    .with_base_url(base_url)
+                    .unwrap(),
+            )
+        };
+        let probe_interval = Duration::from_millis(200);
+        let pool = CapturePool::new(
+            vec![capture(&healthy_url), capture(&degraded_url)],
+            PoolOptions::new()
+                .with_failure_threshold(2)
+                .with_probe_interval(probe_interval),
+        )
+        .unwrap();
+        let fetch = || pool.fetch_image("https://example.com", None);
+        let drain = |requests: &mut tokio::sync::mpsc::UnboundedReceiver<String>| {
+            std::iter::from_fn(|| requests.try_recv().ok()).count()
+        };
+
+        let results = [fetch().await, fetch().await, fetch().await, fetch().await];
+        assert_eq!(
+            results.iter().map(Result::is_ok).collect::<Vec<_>>(),
+            [true, false, true, false]
+        );
+        assert_eq!(pool.ejected_count(), 1);
+        assert_eq!((drain(&mut healthy), drain(&mut degraded)), (2, 2));
+
+        for _ in 0..4 {
+            assert_eq!(fetch().await.unwrap(), png);
+        }
+        assert_eq!((drain(&mut healthy), drain(&mut degraded)), (4, 0));
+
+        // Of several concurrent requests, one probes the ejected endpoint
+        // and the rest stay on the healthy one.
+        tokio::time::sleep(probe_interval).await;
+        let results = futures_util::future::join_all((0..6).map(|_| fetch())).await;
+        assert!(results.into_iter().all(|result| result.unwrap() == png));
+        assert_eq!((drain(&mut healthy), drain(&mut degraded)), (5, 1));
+        assert_eq!(pool.ejected_count(), 0);
+
+        // Recovered, it takes its turn again; its mock server is done
+        // answering, so the request fails.
+        let results = [fetch().await, fetch().await];
+        assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+    }
+}
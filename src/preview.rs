@@ -0,0 +1,296 @@
+//! Link-preview cards rendered from page metadata.
+
+use url::Url;
+
+use crate::MetadataResponse;
+
+const DEFAULT_CLASS_PREFIX: &str = "capture-preview";
+
+/// Arrangement of the image and text of a preview card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PreviewLayout {
+    /// Image to the left of the text.
+    #[default]
+    Horizontal,
+    /// Image above the text.
+    Vertical,
+}
+
+/// How [`MetadataResponse::to_preview_html`] renders a card.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewStyle {
+    pub layout: PreviewLayout,
+    /// Prefix of the card's CSS classes, `capture-preview` when unset.
+    /// Characters other than ASCII letters, digits, `-` and `_` are dropped.
+    pub class_prefix: Option<String>,
+}
+
+impl PreviewStyle {
+    pub fn horizontal() -> Self {
+        Self::default()
+    }
+
+    pub fn vertical() -> Self {
+        Self {
+            layout: PreviewLayout::Vertical,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_class_prefix(mut self, class_prefix: impl Into<String>) -> Self {
+        self.class_prefix = Some(class_prefix.into());
+        self
+    }
+
+    fn class_prefix(&self) -> String {
+        let prefix: String = self
+            .class_prefix
+            .as_deref()
+            .unwrap_or(DEFAULT_CLASS_PREFIX)
+            .chars()
+            .filter(|character| {
+                character.is_ascii_alphanumeric() || *character == '-' || *character == '_'
+            })
+            .collect();
+        if prefix.is_empty() {
+            DEFAULT_CLASS_PREFIX.to_string()
+        } else {
+            prefix
+        }
+    }
+}
+
+/// The parts of a card, taken from the typed metadata with OpenGraph as a
+/// fallback. Links and images are kept only for `http` and `https` URLs.
+struct Card {
+    title: Option<String>,
+    description: Option<String>,
+    url: Option<Url>,
+    image: Option<Url>,
+}
+
+impl Card {
+    fn new(metadata: &MetadataResponse) -> Self {
+        let open_graph = metadata.open_graph();
+        let page = &metadata.metadata;
+        let text = |value: Option<&String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let web_url = |value: Option<&String>| {
+            Url::parse(value?.trim())
+                .ok()
+                .filter(|url| matches!(url.scheme(), "http" | "https"))
+        };
+
+        Self {
+            title: text(page.title.as_ref()).or_else(|| text(open_graph.title.as_ref())),
+            description: text(page.description.as_ref())
+                .or_else(|| text(open_graph.description.as_ref())),
+            url: web_url(page.url.as_ref()).or_else(|| web_url(open_graph.url.as_ref())),
+            image: web_url(page.image.as_ref())
+                .or_else(|| web_url(open_graph.images.first().map(|image| &image.url))),
+        }
+    }
+
+    fn host(&self) -> Option<&str> {
+        self.url.as_ref()?.host_str()
+    }
+
+    /// The title, or the host name for untitled pages.
+    fn heading(&self) -> Option<&str> {
+        self.title.as_deref().or_else(|| self.host())
+    }
+}
+
+impl MetadataResponse {
+    /// A self-contained HTML link-preview card with the page's title,
+    /// description, host name and image. Every value is escaped, so markup
+    /// in the metadata is shown as text.
+    pub fn to_preview_html(&self, style: &PreviewStyle) -> String {
+        let card = Card::new(self);
+        let prefix = style.class_prefix();
+        let (layout, direction, image_size) = match style.layout {
+            PreviewLayout::Horizontal => ("horizontal", "row", "width:120px;height:120px"),
+            PreviewLayout::Vertical => ("vertical", "column", "width:100%;height:180px"),
+        };
+
+        let mut html = String::new();
+        match &card.url {
+            Some(url) => html.push_str(&format!(
+                r#"<a class="{prefix} {prefix}--{layout}" href="{}" rel="noopener noreferrer""#,
+                escape(url.as_str())
+            )),
+            None => html.push_str(&format!(r#"<div class="{prefix} {prefix}--{layout}""#)),
+        }
+        html.push_str(&format!(
+            r#" style="display:flex;flex-direction:{direction};overflow:hidden;border:1px solid #e2e2e2;border-radius:8px;color:inherit;text-decoration:none;font-family:sans-serif">"#
+        ));
+        if let Some(image) = &card.image {
+            html.push_str(&format!(
+                r#"<img class="{prefix}__image" src="{}" alt="" style="{image_size};object-fit:cover;flex-shrink:0">"#,
+                escape(image.as_str())
+            ));
+        }
+        html.push_str(&format!(
+            r#"<span class="{prefix}__body" style="display:flex;flex-direction:column;gap:4px;padding:12px;min-width:0">"#
+        ));
+        if let Some(heading) = card.heading() {
+            html.push_str(&format!(
+                r#"<strong class="{prefix}__title">{}</strong>"#,
+                escape(heading)
+            ));
+        }
+        if let Some(description) = &card.description {
+            html.push_str(&format!(
+                r#"<span class="{prefix}__description" style="font-size:0.9em;opacity:0.8">{}</span>"#,
+                escape(description)
+            ));
+        }
+        if let Some(host) = card.host() {
+            html.push_str(&format!(
+                r#"<span class="{prefix}__host" style="font-size:0.8em;opacity:0.6">{}</span>"#,
+                escape(host)
+            ));
+        }
+        html.push_str("</span>");
+        html.push_str(if card.url.is_some() { "</a>" } else { "</div>" });
+        html
+    }
+
+    /// The card of [`to_preview_html`](Self::to_preview_html) as Markdown:
+    /// the image, the linked title, the description and the host name, one
+    /// per line. Markdown and HTML syntax in the metadata is escaped.
+    pub fn to_preview_markdown(&self) -> String {
+        let card = Card::new(self);
+        let mut lines = Vec::new();
+
+        if let Some(image) = &card.image {
+            let alt = card.heading().map(escape_markdown).unwrap_or_default();
+            lines.push(format!("![{alt}](<{image}>)"));
+        }
+        match (card.heading(), &card.url) {
+            (Some(heading), Some(url)) => {
+                lines.push(format!("**[{}](<{url}>)**", escape_markdown(heading)))
+            }
+            (Some(heading), None) => lines.push(format!("**{}**", escape_markdown(heading))),
+            (None, _) => {}
+        }
+        if let Some(description) = &card.description {
+            lines.push(escape_markdown(description));
+        }
+        if let Some(host) = card.host() {
+            lines.push(format!("_{}_", escape_markdown(host)));
+        }
+
+        lines.join("  \n")
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Escapes Markdown punctuation with backslashes and HTML with entities,
+/// and folds line breaks, so the text renders as one literal line.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '\r' | '\n' => escaped.push(' '),
+            '\\' | '`' | '*' | '_' | '{' | '}' | '[' | ']' | '(' | ')' | '#' | '+' | '-' | '.'
+            | '!' | '|' | '~' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metadata(page: serde_json::Value) -> MetadataResponse {
+        serde_json::from_value(json!({ "success": true, "metadata": page })).unwrap()
+    }
+
+    #[test]
+    fn test_preview_html_is_inert() {
+        let metadata = metadata(json!({
+            "title": "<script>alert(1)</script> & friends",
+            "description": "Say \"hi\" <img src=x onerror=alert(2)>",
+            "url": "https://example.com/post?q=\"><script>",
+            "image": "javascript:alert(3)",
+            "og:image": "https://cdn.example.com/card.png?a=1&b='x'",
+        }));
+
+        let html = metadata.to_preview_html(&PreviewStyle::vertical());
+        assert!(!html.contains("<script"), "{html}");
+        assert!(!html.contains("<img src=x"), "{html}");
+        assert!(!html.contains("javascript:"), "{html}");
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt; &amp; friends"));
+        assert!(html.contains("Say &quot;hi&quot; &lt;img src=x onerror=alert(2)&gt;"));
+        assert!(html.contains(r#"href="https://example.com/post?q=%22%3E%3Cscript%3E""#));
+        assert!(html.contains(r#"src="https://cdn.example.com/card.png?a=1&amp;b=%27x%27""#));
+        assert!(html.starts_with(r#"<a class="capture-preview capture-preview--vertical" href="#));
+        assert!(html.contains(r#"<span class="capture-preview__host" style="font-size:0.8em;opacity:0.6">example.com</span>"#));
+    }
+
+    #[test]
+    fn test_preview_html_without_link_or_image() {
+        let metadata = metadata(json!({ "og:title": "Plain", "url": "data:text/html,hi" }));
+        let style = PreviewStyle::horizontal().with_class_prefix("card\" onclick=\"x");
+
+        let html = metadata.to_preview_html(&style);
+        assert!(html.starts_with(r#"<div class="cardonclickx cardonclickx--horizontal""#));
+        assert!(html.ends_with("</span></div>"));
+        assert!(html.contains(r#"<strong class="cardonclickx__title">Plain</strong>"#));
+        assert!(!html.contains("<img") && !html.contains("href="));
+
+        let untitled = self::metadata(json!({ "url": "https://example.com/" }));
+        assert!(untitled
+            .to_preview_html(&PreviewStyle::default())
+            .contains(r#"<strong class="capture-preview__title">example.com</strong>"#));
+    }
+
+    #[test]
+    fn test_preview_markdown_escapes_syntax() {
+        let metadata = metadata(json!({
+            "title": "[Click](javascript:alert(1)) <b>now</b>",
+            "description": "Line one\n# Line two *bold*",
+            "url": "https://example.com/a_(b)",
+            "image": "https://example.com/og image.png",
+        }));
+
+        assert_eq!(
+            metadata.to_preview_markdown(),
+            [
+                r"![\[Click\]\(javascript:alert\(1\)\) &lt;b&gt;now&lt;/b&gt;](<https://example.com/og%20image.png>)",
+                r"**[\[Click\]\(javascript:alert\(1\)\) &lt;b&gt;now&lt;/b&gt;](<https://example.com/a_(b)>)**",
+                r"Line one \# Line two \*bold\*",
+                r"_example\.com_",
+            ]
+            .join("  \n")
+        );
+        assert_eq!(self::metadata(json!({})).to_preview_markdown(), "");
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::signing::RequestType;
+#[cfg(feature = "http")]
+use crate::CaptureResponse;
+use crate::{Capture, CaptureError, CaptureRequest, RequestOptions, Result};
+
+/// A saved capture configuration, such as "marketing-og" or
+/// "archive-fullpage": a request type and the options to send with it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureProfile {
+    pub request_type: RequestType,
+    #[serde(default)]
+    pub options: RequestOptions,
+}
+
+impl CaptureProfile {
+    pub fn new(request_type: RequestType) -> Self {
+        Self {
+            request_type,
+            options: RequestOptions::new(),
+        }
+    }
+
+    pub fn with_options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The request for `url`, with `overrides` merged over the profile's
+    /// options. Overrides win on conflicting keys.
+    pub fn request(&self, url: &str, overrides: Option<&RequestOptions>) -> CaptureRequest {
+        let mut options = self.options.clone();
+        if let Some(overrides) = overrides {
+            options.extend(overrides.clone());
+        }
+        CaptureRequest::new(self.request_type, url).with_options(options)
+    }
+}
+
+/// Named [`CaptureProfile`]s. Serializes as a JSON object keyed by name, so
+/// it can be saved to and loaded from a config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProfileRegistry {
+    profiles: BTreeMap<String, CaptureProfile>,
+}
+
+impl ProfileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `profile` under `name`, returning the profile it replaced.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        profile: CaptureProfile,
+    ) -> Option<CaptureProfile> {
+        self.profiles.insert(name.into(), profile)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<CaptureProfile> {
+        self.profiles.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CaptureProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Registered names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Like [`get`](Self::get), failing with
+    /// [`CaptureError::UnknownProfile`] for unknown names.
+    pub fn profile(&self, name: &str) -> Result<&CaptureProfile> {
+        self.get(name).ok_or_else(|| CaptureError::UnknownProfile {
+            name: name.to_string(),
+            available: self.names().map(str::to_string).collect(),
+        })
+    }
+}
+
+impl Capture {
+    /// Profiles from [`CaptureOptions::profiles`](crate::CaptureOptions::profiles).
+    pub fn profiles(&self) -> &ProfileRegistry {
+        &self.options.profiles
+    }
+}
+
+#[cfg(feature = "http")]
+impl Capture {
+    /// Captures `url` with the profile registered as `name`.
+    pub async fn capture_with_profile(&self, name: &str, url: &str) -> Result<CaptureResponse> {
+        self.capture_with_profile_and_options(name, url, None).await
+    }
+
+    /// Like [`capture_with_profile`](Self::capture_with_profile), with
+    /// `overrides` merged over the profile's options for this call.
+    pub async fn capture_with_profile_and_options(
+        &self,
+        name: &str,
+        url: &str,
+        overrides: Option<&RequestOptions>,
+    ) -> Result<CaptureResponse> {
+        let request = self.profiles().profile(name)?.request(url, overrides);
+        self.execute(&request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CaptureOptions;
+    use serde_json::json;
+
+    fn options(value: serde_json::Value) -> RequestOptions {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn registry() -> ProfileRegistry {
+        let mut registry = ProfileRegistry::new();
+        registry.register(
+            "marketing-og",
+            CaptureProfile::new(RequestType::Image)
+                .with_options(options(json!({ "vw": 1200, "vh": 630, "type": "jpeg" }))),
+        );
+        registry.register(
+            "archive-fullpage",
+            CaptureProfile::new(RequestType::Pdf).with_options(options(json!({ "full": true }))),
+        );
+        registry.register("text", CaptureProfile::new(RequestType::Content));
+        registry.register("seo", CaptureProfile::new(RequestType::Metadata));
+        registry.register("teaser", CaptureProfile::new(RequestType::Animated));
+        registry
+    }
+
+    #[test]
+    fn test_register_and_build_requests() {
+        let mut registry = registry();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            ["archive-fullpage", "marketing-og", "seo", "teaser", "text"]
+        );
+        let replaced = registry
+            .register("seo", CaptureProfile::new(RequestType::Content))
+            .unwrap();
+        assert_eq!(replaced.request_type, RequestType::Metadata);
+        assert!(registry.remove("seo").is_some() && registry.get("seo").is_none());
+
+        let request = registry
+            .profile("marketing-og")
+            .unwrap()
+            .request("https://example.com", Some(&options(json!({ "vh": 400 }))));
+        assert_eq!(request.request_type, RequestType::Image);
+        assert_eq!(
+            request.options,
+            options(json!({ "vw": 1200, "vh": 400, "type": "jpeg" }))
+        );
+        // The profile itself keeps its options.
+        assert_eq!(registry.get("marketing-og").unwrap().options["vh"], 630);
+    }
+
+    #[test]
+    fn test_profiles_build_the_url_of_their_request_type() {
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_profiles(registry()),
+        );
+
+        for (name, path) in [
+            ("marketing-og", "/image?"),
+            ("archive-fullpage", "/pdf?"),
+            ("text", "/content?"),
+            ("seo", "/metadata?"),
+            ("teaser", "/animated?"),
+        ] {
+            let request = capture
+                .profiles()
+                .profile(name)
+                .unwrap()
+                .request("https://example.com", None);
+            let url = capture.build_request_url(&request).unwrap();
+            assert!(url.contains(path), "{name}: {url}");
+        }
+    }
+
+    #[test]
+    fn test_registry_round_trips() {
+        let registry = registry();
+        let saved = serde_json::to_value(&registry).unwrap();
+        assert_eq!(saved["archive-fullpage"]["request_type"], "pdf");
+        assert_eq!(saved["archive-fullpage"]["options"]["full"], true);
+
+        let loaded: ProfileRegistry = serde_json::from_value(saved).unwrap();
+        assert_eq!(loaded, registry);
+
+        let loaded: ProfileRegistry =
+            serde_json::from_value(json!({ "seo": { "request_type": "metadata" } })).unwrap();
+        assert!(loaded.get("seo").unwrap().options.is_empty());
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_unknown_profile_lists_available_names() {
+        let capture = Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            CaptureOptions::new().with_profile("seo", CaptureProfile::new(RequestType::Metadata)),
+        );
+
+        let error = capture
+            .capture_with_profile("missing", "https://example.com")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            &error,
+            CaptureError::UnknownProfile { name, available }
+                if name == "missing" && available == &["seo"]
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Unknown capture profile `missing`; available: seo"
+        );
+
+        // A known profile goes on to the request, which fails here without
+        // credentials.
+        let capture = Capture::with_options(String::new(), String::new(), capture.options.clone());
+        assert!(matches!(
+            capture
+                .capture_with_profile("seo", "https://example.com")
+                .await,
+            Err(CaptureError::MissingCredentials)
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+//! Audit records of what was requested and what came back.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::rt::{SystemTime, UNIX_EPOCH};
+use crate::signing::RequestType;
+use crate::{CaptureRequest, FetchInfo};
+
+/// Version of the [`Provenance`] schema. Raised whenever a field is added,
+/// removed or changes meaning.
+pub const PROVENANCE_SCHEMA_VERSION: u32 = 1;
+
+/// Version of the capture URL layout (`/{key}/{token}/{type}?{query}`) and
+/// of the token derivation the record's request was signed with.
+pub const URL_FORMAT_VERSION: u32 = 1;
+
+/// Options whose values are credentials and are replaced in records.
+const REDACTED_OPTIONS: &[&str] = &["httpAuth"];
+
+/// What was requested and what was received for one capture, for audit
+/// logs. Built from the request and response only, so the API token and
+/// secret never end up in a record; credentials for the target site, such
+/// as `httpAuth`, are replaced with `"[redacted]"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub schema_version: u32,
+    /// Version of this crate.
+    pub crate_version: String,
+    pub url_format_version: u32,
+    pub request_type: RequestType,
+    /// The captured page.
+    pub url: String,
+    /// Options sent with the request, in key order.
+    pub options: BTreeMap<String, serde_json::Value>,
+    /// Base URL of the capture API host, e.g. `https://cdn.capture.page`.
+    pub endpoint: String,
+    /// Milliseconds since the Unix epoch when the response was read.
+    pub captured_at_ms: u64,
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub content_length: usize,
+    /// Lowercase hex SHA-256 of the response body.
+    pub sha256: String,
+}
+
+impl Provenance {
+    /// The record for `request`, sent to the `endpoint` base URL, answered
+    /// with `info` and `body`. `captured_at_ms` is the current time.
+    pub fn new(request: &CaptureRequest, endpoint: &str, info: &FetchInfo, body: &[u8]) -> Self {
+        let options = request
+            .options
+            .iter()
+            .filter(|(key, _)| key.as_str() != "url")
+            .map(|(key, value)| {
+                let value = if REDACTED_OPTIONS.contains(&key.as_str()) {
+                    serde_json::Value::String("[redacted]".to_string())
+                } else {
+                    value.clone()
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        let captured_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+
+        Self {
+            schema_version: PROVENANCE_SCHEMA_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            url_format_version: URL_FORMAT_VERSION,
+            request_type: request.request_type,
+            url: request.url.clone(),
+            options,
+            endpoint: endpoint.to_string(),
+            captured_at_ms,
+            status: info.status,
+            content_type: info.content_type.clone(),
+            content_length: body.len(),
+            sha256: sha256_hex(body),
+        }
+    }
+}
+
+fn sha256_hex(body: &[u8]) -> String {
+    Sha256::digest(body)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Endpoint;
+    #[cfg(feature = "http")]
+    use crate::{BatchOptions, Capture, CaptureOptions};
+    use serde_json::json;
+
+    fn request() -> CaptureRequest {
+        CaptureRequest::image("https://example.com/pricing").with_options(
+            serde_json::from_value(json!({
+                "vw": 1280,
+                "full": true,
+                "httpAuth": "dXNlcjpzM2NyZXQ=",
+                "blockResources": ["font", "media"],
+            }))
+            .unwrap(),
+        )
+    }
+
+    fn info() -> FetchInfo {
+        FetchInfo {
+            status: 200,
+            content_type: Some("image/png".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_schema_is_stable() {
+        let mut provenance = Provenance::new(&request(), Endpoint::Edge.base_url(), &info(), b"");
+        provenance.captured_at_ms = 1_760_000_000_000;
+        provenance.crate_version = "0.0.0".to_string();
+
+        assert_eq!(
+            serde_json::to_string_pretty(&provenance).unwrap(),
+            r#"{
+  "schema_version": 1,
+  "crate_version": "0.0.0",
+  "url_format_version": 1,
+  "request_type": "image",
+  "url": "https://example.com/pricing",
+  "options": {
+    "blockResources": [
+      "font",
+      "media"
+    ],
+    "full": true,
+    "httpAuth": "[redacted]",
+    "vw": 1280
+  },
+  "endpoint": "https://edge.capture.page",
+  "captured_at_ms": 1760000000000,
+  "status": 200,
+  "content_type": "image/png",
+  "content_length": 0,
+  "sha256": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+}"#
+        );
+        let loaded: Provenance =
+            serde_json::from_str(&serde_json::to_string(&provenance).unwrap()).unwrap();
+        assert_eq!(loaded, provenance);
+    }
+
+    #[test]
+    fn test_hashes_fixture_payloads() {
+        let provenance = Provenance::new(&request(), Endpoint::Cdn.base_url(), &info(), b"abc");
+        assert_eq!(
+            provenance.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(provenance.content_length, 3);
+        assert_eq!(provenance.crate_version, env!("CARGO_PKG_VERSION"));
+        assert!(provenance.captured_at_ms > 1_600_000_000_000);
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_records_never_contain_credentials() {
+        let capture = Capture::with_options(
+            "key_7f3a".to_string(),
+            "secret_91bc".to_string(),
+            CaptureOptions::new().with_provenance(true),
+        );
+        let request = request();
+        let capture_url = capture.build_request_url(&request).unwrap();
+        let token = capture_url.split('/').nth(4).unwrap();
+
+        let body = b"\x89PNG\r\n\x1a\n".to_vec();
+        let url = crate::mock_server::serve_once(
+            "200 OK",
+            &[("content-type", "image/png")],
+            body.clone(),
+        )
+        .await;
+        let settings = capture.fetch_settings(None);
+        let (bytes, info) = capture
+            .fetch_bytes_with_info(&url, &settings, RequestType::Image)
+            .await
+            .unwrap();
+        let provenance =
+            Provenance::new(&request, capture.base_url(settings.endpoint), &info, &bytes);
+        assert_eq!(provenance.sha256, sha256_hex(&body));
+
+        let record = serde_json::to_string(&provenance).unwrap();
+        for secret in ["key_7f3a", "secret_91bc", token, "dXNlcjpzM2NyZXQ="] {
+            assert!(!record.contains(secret), "{secret} in {record}");
+        }
+
+        // Batches record provenance only for requests that got a response;
+        // this one fails before sending without credentials.
+        let capture = Capture::with_options(String::new(), String::new(), capture.options.clone());
+        let report = capture
+            .execute_batch(&[request], &BatchOptions::new())
+            .await;
+        assert!(report.provenance.is_empty());
+        assert!(matches!(
+            report.results[0].1,
+            Err(crate::CaptureError::MissingCredentials)
+        ));
+    }
+}
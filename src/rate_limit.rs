@@ -0,0 +1,590 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rt::{self, SystemTime, UNIX_EPOCH};
+use crate::{CaptureError, Result};
+
+/// Minimum time between two saves while requests are flowing. Consumption
+/// recorded in between is saved by the next request after the debounce,
+/// by [`RateLimiter::flush`], or when the last clone is dropped.
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(1);
+
+type StoreErrorCallback = Arc<dyn Fn(&CaptureError) + Send + Sync>;
+
+/// Consumption within the current window, as persisted by a
+/// [`RateLimitStore`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitState {
+    /// Start of the window, in seconds since the Unix epoch.
+    pub window_start: u64,
+    pub consumed: u64,
+}
+
+/// Where a [`RateLimiter`] keeps its consumption between process runs.
+pub trait RateLimitStore: Send + Sync {
+    /// Returns the saved state, or `None` when nothing was saved yet.
+    fn load(&self) -> Result<Option<RateLimitState>>;
+    fn save(&self, state: &RateLimitState) -> Result<()>;
+}
+
+/// Stores the state as JSON in a single file, replaced atomically on save.
+/// Each save writes a temporary file of its own, so clones of one store
+/// saving at once never mix their writes.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl RateLimitStore for JsonFileStore {
+    fn load(&self) -> Result<Option<RateLimitState>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save(&self, state: &RateLimitState) -> Result<()> {
+        let temporary = rt::temporary_path(&self.path);
+        fs::write(&temporary, serde_json::to_vec(state)?)?;
+        fs::rename(&temporary, &self.path).inspect_err(|_| {
+            let _ = fs::remove_file(&temporary);
+        })?;
+        Ok(())
+    }
+}
+
+/// Client-side cap of `max_requests` per fixed window, e.g. a daily plan
+/// budget. Windows are aligned to the Unix epoch, so daily windows start at
+/// midnight UTC.
+///
+/// Clones share their consumption. With a store attached, consumption is
+/// saved as requests are made, so the budget survives restarts. The store is
+/// loaded exactly once, on first use rather than when the client is built,
+/// so building a client does no file IO and a store that is replaced before
+/// the first request is the one read. The first request to come loads it;
+/// concurrent first requests wait for that load. Saves run without blocking
+/// other requests. For async fetches, loads and saves run on Tokio's
+/// blocking pool rather than on the executor. A store that cannot be read is
+/// reported to [`on_store_error`](Self::on_store_error) and counting starts
+/// fresh.
+#[derive(Clone)]
+pub struct RateLimiter {
+    pub max_requests: u64,
+    pub window: Duration,
+    tracker: Arc<Mutex<Tracker>>,
+}
+
+struct Tracker {
+    state: RateLimitState,
+    loaded: bool,
+    /// Bumped on every change of `state`.
+    generation: u64,
+    last_save: Option<SystemTime>,
+    store: Option<Arc<Store>>,
+    on_store_error: Option<StoreErrorCallback>,
+}
+
+/// A store and the generation of the state it last saved. Saves hold this
+/// lock instead of the tracker's, and never replace newer state with older.
+struct Store {
+    store: Box<dyn RateLimitStore>,
+    saved: Mutex<u64>,
+}
+
+/// State to save once the tracker's lock is released.
+struct PendingSave {
+    store: Arc<Store>,
+    state: RateLimitState,
+    generation: u64,
+    on_store_error: Option<StoreErrorCallback>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("max_requests", &self.max_requests)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u64, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window: window.max(Duration::from_secs(1)),
+            tracker: Arc::new(Mutex::new(Tracker {
+                state: RateLimitState::default(),
+                loaded: false,
+                generation: 0,
+                last_save: None,
+                store: None,
+                on_store_error: None,
+            })),
+        }
+    }
+
+    /// `max_requests` per UTC day.
+    pub fn daily(max_requests: u64) -> Self {
+        Self::new(max_requests, Duration::from_secs(24 * 60 * 60))
+    }
+
+    pub fn with_store(self, store: impl RateLimitStore + 'static) -> Self {
+        self.lock().store = Some(Arc::new(Store {
+            store: Box::new(store),
+            saved: Mutex::new(0),
+        }));
+        self
+    }
+
+    /// Called when the store fails to load or save.
+    pub fn on_store_error(self, callback: impl Fn(&CaptureError) + Send + Sync + 'static) -> Self {
+        self.lock().on_store_error = Some(Arc::new(callback));
+        self
+    }
+
+    /// Requests made in the current window.
+    pub fn consumed(&self) -> u64 {
+        let now = SystemTime::now();
+        let mut tracker = self.lock();
+        tracker.load();
+        tracker.roll(self.window_start(now));
+        tracker.state.consumed
+    }
+
+    /// Saves pending consumption to the store now.
+    pub fn flush(&self) {
+        let pending = self.lock().pending_save(SystemTime::now());
+        if let Some(pending) = pending {
+            pending.run();
+        }
+    }
+
+    /// Counts one request, or fails with
+    /// [`CaptureError::RateLimitExceeded`] once the window's budget is spent.
+    /// Loads and saves the store on the calling thread, as the blocking
+    /// client needs.
+    #[cfg(any(feature = "blocking", test))]
+    pub(crate) fn acquire(&self, now: SystemTime) -> Result<()> {
+        if let Some(pending) = self.count(now)? {
+            pending.run();
+        }
+        Ok(())
+    }
+
+    /// Like `acquire`, with the store's IO moved off the async executor.
+    pub(crate) async fn acquire_async(&self, now: SystemTime) -> Result<()> {
+        if !self.lock().loaded {
+            let limiter = self.clone();
+            // Waiting for a load by another request blocks too, so it is
+            // moved off the executor as well. Should this fail, `count`
+            // loads inline.
+            let _ = rt::blocking_io(move || {
+                limiter.lock().load();
+                Ok(())
+            })
+            .await;
+        }
+        if let Some(pending) = self.count(now)? {
+            let _ = rt::blocking_io(move || {
+                pending.run();
+                Ok(())
+            })
+            .await;
+        }
+        Ok(())
+    }
+
+    /// Counts one request, returning the save to run once the lock is
+    /// released.
+    fn count(&self, now: SystemTime) -> Result<Option<PendingSave>> {
+        let window_start = self.window_start(now);
+        let pending = {
+            let mut tracker = self.lock();
+            tracker.load();
+            tracker.roll(window_start);
+
+            if tracker.state.consumed >= self.max_requests {
+                return Err(CaptureError::RateLimitExceeded {
+                    retry_at: UNIX_EPOCH + Duration::from_secs(window_start) + self.window,
+                });
+            }
+            tracker.state.consumed += 1;
+            tracker.generation += 1;
+
+            let debounced = tracker.last_save.is_some_and(|last_save| {
+                now.duration_since(last_save).unwrap_or_default() < SAVE_DEBOUNCE
+            });
+            if debounced {
+                None
+            } else {
+                tracker.pending_save(now)
+            }
+        };
+        Ok(pending)
+    }
+
+    fn window_start(&self, now: SystemTime) -> u64 {
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let window = self.window.as_secs();
+        now - now % window
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Tracker> {
+        self.tracker.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Tracker {
+    fn load(&mut self) {
+        if self.loaded {
+            return;
+        }
+        self.loaded = true;
+
+        let Some(store) = &self.store else {
+            return;
+        };
+        match store.store.load() {
+            Ok(Some(state)) => self.state = state,
+            Ok(None) => {}
+            Err(error) => self.report(&error),
+        }
+    }
+
+    /// Moves the state to the window starting at `window_start`. A saved
+    /// window that starts later than the current one means the clock moved
+    /// backwards; its consumption is kept rather than handed back.
+    fn roll(&mut self, window_start: u64) {
+        if self.state.window_start == window_start {
+            return;
+        }
+        if self.state.window_start < window_start {
+            self.state.consumed = 0;
+        }
+        self.state.window_start = window_start;
+        self.generation += 1;
+    }
+
+    /// The current state, to save after the lock is released, or `None`
+    /// without a store.
+    fn pending_save(&mut self, now: SystemTime) -> Option<PendingSave> {
+        let store = self.store.clone()?;
+        self.last_save = Some(now);
+        Some(PendingSave {
+            store,
+            state: self.state,
+            generation: self.generation,
+            on_store_error: self.on_store_error.clone(),
+        })
+    }
+
+    fn report(&self, error: &CaptureError) {
+        report(self.on_store_error.as_ref(), error);
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending_save(SystemTime::now()) {
+            pending.run();
+        }
+    }
+}
+
+impl PendingSave {
+    /// Saves the state unless it is already saved, or something newer is.
+    fn run(self) {
+        let mut saved = self.store.saved.lock().unwrap_or_else(|e| e.into_inner());
+        if *saved >= self.generation {
+            return;
+        }
+        match self.store.store.save(&self.state) {
+            Ok(()) => *saved = self.generation,
+            Err(error) => report(self.on_store_error.as_ref(), &error),
+        }
+    }
+}
+
+fn report(on_store_error: Option<&StoreErrorCallback>, error: &CaptureError) {
+    if let Some(on_store_error) = on_store_error {
+        on_store_error(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "capture-rust-{name}-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn file(&self) -> PathBuf {
+            self.0.join("rate-limit.json")
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_daily_budget_survives_restart() {
+        let dir = TempDir::new("restart");
+        let noon = 100 * DAY + DAY / 2;
+
+        let limiter = RateLimiter::daily(3).with_store(JsonFileStore::new(dir.file()));
+        limiter.acquire(at(noon)).unwrap();
+        limiter.acquire(at(noon)).unwrap();
+        drop(limiter);
+
+        // Same day after a restart: one request left.
+        let limiter = RateLimiter::daily(3).with_store(JsonFileStore::new(dir.file()));
+        limiter.acquire(at(noon + 60)).unwrap();
+        let result = limiter.acquire(at(noon + 60));
+        assert!(matches!(
+            result,
+            Err(CaptureError::RateLimitExceeded { retry_at }) if retry_at == at(101 * DAY)
+        ));
+
+        // The next day starts over.
+        limiter.acquire(at(101 * DAY)).unwrap();
+    }
+
+    #[test]
+    fn test_saves_are_debounced_until_flush() {
+        let dir = TempDir::new("debounce");
+        let store = JsonFileStore::new(dir.file());
+        let limiter = RateLimiter::daily(10).with_store(store.clone());
+
+        limiter.acquire(at(DAY)).unwrap();
+        limiter.acquire(at(DAY)).unwrap();
+        assert_eq!(store.load().unwrap().unwrap().consumed, 1);
+
+        limiter.acquire(at(DAY + 2)).unwrap();
+        assert_eq!(store.load().unwrap().unwrap().consumed, 3);
+
+        limiter.acquire(at(DAY + 2)).unwrap();
+        limiter.flush();
+        assert_eq!(store.load().unwrap().unwrap().consumed, 4);
+    }
+
+    #[test]
+    fn test_corrupt_file_starts_fresh_with_warning() {
+        let dir = TempDir::new("corrupt");
+        fs::write(dir.file(), b"{\"window_start\": 8640").unwrap();
+        let warnings = Arc::new(AtomicUsize::new(0));
+
+        let limiter = RateLimiter::daily(1)
+            .with_store(JsonFileStore::new(dir.file()))
+            .on_store_error({
+                let warnings = warnings.clone();
+                move |error| {
+                    assert!(matches!(error, CaptureError::JsonError(_)));
+                    warnings.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        assert_eq!(limiter.consumed(), 0);
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+
+        limiter.acquire(at(DAY)).unwrap();
+        assert!(limiter.acquire(at(DAY)).is_err());
+        // The corrupt file was replaced with a valid one.
+        let saved = JsonFileStore::new(dir.file()).load().unwrap().unwrap();
+        assert_eq!(
+            saved,
+            RateLimitState {
+                window_start: DAY,
+                consumed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_clock_moving_backwards_keeps_consumption() {
+        let dir = TempDir::new("skew");
+        let store = JsonFileStore::new(dir.file());
+        // Saved by a host whose clock ran a day ahead.
+        store
+            .save(&RateLimitState {
+                window_start: 11 * DAY,
+                consumed: 2,
+            })
+            .unwrap();
+
+        let limiter = RateLimiter::daily(2).with_store(store.clone());
+        assert!(limiter.acquire(at(10 * DAY + 5)).is_err());
+        limiter.flush();
+        assert_eq!(
+            store.load().unwrap().unwrap(),
+            RateLimitState {
+                window_start: 10 * DAY,
+                consumed: 2
+            }
+        );
+
+        // Once the current window ends, the budget resets as usual.
+        limiter.acquire(at(11 * DAY)).unwrap();
+    }
+
+    /// Saves wait until the test lets them finish.
+    struct SlowStore {
+        entered: std::sync::mpsc::SyncSender<()>,
+        release: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl RateLimitStore for SlowStore {
+        fn load(&self) -> Result<Option<RateLimitState>> {
+            Ok(None)
+        }
+
+        fn save(&self, _: &RateLimitState) -> Result<()> {
+            self.entered.send(()).unwrap();
+            self.release.lock().unwrap().recv().unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_saves_do_not_block_acquires() {
+        let (entered, saving) = std::sync::mpsc::sync_channel(1);
+        let (release, released) = std::sync::mpsc::channel();
+        let limiter = RateLimiter::daily(2).with_store(SlowStore {
+            entered,
+            release: Mutex::new(released),
+        });
+
+        let first = std::thread::spawn({
+            let limiter = limiter.clone();
+            move || limiter.acquire(at(DAY)).unwrap()
+        });
+        saving.recv().unwrap();
+
+        // The first save is still running; the next acquire is debounced
+        // and must not wait for it.
+        limiter.acquire(at(DAY)).unwrap();
+        assert!(limiter.acquire(at(DAY)).is_err());
+
+        release.send(()).unwrap();
+        first.join().unwrap();
+        // Dropping the last clone saves the second request.
+        release.send(()).unwrap();
+        drop(limiter);
+        saving.recv().unwrap();
+    }
+
+    /// A store whose load waits until the test lets it finish.
+    struct GatedStore {
+        gate: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl RateLimitStore for GatedStore {
+        fn load(&self) -> Result<Option<RateLimitState>> {
+            let gate = self.gate.lock().unwrap();
+            gate.recv_timeout(Duration::from_secs(5))
+                .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?;
+            Ok(Some(RateLimitState {
+                window_start: DAY,
+                consumed: 1,
+            }))
+        }
+
+        fn save(&self, _state: &RateLimitState) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// On the single-threaded test runtime, the gate can only be opened
+    /// while the load is waiting if the load is not holding up the executor.
+    #[tokio::test]
+    async fn test_async_acquire_loads_off_the_executor() {
+        let (open, gate) = std::sync::mpsc::channel();
+        let limiter = RateLimiter::daily(2).with_store(GatedStore {
+            gate: Mutex::new(gate),
+        });
+
+        let acquire = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire_async(at(DAY)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        open.send(()).unwrap();
+        acquire.await.unwrap().unwrap();
+
+        // The saved request was loaded, so this one is the last.
+        assert!(limiter.acquire_async(at(DAY)).await.is_err());
+    }
+
+    #[test]
+    fn test_clones_of_a_store_save_concurrently() {
+        let dir = TempDir::new("concurrent-saves");
+        let store = JsonFileStore::new(dir.file());
+
+        let savers: Vec<_> = (0..4)
+            .map(|saver| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for consumed in 0..50 {
+                        let state = RateLimitState {
+                            window_start: saver,
+                            consumed,
+                        };
+                        store.save(&state).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for saver in savers {
+            saver.join().unwrap();
+        }
+
+        assert_eq!(store.load().unwrap().unwrap().consumed, 49);
+        let files: Vec<_> = fs::read_dir(&dir.0).unwrap().collect();
+        assert_eq!(files.len(), 1, "temporary files left behind");
+    }
+
+    #[test]
+    fn test_without_store_counts_in_memory_and_shares_across_clones() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let clone = limiter.clone();
+
+        limiter.acquire(at(120)).unwrap();
+        clone.acquire(at(130)).unwrap();
+        assert!(limiter.acquire(at(179)).is_err());
+        limiter.acquire(at(180)).unwrap();
+    }
+}
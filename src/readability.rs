@@ -0,0 +1,528 @@
+//! Client-side reader view of captured HTML: the main content of the page
+//! without navigation, footers, cookie notices and similar boilerplate.
+
+use std::collections::HashMap;
+
+use crate::html::{self, Element, Node};
+
+/// Elements dropped wherever they appear.
+const BOILERPLATE_TAGS: &[&str] = &[
+    "aside", "button", "dialog", "footer", "form", "head", "iframe", "nav", "noscript", "script",
+    "select", "style", "svg", "template",
+];
+
+/// ARIA roles of page chrome rather than content.
+const BOILERPLATE_ROLES: &[&str] = &[
+    "alertdialog",
+    "banner",
+    "complementary",
+    "contentinfo",
+    "dialog",
+    "navigation",
+    "search",
+];
+
+/// Words in a `class` or `id` that mark page chrome. Matched against whole
+/// words, so `ad` matches `ad-slot` but not `header`.
+const BOILERPLATE_WORDS: &[&str] = &[
+    "ad",
+    "ads",
+    "advert",
+    "advertisement",
+    "banner",
+    "breadcrumb",
+    "breadcrumbs",
+    "comments",
+    "consent",
+    "cookie",
+    "cookies",
+    "footer",
+    "gdpr",
+    "menu",
+    "modal",
+    "nav",
+    "navbar",
+    "newsletter",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sidebar",
+    "social",
+    "sponsored",
+    "subscribe",
+];
+
+/// Paragraphs shorter than this do not count towards a container's score.
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// The main content of a page, as HTML and markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReaderView {
+    pub(crate) html: String,
+    pub(crate) markdown: String,
+}
+
+/// Extracts the main content of `html`, or `None` when nothing readable is
+/// left once boilerplate is removed.
+///
+/// The content is the longest `<article>`, else `<main>`, else the element
+/// whose paragraphs carry the most text.
+pub(crate) fn extract(html: &str) -> Option<ReaderView> {
+    let nodes = strip_boilerplate(html::parse(html));
+    let root = content_root(&nodes)?;
+
+    let markdown = markdown(&root.children);
+    if markdown.is_empty() {
+        return None;
+    }
+    Some(ReaderView {
+        html: html::to_html(&[Node::Element(root.clone())]),
+        markdown,
+    })
+}
+
+fn strip_boilerplate(nodes: Vec<Node>) -> Vec<Node> {
+    nodes
+        .into_iter()
+        .filter_map(|node| match node {
+            Node::Element(element) if is_boilerplate(&element) => None,
+            Node::Element(mut element) => {
+                element.children = strip_boilerplate(element.children);
+                Some(Node::Element(element))
+            }
+            text => Some(text),
+        })
+        .collect()
+}
+
+fn is_boilerplate(element: &Element) -> bool {
+    if BOILERPLATE_TAGS.contains(&element.name.as_str())
+        || element.attribute("hidden").is_some()
+        || element.attribute("aria-hidden") == Some("true")
+        || element
+            .attribute("role")
+            .is_some_and(|role| BOILERPLATE_ROLES.contains(&role))
+    {
+        return true;
+    }
+
+    // Elements that hold the page content are never chrome, whatever their
+    // class names say.
+    if matches!(element.name.as_str(), "article" | "main" | "body" | "html") {
+        return false;
+    }
+    ["class", "id"]
+        .into_iter()
+        .filter_map(|attribute| element.attribute(attribute))
+        .flat_map(|value| value.split(|c: char| !c.is_ascii_alphanumeric()))
+        .any(|word| BOILERPLATE_WORDS.contains(&word.to_ascii_lowercase().as_str()))
+}
+
+fn content_root(nodes: &[Node]) -> Option<&Element> {
+    let mut elements = Vec::new();
+    collect_elements(nodes, &mut elements);
+
+    let longest = |name: &str| {
+        elements
+            .iter()
+            .filter(|element| element.name == name)
+            .max_by_key(|element| element.text().trim().len())
+            .copied()
+    };
+    if let Some(article) = longest("article") {
+        return Some(article);
+    }
+    if let Some(main) = elements
+        .iter()
+        .find(|element| element.name == "main" || element.attribute("role") == Some("main"))
+    {
+        return Some(main);
+    }
+
+    best_scored(nodes).or_else(|| longest("body"))
+}
+
+fn collect_elements<'a>(nodes: &'a [Node], elements: &mut Vec<&'a Element>) {
+    for node in nodes {
+        if let Node::Element(element) = node {
+            elements.push(element);
+            collect_elements(&element.children, elements);
+        }
+    }
+}
+
+/// Scores each paragraph by its length and commas, credits the score to
+/// its parent in full and to its grandparent by half, and returns the
+/// highest scoring element.
+fn best_scored(nodes: &[Node]) -> Option<&Element> {
+    let mut scores = HashMap::new();
+    score(nodes, &mut Vec::new(), &mut scores);
+
+    let (path, _) = scores
+        .into_iter()
+        .max_by(|(a_path, a), (b_path, b)| a.total_cmp(b).then_with(|| b_path.cmp(a_path)))?;
+    let mut nodes = nodes;
+    let mut element = None;
+    for index in path {
+        let Node::Element(found) = &nodes[index] else {
+            return None;
+        };
+        element = Some(found);
+        nodes = &found.children;
+    }
+    element
+}
+
+fn score(nodes: &[Node], path: &mut Vec<usize>, scores: &mut HashMap<Vec<usize>, f64>) {
+    for (index, node) in nodes.iter().enumerate() {
+        let Node::Element(element) = node else {
+            continue;
+        };
+        path.push(index);
+
+        if element.name == "p" {
+            let text = element.text();
+            let length = text.trim().chars().count();
+            if length >= MIN_PARAGRAPH_CHARS {
+                let points = 1.0 + text.matches(',').count() as f64 + (length / 100).min(3) as f64;
+                for (depth, share) in [(1, 1.0), (2, 0.5)] {
+                    if path.len() > depth {
+                        *scores
+                            .entry(path[..path.len() - depth].to_vec())
+                            .or_default() += points * share;
+                    }
+                }
+            }
+        }
+        score(&element.children, path, scores);
+        path.pop();
+    }
+}
+
+/// Converts content HTML to markdown: headings, paragraphs, lists, quotes,
+/// code, links, emphasis and images. Other elements contribute their text.
+fn markdown(nodes: &[Node]) -> String {
+    let mut writer = MarkdownWriter::default();
+    writer.blocks(nodes, 0);
+    writer.finish()
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    output: String,
+}
+
+impl MarkdownWriter {
+    fn blocks(&mut self, nodes: &[Node], depth: usize) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.inline_text(&html::collapse_whitespace(text)),
+                Node::Element(element) => self.element(element, depth),
+            }
+        }
+    }
+
+    fn element(&mut self, element: &Element, depth: usize) {
+        match element.name.as_str() {
+            name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                let text = inline(&element.children);
+                if !text.is_empty() {
+                    self.paragraph(&format!("{} {text}", "#".repeat(level)));
+                }
+            }
+            "ul" | "ol" => {
+                // Nested lists continue their parent item's list.
+                if depth == 0 {
+                    self.block_break();
+                }
+                let ordered = element.name == "ol";
+                let items = element.children.iter().filter_map(|node| match node {
+                    Node::Element(item) if item.name == "li" => Some(item),
+                    _ => None,
+                });
+                for (number, item) in items.enumerate() {
+                    self.list_item(item, ordered.then_some(number + 1), depth);
+                }
+                if depth == 0 {
+                    self.block_break();
+                }
+            }
+            "blockquote" => {
+                let quoted = markdown(&element.children);
+                if !quoted.is_empty() {
+                    let quoted: Vec<_> = quoted
+                        .lines()
+                        .map(|line| format!("> {line}").trim_end().to_string())
+                        .collect();
+                    self.paragraph(&quoted.join("\n"));
+                }
+            }
+            "pre" => {
+                let code = element.text();
+                let code = code.trim_matches('\n');
+                if !code.is_empty() {
+                    self.paragraph(&format!("```\n{code}\n```"));
+                }
+            }
+            "hr" => self.paragraph("---"),
+            "br" => self.output.push('\n'),
+            "address" | "div" | "dl" | "dd" | "dt" | "figure" | "figcaption" | "header" | "p"
+            | "section" | "table" | "tr" | "article" | "main" | "body" | "html" => {
+                self.block_break();
+                self.blocks(&element.children, depth);
+                self.block_break();
+            }
+            _ => self.inline_text(&inline_element(element)),
+        }
+    }
+
+    fn list_item(&mut self, item: &Element, number: Option<usize>, depth: usize) {
+        let (nested, content): (Vec<&Node>, Vec<&Node>) = item.children.iter().partition(
+            |node| matches!(node, Node::Element(element) if matches!(element.name.as_str(), "ul" | "ol")),
+        );
+        let content: Vec<Node> = content.into_iter().cloned().collect();
+        let marker = number.map_or_else(|| "-".to_string(), |number| format!("{number}."));
+
+        self.trim_trailing_spaces();
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+        self.output.push_str(&"  ".repeat(depth));
+        self.output.push_str(&marker);
+        self.output.push(' ');
+        self.output.push_str(&inline(&content));
+        self.output.push('\n');
+        for list in nested {
+            if let Node::Element(list) = list {
+                self.element(list, depth + 1);
+            }
+        }
+    }
+
+    fn inline_text(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            if !text.is_empty() && !self.output.ends_with([' ', '\n']) && !self.output.is_empty() {
+                self.output.push(' ');
+            }
+            return;
+        }
+        let text = if self.output.is_empty() || self.output.ends_with('\n') {
+            text.trim_start()
+        } else {
+            text
+        };
+        self.output.push_str(text);
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        self.block_break();
+        self.output.push_str(text);
+        self.block_break();
+    }
+
+    /// Ends the current block with a blank line, unless it already is.
+    fn block_break(&mut self) {
+        self.trim_trailing_spaces();
+        if self.output.is_empty() || self.output.ends_with("\n\n") {
+            return;
+        }
+        self.output.push_str(if self.output.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        });
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.output.trim_end_matches(' ').len();
+        self.output.truncate(trimmed);
+    }
+
+    fn finish(self) -> String {
+        self.output.trim().to_string()
+    }
+}
+
+/// Renders `nodes` as a single line of inline markdown.
+fn inline(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(value) => text.push_str(value),
+            Node::Element(element) => text.push_str(&inline_element(element)),
+        }
+    }
+    html::collapse_whitespace(&text).trim().to_string()
+}
+
+fn inline_element(element: &Element) -> String {
+    let content = inline(&element.children);
+    match element.name.as_str() {
+        "a" => match element.attribute("href") {
+            Some(href) if !content.is_empty() && !href.starts_with("javascript:") => {
+                format!("[{content}]({href})")
+            }
+            _ => content,
+        },
+        "strong" | "b" if !content.is_empty() => format!("**{content}**"),
+        "em" | "i" if !content.is_empty() => format!("*{content}*"),
+        "code" if !content.is_empty() => format!("`{content}`"),
+        "img" => match element.attribute("src") {
+            Some(src) => format!("![{}]({src})", element.attribute("alt").unwrap_or_default()),
+            None => String::new(),
+        },
+        "br" => " ".to_string(),
+        // Keeps block-level children apart.
+        name if !content.is_empty() && !is_inline(name) => format!(" {content} "),
+        _ => content,
+    }
+}
+
+fn is_inline(name: &str) -> bool {
+    matches!(
+        name,
+        "abbr"
+            | "cite"
+            | "del"
+            | "ins"
+            | "kbd"
+            | "mark"
+            | "q"
+            | "s"
+            | "small"
+            | "span"
+            | "sub"
+            | "sup"
+            | "time"
+            | "u"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A news article page with the usual clutter around it.
+    const CLUTTERED_ARTICLE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <title>Rust 2024 | Example News</title>
+  <style>body { font-family: sans-serif; }</style>
+  <script>window.dataLayer = [];</script>
+</head>
+<body>
+  <div id="cookie-banner" class="consent-popup">
+    <p>We use cookies to improve your experience. Accept cookies?</p>
+    <button>Accept all</button>
+  </div>
+  <header class="site-header">
+    <a href="/" class="logo">Example News</a>
+    <nav><ul><li><a href="/">Home</a></li><li><a href="/tech">Tech</a></li></ul></nav>
+  </header>
+  <div class="layout">
+    <aside class="sidebar"><h3>Trending</h3><p>Ten gadgets you will not believe exist.</p></aside>
+    <article class="post">
+      <h1>The Rust 2024 edition is here</h1>
+      <p class="byline">By <a href="/authors/jo">Jo Writer</a></p>
+      <p>The new edition ships with <strong>async closures</strong>, better
+         <em>lifetime capture</em> rules, and a reserved <code>gen</code> keyword.</p>
+      <div class="ad-slot"><p>Advertisement: buy our shiny widgets today, limited offer!</p></div>
+      <h2>What changed</h2>
+      <ul>
+        <li>Unsafe extern blocks</li>
+        <li>Never type fallback
+          <ul><li>Now <code>!</code> instead of <code>()</code></li></ul>
+        </li>
+      </ul>
+      <blockquote><p>Editions let Rust evolve without breaking code.</p></blockquote>
+      <pre><code>cargo fix --edition</code></pre>
+      <p>Read the <a href="https://doc.rust-lang.org/edition-guide/">edition guide</a> for details.</p>
+      <div class="share-buttons"><a href="https://twitter.com/share">Share on X</a></div>
+    </article>
+  </div>
+  <section class="related"><h2>Related posts</h2><p>Why Rust keeps winning developer surveys, again.</p></section>
+  <footer><p>Copyright &copy; 2024 Example News. All rights reserved.</p></footer>
+</body>
+</html>"##;
+
+    #[test]
+    fn test_cluttered_article() {
+        let reader = extract(CLUTTERED_ARTICLE).unwrap();
+
+        assert_eq!(
+            reader.markdown,
+            "# The Rust 2024 edition is here\n\n\
+             By [Jo Writer](/authors/jo)\n\n\
+             The new edition ships with **async closures**, better *lifetime capture* rules, \
+             and a reserved `gen` keyword.\n\n\
+             ## What changed\n\n\
+             - Unsafe extern blocks\n\
+             - Never type fallback\n  \
+             - Now `!` instead of `()`\n\n\
+             > Editions let Rust evolve without breaking code.\n\n\
+             ```\ncargo fix --edition\n```\n\n\
+             Read the [edition guide](https://doc.rust-lang.org/edition-guide/) for details."
+        );
+
+        assert!(reader.html.starts_with("<article class=\"post\">"));
+        for boilerplate in [
+            "cookies",
+            "Home",
+            "Trending",
+            "Advertisement",
+            "Share on X",
+            "Related posts",
+            "Copyright",
+            "dataLayer",
+        ] {
+            assert!(!reader.html.contains(boilerplate), "{boilerplate}");
+            assert!(!reader.markdown.contains(boilerplate), "{boilerplate}");
+        }
+    }
+
+    #[test]
+    fn test_page_without_article_uses_paragraph_scores() {
+        let html = r#"<body>
+          <div class="menu"><p>Products, pricing, customers, and everything else we sell.</p></div>
+          <div id="top"><span>Welcome</span></div>
+          <div id="story">
+            <p>First paragraph of the story, with enough words, commas, and length to count.</p>
+            <p>Second paragraph of the story, which also carries plenty of readable text.</p>
+          </div>
+          <div id="bottom"><p>Short note.</p></div>
+        </body>"#;
+
+        let reader = extract(html).unwrap();
+        assert!(reader.html.starts_with("<div id=\"story\">"));
+        assert_eq!(
+            reader.markdown,
+            "First paragraph of the story, with enough words, commas, and length to count.\n\n\
+             Second paragraph of the story, which also carries plenty of readable text."
+        );
+    }
+
+    #[test]
+    fn test_main_element_and_empty_pages() {
+        let reader = extract("<nav>Menu</nav><main><p>Hello</p></main>").unwrap();
+        assert_eq!(reader.markdown, "Hello");
+
+        assert!(extract("").is_none());
+        assert!(extract("<nav><p>Only navigation here, nothing to read.</p></nav>").is_none());
+    }
+
+    #[test]
+    fn test_deeply_nested_page() {
+        let html = format!(
+            "<article>{}<p>Deep inside, the story still reads fine, with commas, and length.</p>",
+            "<div><blockquote><ul><li>".repeat(25_000)
+        );
+
+        let reader = extract(&html).unwrap();
+        assert!(reader.html.starts_with("<article><div><blockquote>"));
+        assert!(reader
+            .markdown
+            .ends_with("Deep inside, the story still reads fine, with commas, and length."));
+    }
+}
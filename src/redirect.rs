@@ -0,0 +1,131 @@
+//! Which redirects of the capture API the crate-built client follows.
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+use reqwest::redirect::Policy;
+
+/// Hops followed by [`RedirectPolicy::FollowSameHost`] and the default
+/// [`RedirectPolicy::Follow`], as in reqwest.
+const DEFAULT_MAX_HOPS: usize = 10;
+
+/// Redirect handling of the crate-built HTTP client, from
+/// [`CaptureOptions::with_redirect_policy`](crate::CaptureOptions::with_redirect_policy).
+/// A redirect that is not followed fails the fetch with
+/// [`CaptureError::RedirectNotFollowed`](crate::CaptureError::RedirectNotFollowed).
+/// The `*_stored` methods never follow redirects, whatever the policy.
+///
+/// On wasm32 the browser follows redirects itself and the policy is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Follows up to this many redirects; more fail the request.
+    Follow(usize),
+    /// Follows no redirect.
+    None,
+    /// Follows redirects that stay on the host of the capture URL, up to
+    /// ten of them.
+    FollowSameHost,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Follow(DEFAULT_MAX_HOPS)
+    }
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl RedirectPolicy {
+    pub(crate) fn to_reqwest(self) -> Policy {
+        match self {
+            RedirectPolicy::Follow(max_hops) => Policy::limited(max_hops),
+            RedirectPolicy::None => Policy::none(),
+            RedirectPolicy::FollowSameHost => Policy::custom(|attempt| {
+                let origin = attempt.previous().first().and_then(|url| url.host_str());
+                if attempt.previous().len() > DEFAULT_MAX_HOPS {
+                    attempt.error("too many redirects")
+                } else if attempt.url().host_str() == origin {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            }),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::mock_server::serve_once;
+    use crate::{Capture, CaptureError, CaptureOptions, ErrorKind, RequestType};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    fn capture(policy: Option<RedirectPolicy>) -> Capture {
+        let mut options = CaptureOptions::new();
+        if let Some(policy) = policy {
+            options = options.with_redirect_policy(policy);
+        }
+        Capture::with_options("test_key".to_string(), "test_secret".to_string(), options)
+    }
+
+    async fn redirect_to(location: &str) -> String {
+        serve_once("302 Found", &[("location", location)], Vec::new()).await
+    }
+
+    async fn fetch(capture: &Capture, url: &str) -> crate::Result<Vec<u8>> {
+        let settings = capture.fetch_settings(None);
+        let (bytes, _) = capture
+            .fetch_bytes_with_info(url, &settings, RequestType::Image)
+            .await?;
+        Ok(bytes)
+    }
+
+    async fn png_server() -> String {
+        serve_once("200 OK", &[("content-type", "image/png")], PNG.to_vec()).await
+    }
+
+    #[tokio::test]
+    async fn test_follow_is_the_default() {
+        for capture in [capture(None), capture(Some(RedirectPolicy::Follow(1)))] {
+            let url = redirect_to(&png_server().await).await;
+            assert_eq!(fetch(&capture, &url).await.unwrap(), PNG);
+        }
+
+        let url = redirect_to(&redirect_to(&png_server().await).await).await;
+        let error = fetch(&capture(Some(RedirectPolicy::Follow(1))), &url)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, CaptureError::HttpError(ref error) if error.is_redirect()));
+    }
+
+    #[tokio::test]
+    async fn test_none_reports_the_location() {
+        let target = png_server().await;
+        let url = redirect_to(&target).await;
+        let error = fetch(&capture(Some(RedirectPolicy::None)), &url)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            &error,
+            CaptureError::RedirectNotFollowed { status: 302, location: Some(location) }
+                if *location == target
+        ));
+        assert_eq!(error.kind(), ErrorKind::Config);
+    }
+
+    #[tokio::test]
+    async fn test_follow_same_host_stops_at_other_hosts() {
+        let capture = capture(Some(RedirectPolicy::FollowSameHost));
+
+        let url = redirect_to(&png_server().await).await;
+        assert_eq!(fetch(&capture, &url).await.unwrap(), PNG);
+
+        let url = redirect_to("http://elsewhere.invalid/object.png").await;
+        let error = fetch(&capture, &url).await.unwrap_err();
+        assert!(matches!(
+            &error,
+            CaptureError::RedirectNotFollowed { location: Some(location), .. }
+                if location == "http://elsewhere.invalid/object.png"
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::signing::RequestType;
+#[cfg(feature = "http")]
+use crate::Provenance;
+use crate::{Capture, ContentResponse, MetadataResponse, RequestOptions, Result};
+
+/// A single capture call, described independently of the client that runs it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureRequest {
+    pub request_type: RequestType,
+    pub url: String,
+    pub options: RequestOptions,
+}
+
+impl CaptureRequest {
+    pub fn new(request_type: RequestType, url: impl Into<String>) -> Self {
+        Self {
+            request_type,
+            url: url.into(),
+            options: RequestOptions::new(),
+        }
+    }
+
+    pub fn image(url: impl Into<String>) -> Self {
+        Self::new(RequestType::Image, url)
+    }
+
+    pub fn pdf(url: impl Into<String>) -> Self {
+        Self::new(RequestType::Pdf, url)
+    }
+
+    pub fn content(url: impl Into<String>) -> Self {
+        Self::new(RequestType::Content, url)
+    }
+
+    pub fn metadata(url: impl Into<String>) -> Self {
+        Self::new(RequestType::Metadata, url)
+    }
+
+    pub fn animated(url: impl Into<String>) -> Self {
+        Self::new(RequestType::Animated, url)
+    }
+
+    pub fn with_options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// The result of a [`CaptureRequest`], shaped by its request type.
+#[derive(Debug)]
+pub enum CaptureResponse {
+    /// Image, PDF or animated capture bytes.
+    Bytes(Vec<u8>),
+    Content(ContentResponse),
+    Metadata(MetadataResponse),
+}
+
+#[cfg(feature = "http")]
+impl CaptureResponse {
+    fn decode(request: &CaptureRequest, bytes: Vec<u8>) -> Result<Self> {
+        Ok(match request.request_type {
+            RequestType::Image | RequestType::Pdf | RequestType::Animated => Self::Bytes(bytes),
+            RequestType::Content => {
+                Self::Content(ContentResponse::from_slice(&bytes, &request.url)?)
+            }
+            RequestType::Metadata => Self::Metadata(MetadataResponse::from_slice(&bytes)?),
+        })
+    }
+}
+
+impl Capture {
+    pub fn build_request_url(&self, request: &CaptureRequest) -> Result<String> {
+        self.build_url(request.request_type, &request.url, Some(&request.options))
+    }
+}
+
+#[cfg(feature = "http")]
+impl Capture {
+    /// Runs `request` with the matching `fetch_*` method.
+    pub async fn execute(&self, request: &CaptureRequest) -> Result<CaptureResponse> {
+        let url = request.url.as_str();
+        let options = Some(&request.options);
+
+        Ok(match request.request_type {
+            RequestType::Image => CaptureResponse::Bytes(self.fetch_image(url, options).await?),
+            RequestType::Pdf => CaptureResponse::Bytes(self.fetch_pdf(url, options).await?),
+            RequestType::Animated => {
+                CaptureResponse::Bytes(self.fetch_animated(url, options).await?)
+            }
+            RequestType::Content => {
+                CaptureResponse::Content(self.fetch_content(url, options).await?)
+            }
+            RequestType::Metadata => {
+                CaptureResponse::Metadata(self.fetch_metadata(url, options).await?)
+            }
+        })
+    }
+
+    /// Like [`execute`](Self::execute), also returning the
+    /// [`Provenance`] of the response for audit logs.
+    pub async fn execute_with_provenance(
+        &self,
+        request: &CaptureRequest,
+    ) -> Result<(CaptureResponse, Provenance)> {
+        let settings = self.fetch_settings(None);
+        let capture_url = self.build_url_at(
+            settings.endpoint,
+            request.request_type,
+            &request.url,
+            Some(&request.options),
+        )?;
+        let (bytes, info) = self
+            .fetch_bytes_with_info(&capture_url, &settings, request.request_type)
+            .await?;
+        let provenance = Provenance::new(request, self.base_url(settings.endpoint), &info, &bytes);
+        Ok((CaptureResponse::decode(request, bytes)?, provenance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_request_url_matches_typed_builder() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let mut options = RequestOptions::new();
+        options.insert("full".to_string(), serde_json::Value::Bool(true));
+
+        let request = CaptureRequest::pdf("https://example.com").with_options(options.clone());
+        assert_eq!(
+            capture.build_request_url(&request).unwrap(),
+            capture
+                .build_pdf_url("https://example.com", Some(&options))
+                .unwrap()
+        );
+    }
+}
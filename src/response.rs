@@ -0,0 +1,698 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Page content returned by the content endpoint. Representations left out
+/// by [`ContentOptions::format`](crate::ContentOptions::format) are empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentResponse {
+    pub success: bool,
+    #[serde(default)]
+    pub html: String,
+    #[serde(default, rename = "textContent")]
+    pub text_content: String,
+    #[serde(default)]
+    pub markdown: String,
+    /// Why the capture failed, alongside `"success": false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Console and network messages, present when `diagnostics` was set.
+    #[serde(default)]
+    pub diagnostics: Vec<DiagnosticEntry>,
+    /// Fields this version of the crate does not know, kept so that they
+    /// survive a serialization round trip.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+    /// The main content of `html`, without navigation, footers, cookie
+    /// notices and similar boilerplate. Extracted on the client; `None` when
+    /// nothing readable was found.
+    #[cfg(feature = "readability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "readability")))]
+    #[serde(skip)]
+    pub reader_html: Option<String>,
+    /// `reader_html` as markdown.
+    #[cfg(feature = "readability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "readability")))]
+    #[serde(skip)]
+    pub reader_markdown: Option<String>,
+    /// Outbound links of `html`, resolved against the captured URL. Read on
+    /// the client; `None` when the response holds no HTML.
+    #[cfg(feature = "links")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "links")))]
+    #[serde(skip)]
+    pub links: Option<Vec<crate::Link>>,
+}
+
+impl ContentResponse {
+    /// Reads a content endpoint response body for the page at `page_url`,
+    /// filling the reader view and links when their features are enabled.
+    /// A body reporting `"success": false` is a
+    /// [`CaptureError::ApiFailure`](crate::CaptureError::ApiFailure).
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    pub(crate) fn from_slice(body: &[u8], page_url: &str) -> crate::Result<Self> {
+        #[allow(unused_mut)]
+        let mut content: Self = serde_json::from_slice(body)?;
+        check_success(content.success, &content.error)?;
+        #[cfg(feature = "readability")]
+        if let Some(reader) = crate::readability::extract(&content.html) {
+            content.reader_html = Some(reader.html);
+            content.reader_markdown = Some(reader.markdown);
+        }
+        #[cfg(feature = "links")]
+        if !content.html.is_empty() {
+            content.links = Some(crate::links::extract(&content.html, page_url));
+        }
+        #[cfg(not(feature = "links"))]
+        let _ = page_url;
+        Ok(content)
+    }
+
+    /// Words in `text_content`. Text is split at whitespace, and each Han,
+    /// Hiragana or Katakana character counts as a word of its own, since
+    /// those scripts do not separate words with spaces. Tokens without any
+    /// letter or digit, such as a lone dash, are not words.
+    pub fn word_count(&self) -> usize {
+        self.text_content.split_whitespace().map(token_words).sum()
+    }
+
+    /// Time to read `text_content` at `words_per_minute`, rounded up to a
+    /// whole second. Zero for an empty page or a zero rate. CJK characters
+    /// count as words, so use a characters-per-minute rate for CJK pages.
+    pub fn reading_time(&self, words_per_minute: u32) -> Duration {
+        if words_per_minute == 0 {
+            return Duration::ZERO;
+        }
+        let seconds = (self.word_count() as u64 * 60).div_ceil(u64::from(words_per_minute));
+        Duration::from_secs(seconds)
+    }
+
+    /// Text of the first heading in `markdown`, skipping fenced code
+    /// blocks.
+    pub fn title_guess(&self) -> Option<&str> {
+        let mut in_fence = false;
+        for line in self.markdown.lines() {
+            let line = line.trim();
+            if line.starts_with("```") || line.starts_with("~~~") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+            let text = line.trim_start_matches('#');
+            let level = line.len() - text.len();
+            if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with(' ')) {
+                continue;
+            }
+            let title = text.trim().trim_end_matches('#').trim_end();
+            if !title.is_empty() {
+                return Some(title);
+            }
+        }
+        None
+    }
+}
+
+fn token_words(token: &str) -> usize {
+    let mut words = 0;
+    let mut in_word = false;
+    for character in token.chars() {
+        if is_cjk(character) {
+            words += 1;
+            in_word = false;
+        } else if character.is_alphanumeric() && !in_word {
+            words += 1;
+            in_word = true;
+        }
+    }
+    words
+}
+
+/// Han, Hiragana and Katakana, written without spaces between words.
+fn is_cjk(character: char) -> bool {
+    matches!(
+        character,
+        '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{20000}'..='\u{2fa1f}'
+    )
+}
+
+fn check_success(success: bool, error: &Option<String>) -> crate::Result<()> {
+    if success {
+        return Ok(());
+    }
+    Err(crate::CaptureError::ApiFailure {
+        message: error
+            .clone()
+            .unwrap_or_else(|| "no error message".to_string()),
+    })
+}
+
+/// A console or network message recorded while the page loaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticEntry {
+    /// Severity such as `error` or `warning`.
+    #[serde(default)]
+    pub level: String,
+    /// Where the entry came from, such as `console` or `network`.
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataResponse {
+    pub success: bool,
+    #[serde(default)]
+    pub metadata: PageMetadata,
+    /// The page's `<script type="application/ld+json">` blocks, when
+    /// requested with
+    /// [`MetadataOptions::include_structured_data`](crate::MetadataOptions::include_structured_data).
+    /// Blocks sent as JSON text are parsed; ones that are not valid JSON
+    /// are left out.
+    #[serde(
+        default,
+        rename = "structuredData",
+        deserialize_with = "structured_data",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub structured_data: Option<Vec<serde_json::Value>>,
+    /// Why the capture failed, alongside `"success": false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Fields this version of the crate does not know, kept so that they
+    /// survive a serialization round trip.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl MetadataResponse {
+    /// Reads a metadata endpoint response body. A body reporting
+    /// `"success": false` is a
+    /// [`CaptureError::ApiFailure`](crate::CaptureError::ApiFailure).
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    pub(crate) fn from_slice(body: &[u8]) -> crate::Result<Self> {
+        let mut metadata: Self = serde_json::from_slice(body)?;
+        check_success(metadata.success, &metadata.error)?;
+        metadata.lift_structured_data();
+        Ok(metadata)
+    }
+
+    /// Moves JSON-LD reported inside the metadata map to `structured_data`.
+    fn lift_structured_data(&mut self) {
+        for key in ["structuredData", "jsonLd"] {
+            if let Some(blocks) = self.metadata.extra.remove(key) {
+                self.structured_data
+                    .get_or_insert_with(Vec::new)
+                    .extend(structured_blocks(blocks));
+            }
+        }
+    }
+
+    /// Rendered page height in pixels, when the metadata endpoint reports it.
+    pub fn content_height(&self) -> Option<u32> {
+        self.dimension("contentHeight")
+    }
+
+    /// Rendered page width in pixels, when the metadata endpoint reports it.
+    pub fn content_width(&self) -> Option<u32> {
+        self.dimension("contentWidth")
+    }
+
+    fn dimension(&self, key: &str) -> Option<u32> {
+        self.metadata
+            .extra
+            .get(key)
+            .and_then(|value| value.as_u64())
+            .and_then(|value| u32::try_from(value).ok())
+    }
+}
+
+fn structured_data<'de, D>(deserializer: D) -> Result<Option<Vec<serde_json::Value>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let blocks = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(blocks.map(structured_blocks))
+}
+
+/// JSON-LD blocks from a single block or an array of them, where each block
+/// is either JSON or the text of a script element.
+fn structured_blocks(blocks: serde_json::Value) -> Vec<serde_json::Value> {
+    let blocks = match blocks {
+        serde_json::Value::Array(blocks) => blocks,
+        serde_json::Value::Null => Vec::new(),
+        block => vec![block],
+    };
+    blocks
+        .into_iter()
+        .filter_map(|block| match block {
+            serde_json::Value::String(text) => serde_json::from_str(&text).ok(),
+            serde_json::Value::Null => None,
+            block => Some(block),
+        })
+        .collect()
+}
+
+/// Metadata of a page, as reported by the metadata endpoint. Every field is
+/// optional; keys without a field here, such as `contentHeight`, are kept in
+/// `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Canonical URL of the page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Preview image, e.g. from `og:image`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Site icon, reported as `logo`.
+    #[serde(default, rename = "logo", skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Publication date, reported as `date`, usually in RFC 3339 format.
+    #[serde(default, rename = "date", skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl PageMetadata {
+    /// `published_at` parsed as an RFC 3339 date, `None` when missing or in
+    /// another format.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn published_at_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        chrono::DateTime::parse_from_rfc3339(self.published_at.as_deref()?).ok()
+    }
+}
+
+/// Response details returned alongside fetched bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchInfo {
+    pub status: u16,
+    pub content_type: Option<String>,
+    /// Upstream `Content-Disposition`, e.g. when `file_name` was set.
+    pub content_disposition: Option<String>,
+    /// `Location` of a redirect response.
+    pub location: Option<String>,
+    /// `ETag`, used to revalidate a [`DiskCache`](crate::DiskCache) entry.
+    pub etag: Option<String>,
+    /// `Cache-Control`, whose `max-age` bounds how long a
+    /// [`DiskCache`](crate::DiskCache) keeps the response.
+    pub cache_control: Option<String>,
+    /// Diagnostics from the `x-capture-diagnostics` header, present when
+    /// `diagnostics` was set. Empty when absent or unreadable.
+    pub diagnostics: Vec<DiagnosticEntry>,
+    /// Labels from the call's [`FetchOverrides`](crate::FetchOverrides).
+    pub labels: BTreeMap<String, String>,
+    /// Time spent waiting for a request slot under
+    /// `max_concurrent_requests`.
+    pub queue_time: Duration,
+}
+
+#[cfg(feature = "http")]
+impl FetchInfo {
+    pub(crate) fn from_response(response: &reqwest::Response) -> Self {
+        Self::from_parts(response.status(), response.headers())
+    }
+
+    /// Reads the details shared by async and blocking responses.
+    pub(crate) fn from_parts(
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+
+        Self {
+            status: status.as_u16(),
+            content_type: header(reqwest::header::CONTENT_TYPE),
+            content_disposition: header(reqwest::header::CONTENT_DISPOSITION),
+            location: header(reqwest::header::LOCATION),
+            etag: header(reqwest::header::ETAG),
+            cache_control: header(reqwest::header::CACHE_CONTROL),
+            diagnostics: header(reqwest::header::HeaderName::from_static(DIAGNOSTICS_HEADER))
+                .map(|value| parse_diagnostics(&value))
+                .unwrap_or_default(),
+            labels: BTreeMap::new(),
+            queue_time: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+const DIAGNOSTICS_HEADER: &str = "x-capture-diagnostics";
+
+/// Reads a JSON array of diagnostic entries, tolerating malformed input.
+#[cfg(feature = "http")]
+fn parse_diagnostics(value: &str) -> Vec<DiagnosticEntry> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+/// Light and dark renders of the same page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenshotPair<T = Vec<u8>> {
+    pub light: T,
+    pub dark: T,
+}
+
+/// Light and dark screenshots returned by `fetch_screenshot_themes`.
+pub type ThemedScreenshots = ScreenshotPair<Vec<u8>>;
+
+/// Screenshot options from `plan_full_page_capture`.
+#[derive(Debug)]
+pub struct FullPagePlan {
+    pub options: crate::ScreenshotOptions,
+    /// The metadata request's error when the plan fell back to defaults
+    /// because of it. A page that reports no usable height also gets the
+    /// defaults, without a warning.
+    pub warning: Option<crate::CaptureError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content_fixture(diagnostics: Option<serde_json::Value>) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "success": true,
+            "html": "<main></main>",
+            "textContent": "",
+            "markdown": "",
+        });
+        if let Some(diagnostics) = diagnostics {
+            body["diagnostics"] = diagnostics;
+        }
+        body
+    }
+
+    #[test]
+    fn test_content_diagnostics_with_console_errors() {
+        let body = content_fixture(Some(serde_json::json!([
+            {
+                "level": "error",
+                "source": "console",
+                "message": "Uncaught TypeError: app is undefined"
+            },
+            { "level": "warning", "source": "network", "message": "404 /app.js" }
+        ])));
+
+        let content: ContentResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(content.diagnostics.len(), 2);
+        assert_eq!(content.diagnostics[0].level, "error");
+        assert_eq!(content.diagnostics[1].source, "network");
+    }
+
+    #[test]
+    fn test_content_diagnostics_empty_or_missing() {
+        for body in [
+            content_fixture(Some(serde_json::json!([]))),
+            content_fixture(None),
+        ] {
+            let content: ContentResponse = serde_json::from_value(body).unwrap();
+            assert!(content.diagnostics.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_content_with_partial_representations() {
+        let content: ContentResponse = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "markdown": "# Title",
+        }))
+        .unwrap();
+        assert_eq!(content.markdown, "# Title");
+        assert!(content.html.is_empty() && content.text_content.is_empty());
+
+        let content: ContentResponse =
+            serde_json::from_value(serde_json::json!({ "success": true })).unwrap();
+        assert!(content.markdown.is_empty());
+    }
+
+    #[cfg(feature = "readability")]
+    #[test]
+    fn test_reader_view_is_filled() {
+        let body = serde_json::json!({
+            "success": true,
+            "html": "<nav>Home</nav><main><h1>Title</h1><p>Body text.</p></main>",
+        });
+        let content =
+            ContentResponse::from_slice(body.to_string().as_bytes(), "https://example.com")
+                .unwrap();
+        assert_eq!(
+            content.reader_markdown.as_deref(),
+            Some("# Title\n\nBody text.")
+        );
+        assert!(content.reader_html.unwrap().starts_with("<main>"));
+
+        let body = serde_json::json!({ "success": true, "html": "<nav>Home</nav>" });
+        let content =
+            ContentResponse::from_slice(body.to_string().as_bytes(), "https://example.com")
+                .unwrap();
+        assert!(content.reader_html.is_none() && content.reader_markdown.is_none());
+    }
+
+    #[cfg(feature = "links")]
+    #[test]
+    fn test_links_are_filled() {
+        let body = serde_json::json!({
+            "success": true,
+            "html": r#"<p><a href="/next">Next</a></p>"#,
+        });
+        let content =
+            ContentResponse::from_slice(body.to_string().as_bytes(), "https://example.com/a/b")
+                .unwrap();
+        let links = content.links.unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].href, "https://example.com/next");
+
+        let body = serde_json::json!({ "success": true, "markdown": "[Next](/next)" });
+        let content =
+            ContentResponse::from_slice(body.to_string().as_bytes(), "https://example.com")
+                .unwrap();
+        assert!(content.links.is_none());
+    }
+
+    #[test]
+    fn test_responses_round_trip_with_unknown_fields() {
+        let mut body = content_fixture(Some(serde_json::json!([
+            { "level": "error", "source": "console", "message": "boom" }
+        ])));
+        body["screenshotUrl"] = "https://cdn.example/shot.png".into();
+        body["timings"] = serde_json::json!({ "load": 812 });
+        let content: ContentResponse = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(content.extra.len(), 2);
+
+        let serialized = serde_json::to_value(&content).unwrap();
+        assert_eq!(serialized, body);
+        let again: ContentResponse = serde_json::from_value(serialized).unwrap();
+        assert_eq!(again, content);
+
+        let body = serde_json::json!({
+            "success": false,
+            "metadata": { "title": "Example", "contentHeight": 5200 },
+            "error": "Navigation timeout",
+            "requestId": "abc123",
+        });
+        let metadata: MetadataResponse = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(metadata.extra["requestId"], "abc123");
+
+        let serialized = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(serialized, body);
+        let again: MetadataResponse = serde_json::from_value(serialized).unwrap();
+        assert_eq!(again, metadata);
+    }
+
+    fn text(text_content: &str, markdown: &str) -> ContentResponse {
+        serde_json::from_value(serde_json::json!({
+            "success": true,
+            "textContent": text_content,
+            "markdown": markdown,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_word_count_and_reading_time() {
+        let english = text("The quick brown fox — it's fast!\n\nJumps over 2 dogs.", "");
+        assert_eq!(english.word_count(), 10);
+        assert_eq!(english.reading_time(200), Duration::from_secs(3));
+
+        let german = text("Die Straßenbahn fährt um 8:15 Uhr über die Brücke.", "");
+        assert_eq!(german.word_count(), 9);
+
+        // Japanese and Chinese count one word per character; Latin runs
+        // inside count once.
+        let japanese = text("東京タワーは2024年も人気です。", "");
+        assert_eq!(japanese.word_count(), 13);
+        let chinese = text("我喜欢用 Rust 写代码", "");
+        assert_eq!(chinese.word_count(), 8);
+
+        let words = vec!["word"; 450].join(" ");
+        assert_eq!(text(&words, "").reading_time(200), Duration::from_secs(135));
+        assert_eq!(text("", "").reading_time(200), Duration::ZERO);
+        assert_eq!(english.reading_time(0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_title_guess() {
+        let content = text(
+            "",
+            "Intro text\n\n```sh\n# not a heading\n```\n\n##   Getting Started ##\n\n# Later",
+        );
+        assert_eq!(content.title_guess(), Some("Getting Started"));
+        assert_eq!(text("", "#hashtag\n#\n").title_guess(), None);
+        assert_eq!(text("", "").title_guess(), None);
+    }
+
+    #[test]
+    fn test_structured_data_is_lifted_out_of_the_metadata_map() {
+        let body = serde_json::json!({
+            "success": true,
+            "metadata": {
+                "title": "Trail shoe",
+                "jsonLd": r#"{"@context":"https://schema.org","@type":"Product","name":"Trail shoe"}"#,
+            }
+        });
+        let metadata = MetadataResponse::from_slice(body.to_string().as_bytes()).unwrap();
+        assert_eq!(
+            metadata.structured_data,
+            Some(vec![serde_json::json!({
+                "@context": "https://schema.org",
+                "@type": "Product",
+                "name": "Trail shoe",
+            })])
+        );
+        assert!(metadata.metadata.extra.is_empty());
+
+        let body = serde_json::json!({ "success": true, "metadata": { "title": "Plain" } });
+        let metadata = MetadataResponse::from_slice(body.to_string().as_bytes()).unwrap();
+        assert!(metadata.structured_data.is_none());
+    }
+
+    #[test]
+    fn test_structured_data_skips_invalid_blocks() {
+        let body = serde_json::json!({
+            "success": true,
+            "metadata": {},
+            "structuredData": [
+                { "@type": "Article", "headline": "Launch week" },
+                r#"{"@type": "BreadcrumbList", "itemListElement": []}"#,
+                r#"{"@type": "Organization", "name": "#,
+                null,
+                [{ "@type": "Person" }, { "@type": "WebSite" }],
+            ]
+        });
+        let metadata = MetadataResponse::from_slice(body.to_string().as_bytes()).unwrap();
+        let types: Vec<_> = metadata
+            .structured_data
+            .as_deref()
+            .unwrap()
+            .iter()
+            .map(|block| {
+                block
+                    .get("@type")
+                    .map_or("graph", |kind| kind.as_str().unwrap())
+            })
+            .collect();
+        assert_eq!(types, ["Article", "BreadcrumbList", "graph"]);
+
+        let saved = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(saved["structuredData"].as_array().unwrap().len(), 3);
+        let reloaded: MetadataResponse = serde_json::from_value(saved).unwrap();
+        assert_eq!(reloaded, metadata);
+    }
+
+    #[test]
+    fn test_typed_page_metadata() {
+        let metadata: MetadataResponse = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "metadata": {
+                "title": "Rust 1.82 is out",
+                "description": "Release notes for Rust 1.82.",
+                "url": "https://blog.rust-lang.org/2024/10/17/Rust-1.82.0.html",
+                "image": "https://blog.rust-lang.org/images/rust-social.jpg",
+                "logo": "https://blog.rust-lang.org/images/favicon.ico",
+                "author": "The Rust Release Team",
+                "date": "2024-10-17T00:00:00.000Z",
+                "publisher": "Rust Blog",
+                "lang": "en",
+                "contentHeight": 4200,
+            }
+        }))
+        .unwrap();
+        let page = &metadata.metadata;
+        assert_eq!(page.title.as_deref(), Some("Rust 1.82 is out"));
+        assert_eq!(page.author.as_deref(), Some("The Rust Release Team"));
+        assert_eq!(
+            page.favicon.as_deref(),
+            Some("https://blog.rust-lang.org/images/favicon.ico")
+        );
+        assert_eq!(
+            page.published_at.as_deref(),
+            Some("2024-10-17T00:00:00.000Z")
+        );
+        assert_eq!(page.extra.len(), 3);
+        assert_eq!(page.extra["publisher"], "Rust Blog");
+        assert_eq!(metadata.content_height(), Some(4200));
+
+        let minimal: MetadataResponse = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "metadata": { "title": "Example", "author": null }
+        }))
+        .unwrap();
+        assert_eq!(
+            minimal.metadata,
+            PageMetadata {
+                title: Some("Example".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&minimal.metadata).unwrap(),
+            serde_json::json!({ "title": "Example" })
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_published_at_datetime() {
+        let mut page = PageMetadata {
+            published_at: Some("2024-10-17T09:30:00+02:00".to_string()),
+            ..Default::default()
+        };
+        let published = page.published_at_datetime().unwrap();
+        assert_eq!(published.timestamp(), 1_729_150_200);
+
+        page.published_at = Some("October 17, 2024".to_string());
+        assert!(page.published_at_datetime().is_none());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_diagnostics_header_is_tolerant() {
+        let entries = parse_diagnostics(r#"[{"level":"error","message":"boom"}]"#);
+        assert_eq!(
+            entries,
+            vec![DiagnosticEntry {
+                level: "error".to_string(),
+                source: String::new(),
+                message: "boom".to_string(),
+            }]
+        );
+        assert!(parse_diagnostics("not json").is_empty());
+    }
+}
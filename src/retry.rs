@@ -0,0 +1,195 @@
+#[cfg(feature = "http")]
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "http")]
+use crate::rt;
+#[cfg(feature = "http")]
+use crate::Result;
+use crate::{CaptureError, ErrorKind};
+
+/// Longest backoff, as a power of two of `backoff`.
+#[cfg(feature = "http")]
+const MAX_BACKOFF_DOUBLINGS: u32 = 5;
+
+/// Which failed fetches are retried, and how often.
+///
+/// By default transport errors, 5xx responses and 429s are retried up to
+/// three attempts in total. Failures of the target site are not, since
+/// asking again rarely helps; add [`ErrorKind::TargetSite`] with
+/// [`retry_on`](Self::retry_on) to retry them too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles for each further retry.
+    pub backoff: Duration,
+    pub retry_on: Vec<ErrorKind>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(500),
+            retry_on: vec![
+                ErrorKind::Transport,
+                ErrorKind::Server,
+                ErrorKind::RateLimited,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Also retries errors of `kind`.
+    pub fn retry_on(mut self, kind: ErrorKind) -> Self {
+        if !self.retry_on.contains(&kind) {
+            self.retry_on.push(kind);
+        }
+        self
+    }
+
+    /// Whether `error`, returned by attempt number `attempt` (from 1),
+    /// should be retried.
+    pub fn should_retry(&self, error: &CaptureError, attempt: u32) -> bool {
+        attempt < self.max_attempts && self.retry_on.contains(&error.kind())
+    }
+
+    #[cfg(feature = "http")]
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff * 2u32.pow((attempt - 1).min(MAX_BACKOFF_DOUBLINGS))
+    }
+}
+
+/// Runs `operation` until it succeeds or `policy` gives up on its error.
+/// Without a policy it runs once.
+#[cfg(feature = "http")]
+pub(crate) async fn run<T, F, Fut>(policy: Option<&RetryPolicy>, operation: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Err(error) if policy.is_some_and(|policy| policy.should_retry(&error, attempt)) => {
+                if let Some(policy) = policy {
+                    rt::sleep(policy.delay(attempt)).await;
+                }
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// [`run`] for blocking operations, sleeping the current thread between
+/// attempts.
+#[cfg(feature = "blocking")]
+pub(crate) fn run_blocking<T>(
+    policy: Option<&RetryPolicy>,
+    operation: impl Fn() -> Result<T>,
+) -> Result<T> {
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(error) if policy.is_some_and(|policy| policy.should_retry(&error, attempt)) => {
+                if let Some(policy) = policy {
+                    std::thread::sleep(policy.delay(attempt));
+                }
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::time::Instant;
+
+    fn target_error() -> CaptureError {
+        CaptureError::TargetSiteError {
+            target_status: Some(503),
+            message: "target unavailable".to_string(),
+        }
+    }
+
+    fn server_error() -> CaptureError {
+        CaptureError::RequestFailed {
+            status: 502,
+            message: "bad gateway".to_string(),
+        }
+    }
+
+    async fn attempts(policy: Option<&RetryPolicy>, error: fn() -> CaptureError) -> u32 {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = run(policy, || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move { Err(error()) }
+        })
+        .await;
+        assert!(result.is_err());
+        calls.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_target_site_errors_are_not_retried_by_default() {
+        let policy = RetryPolicy::new();
+
+        assert_eq!(attempts(Some(&policy), target_error).await, 1);
+        assert_eq!(attempts(Some(&policy), server_error).await, 3);
+        assert_eq!(attempts(None, server_error).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_on_opts_into_target_site_retries() {
+        let policy = RetryPolicy::new()
+            .with_max_attempts(4)
+            .with_backoff(Duration::from_secs(1))
+            .retry_on(ErrorKind::TargetSite);
+
+        let started = Instant::now();
+        assert_eq!(attempts(Some(&policy), target_error).await, 4);
+        // 1s + 2s + 4s of backoff.
+        assert_eq!(started.elapsed(), Duration::from_secs(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_success_stops_retrying() {
+        let policy = RetryPolicy::new();
+        let calls = AtomicU32::new(0);
+
+        let result = run(Some(&policy), || {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    Err(server_error())
+                } else {
+                    Ok(call)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
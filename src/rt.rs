@@ -0,0 +1,230 @@
+//! The crate's runtime touchpoints: sleeping, spawning, file IO and network
+//! futures.
+//!
+//! With `runtime-tokio` (the default) they run on the caller's Tokio
+//! runtime, which is also the fallback when neither runtime feature is
+//! enabled. With only `runtime-agnostic` they run on a small background
+//! Tokio runtime owned by the crate, so the public futures can be polled by
+//! any executor, such as async-std or smol.
+//!
+//! On wasm32 neither applies: timers and spawned tasks use the browser's
+//! event loop, there is no file IO, and the clocks come from `web-time`, as
+//! the `std::time` ones panic there.
+//!
+//! Without the `http` feature only the clocks are left.
+
+#[cfg(feature = "http")]
+use std::future::Future;
+#[cfg(feature = "http")]
+use std::path::Path;
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) use std::time::Instant;
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+pub(crate) use web_time::Instant;
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) async fn sleep(duration: Duration) {
+    compat(async move { tokio::time::sleep(duration).await }).await
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) async fn write_file(path: &Path, bytes: Vec<u8>) -> std::io::Result<()> {
+    compat(tokio::fs::write(path.to_path_buf(), bytes)).await
+}
+
+/// Runs `io`, a sequence of blocking `std::fs` calls, on Tokio's blocking
+/// pool instead of an executor thread.
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) async fn blocking_io<T, F>(io: F) -> std::io::Result<T>
+where
+    F: FnOnce() -> std::io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    compat(async move {
+        tokio::task::spawn_blocking(io)
+            .await
+            .map_err(std::io::Error::other)?
+    })
+    .await
+}
+
+/// A sibling of `path` to write before renaming it over `path`. Unique per
+/// call, so concurrent writers of one file, in this process or another,
+/// never share a temporary file.
+#[cfg(feature = "http")]
+pub(crate) fn temporary_path(path: &std::path::Path) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    // There are no processes on wasm32, and `process::id` panics there.
+    #[cfg(not(target_arch = "wasm32"))]
+    let process = std::process::id();
+    #[cfg(target_arch = "wasm32")]
+    let process = 0;
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(
+        ".{process}.{}.tmp",
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    temporary.into()
+}
+
+#[cfg(all(
+    feature = "http",
+    not(target_arch = "wasm32"),
+    not(all(feature = "runtime-agnostic", not(feature = "runtime-tokio")))
+))]
+pub(crate) use tokio_runtime::{compat, spawn};
+
+#[cfg(all(
+    feature = "http",
+    not(target_arch = "wasm32"),
+    feature = "runtime-agnostic",
+    not(feature = "runtime-tokio")
+))]
+pub(crate) use background_runtime::{compat, spawn};
+
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+pub(crate) use wasm_runtime::{blocking_io, compat, sleep, spawn, write_file};
+
+#[cfg(all(
+    feature = "http",
+    not(target_arch = "wasm32"),
+    not(all(feature = "runtime-agnostic", not(feature = "runtime-tokio")))
+))]
+mod tokio_runtime {
+    use super::*;
+
+    /// Runs `future` as is; it is polled within the caller's runtime.
+    pub(crate) fn compat<F: Future>(future: F) -> F {
+        future
+    }
+
+    /// Spawns `future` onto the current Tokio runtime. Returns `false`
+    /// outside one, where there is nothing to spawn onto.
+    pub(crate) fn spawn<F>(future: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(future);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "http",
+    not(target_arch = "wasm32"),
+    feature = "runtime-agnostic",
+    not(feature = "runtime-tokio")
+))]
+mod background_runtime {
+    use super::*;
+    use std::pin::Pin;
+    use std::sync::OnceLock;
+    use std::task::{Context, Poll};
+
+    use tokio::runtime::Runtime;
+
+    fn runtime() -> &'static Runtime {
+        static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+        RUNTIME.get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("capture-rust")
+                .enable_all()
+                .build()
+                .expect("failed to start the capture-rust background runtime")
+        })
+    }
+
+    /// Polls, and drops, the wrapped future within the background runtime,
+    /// so Tokio-based IO and timers work under any executor.
+    pub(crate) struct Compat<F> {
+        inner: Option<Pin<Box<F>>>,
+    }
+
+    impl<F: Future> Future for Compat<F> {
+        type Output = F::Output;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+            let _guard = runtime().enter();
+            let inner = self.inner.as_mut().expect("only taken on drop");
+            inner.as_mut().poll(cx)
+        }
+    }
+
+    impl<F> Drop for Compat<F> {
+        fn drop(&mut self) {
+            let _guard = runtime().enter();
+            self.inner = None;
+        }
+    }
+
+    pub(crate) fn compat<F: Future>(future: F) -> Compat<F> {
+        Compat {
+            inner: Some(Box::pin(future)),
+        }
+    }
+
+    /// Spawns `future` onto the background runtime. Always succeeds.
+    pub(crate) fn spawn<F>(future: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        runtime().spawn(future);
+        true
+    }
+}
+
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+mod wasm_runtime {
+    use super::*;
+
+    /// Runs `future` as is; reqwest's wasm futures need no runtime.
+    pub(crate) fn compat<F: Future>(future: F) -> F {
+        future
+    }
+
+    /// Waits on a browser timer, capped at `u32::MAX` milliseconds.
+    pub(crate) async fn sleep(duration: Duration) {
+        let millis = u32::try_from(duration.as_millis()).unwrap_or(u32::MAX);
+        gloo_timers::future::TimeoutFuture::new(millis).await
+    }
+
+    /// There is no file system to write to.
+    pub(crate) async fn write_file(_path: &Path, _bytes: Vec<u8>) -> std::io::Result<()> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    /// There are no threads to move `io` to, so it runs inline; `std::fs`
+    /// calls fail there by themselves.
+    pub(crate) async fn blocking_io<T, F>(io: F) -> std::io::Result<T>
+    where
+        F: FnOnce() -> std::io::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        io()
+    }
+
+    /// Spawns `future` onto the browser's event loop. Always succeeds.
+    pub(crate) fn spawn<F>(future: F) -> bool
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        wasm_bindgen_futures::spawn_local(future);
+        true
+    }
+}
@@ -0,0 +1,367 @@
+#[cfg(feature = "http")]
+use std::collections::HashSet;
+#[cfg(feature = "http")]
+use std::future::Future;
+#[cfg(feature = "http")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "http")]
+use futures_util::future::join_all;
+
+use crate::error::invalid_option;
+#[cfg(feature = "http")]
+use crate::rt;
+use crate::{Capture, Result, ScreenshotOptions};
+
+/// A region of the page in CSS pixels, measured from the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClipRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// One shot in a screenshot set. Each variant decides the shape of the
+/// capture (`full`, `selector`, crop) and takes everything else from the
+/// set's base options.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ScreenshotVariant {
+    /// The whole scrollable page. Keeps the base `max_height`.
+    FullPage,
+    /// The element matching a CSS selector. Keeps the base
+    /// `selector_padding`.
+    Element(String),
+    /// The visible viewport only.
+    Viewport,
+    /// A fixed region, sent as `clipX`, `clipY`, `clipWidth` and
+    /// `clipHeight`.
+    Clip(ClipRect),
+}
+
+impl ScreenshotVariant {
+    /// Expands `base` into the options for this variant and validates them.
+    pub fn options(&self, base: &ScreenshotOptions) -> Result<ScreenshotOptions> {
+        let mut options = ScreenshotOptions {
+            full: None,
+            max_height: None,
+            selector: None,
+            selector_id: None,
+            selector_padding: None,
+            clip_x: None,
+            clip_y: None,
+            clip_width: None,
+            clip_height: None,
+            ..base.clone()
+        };
+
+        match self {
+            ScreenshotVariant::FullPage => {
+                options.full = Some(true);
+                options.max_height = base.max_height;
+            }
+            ScreenshotVariant::Element(selector) => {
+                if selector.trim().is_empty() {
+                    return Err(invalid_option("selector", "must not be empty"));
+                }
+                options.selector = Some(selector.clone());
+                options.selector_padding = base.selector_padding;
+            }
+            ScreenshotVariant::Viewport => {}
+            ScreenshotVariant::Clip(rect) => {
+                options.clip_x = Some(rect.x);
+                options.clip_y = Some(rect.y);
+                options.clip_width = Some(rect.width);
+                options.clip_height = Some(rect.height);
+            }
+        }
+
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// File name stem used by [`Capture::fetch_screenshot_set_to_dir`].
+    #[cfg(feature = "http")]
+    fn file_stem(&self) -> String {
+        match self {
+            ScreenshotVariant::FullPage => "full-page".to_string(),
+            ScreenshotVariant::Viewport => "viewport".to_string(),
+            ScreenshotVariant::Element(selector) => {
+                let slug: String = selector
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() {
+                            c.to_ascii_lowercase()
+                        } else {
+                            '-'
+                        }
+                    })
+                    .collect();
+                let slug = slug
+                    .split('-')
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("-");
+                format!("element-{slug}")
+            }
+            ScreenshotVariant::Clip(rect) => {
+                format!("clip-{}-{}-{}x{}", rect.x, rect.y, rect.width, rect.height)
+            }
+        }
+    }
+}
+
+impl Capture {
+    /// Builds a screenshot URL per variant of `base`. Each entry fails on
+    /// its own when its expanded options are invalid.
+    pub fn build_screenshot_set_urls(
+        &self,
+        url: &str,
+        base: &ScreenshotOptions,
+        variants: &[ScreenshotVariant],
+    ) -> Vec<(ScreenshotVariant, Result<String>)> {
+        variants
+            .iter()
+            .map(|variant| {
+                let capture_url = variant
+                    .options(base)
+                    .and_then(|options| self.build_screenshot_url(url, Some(&options)));
+                (variant.clone(), capture_url)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "http")]
+impl Capture {
+    /// Fetches a screenshot per variant of `base` concurrently. Results are
+    /// in `variants` order, and a failing variant does not affect the others.
+    pub async fn fetch_screenshot_set(
+        &self,
+        url: &str,
+        base: &ScreenshotOptions,
+        variants: &[ScreenshotVariant],
+    ) -> Vec<(ScreenshotVariant, Result<Vec<u8>>)> {
+        run_set(variants, base, |options| async move {
+            self.fetch_screenshot(url, Some(&options)).await
+        })
+        .await
+    }
+
+    /// Like [`fetch_screenshot_set`](Self::fetch_screenshot_set), but writes
+    /// each screenshot into `dir` as e.g. `full-page.png` or
+    /// `element-main-hero.png` and returns the paths. Variants whose names
+    /// would collide, such as `.hero` and `#hero`, get a `-2`, `-3`, ...
+    /// suffix in `variants` order.
+    pub async fn fetch_screenshot_set_to_dir(
+        &self,
+        url: &str,
+        base: &ScreenshotOptions,
+        variants: &[ScreenshotVariant],
+        dir: impl AsRef<Path>,
+    ) -> Vec<(ScreenshotVariant, Result<PathBuf>)> {
+        let dir = dir.as_ref();
+        let extension = match base.image_type.as_deref() {
+            Some("jpeg") => "jpg",
+            Some("webp") => "webp",
+            _ => "png",
+        };
+
+        let screenshots = self.fetch_screenshot_set(url, base, variants).await;
+        let mut paths = Vec::with_capacity(screenshots.len());
+        for ((variant, screenshot), stem) in screenshots.into_iter().zip(file_stems(variants)) {
+            let path = dir.join(format!("{stem}.{extension}"));
+            let written = match screenshot {
+                Ok(bytes) => rt::write_file(&path, bytes)
+                    .await
+                    .map(|()| path)
+                    .map_err(Into::into),
+                Err(error) => Err(error),
+            };
+            paths.push((variant, written));
+        }
+        paths
+    }
+}
+
+/// File stems for `variants`, suffixed where they would otherwise repeat.
+#[cfg(feature = "http")]
+fn file_stems(variants: &[ScreenshotVariant]) -> Vec<String> {
+    let mut used = HashSet::new();
+    variants
+        .iter()
+        .map(|variant| {
+            let stem = variant.file_stem();
+            let unique = (1..)
+                .map(|n| match n {
+                    1 => stem.clone(),
+                    n => format!("{stem}-{n}"),
+                })
+                .find(|candidate| !used.contains(candidate))
+                .expect("an unused suffix exists");
+            used.insert(unique.clone());
+            unique
+        })
+        .collect()
+}
+
+#[cfg(feature = "http")]
+async fn run_set<F, Fut>(
+    variants: &[ScreenshotVariant],
+    base: &ScreenshotOptions,
+    fetch: F,
+) -> Vec<(ScreenshotVariant, Result<Vec<u8>>)>
+where
+    F: Fn(ScreenshotOptions) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    join_all(variants.iter().map(|variant| {
+        let options = variant.options(base);
+        let fetch = &fetch;
+        async move {
+            let screenshot = match options {
+                Ok(options) => fetch(options).await,
+                Err(error) => Err(error),
+            };
+            (variant.clone(), screenshot)
+        }
+    }))
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "http")]
+    use crate::CaptureError;
+
+    fn variants() -> Vec<ScreenshotVariant> {
+        vec![
+            ScreenshotVariant::FullPage,
+            ScreenshotVariant::Element("main .hero".to_string()),
+            ScreenshotVariant::Viewport,
+            ScreenshotVariant::Clip(ClipRect {
+                x: 0,
+                y: 100,
+                width: 600,
+                height: 300,
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_variants_set_their_own_query_params() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let base = ScreenshotOptions {
+            vw: Some(1280),
+            full: Some(true),
+            max_height: Some(4000),
+            selector_padding: Some(16),
+            ..Default::default()
+        };
+
+        let urls: Vec<_> = capture
+            .build_screenshot_set_urls("https://example.com", &base, &variants())
+            .into_iter()
+            .map(|(_, url)| url.unwrap())
+            .collect();
+
+        assert!(urls.iter().all(|url| url.contains("vw=1280")));
+        assert!(urls[0].contains("full=true") && urls[0].contains("maxHeight=4000"));
+        assert!(!urls[0].contains("selector"));
+        assert!(
+            urls[1].contains("selector=main%20.hero") && urls[1].contains("selectorPadding=16")
+        );
+        assert!(!urls[1].contains("full="));
+        assert!(!urls[2].contains("full=") && !urls[2].contains("selector"));
+        assert!(urls[3].contains("clipX=0") && urls[3].contains("clipY=100"));
+        assert!(urls[3].contains("clipWidth=600") && urls[3].contains("clipHeight=300"));
+        assert!(urls.iter().take(3).all(|url| !url.contains("clip")));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_failing_variants_are_isolated() {
+        let base = ScreenshotOptions::default();
+        let variants = [
+            ScreenshotVariant::FullPage,
+            ScreenshotVariant::Element(String::new()),
+            ScreenshotVariant::Element("#broken".to_string()),
+            ScreenshotVariant::Viewport,
+        ];
+
+        let results = run_set(&variants, &base, |options| async move {
+            if options.selector.as_deref() == Some("#broken") {
+                return Err(CaptureError::RequestFailed {
+                    status: 404,
+                    message: "selector not found".to_string(),
+                });
+            }
+            Ok(if options.full == Some(true) {
+                b"full".to_vec()
+            } else {
+                b"viewport".to_vec()
+            })
+        })
+        .await;
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|(variant, _)| variant)
+                .collect::<Vec<_>>(),
+            variants.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(results[0].1.as_ref().unwrap(), b"full");
+        assert!(matches!(
+            results[1].1,
+            Err(CaptureError::InvalidOption { ref field, .. }) if field == "selector"
+        ));
+        assert!(matches!(
+            results[2].1,
+            Err(CaptureError::RequestFailed { status: 404, .. })
+        ));
+        assert_eq!(results[3].1.as_ref().unwrap(), b"viewport");
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_variant_file_stems() {
+        let stems: Vec<_> = variants()
+            .iter()
+            .map(ScreenshotVariant::file_stem)
+            .collect();
+        assert_eq!(
+            stems,
+            [
+                "full-page",
+                "element-main-hero",
+                "viewport",
+                "clip-0-100-600x300"
+            ]
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_colliding_file_stems_are_suffixed() {
+        let variants = [
+            ScreenshotVariant::Element(".hero".to_string()),
+            ScreenshotVariant::Element("#hero".to_string()),
+            ScreenshotVariant::Element("hero-2".to_string()),
+            ScreenshotVariant::Viewport,
+            ScreenshotVariant::Viewport,
+        ];
+
+        assert_eq!(
+            file_stems(&variants),
+            [
+                "element-hero",
+                "element-hero-2",
+                "element-hero-2-2",
+                "viewport",
+                "viewport-2"
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! The API secret, kept out of `Debug` output and, with the `zeroize`
+//! feature, wiped from memory when dropped.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+
+/// Shown in place of a secret in `Debug` output.
+const REDACTED: &str = "***";
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub(crate) struct Secret(String);
+
+impl Secret {
+    /// Takes ownership of `secret` without copying it.
+    pub(crate) fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(REDACTED, f)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Overwrites a temporary copy of a secret with zeros under the `zeroize`
+/// feature; without it the copy is simply dropped.
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+pub(crate) fn wipe(value: &mut String) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(value);
+    #[cfg(not(feature = "zeroize"))]
+    let _ = value;
+}
+
+#[cfg(feature = "zeroize")]
+mod zeroizing {
+    use super::Secret;
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+
+    impl Drop for Secret {
+        fn drop(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    impl ZeroizeOnDrop for Secret {}
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_wipe_zeroes_the_buffer() {
+            let mut copy = String::from("secret_4e2b");
+            crate::secret::wipe(&mut copy);
+            assert!(copy.is_empty());
+
+            // Zeroize clears the String after overwriting its whole
+            // capacity; the overwrite itself is covered by zeroize's tests.
+            let mut secret = Secret::new(String::from("secret_4e2b"));
+            secret.0.zeroize();
+            assert!(secret.is_empty());
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! [`tower::Service`] adapter for [`Capture`], enabled by the `tower` feature.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::{BackendFuture, Capture, CaptureError, CaptureRequest, CaptureResponse};
+
+/// Runs [`CaptureRequest`]s through a shared [`Capture`] so that tower
+/// middleware (timeouts, concurrency limits, load shedding) can wrap it.
+#[derive(Clone)]
+pub struct CaptureService {
+    capture: Arc<Capture>,
+}
+
+impl CaptureService {
+    pub fn new(capture: impl Into<Arc<Capture>>) -> Self {
+        Self {
+            capture: capture.into(),
+        }
+    }
+}
+
+impl tower::Service<CaptureRequest> for CaptureService {
+    type Response = CaptureResponse;
+    type Error = CaptureError;
+    type Future = BackendFuture<'static, CaptureResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: CaptureRequest) -> Self::Future {
+        let capture = Arc::clone(&self.capture);
+        Box::pin(async move { capture.execute(&request).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tower::{Service, ServiceBuilder, ServiceExt};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    fn service_for(base_url: &str) -> CaptureService {
+        CaptureService::new(Capture::with_options(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+            crate::CaptureOptions::new()
+                .with_base_url(base_url)
+                .unwrap(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_service_decodes_each_request_type() {
+        let url = crate::mock_server::serve_once("200 OK", &[], PNG.to_vec()).await;
+        let response = service_for(&url)
+            .oneshot(CaptureRequest::image("https://example.com"))
+            .await
+            .unwrap();
+        assert!(matches!(response, CaptureResponse::Bytes(bytes) if bytes == PNG));
+
+        let body = br#"{"success":true,"metadata":{"title":"Example"}}"#.to_vec();
+        let url = crate::mock_server::serve_once("200 OK", &[], body).await;
+        let response = service_for(&url)
+            .oneshot(CaptureRequest::metadata("https://example.com"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response,
+            CaptureResponse::Metadata(metadata)
+                if metadata.metadata.title.as_deref() == Some("Example")
+        ));
+
+        let body = br##"{"success":true,"markdown":"# Example"}"##.to_vec();
+        let url = crate::mock_server::serve_once("200 OK", &[], body).await;
+        let response = service_for(&url)
+            .oneshot(CaptureRequest::content("https://example.com"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response,
+            CaptureResponse::Content(content) if content.markdown == "# Example"
+        ));
+
+        let url =
+            crate::mock_server::serve_once("500 Internal Server Error", &[], b"boom".to_vec())
+                .await;
+        let result = service_for(&url)
+            .oneshot(CaptureRequest::pdf("https://example.com"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(CaptureError::RequestFailed { status: 500, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tower_layers_wrap_the_service() {
+        let (url, server) = crate::mock_server::serve_held(PNG.to_vec()).await;
+        let service = ServiceBuilder::new()
+            .concurrency_limit(1)
+            .service(service_for(&url));
+
+        let calls: Vec<_> = (0..2)
+            .map(|_| {
+                let service = service.clone();
+                tokio::spawn(service.oneshot(CaptureRequest::image("https://example.com")))
+            })
+            .collect();
+        // The second request waits for the first one's slot.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(server.accepted(), 1);
+        server.release(1);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.accepted() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        server.release(1);
+        for call in calls {
+            assert!(matches!(call.await.unwrap(), Ok(CaptureResponse::Bytes(_))));
+        }
+
+        let service = ServiceBuilder::new()
+            .timeout(Duration::from_millis(100))
+            .service(service_for(&url));
+        let error = service
+            .oneshot(CaptureRequest::image("https://example.com"))
+            .await
+            .unwrap_err();
+        assert!(error.is::<tower::timeout::error::Elapsed>());
+    }
+
+    #[tokio::test]
+    async fn test_service_is_ready_and_reports_errors() {
+        let mut service = CaptureService::new(Capture::new("".to_string(), "".to_string()));
+
+        let result = service
+            .ready()
+            .await
+            .unwrap()
+            .call(CaptureRequest::image("https://example.com"))
+            .await;
+        assert!(matches!(result, Err(CaptureError::MissingCredentials)));
+    }
+
+    #[tokio::test]
+    async fn test_service_rejects_missing_url() {
+        let service = CaptureService::new(Capture::new(
+            "test_key".to_string(),
+            "test_secret".to_string(),
+        ));
+
+        let result = service.oneshot(CaptureRequest::metadata("")).await;
+        assert!(matches!(result, Err(CaptureError::MissingUrl)));
+    }
+}
@@ -0,0 +1,244 @@
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+
+use crate::rt;
+use crate::{Capture, CaptureError, Endpoint, Result};
+
+pub type SessionActionPayload = HashMap<String, serde_json::Value>;
+pub type SessionActionResponse = serde_json::Value;
+pub type SessionResponse = serde_json::Value;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CreateSessionOptions {
+    #[serde(rename = "maxTtlSeconds", skip_serializing_if = "Option::is_none")]
+    pub max_ttl_seconds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<bool>,
+    #[serde(rename = "bypassBotDetection", skip_serializing_if = "Option::is_none")]
+    pub bypass_bot_detection: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cdp: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionActionRequest<'a> {
+    #[serde(rename = "type")]
+    action_type: &'a str,
+    payload: &'a SessionActionPayload,
+}
+
+impl Capture {
+    pub async fn create_session(
+        &self,
+        options: Option<&CreateSessionOptions>,
+    ) -> Result<SessionResponse> {
+        let default_options;
+        let options = match options {
+            Some(options) => options,
+            None => {
+                default_options = CreateSessionOptions::default();
+                &default_options
+            }
+        };
+
+        self.sessions_request(Method::POST, "", Some(options)).await
+    }
+
+    pub async fn get_session(&self, session_id: &str) -> Result<SessionResponse> {
+        self.sessions_request::<SessionResponse, serde_json::Value>(
+            Method::GET,
+            &format!("/{}", self.escape_session_id(session_id)?),
+            None,
+        )
+        .await
+    }
+
+    pub async fn close_session(&self, session_id: &str) -> Result<SessionResponse> {
+        self.sessions_request::<SessionResponse, serde_json::Value>(
+            Method::DELETE,
+            &format!("/{}", self.escape_session_id(session_id)?),
+            None,
+        )
+        .await
+    }
+
+    pub async fn execute_action(
+        &self,
+        session_id: &str,
+        action_type: &str,
+        payload: Option<&SessionActionPayload>,
+    ) -> Result<SessionActionResponse> {
+        let default_payload;
+        let payload = match payload {
+            Some(payload) => payload,
+            None => {
+                default_payload = SessionActionPayload::new();
+                &default_payload
+            }
+        };
+        let body = SessionActionRequest {
+            action_type,
+            payload,
+        };
+
+        self.sessions_request(
+            Method::POST,
+            &format!("/{}/actions", self.escape_session_id(session_id)?),
+            Some(&body),
+        )
+        .await
+    }
+
+    fn sessions_bearer_token(&self) -> Result<String> {
+        if self.fetch_disabled {
+            return Err(CaptureError::FetchDisabled);
+        }
+        let credentials = self.credentials()?;
+
+        let mut pair = format!("{}:{}", credentials.key, credentials.secret.expose());
+        let token = general_purpose::STANDARD.encode(&pair);
+        crate::secret::wipe(&mut pair);
+        Ok(token)
+    }
+
+    fn session_url(&self, path: &str) -> String {
+        format!("{}/v1/sessions{path}", self.base_url(Endpoint::Edge))
+    }
+
+    async fn sessions_request<T, B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        let mut request = self.client.request(method, self.session_url(path)).header(
+            "Authorization",
+            format!("Bearer {}", self.sessions_bearer_token()?),
+        );
+
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        if let Some(timeout) = self.options.timeout {
+            request = request.timeout(timeout);
+        }
+
+        let (status, body_text) = rt::compat(async {
+            let response = request.send().await?;
+            let status = response.status();
+            Ok::<_, CaptureError>((status, response.text().await?))
+        })
+        .await?;
+
+        if !status.is_success() {
+            let body = serde_json::from_str::<serde_json::Value>(&body_text)
+                .unwrap_or_else(|_| serde_json::json!({ "error": body_text }));
+            let message = body
+                .get("error")
+                .and_then(|value| value.as_str())
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(|| {
+                    format!(
+                        "Capture Sessions API request failed with status {}",
+                        status.as_u16()
+                    )
+                });
+
+            return Err(CaptureError::SessionsApiError {
+                status: status.as_u16(),
+                body,
+                message,
+            });
+        }
+
+        Ok(serde_json::from_str(&body_text)?)
+    }
+
+    fn escape_session_id(&self, session_id: &str) -> Result<String> {
+        if session_id.is_empty() {
+            return Err(CaptureError::MissingSessionId);
+        }
+
+        Ok(urlencoding::encode(session_id).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_bearer_token() {
+        let capture = Capture::new("user_123".to_string(), "secret".to_string());
+
+        assert_eq!(
+            capture.sessions_bearer_token().unwrap(),
+            "dXNlcl8xMjM6c2VjcmV0"
+        );
+    }
+
+    #[test]
+    fn test_session_url_uses_edge_url() {
+        let capture = Capture::new("user_123".to_string(), "secret".to_string());
+
+        assert_eq!(
+            capture.session_url("/sess_123/actions"),
+            "https://edge.capture.page/v1/sessions/sess_123/actions"
+        );
+
+        let proxied = Capture::with_options(
+            "user_123".to_string(),
+            "secret".to_string(),
+            crate::CaptureOptions::new()
+                .with_edge_base_url("https://proxy.internal/edge/")
+                .unwrap(),
+        );
+        assert_eq!(
+            proxied.session_url("/sess_123"),
+            "https://proxy.internal/edge/v1/sessions/sess_123"
+        );
+    }
+
+    #[test]
+    fn test_session_id_escaping() {
+        let capture = Capture::new("user_123".to_string(), "secret".to_string());
+
+        assert_eq!(
+            capture.escape_session_id("sess_123/child").unwrap(),
+            "sess_123%2Fchild"
+        );
+    }
+
+    #[test]
+    fn test_create_session_options_serialization() {
+        let options = CreateSessionOptions {
+            max_ttl_seconds: Some(300),
+            proxy: None,
+            bypass_bot_detection: None,
+            cdp: Some(true),
+        };
+
+        let value = serde_json::to_value(options).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "maxTtlSeconds": 300,
+                "cdp": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_create_session_options_omit_empty_serialization() {
+        let options = CreateSessionOptions::default();
+
+        let value = serde_json::to_value(options).unwrap();
+        assert_eq!(value, serde_json::json!({}));
+    }
+}
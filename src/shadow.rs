@@ -0,0 +1,315 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::client::Endpoint;
+use crate::concurrency::RequestSlots;
+use crate::metrics::Metric;
+use crate::overrides;
+use crate::rt::{self, Instant, SystemTime};
+use crate::{Capture, RateLimiter};
+
+type ShadowCallback = Arc<dyn Fn(&ShadowReport) + Send + Sync>;
+
+/// Mirrors a sample of fetches to a second endpoint for comparison.
+///
+/// Shadow requests run in a spawned task: their outcome only reaches the
+/// callbacks and the client's [`MetricsHook`](crate::MetricsHook), never the
+/// caller, and the caller's fetch does not wait on them. They never take the
+/// client's request slots or count against its rate limiter, so callers
+/// neither queue behind them nor lose budget to them. Shadows have their own
+/// limits instead, [`with_max_in_flight`](Self::with_max_in_flight) and
+/// [`with_rate_limiter`](Self::with_rate_limiter); a sampled fetch is not
+/// mirrored while either is exhausted. Shadow bodies are read up to the
+/// client's `max_response_bytes`.
+#[derive(Clone)]
+pub struct ShadowConfig {
+    pub target: Endpoint,
+    /// Fraction of fetches to mirror, from 0.0 to 1.0.
+    pub sample_rate: f64,
+    /// Compare response bodies as well as statuses.
+    pub compare_bodies: bool,
+    on_report: Option<ShadowCallback>,
+    on_divergence: Option<ShadowCallback>,
+    fetches: Arc<AtomicU64>,
+    in_flight: Option<RequestSlots>,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl fmt::Debug for ShadowConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShadowConfig")
+            .field("target", &self.target)
+            .field("sample_rate", &self.sample_rate)
+            .field("compare_bodies", &self.compare_bodies)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ShadowConfig {
+    pub fn new(target: Endpoint, sample_rate: f64) -> Self {
+        Self {
+            target,
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            compare_bodies: false,
+            on_report: None,
+            on_divergence: None,
+            fetches: Arc::new(AtomicU64::new(0)),
+            in_flight: None,
+            rate_limiter: None,
+        }
+    }
+
+    pub fn with_compare_bodies(mut self, compare_bodies: bool) -> Self {
+        self.compare_bodies = compare_bodies;
+        self
+    }
+
+    /// Called with every completed comparison, e.g. to record latencies.
+    pub fn on_report(mut self, callback: impl Fn(&ShadowReport) + Send + Sync + 'static) -> Self {
+        self.on_report = Some(Arc::new(callback));
+        self
+    }
+
+    /// Called when the shadow response differs from the primary one.
+    pub fn on_divergence(
+        mut self,
+        callback: impl Fn(&ShadowReport) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_divergence = Some(Arc::new(callback));
+        self
+    }
+
+    /// Mirrors at most `max` fetches at once; fetches sampled while that
+    /// many shadow requests are running are not mirrored. Unlimited by
+    /// default.
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.in_flight = Some(RequestSlots::new(max));
+        self
+    }
+
+    /// Budget for shadow requests, separate from the client's own rate
+    /// limiter. Fetches sampled once it is spent are not mirrored.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Copy of this configuration with its own sampling sequence and
+    /// in-flight limit.
+    pub(crate) fn detached(&self) -> Self {
+        Self {
+            fetches: Arc::new(AtomicU64::new(0)),
+            in_flight: self.in_flight.as_ref().map(RequestSlots::detached),
+            ..self.clone()
+        }
+    }
+
+    /// Deterministic sampling: the n-th fetch is mirrored when
+    /// `floor(n * rate)` steps up, so exactly `rate` of fetches are mirrored
+    /// over any long run.
+    pub(crate) fn should_sample(&self) -> bool {
+        let n = self.fetches.fetch_add(1, Ordering::Relaxed);
+        let before = (n as f64 * self.sample_rate).floor();
+        let after = ((n + 1) as f64 * self.sample_rate).floor();
+        after > before
+    }
+
+    fn report(&self, report: &ShadowReport) {
+        if let Some(on_report) = &self.on_report {
+            on_report(report);
+        }
+        if report.diverged() {
+            if let Some(on_divergence) = &self.on_divergence {
+                on_divergence(report);
+            }
+        }
+    }
+}
+
+/// Comparison of one primary fetch with its shadow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowReport {
+    pub target: Endpoint,
+    /// HTTP status of each side, or `None` when the request never got one.
+    pub primary_status: Option<u16>,
+    pub shadow_status: Option<u16>,
+    pub primary_latency: Duration,
+    pub shadow_latency: Duration,
+    /// Whether the bodies were identical, when `compare_bodies` is set and
+    /// both sides returned one.
+    pub bodies_match: Option<bool>,
+}
+
+impl ShadowReport {
+    pub fn diverged(&self) -> bool {
+        self.primary_status != self.shadow_status || self.bodies_match == Some(false)
+    }
+}
+
+/// What the caller-facing side of a shadowed fetch observed.
+#[derive(Debug, Clone)]
+pub(crate) struct Observation {
+    pub(crate) status: Option<u16>,
+    pub(crate) digest: Option<[u8; 16]>,
+    pub(crate) latency: Duration,
+}
+
+impl Observation {
+    pub(crate) fn new(status: Option<u16>, body: Option<&[u8]>, latency: Duration) -> Self {
+        Self {
+            status,
+            digest: body.map(|body| md5::compute(body).0),
+            latency,
+        }
+    }
+}
+
+fn compare(config: &ShadowConfig, primary: &Observation, shadow: &Observation) -> ShadowReport {
+    let bodies_match = match (config.compare_bodies, primary.digest, shadow.digest) {
+        (true, Some(primary), Some(shadow)) => Some(primary == shadow),
+        _ => None,
+    };
+
+    ShadowReport {
+        target: config.target,
+        primary_status: primary.status,
+        shadow_status: shadow.status,
+        primary_latency: primary.latency,
+        shadow_latency: shadow.latency,
+        bodies_match,
+    }
+}
+
+/// Spawns the shadow request for `shadow_url`, sent with `capture`'s HTTP
+/// client, and returns the channel the primary side reports its observation
+/// on. Returns `None` when all shadow slots are taken, and outside a Tokio
+/// runtime with the `runtime-tokio` feature, where there is nothing to spawn
+/// onto. A shadow the rate limiter refuses is dropped unsent.
+pub(crate) fn spawn(
+    config: &ShadowConfig,
+    capture: &Capture,
+    shadow_url: String,
+) -> Option<oneshot::Sender<Observation>> {
+    let slot = match &config.in_flight {
+        Some(in_flight) => Some(in_flight.try_acquire()?),
+        None => None,
+    };
+
+    let (sender, receiver) = oneshot::channel();
+    let config = config.clone();
+    let client = capture.client.clone();
+    let max_response_bytes = capture.options.max_response_bytes;
+    let metrics = capture.options.metrics.clone();
+
+    let spawned = rt::spawn(async move {
+        if let Some(rate_limiter) = &config.rate_limiter {
+            if rate_limiter.acquire_async(SystemTime::now()).await.is_err() {
+                return;
+            }
+        }
+
+        let started = Instant::now();
+        let shadow = match client.get(&shadow_url).send().await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let body = if config.compare_bodies {
+                    overrides::read_body(response, max_response_bytes)
+                        .await
+                        .ok()
+                } else {
+                    None
+                };
+                Observation::new(Some(status), body.as_deref(), started.elapsed())
+            }
+            Err(_) => Observation::new(None, None, started.elapsed()),
+        };
+        drop(slot);
+
+        if let Ok(primary) = receiver.await {
+            let report = compare(&config, &primary, &shadow);
+            config.report(&report);
+            if let Some(metrics) = &metrics {
+                metrics.record(&Metric::Shadow(&report));
+            }
+        }
+    });
+
+    spawned.then_some(sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn observation(status: u16, body: &[u8]) -> Observation {
+        Observation::new(Some(status), Some(body), Duration::from_millis(10))
+    }
+
+    #[test]
+    fn test_sampler_hits_configured_rate() {
+        let config = ShadowConfig::new(Endpoint::Edge, 0.05);
+        let sampled = (0..1000).filter(|_| config.should_sample()).count();
+        assert_eq!(sampled, 50);
+
+        let never = ShadowConfig::new(Endpoint::Edge, 0.0);
+        assert!((0..100).all(|_| !never.should_sample()));
+
+        let always = ShadowConfig::new(Endpoint::Edge, 1.0);
+        assert!((0..100).all(|_| always.should_sample()));
+    }
+
+    #[test]
+    fn test_divergence_callback_fires_on_differing_bodies() {
+        let divergences = Arc::new(Mutex::new(Vec::new()));
+        let reports = Arc::new(Mutex::new(0));
+        let config = ShadowConfig::new(Endpoint::Edge, 1.0)
+            .with_compare_bodies(true)
+            .on_report({
+                let reports = reports.clone();
+                move |_| *reports.lock().unwrap() += 1
+            })
+            .on_divergence({
+                let divergences = divergences.clone();
+                move |report| divergences.lock().unwrap().push(report.clone())
+            });
+
+        let same = compare(
+            &config,
+            &observation(200, b"png"),
+            &observation(200, b"png"),
+        );
+        config.report(&same);
+        let different = compare(
+            &config,
+            &observation(200, b"png"),
+            &observation(200, b"gif"),
+        );
+        config.report(&different);
+        let failed = compare(
+            &config,
+            &observation(200, b"png"),
+            &observation(502, b"png"),
+        );
+        config.report(&failed);
+
+        assert_eq!(*reports.lock().unwrap(), 3);
+        let divergences = divergences.lock().unwrap();
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(divergences[0].bodies_match, Some(false));
+        assert_eq!(divergences[1].shadow_status, Some(502));
+    }
+
+    #[test]
+    fn test_bodies_ignored_unless_compared() {
+        let config = ShadowConfig::new(Endpoint::Edge, 1.0);
+        let report = compare(&config, &observation(200, b"a"), &observation(200, b"b"));
+
+        assert_eq!(report.bodies_match, None);
+        assert!(!report.diverged());
+    }
+}
@@ -0,0 +1,485 @@
+//! Capture URL signing: the query string, its token and the signed URL,
+//! usable without a [`Capture`](crate::Capture), e.g. in stateless edge
+//! functions. The `build_*` methods of `Capture` sign through these too.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{CaptureError, Endpoint, RequestOptions, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestType {
+    Image,
+    Pdf,
+    Content,
+    Metadata,
+    Animated,
+}
+
+impl RequestType {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            RequestType::Image => "image",
+            RequestType::Pdf => "pdf",
+            RequestType::Content => "content",
+            RequestType::Metadata => "metadata",
+            RequestType::Animated => "animated",
+        }
+    }
+}
+
+/// The token of a capture URL: the MD5 hex digest of `secret` followed by
+/// `query`, as produced by [`to_query_string`].
+pub fn generate_token(secret: &str, query: &str) -> String {
+    // Hashed in two parts so no concatenated copy of the secret is made.
+    let mut context = md5::Context::new();
+    context.consume(secret);
+    context.consume(query);
+    format!("{:x}", context.compute())
+}
+
+/// Serializes options into a query string. Keys are emitted in sorted order
+/// so that equal options always produce the same string, and so the same
+/// token. Values that are not strings, numbers or booleans, and empty
+/// strings, are left out.
+pub fn to_query_string(options: &RequestOptions) -> String {
+    let mut entries: Vec<_> = options.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    let mut params = Vec::new();
+
+    for (key, value) in entries {
+        let value_str = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => continue,
+        };
+
+        if !value_str.is_empty() {
+            params.push(format!(
+                "{}={}",
+                urlencoding::encode(key),
+                urlencoding::encode(&value_str)
+            ));
+        }
+    }
+
+    params.join("&")
+}
+
+/// Signs a capture URL of `target_url` on the CDN endpoint, byte for byte
+/// what [`Capture::new(key, secret)`](crate::Capture::new) builds. The
+/// token does not cover the host, so the URL also works with the host of
+/// [`Endpoint::Edge`] swapped in.
+///
+/// Fails with [`CaptureError::MissingCredentials`] when `key` or `secret`
+/// is empty and with [`CaptureError::MissingUrl`] when `target_url` is.
+/// `options` are sent as given; no structured option checks run here.
+pub fn sign_capture_url(
+    key: &str,
+    secret: &str,
+    request_type: RequestType,
+    target_url: &str,
+    options: &RequestOptions,
+) -> Result<String> {
+    if key.is_empty() || secret.is_empty() {
+        return Err(CaptureError::MissingCredentials);
+    }
+    sign_target(
+        Endpoint::Cdn.base_url(),
+        key,
+        secret,
+        request_type,
+        target_url,
+        options.clone(),
+    )
+}
+
+/// Adds `target_url` to `options` as the `url` parameter and signs them.
+pub(crate) fn sign_target(
+    base_url: &str,
+    key: &str,
+    secret: &str,
+    request_type: RequestType,
+    target_url: &str,
+    mut options: RequestOptions,
+) -> Result<String> {
+    if target_url.is_empty() {
+        return Err(CaptureError::MissingUrl);
+    }
+    options.insert(
+        "url".to_string(),
+        serde_json::Value::String(target_url.to_string()),
+    );
+    Ok(sign(base_url, key, secret, request_type, &options))
+}
+
+/// `{base_url}/{key}/{token}/{type}?{query}`, without any checks.
+pub(crate) fn sign(
+    base_url: &str,
+    key: &str,
+    secret: &str,
+    request_type: RequestType,
+    options: &RequestOptions,
+) -> String {
+    let query = to_query_string(options);
+    let token = generate_token(secret, &query);
+
+    format!("{base_url}/{key}/{token}/{}?{query}", request_type.as_str())
+}
+
+impl FromStr for RequestType {
+    type Err = ParseUrlError;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "image" => Ok(RequestType::Image),
+            "pdf" => Ok(RequestType::Pdf),
+            "content" => Ok(RequestType::Content),
+            "metadata" => Ok(RequestType::Metadata),
+            "animated" => Ok(RequestType::Animated),
+            _ => Err(ParseUrlError::UnknownRequestType(value.to_string())),
+        }
+    }
+}
+
+/// Why a string is not a well-formed capture URL.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseUrlError {
+    #[error("not an absolute URL")]
+    NotAUrl,
+    #[error("key segment is missing or malformed")]
+    BadKeySegment,
+    #[error("token segment is missing")]
+    MissingToken,
+    #[error("token segment is not an MD5 hex digest")]
+    BadToken,
+    #[error("request type segment is missing")]
+    MissingRequestType,
+    #[error("unknown request type `{0}`")]
+    UnknownRequestType(String),
+    #[error("path has {0} segments, expected [base path/]key/token/type")]
+    UnexpectedSegments(usize),
+    #[error("query has no target `url`")]
+    MissingTargetUrl,
+}
+
+/// The parts of a signed capture URL, as produced by the `build_*` methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedCaptureUrl {
+    pub key: String,
+    pub token: String,
+    pub request_type: RequestType,
+    /// Target page URL from the `url` query parameter.
+    pub url: String,
+    /// The remaining query parameters, as strings.
+    pub options: RequestOptions,
+}
+
+impl ParsedCaptureUrl {
+    /// Splits a capture URL into its parts. Untrusted input is fine: every
+    /// malformation maps to a [`ParseUrlError`] instead of a panic or a
+    /// misattributed segment.
+    ///
+    /// URLs built for a base URL with a path, e.g. a proxy at
+    /// `https://proxy.example/capture`, parse too: the key, token and type
+    /// are the last three segments, and the base path before them is
+    /// skipped. Longer paths whose last three segments are not a well-formed
+    /// key, token and type are [`ParseUrlError::UnexpectedSegments`].
+    pub fn parse(capture_url: &str) -> std::result::Result<Self, ParseUrlError> {
+        let parsed = url::Url::parse(capture_url).map_err(|_| ParseUrlError::NotAUrl)?;
+        let path = parsed
+            .path()
+            .strip_prefix('/')
+            .ok_or(ParseUrlError::NotAUrl)?;
+        let segments: Vec<&str> = path.split('/').collect();
+
+        let (key, token, request_type) = match segments.as_slice() {
+            [key, token, request_type] => (*key, *token, *request_type),
+            [key, token] => {
+                check_key(key)?;
+                check_token(token)?;
+                return Err(ParseUrlError::MissingRequestType);
+            }
+            [key] => {
+                check_key(key)?;
+                return Err(ParseUrlError::MissingToken);
+            }
+            [.., key, token, request_type]
+                if check_key(key).is_ok()
+                    && check_token(token).is_ok()
+                    && request_type.parse::<RequestType>().is_ok() =>
+            {
+                (*key, *token, *request_type)
+            }
+            segments => return Err(ParseUrlError::UnexpectedSegments(segments.len())),
+        };
+
+        let key = check_key(key)?;
+        check_token(token)?;
+        if request_type.is_empty() {
+            return Err(ParseUrlError::MissingRequestType);
+        }
+        let request_type = request_type.parse()?;
+
+        let mut options: RequestOptions = parsed
+            .query_pairs()
+            .map(|(name, value)| (name.into_owned(), serde_json::Value::String(value.into())))
+            .collect();
+        let url = match options.remove("url") {
+            Some(serde_json::Value::String(url)) if !url.is_empty() => url,
+            _ => return Err(ParseUrlError::MissingTargetUrl),
+        };
+
+        Ok(Self {
+            key,
+            token: token.to_string(),
+            request_type,
+            url,
+            options,
+        })
+    }
+}
+
+impl FromStr for ParsedCaptureUrl {
+    type Err = ParseUrlError;
+
+    fn from_str(capture_url: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(capture_url)
+    }
+}
+
+/// Decodes the key segment. Keys never contain `/`, so an encoded slash is
+/// treated as malformed rather than as part of the key.
+fn check_key(segment: &str) -> std::result::Result<String, ParseUrlError> {
+    let key = urlencoding::decode(segment).map_err(|_| ParseUrlError::BadKeySegment)?;
+    if key.is_empty() || key.contains('/') || key.chars().any(char::is_control) {
+        return Err(ParseUrlError::BadKeySegment);
+    }
+    Ok(key.into_owned())
+}
+
+fn check_token(segment: &str) -> std::result::Result<(), ParseUrlError> {
+    if segment.is_empty() {
+        return Err(ParseUrlError::MissingToken);
+    }
+    if segment.len() != 32 || !segment.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(ParseUrlError::BadToken);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Capture;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_parse_round_trips_built_url() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let mut options = RequestOptions::new();
+        options.insert("full".to_string(), serde_json::Value::Bool(true));
+        let url = capture
+            .build_pdf_url("https://example.com/a b", Some(&options))
+            .unwrap();
+
+        let parsed = ParsedCaptureUrl::parse(&url).unwrap();
+        assert_eq!(parsed.key, "test_key");
+        assert_eq!(parsed.request_type, RequestType::Pdf);
+        assert_eq!(parsed.url, "https://example.com/a b");
+        assert_eq!(parsed.options["full"], serde_json::json!("true"));
+        assert_eq!(
+            parsed.token,
+            generate_token("test_secret", url.split_once('?').unwrap().1)
+        );
+    }
+
+    #[test]
+    fn test_free_function_matches_capture_urls() {
+        let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+        let options: RequestOptions = serde_json::from_value(serde_json::json!({
+            "vw": 1280,
+            "full": true,
+            "delay": 1.5,
+            "userAgent": "Mozilla/5.0 (X11; Linux x86_64)",
+            "selector": "",
+            "blockResources": ["font"],
+        }))
+        .unwrap();
+        let target = "https://example.com/a b?q=ü&x=1";
+
+        for request_type in [
+            RequestType::Image,
+            RequestType::Pdf,
+            RequestType::Content,
+            RequestType::Metadata,
+            RequestType::Animated,
+        ] {
+            for options in [RequestOptions::new(), options.clone()] {
+                assert_eq!(
+                    sign_capture_url("test_key", "test_secret", request_type, target, &options)
+                        .unwrap(),
+                    capture
+                        .build_url(request_type, target, Some(&options))
+                        .unwrap()
+                );
+            }
+        }
+
+        // An explicit `url` option is replaced by the target, as in `Capture`.
+        let mut with_url = RequestOptions::new();
+        with_url.insert("url".to_string(), serde_json::json!("https://other.com"));
+        assert_eq!(
+            sign_capture_url(
+                "test_key",
+                "test_secret",
+                RequestType::Image,
+                target,
+                &with_url
+            )
+            .unwrap(),
+            capture.build_image_url(target, Some(&with_url)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_free_function_rejects_missing_inputs() {
+        let options = RequestOptions::new();
+        let sign = |key, secret, target| {
+            sign_capture_url(key, secret, RequestType::Image, target, &options)
+        };
+
+        assert!(matches!(
+            sign("", "test_secret", "https://example.com"),
+            Err(CaptureError::MissingCredentials)
+        ));
+        assert!(matches!(
+            sign("test_key", "", "https://example.com"),
+            Err(CaptureError::MissingCredentials)
+        ));
+        assert!(matches!(
+            sign("test_key", "test_secret", ""),
+            Err(CaptureError::MissingUrl)
+        ));
+    }
+
+    #[test]
+    fn test_parse_round_trips_a_base_url_with_a_path() {
+        let mut options = RequestOptions::new();
+        options.insert("vw".to_string(), serde_json::json!(1280));
+        for base_url in [
+            "https://proxy.example/capture",
+            "https://proxy.example/a/b/",
+        ] {
+            let capture = Capture::with_options(
+                "test_key".to_string(),
+                "test_secret".to_string(),
+                crate::CaptureOptions::new()
+                    .with_base_url(base_url)
+                    .unwrap(),
+            );
+            let built = capture
+                .build_image_url("https://example.com", Some(&options))
+                .unwrap();
+
+            let parsed = ParsedCaptureUrl::parse(&built).unwrap();
+            assert_eq!(parsed.key, "test_key", "{built}");
+            assert_eq!(parsed.request_type, RequestType::Image);
+            assert_eq!(parsed.url, "https://example.com");
+            assert_eq!(parsed.options["vw"], "1280");
+            assert_eq!(
+                parsed.token,
+                generate_token("test_secret", built.split_once('?').unwrap().1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_reports_each_malformation() {
+        let cases = [
+            ("not a url", ParseUrlError::NotAUrl),
+            ("https://cdn.capture.page", ParseUrlError::BadKeySegment),
+            ("https://cdn.capture.page/key", ParseUrlError::MissingToken),
+            ("https://cdn.capture.page/key/", ParseUrlError::MissingToken),
+            (
+                "https://cdn.capture.page/key/abc/image",
+                ParseUrlError::BadToken,
+            ),
+            (
+                &format!("https://cdn.capture.page/key/{TOKEN}"),
+                ParseUrlError::MissingRequestType,
+            ),
+            (
+                &format!("https://cdn.capture.page/key/{TOKEN}/"),
+                ParseUrlError::MissingRequestType,
+            ),
+            (
+                &format!("https://cdn.capture.page/key/{TOKEN}/video?url=x"),
+                ParseUrlError::UnknownRequestType("video".to_string()),
+            ),
+            (
+                &format!("https://cdn.capture.page//{TOKEN}/image?url=x"),
+                ParseUrlError::BadKeySegment,
+            ),
+            (
+                &format!("https://cdn.capture.page/a%2Fb/{TOKEN}/image?url=x"),
+                ParseUrlError::BadKeySegment,
+            ),
+            (
+                &format!("https://cdn.capture.page/%FF/{TOKEN}/image?url=x"),
+                ParseUrlError::BadKeySegment,
+            ),
+            (
+                &format!("https://cdn.capture.page/key/{TOKEN}/image/extra?url=x"),
+                ParseUrlError::UnexpectedSegments(4),
+            ),
+            (
+                &format!("https://cdn.capture.page/key//{TOKEN}/image?url=x"),
+                ParseUrlError::UnexpectedSegments(4),
+            ),
+            (
+                &format!("https://proxy.example/capture/key/{TOKEN}/video?url=x"),
+                ParseUrlError::UnexpectedSegments(4),
+            ),
+            (
+                &format!("https://cdn.capture.page/key/{TOKEN}/image?full=true"),
+                ParseUrlError::MissingTargetUrl,
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(ParsedCaptureUrl::parse(input), Err(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_never_panics_on_random_input() {
+        const ALPHABET: &[u8] = b"/%?=&#:.aZ09-_~ \\\xc3\xa9FfxX";
+        const PREFIXES: &[&str] = &["", "https://cdn.capture.page/", "http://h/", "https:///"];
+
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..5000 {
+            let len = (next() % 64) as usize;
+            let bytes: Vec<u8> = (0..len)
+                .map(|_| ALPHABET[(next() % ALPHABET.len() as u64) as usize])
+                .collect();
+            let prefix = PREFIXES[(next() % PREFIXES.len() as u64) as usize];
+            let input = format!("{prefix}{}", String::from_utf8_lossy(&bytes));
+
+            let _ = ParsedCaptureUrl::parse(&input);
+        }
+    }
+}
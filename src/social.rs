@@ -0,0 +1,263 @@
+//! OpenGraph and Twitter card tags, grouped out of the flat metadata map.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::MetadataResponse;
+
+/// `og:*` tags of a page. See [`MetadataResponse::open_graph`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraph {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub site_name: Option<String>,
+    /// `og:type`, e.g. `article` or `website`.
+    pub og_type: Option<String>,
+    pub locale: Option<String>,
+    /// Every `og:image`, in page order, with its structured properties.
+    pub images: Vec<OgImage>,
+    /// Other `og:*` tags, keyed by their full name.
+    pub extra: HashMap<String, Value>,
+}
+
+/// One `og:image` and the `og:image:*` properties that follow it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OgImage {
+    pub url: String,
+    pub secure_url: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// `og:image:type`, e.g. `image/png`.
+    pub mime_type: Option<String>,
+    pub alt: Option<String>,
+}
+
+/// `twitter:*` tags of a page. See [`MetadataResponse::twitter_card`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TwitterCard {
+    /// `twitter:card`, e.g. `summary_large_image`.
+    pub card: Option<String>,
+    /// `@username` of the site.
+    pub site: Option<String>,
+    /// `@username` of the author.
+    pub creator: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub image_alt: Option<String>,
+    /// Other `twitter:*` tags, keyed by their full name.
+    pub extra: HashMap<String, Value>,
+}
+
+const OG_IMAGE_PROPERTIES: &[&str] = &[
+    "og:image:url",
+    "og:image:secure_url",
+    "og:image:width",
+    "og:image:height",
+    "og:image:type",
+    "og:image:alt",
+];
+
+impl MetadataResponse {
+    /// The page's `og:*` tags. A tag reported as an array, such as several
+    /// `og:image`s, yields one entry per element; the n-th `og:image:width`
+    /// belongs to the n-th image. Empty when the page has no such tags.
+    pub fn open_graph(&self) -> OpenGraph {
+        let tags = &self.metadata.extra;
+        const KNOWN: &[&str] = &[
+            "og:title",
+            "og:description",
+            "og:url",
+            "og:site_name",
+            "og:type",
+            "og:locale",
+            "og:image",
+        ];
+
+        OpenGraph {
+            title: first(tags, "og:title"),
+            description: first(tags, "og:description"),
+            url: first(tags, "og:url"),
+            site_name: first(tags, "og:site_name"),
+            og_type: first(tags, "og:type"),
+            locale: first(tags, "og:locale"),
+            images: images(tags),
+            extra: namespaced(tags, "og:", |key| {
+                KNOWN.contains(&key) || OG_IMAGE_PROPERTIES.contains(&key)
+            }),
+        }
+    }
+
+    /// The page's `twitter:*` tags. Empty when the page has none; Twitter
+    /// falls back to OpenGraph for missing tags, which is left to the caller.
+    pub fn twitter_card(&self) -> TwitterCard {
+        let tags = &self.metadata.extra;
+        const KNOWN: &[&str] = &[
+            "twitter:card",
+            "twitter:site",
+            "twitter:creator",
+            "twitter:title",
+            "twitter:description",
+            "twitter:image",
+            "twitter:image:alt",
+        ];
+
+        TwitterCard {
+            card: first(tags, "twitter:card"),
+            site: first(tags, "twitter:site"),
+            creator: first(tags, "twitter:creator"),
+            title: first(tags, "twitter:title"),
+            description: first(tags, "twitter:description"),
+            image: first(tags, "twitter:image"),
+            image_alt: first(tags, "twitter:image:alt"),
+            extra: namespaced(tags, "twitter:", |key| KNOWN.contains(&key)),
+        }
+    }
+}
+
+/// Values of `key` as strings: one for a scalar, one per element for an
+/// array. Numbers are written out; other values are skipped.
+fn strings(tags: &HashMap<String, Value>, key: &str) -> Vec<String> {
+    let values = match tags.get(key) {
+        Some(Value::Array(values)) => values.as_slice(),
+        Some(value) => std::slice::from_ref(value),
+        None => &[],
+    };
+    values
+        .iter()
+        .filter_map(|value| match value {
+            Value::String(value) => Some(value.trim().to_string()),
+            Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        })
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+fn first(tags: &HashMap<String, Value>, key: &str) -> Option<String> {
+    strings(tags, key).into_iter().next()
+}
+
+fn images(tags: &HashMap<String, Value>) -> Vec<OgImage> {
+    let mut urls = strings(tags, "og:image");
+    if urls.is_empty() {
+        urls = strings(tags, "og:image:url");
+    }
+    let property = |key: &str| strings(tags, key);
+    let (secure_urls, widths, heights, mime_types, alts) = (
+        property("og:image:secure_url"),
+        property("og:image:width"),
+        property("og:image:height"),
+        property("og:image:type"),
+        property("og:image:alt"),
+    );
+    let dimension = |values: &[String], index: usize| {
+        values
+            .get(index)
+            .and_then(|value| value.parse::<u32>().ok())
+    };
+
+    urls.into_iter()
+        .enumerate()
+        .map(|(index, url)| OgImage {
+            url,
+            secure_url: secure_urls.get(index).cloned(),
+            width: dimension(&widths, index),
+            height: dimension(&heights, index),
+            mime_type: mime_types.get(index).cloned(),
+            alt: alts.get(index).cloned(),
+        })
+        .collect()
+}
+
+fn namespaced(
+    tags: &HashMap<String, Value>,
+    prefix: &str,
+    known: impl Fn(&str) -> bool,
+) -> HashMap<String, Value> {
+    tags.iter()
+        .filter(|(key, _)| key.starts_with(prefix) && !known(key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(tags: Value) -> MetadataResponse {
+        serde_json::from_value(serde_json::json!({ "success": true, "metadata": tags })).unwrap()
+    }
+
+    #[test]
+    fn test_open_graph_groups_repeated_images() {
+        let metadata = metadata(serde_json::json!({
+            "title": "Launch week",
+            "og:title": "Launch week",
+            "og:type": "article",
+            "og:site_name": "Example",
+            "og:image": [
+                "https://example.com/wide.png",
+                "https://example.com/square.png"
+            ],
+            "og:image:width": ["1200", 600],
+            "og:image:height": ["630"],
+            "og:image:alt": ["Wide banner", "Square logo"],
+            "og:video": "https://example.com/teaser.mp4",
+            "og:image:rotation": "90",
+        }));
+
+        let open_graph = metadata.open_graph();
+        assert_eq!(open_graph.title.as_deref(), Some("Launch week"));
+        assert_eq!(open_graph.og_type.as_deref(), Some("article"));
+        assert_eq!(
+            open_graph.images,
+            [
+                OgImage {
+                    url: "https://example.com/wide.png".to_string(),
+                    width: Some(1200),
+                    height: Some(630),
+                    alt: Some("Wide banner".to_string()),
+                    ..Default::default()
+                },
+                OgImage {
+                    url: "https://example.com/square.png".to_string(),
+                    width: Some(600),
+                    alt: Some("Square logo".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+        let mut extra: Vec<_> = open_graph.extra.keys().map(String::as_str).collect();
+        extra.sort_unstable();
+        assert_eq!(extra, ["og:image:rotation", "og:video"]);
+
+        // No twitter tags at all.
+        assert_eq!(metadata.twitter_card(), TwitterCard::default());
+    }
+
+    #[test]
+    fn test_twitter_card_with_missing_tags() {
+        let metadata = metadata(serde_json::json!({
+            "twitter:card": "summary_large_image",
+            "twitter:site": "@example",
+            "twitter:image:alt": "Banner",
+            "twitter:label1": "Reading time",
+            "og:image:url": "https://example.com/only.png",
+        }));
+
+        let card = metadata.twitter_card();
+        assert_eq!(card.card.as_deref(), Some("summary_large_image"));
+        assert_eq!(card.site.as_deref(), Some("@example"));
+        assert_eq!(card.image_alt.as_deref(), Some("Banner"));
+        assert!(card.title.is_none() && card.image.is_none() && card.creator.is_none());
+        assert_eq!(card.extra["twitter:label1"], "Reading time");
+
+        let open_graph = metadata.open_graph();
+        assert_eq!(open_graph.images.len(), 1);
+        assert_eq!(open_graph.images[0].url, "https://example.com/only.png");
+        assert!(open_graph.title.is_none() && open_graph.extra.is_empty());
+    }
+}
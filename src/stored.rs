@@ -0,0 +1,168 @@
+use url::Url;
+
+use crate::signing::RequestType;
+use crate::{
+    Capture, CaptureError, FetchInfo, PdfOptions, RequestOptions, Result, ScreenshotOptions,
+};
+
+/// Outcome of a capture requested with `s3Redirect`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoredCapture {
+    /// The service returned the file itself instead of storing it, e.g.
+    /// when no storage is configured for the account.
+    Bytes(Vec<u8>),
+    /// The file was stored; this is the object's URL from the redirect.
+    StoredAt(Url),
+}
+
+impl Capture {
+    /// Captures a PDF with `s3Redirect` set and returns where it was stored,
+    /// without downloading it. The redirect is never followed: with a client
+    /// from `with_client` whose redirect policy follows it, the call fails
+    /// with [`CaptureError::RedirectFollowed`] rather than returning the
+    /// stored file in place of its URL.
+    pub async fn fetch_pdf_stored(&self, url: &str, options: &PdfOptions) -> Result<StoredCapture> {
+        let options = PdfOptions {
+            s3_redirect: Some(true),
+            ..options.clone()
+        };
+        let request_options = options.try_to_request_options()?;
+        self.fetch_stored(RequestType::Pdf, url, &request_options)
+            .await
+    }
+
+    /// Screenshot counterpart of [`fetch_pdf_stored`](Self::fetch_pdf_stored).
+    pub async fn fetch_screenshot_stored(
+        &self,
+        url: &str,
+        options: &ScreenshotOptions,
+    ) -> Result<StoredCapture> {
+        let options = ScreenshotOptions {
+            s3_redirect: Some(true),
+            ..options.clone()
+        };
+        let request_options = options.try_to_request_options()?;
+        self.fetch_stored(RequestType::Image, url, &request_options)
+            .await
+    }
+
+    async fn fetch_stored(
+        &self,
+        request_type: RequestType,
+        url: &str,
+        request_options: &RequestOptions,
+    ) -> Result<StoredCapture> {
+        let settings = self.fetch_settings(None);
+        let capture_url =
+            self.build_url_at(settings.endpoint, request_type, url, Some(request_options))?;
+        self.fetch_stored_url(&capture_url, request_type).await
+    }
+
+    pub(crate) async fn fetch_stored_url(
+        &self,
+        capture_url: &str,
+        request_type: RequestType,
+    ) -> Result<StoredCapture> {
+        let mut settings = self.fetch_settings(None);
+        settings.follow_redirects = false;
+        let (bytes, info) = self
+            .fetch_bytes_with_info(capture_url, &settings, request_type)
+            .await?;
+        stored_capture(bytes, &info)
+    }
+}
+
+fn stored_capture(bytes: Vec<u8>, info: &FetchInfo) -> Result<StoredCapture> {
+    if !(300..400).contains(&info.status) {
+        return Ok(StoredCapture::Bytes(bytes));
+    }
+
+    let location = info
+        .location
+        .as_deref()
+        .ok_or_else(|| CaptureError::RequestFailed {
+            status: info.status,
+            message: "redirect without a Location header".to_string(),
+        })?;
+    Ok(StoredCapture::StoredAt(Url::parse(location)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::serve_once;
+
+    fn capture() -> Capture {
+        Capture::new("test_key".to_string(), "test_secret".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_redirect_returns_the_stored_url() {
+        let object = "https://bucket.s3.amazonaws.com/captures/invoice.pdf";
+        let server = serve_once("302 Found", &[("location", object)], Vec::new()).await;
+
+        let stored = capture()
+            .fetch_stored_url(
+                &format!("{server}/key/token/pdf?s3Redirect=true"),
+                RequestType::Pdf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored, StoredCapture::StoredAt(Url::parse(object).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_direct_response_returns_the_bytes() {
+        let server = serve_once(
+            "200 OK",
+            &[("content-type", "application/pdf")],
+            b"%PDF-1.7".to_vec(),
+        )
+        .await;
+
+        let stored = capture()
+            .fetch_stored_url(
+                &format!("{server}/key/token/pdf?s3Redirect=true"),
+                RequestType::Pdf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored, StoredCapture::Bytes(b"%PDF-1.7".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_followed_by_a_user_client_is_an_error() {
+        let object = serve_once(
+            "200 OK",
+            &[("content-type", "application/pdf")],
+            b"%PDF-1.7".to_vec(),
+        )
+        .await;
+        let object = format!("{object}/captures/invoice.pdf");
+        let server = serve_once("302 Found", &[("location", &object)], Vec::new()).await;
+        let capture = capture().with_client(reqwest::Client::new());
+
+        let result = capture
+            .fetch_stored_url(
+                &format!("{server}/key/token/pdf?s3Redirect=true"),
+                RequestType::Pdf,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(CaptureError::RedirectFollowed { ref location }) if *location == object
+        ));
+    }
+
+    #[test]
+    fn test_redirect_without_location_is_an_error() {
+        let info = FetchInfo {
+            status: 302,
+            ..Default::default()
+        };
+        assert!(matches!(
+            stored_capture(Vec::new(), &info),
+            Err(CaptureError::RequestFailed { status: 302, .. })
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+//! Feature-gate checks, run for each feature combination by
+//! `scripts/check-features.sh`.
+
+use capture_rust::{Capture, RequestType, ScreenshotOptions};
+
+/// URL building is the core of the crate and must not depend on any
+/// optional feature.
+#[test]
+fn test_url_building_needs_no_optional_features() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+
+    let url = capture
+        .build_screenshot_url(
+            "https://example.com",
+            Some(&ScreenshotOptions {
+                full: Some(true),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+    assert!(url.starts_with("https://cdn.capture.page/test_key/"));
+    assert!(url.contains("/image?"));
+
+    let request = capture_rust::CaptureRequest::new(RequestType::Metadata, "https://example.com");
+    assert!(capture.build_request_url(&request).is_ok());
+}
+
+/// Without the `http` feature the crate still signs the same URLs.
+#[cfg(not(feature = "http"))]
+#[test]
+fn test_url_signing_without_http() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    assert_eq!(
+        capture.build_image_url("https://example.com", None).unwrap(),
+        "https://cdn.capture.page/test_key/45a53efd248f8e9c303bcfa770b9f28b/image?url=https%3A%2F%2Fexample.com"
+    );
+    assert_eq!(
+        capture
+            .clone()
+            .with_edge()
+            .build_pdf_url("https://example.com", None)
+            .unwrap(),
+        "https://edge.capture.page/test_key/45a53efd248f8e9c303bcfa770b9f28b/pdf?url=https%3A%2F%2Fexample.com"
+    );
+    assert!(
+        Capture::url_builder_only("test_key".to_string(), "test_secret".to_string())
+            .build_content_url("https://example.com", None)
+            .is_ok()
+    );
+    assert_eq!(
+        capture_rust::signing::sign_capture_url(
+            "test_key",
+            "test_secret",
+            RequestType::Image,
+            "https://example.com",
+            &Default::default(),
+        )
+        .unwrap(),
+        capture
+            .build_image_url("https://example.com", None)
+            .unwrap()
+    );
+}
+
+#[cfg(feature = "tower")]
+#[test]
+fn test_tower_feature_exposes_capture_service() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    let _service: capture_rust::CaptureService = capture_rust::CaptureService::new(capture);
+}
+
+#[cfg(feature = "readability")]
+#[test]
+fn test_readability_feature_exposes_reader_view() {
+    let content: capture_rust::ContentResponse =
+        serde_json::from_str(r#"{"success":true,"html":"<p>Hi</p>"}"#).unwrap();
+    // Filled by the fetch methods, not by plain deserialization.
+    assert!(content.reader_html.is_none() && content.reader_markdown.is_none());
+}
+
+#[cfg(feature = "links")]
+#[test]
+fn test_links_feature_exposes_links() {
+    let content: capture_rust::ContentResponse =
+        serde_json::from_str(r#"{"success":true,"html":"<a href=\"/x\">X</a>"}"#).unwrap();
+    // Filled by the fetch methods, not by plain deserialization.
+    let _: Option<Vec<capture_rust::Link>> = content.links;
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_feature_parses_published_at() {
+    let page = capture_rust::PageMetadata {
+        published_at: Some("2024-10-17T00:00:00Z".to_string()),
+        ..Default::default()
+    };
+    assert!(page.published_at_datetime().is_some());
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_zeroize_feature_keeps_signing_unchanged() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    // Clones share the credentials; dropping one must not wipe them.
+    drop(capture.clone());
+
+    assert_eq!(
+        capture.build_image_url("https://example.com", None).unwrap(),
+        "https://cdn.capture.page/test_key/45a53efd248f8e9c303bcfa770b9f28b/image?url=https%3A%2F%2Fexample.com"
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml_feature_reads_config_files() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/capture.toml");
+    let capture =
+        Capture::from_config(capture_rust::CaptureConfig::from_path(path).unwrap()).unwrap();
+    assert!(capture
+        .build_image_url("https://example.com", None)
+        .unwrap()
+        .starts_with("https://edge.capture.page/config_key/"));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_feature_exposes_blocking_client() {
+    let capture =
+        capture_rust::blocking::Capture::new("test_key".to_string(), "test_secret".to_string());
+    assert_eq!(
+        capture.build_image_url("https://example.com", None).unwrap(),
+        "https://cdn.capture.page/test_key/45a53efd248f8e9c303bcfa770b9f28b/image?url=https%3A%2F%2Fexample.com"
+    );
+}
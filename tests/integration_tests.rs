@@ -1,4 +1,11 @@
-use capture_rust::{Capture, CaptureOptions, CreateSessionOptions, SessionActionPayload};
+use capture_rust::{
+    Capture, CaptureError, CaptureOptions, ContentOptions, MetadataOptions, ParsedCaptureUrl,
+    PdfOptions,
+};
+#[cfg(feature = "http")]
+use capture_rust::{
+    CreateSessionOptions, ResourceType, ScreenshotOptions, SessionActionPayload, UserAgentPreset,
+};
 use std::collections::HashMap;
 
 #[tokio::test]
@@ -133,6 +140,173 @@ async fn test_request_options_filtering() {
     assert!(!pdf_url.contains("empty="));
 }
 
+#[tokio::test]
+async fn test_pdf_structured_wait_and_visual_options() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    let options = PdfOptions {
+        wait_for: Some("#content".to_string()),
+        dark_mode: Some(true),
+        block_ads: Some(true),
+        fresh: Some(true),
+        ..PdfOptions::new().format("A4")
+    };
+
+    let pdf_url = capture
+        .build_pdf_url_structured("https://example.com", Some(&options))
+        .unwrap();
+
+    assert!(pdf_url.contains("/pdf?"));
+    assert!(pdf_url.contains("waitFor=%23content"));
+    assert!(pdf_url.contains("darkMode=true"));
+    assert!(pdf_url.contains("blockAds=true"));
+    assert!(pdf_url.contains("fresh=true"));
+}
+
+#[test]
+fn test_content_structured_blocking_and_detection_options() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    let options = ContentOptions::new()
+        .wait_for("#content")
+        .block_ads(true)
+        .block_cookie_banners(true)
+        .bypass_bot_detection(true)
+        .fresh(true);
+
+    let content_url = capture
+        .build_content_url_structured("https://example.com", Some(&options))
+        .unwrap();
+
+    assert!(content_url.contains("/content?"));
+    assert!(content_url.contains("blockAds=true"));
+    assert!(content_url.contains("blockCookieBanners=true"));
+    assert!(content_url.contains("bypassBotDetection=true"));
+    assert!(content_url.contains("fresh=true"));
+
+    // Re-signing the same parameters reproduces the token.
+    let parsed = ParsedCaptureUrl::parse(&content_url).unwrap();
+    let resigned = capture
+        .build_content_url(&parsed.url, Some(&parsed.options))
+        .unwrap();
+    assert_eq!(resigned, content_url);
+}
+
+#[test]
+fn test_metadata_structured_request_options() {
+    let mut additional = HashMap::new();
+    additional.insert("stealth".to_string(), serde_json::Value::Bool(false));
+    let options = MetadataOptions {
+        http_auth: Some("user:pass".to_string()),
+        user_agent: Some("bot/1.0".to_string()),
+        delay: Some(3),
+        wait_for: Some("#content".to_string()),
+        stealth: Some(true),
+        fresh: Some(true),
+        language: Some("de".to_string()),
+        include_structured_data: Some(true),
+        additional_options: Some(additional),
+    };
+
+    let request_options = options.to_request_options();
+    assert_eq!(request_options["httpAuth"], "user:pass");
+    assert_eq!(request_options["userAgent"], "bot/1.0");
+    assert_eq!(request_options["delay"], 3);
+    assert_eq!(request_options["waitFor"], "#content");
+    assert_eq!(request_options["fresh"], true);
+    assert_eq!(request_options["lang"], "de");
+    assert_eq!(request_options["structuredData"], true);
+    // Additional options are merged last and win.
+    assert_eq!(request_options["stealth"], false);
+
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    let metadata_url = capture
+        .build_metadata_url_structured("https://example.com", Some(&options))
+        .unwrap();
+    assert!(metadata_url.contains("/metadata?"));
+    assert!(metadata_url.contains("waitFor=%23content"));
+
+    let parsed = MetadataOptions::from_query_str("delay=3&fresh=true&userAgent=bot").unwrap();
+    assert_eq!(parsed.delay, Some(3));
+    assert_eq!(parsed.fresh, Some(true));
+
+    let too_long = MetadataOptions {
+        delay: Some(60),
+        ..Default::default()
+    };
+    assert!(matches!(
+        capture.build_metadata_url_structured("https://example.com", Some(&too_long)),
+        Err(CaptureError::InvalidOption { field, .. }) if field == "delay"
+    ));
+}
+
+#[test]
+fn test_fresh_reaches_content_and_metadata_urls() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    let url = "https://example.com";
+
+    for fresh in [true, false] {
+        let expected = format!("fresh={fresh}");
+        let content_url = capture
+            .build_content_url_structured(url, Some(&ContentOptions::new().fresh(fresh)))
+            .unwrap();
+        assert!(content_url.contains(&expected), "{content_url}");
+        let metadata_url = capture
+            .build_metadata_url_structured(url, Some(&MetadataOptions::new().fresh(fresh)))
+            .unwrap();
+        assert!(metadata_url.contains(&expected), "{metadata_url}");
+    }
+
+    // Unset, the API's default caching applies.
+    let metadata_url = capture
+        .build_metadata_url_structured(url, Some(&MetadataOptions::new().stealth(true)))
+        .unwrap();
+    assert!(!metadata_url.contains("fresh"));
+}
+
+#[test]
+fn test_metadata_language_hint() {
+    let capture = Capture::new("test_key".to_string(), "test_secret".to_string());
+    let url = "https://example.com";
+
+    for tag in ["en", "pt-BR", "zh-Hant-TW", "es-419", "de-CH-1996"] {
+        let options = MetadataOptions::new().language(tag);
+        assert!(options.validate().is_ok(), "{tag}");
+    }
+    let metadata_url = capture
+        .build_metadata_url_structured(
+            url,
+            Some(&MetadataOptions::new().language("pt-BR").fresh(true)),
+        )
+        .unwrap();
+    assert_eq!(
+        metadata_url,
+        "https://cdn.capture.page/test_key/c86a8968e6a442ee203bc4cbd449a000/metadata?fresh=true&lang=pt-BR&url=https%3A%2F%2Fexample.com"
+    );
+
+    for tag in [
+        "",
+        "e",
+        "en_US",
+        "en-",
+        "-en",
+        "englishtext",
+        "en-US!",
+        "12",
+    ] {
+        assert!(
+            matches!(
+                capture.build_metadata_url_structured(url, Some(&MetadataOptions::new().language(tag))),
+                Err(CaptureError::InvalidOption { field, .. }) if field == "language"
+            ),
+            "{tag:?}"
+        );
+    }
+
+    let parsed = MetadataOptions::from_query_str("lang=fr-CA").unwrap();
+    assert_eq!(parsed.language.as_deref(), Some("fr-CA"));
+    assert!(MetadataOptions::from_query_str("lang=fr_CA").is_err());
+}
+
+#[cfg(feature = "http")]
 #[tokio::test]
 #[ignore = "requires live Capture credentials and creates a billable browser session"]
 async fn test_live_session_screenshot_example_dot_com() {
@@ -213,3 +387,123 @@ async fn test_live_session_screenshot_example_dot_com() {
 
     result.expect("live session screenshot flow");
 }
+
+/// Reads live credentials, returning `None` so the contract test can skip
+/// cleanly when they are absent.
+#[cfg(feature = "http")]
+fn live_credentials() -> Option<(String, String)> {
+    let key = std::env::var("CAPTURE_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())?;
+    let secret = std::env::var("CAPTURE_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())?;
+    Some((key, secret))
+}
+
+/// Strips credentials and request tokens from text that may end up in test
+/// failure output.
+fn redact(text: &str, key: &str, secret: &str) -> String {
+    let redacted = text.replace(secret, "[secret]").replace(key, "[key]");
+    let mut segments: Vec<&str> = redacted.split('/').collect();
+    for index in 1..segments.len() {
+        if segments[index - 1] == "[key]" {
+            segments[index] = "[token]";
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(feature = "http")]
+async fn get_live(url: &str, key: &str, secret: &str) -> (u16, String) {
+    let response = reqwest::get(url)
+        .await
+        .unwrap_or_else(|e| panic!("request failed: {}", redact(&e.to_string(), key, secret)));
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    (response.status().as_u16(), content_type)
+}
+
+#[test]
+fn test_redact_hides_credentials_and_tokens() {
+    let url = "https://cdn.capture.page/my_key/0123abcd/image?userAgent=my_secret";
+
+    assert_eq!(
+        redact(url, "my_key", "my_secret"),
+        "https://cdn.capture.page/[key]/[token]/image?userAgent=[secret]"
+    );
+}
+
+#[cfg(feature = "http")]
+#[tokio::test]
+#[ignore = "requires live Capture credentials and performs billable captures"]
+async fn test_live_cdn_and_edge_signature_contract() {
+    let Some((key, secret)) = live_credentials() else {
+        eprintln!("skipping: CAPTURE_KEY and CAPTURE_SECRET are not set");
+        return;
+    };
+    let cdn = Capture::new(key.clone(), secret.clone());
+    let edge = Capture::new(key.clone(), secret.clone()).with_edge();
+
+    let screenshot_options = ScreenshotOptions {
+        vw: Some(1280),
+        vh: Some(720),
+        scale_factor: Some(1.5),
+        full: Some(true),
+        max_height: Some(5000),
+        delay_ms: Some(250),
+        wait_for: Some("body".to_string()),
+        wait_for_timeout: Some(5000),
+        dark_mode: Some(true),
+        block_cookie_banners: Some(true),
+        block_ads: Some(true),
+        block_resources: Some(vec![ResourceType::Font, ResourceType::Media]),
+        block_urls: Some(vec!["*.doubleclick.net".to_string()]),
+        image_type: Some("jpeg".to_string()),
+        quality: Some(80),
+        resize_width: Some(640),
+        user_agent: Some(UserAgentPreset::ChromeDesktop.as_str().to_string()),
+        fresh: Some(true),
+        ..Default::default()
+    };
+
+    let mut content_types = Vec::new();
+    for capture in [&cdn, &edge] {
+        let url = capture
+            .build_screenshot_url("https://example.com", Some(&screenshot_options))
+            .unwrap();
+        let (status, content_type) = get_live(&url, &key, &secret).await;
+        assert!(
+            (200..300).contains(&status),
+            "status {status} for {}",
+            redact(&url, &key, &secret)
+        );
+        content_types.push(content_type);
+    }
+    assert_eq!(content_types[0], content_types[1]);
+
+    let content_url = cdn
+        .build_content_url_structured(
+            "https://example.com",
+            Some(&ContentOptions {
+                wait_for: Some("h1".to_string()),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+    let metadata_url = edge
+        .build_metadata_url_structured("https://example.com", Some(&MetadataOptions::default()))
+        .unwrap();
+    for url in [content_url, metadata_url] {
+        let (status, content_type) = get_live(&url, &key, &secret).await;
+        assert!(
+            (200..300).contains(&status) && content_type.contains("json"),
+            "status {status} ({content_type}) for {}",
+            redact(&url, &key, &secret)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Compile-time check of the crate-root public API. Every item here must stay
+//! reachable at the same path with the same signature; removing or changing
+//! one fails to compile. Checked with the `http` feature, which the
+//! default features include.
+#![cfg(feature = "http")]
+
+use capture_rust::{
+    Capture, CaptureError, CaptureOptions, ContentOptions, ContentResponse, CreateSessionOptions,
+    FullPagePlan, MetadataOptions, MetadataResponse, PdfOptions, RequestOptions, RequestType,
+    Result, ScreenshotOptions, ScreenshotPair, SessionActionPayload, SessionActionResponse,
+    SessionResponse, UserAgentPreset,
+};
+use std::time::Duration;
+
+type BuildRaw = fn(&Capture, &str, Option<&RequestOptions>) -> Result<String>;
+
+#[test]
+fn test_public_constructors_and_builders() {
+    let _: fn(String, String) -> Capture = Capture::new;
+    let _: fn(String, String, CaptureOptions) -> Capture = Capture::with_options;
+    let _: fn(Capture) -> Capture = Capture::with_edge;
+    let _: fn(Capture, Duration) -> Capture = Capture::with_timeout;
+    let _: fn(Capture, reqwest::Client) -> Capture = Capture::with_client;
+
+    let _: fn() -> CaptureOptions = CaptureOptions::new;
+    let _: fn(CaptureOptions) -> CaptureOptions = CaptureOptions::with_edge;
+    let _: fn(CaptureOptions, Duration) -> CaptureOptions = CaptureOptions::with_timeout;
+    let _: fn(CaptureOptions, reqwest::Client) -> CaptureOptions = CaptureOptions::with_client;
+
+    let options = CaptureOptions::default();
+    let _: (
+        bool,
+        Option<Duration>,
+        Option<std::sync::Arc<reqwest::Client>>,
+    ) = (options.use_edge, options.timeout, options.client);
+}
+
+#[test]
+fn test_public_url_builders() {
+    let _: BuildRaw = Capture::build_image_url;
+    let _: BuildRaw = Capture::build_pdf_url;
+    let _: BuildRaw = Capture::build_content_url;
+    let _: BuildRaw = Capture::build_metadata_url;
+    let _: BuildRaw = Capture::build_animated_url;
+    let _: fn(&Capture, &str, Option<&ScreenshotOptions>) -> Result<String> =
+        Capture::build_screenshot_url;
+    let _: fn(&Capture, &str, Option<&PdfOptions>) -> Result<String> =
+        Capture::build_pdf_url_structured;
+    let _: fn(&Capture, &str, Option<&ContentOptions>) -> Result<String> =
+        Capture::build_content_url_structured;
+    let _: fn(&Capture, &str, Option<&MetadataOptions>) -> Result<String> =
+        Capture::build_metadata_url_structured;
+    let _: fn(&Capture, &str, &ScreenshotOptions) -> Result<ScreenshotPair<String>> =
+        Capture::build_screenshot_pair_urls;
+}
+
+#[allow(dead_code)]
+async fn public_fetch_methods(capture: &Capture) -> Result<()> {
+    let raw: Option<&RequestOptions> = None;
+    let _: Vec<u8> = capture.fetch_image("", raw).await?;
+    let _: Vec<u8> = capture.fetch_pdf("", raw).await?;
+    let _: ContentResponse = capture.fetch_content("", raw).await?;
+    let _: MetadataResponse = capture.fetch_metadata("", raw).await?;
+    let _: Vec<u8> = capture.fetch_animated("", raw).await?;
+    let _: Vec<u8> = capture.fetch_screenshot("", None).await?;
+    let _: Vec<u8> = capture.fetch_pdf_structured("", None).await?;
+    let _: ContentResponse = capture.fetch_content_structured("", None).await?;
+    let _: MetadataResponse = capture.fetch_metadata_structured("", None).await?;
+    let base = ScreenshotOptions::default();
+    let _: ScreenshotPair = capture.fetch_screenshot_pair("", &base).await?;
+    let _: ScreenshotPair<Result<Vec<u8>>> = capture.fetch_screenshot_pair_partial("", &base).await;
+    let _: FullPagePlan = capture.plan_full_page_capture("").await?;
+
+    let _: SessionResponse = capture
+        .create_session(Some(&CreateSessionOptions::default()))
+        .await?;
+    let _: SessionResponse = capture.get_session("").await?;
+    let _: SessionResponse = capture.close_session("").await?;
+    let payload = SessionActionPayload::new();
+    let _: SessionActionResponse = capture.execute_action("", "", Some(&payload)).await?;
+    Ok(())
+}
+
+#[test]
+fn test_public_option_types() {
+    let _: fn(&ScreenshotOptions) -> RequestOptions = ScreenshotOptions::to_request_options;
+    let _: fn(&ScreenshotOptions) -> Result<RequestOptions> =
+        ScreenshotOptions::try_to_request_options;
+    let _: fn(&ScreenshotOptions) -> Result<()> = ScreenshotOptions::validate;
+    let _: fn(ScreenshotOptions, UserAgentPreset) -> ScreenshotOptions =
+        ScreenshotOptions::user_agent_preset;
+    let _: fn(&PdfOptions) -> RequestOptions = PdfOptions::to_request_options;
+    let _: fn(&ContentOptions) -> RequestOptions = ContentOptions::to_request_options;
+    let _: fn(&MetadataOptions) -> RequestOptions = MetadataOptions::to_request_options;
+    let _: fn(&UserAgentPreset) -> &'static str = UserAgentPreset::as_str;
+
+    let _ = [
+        RequestType::Image,
+        RequestType::Pdf,
+        RequestType::Content,
+        RequestType::Metadata,
+        RequestType::Animated,
+    ];
+}
+
+#[test]
+fn test_public_response_and_error_types() {
+    let _: fn(&MetadataResponse) -> Option<u32> = MetadataResponse::content_height;
+    let _: fn(&MetadataResponse) -> Option<u32> = MetadataResponse::content_width;
+
+    let error = CaptureError::InvalidOption {
+        field: String::new(),
+        reason: String::new(),
+    };
+    assert!(matches!(
+        error,
+        CaptureError::HttpError(_)
+            | CaptureError::UrlError(_)
+            | CaptureError::MissingCredentials
+            | CaptureError::MissingUrl
+            | CaptureError::MissingSessionId
+            | CaptureError::InvalidUrl
+            | CaptureError::JsonError(_)
+            | CaptureError::InvalidOption { .. }
+            | CaptureError::RequestFailed { .. }
+            | CaptureError::SessionsApiError { .. }
+    ));
+}
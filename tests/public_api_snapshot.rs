@@ -0,0 +1,704 @@
+//! Snapshot of the crate's public API as rustdoc sees it, compared line for
+//! line with `tests/fixtures/public_api.txt`. Complements the compile-time
+//! checks in `public_api.rs`: any change to the public surface, including an
+//! added item or trait impl, shows up as a fixture diff in review.
+//!
+//! Items are listed at the paths users reach them by, with their signatures,
+//! so moving code between modules or reformatting it changes nothing. The
+//! API is read from rustdoc's JSON output, which needs a nightly toolchain
+//! (`rustup toolchain install nightly`); it is documented with all features.
+//!
+//! After an intended change, regenerate the fixture with
+//! `UPDATE_PUBLIC_API=1 cargo test --test public_api_snapshot`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde_json::Value;
+
+const FIXTURE: &str = "tests/fixtures/public_api.txt";
+
+/// Auto traits whose impls are part of the API. Others, such as unstable
+/// ones, vary with the nightly toolchain.
+const AUTO_TRAITS: &[&str] = &["Send", "Sync", "Unpin", "UnwindSafe", "RefUnwindSafe"];
+
+/// Dependencies that, depending on the resolved version, define items the
+/// crate names through another one. Without a committed lockfile, paths into
+/// them are rendered through the crate the dependency is declared as.
+const FACADES: &[(&str, &str)] = &[("serde_core", "serde")];
+
+#[test]
+fn test_public_api_matches_snapshot() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let snapshot = public_api(&rustdoc_json(root));
+
+    let fixture = root.join(FIXTURE);
+    if std::env::var_os("UPDATE_PUBLIC_API").is_some() {
+        fs::write(&fixture, &snapshot).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&fixture).unwrap();
+    if snapshot != expected {
+        let changed: Vec<_> = diff(&expected, &snapshot).collect();
+        panic!(
+            "the public API differs from {FIXTURE}; rerun with UPDATE_PUBLIC_API=1 if \
+             intended:\n{}",
+            changed.join("\n")
+        );
+    }
+}
+
+/// Documents the library with nightly rustdoc into its own target
+/// directory, which the running `cargo test` does not hold a lock on.
+fn rustdoc_json(root: &Path) -> Value {
+    let target = root.join("target").join("public-api");
+    let output = Command::new("cargo")
+        .args(["+nightly", "rustdoc", "--lib", "--all-features", "--quiet"])
+        .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+        .args(["--cap-lints", "allow"])
+        .current_dir(root)
+        .env("CARGO_TARGET_DIR", &target)
+        .env_remove("RUSTDOCFLAGS")
+        .output()
+        .expect("cargo is on PATH");
+    assert!(
+        output.status.success(),
+        "nightly rustdoc failed; is a nightly toolchain installed?\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json = fs::read(target.join("doc").join("capture_rust.json")).unwrap();
+    serde_json::from_slice(&json).unwrap()
+}
+
+/// One line per public item, trait impl and field, sorted.
+fn public_api(krate: &Value) -> String {
+    let api = Api::new(krate);
+    let mut lines = BTreeSet::new();
+    api.module(&krate["root"], "capture_rust", &mut lines);
+
+    let mut snapshot = String::new();
+    for line in lines {
+        snapshot.push_str(&line);
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+struct Api<'a> {
+    index: &'a serde_json::Map<String, Value>,
+    paths: &'a serde_json::Map<String, Value>,
+    /// Where each of the crate's items is reachable from outside.
+    public_paths: HashMap<String, String>,
+}
+
+impl<'a> Api<'a> {
+    fn new(krate: &'a Value) -> Self {
+        let mut api = Self {
+            index: krate["index"].as_object().unwrap(),
+            paths: krate["paths"].as_object().unwrap(),
+            public_paths: HashMap::new(),
+        };
+        let mut public_paths = HashMap::new();
+        api.collect_paths(&krate["root"], "capture_rust", &mut public_paths);
+        api.public_paths = public_paths;
+        api
+    }
+
+    fn item(&self, id: &Value) -> Option<&'a Value> {
+        self.index.get(&id.to_string())
+    }
+
+    /// The public items of a module, with re-exports resolved to the items
+    /// they name.
+    fn members(&self, module: &Value) -> Vec<(String, &'a Value)> {
+        let mut members = Vec::new();
+        let Some(module) = self.item(module) else {
+            return members;
+        };
+        for id in module["inner"]["module"]["items"].as_array().unwrap() {
+            let Some(item) = self.item(id) else { continue };
+            if item["visibility"] != "public" {
+                continue;
+            }
+            match &item["inner"]["use"] {
+                Value::Null => members.push((item["name"].as_str().unwrap().to_string(), item)),
+                reexport if reexport["is_glob"] == true => {
+                    members.extend(self.members(&reexport["id"]));
+                }
+                reexport => {
+                    if let Some(target) = self.item(&reexport["id"]) {
+                        members.push((reexport["name"].as_str().unwrap().to_string(), target));
+                    }
+                }
+            }
+        }
+        members
+    }
+
+    fn collect_paths(&self, module: &Value, path: &str, paths: &mut HashMap<String, String>) {
+        for (name, item) in self.members(module) {
+            let item_path = format!("{path}::{name}");
+            if !item["inner"]["module"].is_null() {
+                self.collect_paths(&item["id"], &item_path, paths);
+            }
+            // Items reachable at several paths are named by the shortest.
+            let id = item["id"].to_string();
+            let shorter = paths.get(&id).is_none_or(|known| {
+                (known.matches("::").count(), known.as_str())
+                    > (item_path.matches("::").count(), item_path.as_str())
+            });
+            if shorter {
+                paths.insert(id, item_path);
+            }
+        }
+    }
+
+    fn module(&self, module: &Value, path: &str, lines: &mut BTreeSet<String>) {
+        for (name, item) in self.members(module) {
+            let path = format!("{path}::{name}");
+            self.declaration(item, &path, lines);
+        }
+    }
+
+    fn declaration(&self, item: &Value, path: &str, lines: &mut BTreeSet<String>) {
+        let (kind, inner) = item["inner"].as_object().unwrap().iter().next().unwrap();
+        match kind.as_str() {
+            "module" => {
+                lines.insert(format!("pub mod {path}"));
+                self.module(&item["id"], path, lines);
+            }
+            "struct" => {
+                let generics = self.generics(&inner["generics"]);
+                let kind = &inner["kind"];
+                if let Some(fields) = kind["tuple"].as_array() {
+                    let fields: Vec<_> = fields
+                        .iter()
+                        .map(|field| match self.item(field) {
+                            Some(field) => {
+                                format!("pub {}", self.ty(&field["inner"]["struct_field"]))
+                            }
+                            None => "_".to_string(),
+                        })
+                        .collect();
+                    lines.insert(format!(
+                        "pub struct {path}{generics}({})",
+                        fields.join(", ")
+                    ));
+                } else {
+                    lines.insert(format!("pub struct {path}{generics}"));
+                }
+                for field in kind["plain"]["fields"].as_array().into_iter().flatten() {
+                    if let Some(field) = self
+                        .item(field)
+                        .filter(|field| field["visibility"] == "public")
+                    {
+                        lines.insert(format!(
+                            "pub {path}::{}: {}",
+                            field["name"].as_str().unwrap(),
+                            self.ty(&field["inner"]["struct_field"])
+                        ));
+                    }
+                }
+                self.impls(&inner["impls"], path, lines);
+            }
+            "enum" => {
+                lines.insert(format!(
+                    "pub enum {path}{}",
+                    self.generics(&inner["generics"])
+                ));
+                for variant in inner["variants"].as_array().unwrap() {
+                    let variant = self.item(variant).unwrap();
+                    lines.insert(format!(
+                        "pub {path}::{}{}",
+                        variant["name"].as_str().unwrap(),
+                        self.variant_fields(&variant["inner"]["variant"]["kind"])
+                    ));
+                }
+                self.impls(&inner["impls"], path, lines);
+            }
+            "trait" => {
+                let bounds = self.bounds(&inner["bounds"]);
+                let bounds = if bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {bounds}")
+                };
+                let unsafety = if inner["is_unsafe"] == true {
+                    "unsafe "
+                } else {
+                    ""
+                };
+                lines.insert(format!(
+                    "pub {unsafety}trait {path}{}{bounds}",
+                    self.generics(&inner["generics"])
+                ));
+                for member in inner["items"].as_array().unwrap() {
+                    self.associated(self.item(member).unwrap(), path, lines);
+                }
+            }
+            "function" => {
+                lines.insert(self.function(inner, path));
+            }
+            "constant" => {
+                lines.insert(format!("pub const {path}: {}", self.ty(&inner["type"])));
+            }
+            "static" => {
+                lines.insert(format!("pub static {path}: {}", self.ty(&inner["type"])));
+            }
+            "type_alias" => {
+                lines.insert(format!(
+                    "pub type {path}{} = {}",
+                    self.generics(&inner["generics"]),
+                    self.ty(&inner["type"])
+                ));
+            }
+            "macro" | "proc_macro" => {
+                lines.insert(format!("pub macro {path}!"));
+            }
+            _ => {}
+        }
+    }
+
+    fn variant_fields(&self, kind: &Value) -> String {
+        if let Some(fields) = kind["tuple"].as_array() {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|field| match self.item(field) {
+                    Some(field) => self.ty(&field["inner"]["struct_field"]),
+                    None => "_".to_string(),
+                })
+                .collect();
+            return format!("({})", fields.join(", "));
+        }
+        if let Some(fields) = kind["struct"]["fields"].as_array() {
+            let fields: Vec<_> = fields
+                .iter()
+                .filter_map(|field| self.item(field))
+                .map(|field| {
+                    format!(
+                        "{}: {}",
+                        field["name"].as_str().unwrap(),
+                        self.ty(&field["inner"]["struct_field"])
+                    )
+                })
+                .collect();
+            return format!(" {{ {} }}", fields.join(", "));
+        }
+        String::new()
+    }
+
+    /// Inherent methods and constants, and the traits the type implements,
+    /// including the stable auto traits. Blanket impls are left out.
+    fn impls(&self, impls: &Value, path: &str, lines: &mut BTreeSet<String>) {
+        for id in impls.as_array().unwrap() {
+            let item = self.item(id).unwrap();
+            let inner = &item["inner"]["impl"];
+            if !inner["blanket_impl"].is_null() {
+                continue;
+            }
+            if inner["is_synthetic"] == true
+                && !AUTO_TRAITS.contains(&inner["trait"]["path"].as_str().unwrap_or_default())
+            {
+                continue;
+            }
+            if inner["trait"].is_null() {
+                for member in inner["items"].as_array().unwrap() {
+                    let member = self.item(member).unwrap();
+                    if member["visibility"] == "public" {
+                        self.associated(member, path, lines);
+                    }
+                }
+                continue;
+            }
+            let negative = if inner["is_negative"] == true {
+                "!"
+            } else {
+                ""
+            };
+            lines.insert(format!(
+                "impl{} {negative}{} for {}{}",
+                self.generics(&inner["generics"]),
+                self.path(&inner["trait"]),
+                self.ty(&inner["for"]),
+                self.where_clause(&inner["generics"])
+            ));
+        }
+    }
+
+    fn associated(&self, member: &Value, path: &str, lines: &mut BTreeSet<String>) {
+        let name = member["name"].as_str().unwrap();
+        let (kind, inner) = member["inner"].as_object().unwrap().iter().next().unwrap();
+        let path = format!("{path}::{name}");
+        match kind.as_str() {
+            "function" => {
+                lines.insert(self.function(inner, &path));
+            }
+            "assoc_const" => {
+                lines.insert(format!("pub const {path}: {}", self.ty(&inner["type"])));
+            }
+            "assoc_type" => {
+                let bounds = self.bounds(&inner["bounds"]);
+                let bounds = if bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {bounds}")
+                };
+                let default = match &inner["type"] {
+                    Value::Null => String::new(),
+                    ty => format!(" = {}", self.ty(ty)),
+                };
+                lines.insert(format!("pub type {path}{bounds}{default}"));
+            }
+            _ => {}
+        }
+    }
+
+    fn function(&self, inner: &Value, path: &str) -> String {
+        let header = &inner["header"];
+        let mut qualifiers = String::new();
+        for (flag, keyword) in [
+            ("is_const", "const "),
+            ("is_async", "async "),
+            ("is_unsafe", "unsafe "),
+        ] {
+            if header[flag] == true {
+                qualifiers.push_str(keyword);
+            }
+        }
+        let sig = &inner["sig"];
+        let inputs: Vec<_> = sig["inputs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|input| {
+                let (name, ty) = (input[0].as_str().unwrap(), &input[1]);
+                if name != "self" {
+                    return format!("{name}: {}", self.ty(ty));
+                }
+                match self.ty(ty).as_str() {
+                    "Self" => "self".to_string(),
+                    "&Self" => "&self".to_string(),
+                    "&mut Self" => "&mut self".to_string(),
+                    other => format!("self: {other}"),
+                }
+            })
+            .collect();
+        let output = match &sig["output"] {
+            Value::Null => String::new(),
+            ty => format!(" -> {}", self.ty(ty)),
+        };
+        format!(
+            "pub {qualifiers}fn {path}{}({}){output}{}",
+            self.generics(&inner["generics"]),
+            inputs.join(", "),
+            self.where_clause(&inner["generics"])
+        )
+    }
+
+    fn generics(&self, generics: &Value) -> String {
+        let params: Vec<_> = generics["params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|param| {
+                let name = param["name"].as_str().unwrap();
+                let kind = &param["kind"];
+                if let Some(lifetime) = kind.get("lifetime") {
+                    let outlives: Vec<_> = lifetime["outlives"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|bound| bound.as_str().unwrap())
+                        .collect();
+                    return Some(if outlives.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{name}: {}", outlives.join(" + "))
+                    });
+                }
+                if let Some(ty) = kind.get("type") {
+                    if ty["is_synthetic"] == true {
+                        return None;
+                    }
+                    let bounds = self.bounds(&ty["bounds"]);
+                    let mut param = if bounds.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{name}: {bounds}")
+                    };
+                    if !ty["default"].is_null() {
+                        param.push_str(&format!(" = {}", self.ty(&ty["default"])));
+                    }
+                    return Some(param);
+                }
+                let constant = &kind["const"];
+                Some(format!("const {name}: {}", self.ty(&constant["type"])))
+            })
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
+
+    fn where_clause(&self, generics: &Value) -> String {
+        let predicates: Vec<_> = generics["where_predicates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|predicate| {
+                if let Some(bound) = predicate.get("bound_predicate") {
+                    return format!(
+                        "{}: {}",
+                        self.ty(&bound["type"]),
+                        self.bounds(&bound["bounds"])
+                    );
+                }
+                if let Some(lifetime) = predicate.get("lifetime_predicate") {
+                    let outlives: Vec<_> = lifetime["outlives"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|bound| bound.as_str().unwrap())
+                        .collect();
+                    return format!(
+                        "{}: {}",
+                        lifetime["lifetime"].as_str().unwrap(),
+                        outlives.join(" + ")
+                    );
+                }
+                let equality = &predicate["eq_predicate"];
+                format!(
+                    "{} = {}",
+                    self.ty(&equality["lhs"]),
+                    self.term(&equality["rhs"])
+                )
+            })
+            .collect();
+        if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" where {}", predicates.join(", "))
+        }
+    }
+
+    fn bounds(&self, bounds: &Value) -> String {
+        let bounds: Vec<_> = bounds
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bound| {
+                if let Some(lifetime) = bound["outlives"].as_str() {
+                    return lifetime.to_string();
+                }
+                if let Some(captures) = bound["use"].as_array() {
+                    let captures: Vec<_> = captures
+                        .iter()
+                        .map(|capture| {
+                            capture["lifetime"]
+                                .as_str()
+                                .or(capture["param"].as_str())
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    return format!("use<{}>", captures.join(", "));
+                }
+                let bound = &bound["trait_bound"];
+                let modifier = match bound["modifier"].as_str() {
+                    Some("maybe") => "?",
+                    Some("maybe_const") => "~const ",
+                    _ => "",
+                };
+                format!("{modifier}{}", self.path(&bound["trait"]))
+            })
+            .collect();
+        bounds.join(" + ")
+    }
+
+    /// A path to an item: its public path when it is the crate's own, the
+    /// full path of its definition otherwise, e.g. `alloc::string::String`.
+    fn path(&self, path: &Value) -> String {
+        let id = path["id"].to_string();
+        let name =
+            self.public_paths
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| match self.paths.get(&id) {
+                    Some(summary) if summary["crate_id"] != 0 => summary["path"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|segment| segment.as_str().unwrap())
+                        .enumerate()
+                        .map(
+                            |(i, segment)| match FACADES.iter().find(|(c, _)| *c == segment) {
+                                Some((_, facade)) if i == 0 => facade,
+                                _ => segment,
+                            },
+                        )
+                        .collect::<Vec<_>>()
+                        .join("::"),
+                    _ => path["path"].as_str().unwrap().to_string(),
+                });
+        format!("{name}{}", self.generic_args(&path["args"]))
+    }
+
+    fn generic_args(&self, args: &Value) -> String {
+        if let Some(angle) = args.get("angle_bracketed") {
+            let mut rendered: Vec<_> = angle["args"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|arg| {
+                    if let Some(lifetime) = arg["lifetime"].as_str() {
+                        return lifetime.to_string();
+                    }
+                    if let Some(constant) = arg.get("const") {
+                        return constant["expr"].as_str().unwrap_or("_").to_string();
+                    }
+                    if arg == "infer" {
+                        return "_".to_string();
+                    }
+                    self.ty(&arg["type"])
+                })
+                .collect();
+            for constraint in angle["constraints"].as_array().unwrap() {
+                let name = format!(
+                    "{}{}",
+                    constraint["name"].as_str().unwrap(),
+                    self.generic_args(&constraint["args"])
+                );
+                let binding = &constraint["binding"];
+                rendered.push(match binding.get("equality") {
+                    Some(term) => format!("{name} = {}", self.term(term)),
+                    None => format!("{name}: {}", self.bounds(&binding["constraint"])),
+                });
+            }
+            if rendered.is_empty() {
+                return String::new();
+            }
+            return format!("<{}>", rendered.join(", "));
+        }
+        if let Some(parenthesized) = args.get("parenthesized") {
+            let inputs: Vec<_> = parenthesized["inputs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|input| self.ty(input))
+                .collect();
+            let output = match &parenthesized["output"] {
+                Value::Null => String::new(),
+                ty => format!(" -> {}", self.ty(ty)),
+            };
+            return format!("({}){output}", inputs.join(", "));
+        }
+        String::new()
+    }
+
+    fn term(&self, term: &Value) -> String {
+        match term.get("type") {
+            Some(ty) => self.ty(ty),
+            None => term["constant"]["expr"].as_str().unwrap_or("_").to_string(),
+        }
+    }
+
+    fn ty(&self, ty: &Value) -> String {
+        let (kind, inner) = match ty {
+            Value::String(kind) if kind == "infer" => return "_".to_string(),
+            Value::Object(ty) => ty.iter().next().unwrap(),
+            _ => return "_".to_string(),
+        };
+        match kind.as_str() {
+            "resolved_path" => self.path(inner),
+            "generic" | "primitive" => inner.as_str().unwrap().to_string(),
+            "borrowed_ref" => {
+                let lifetime = inner["lifetime"]
+                    .as_str()
+                    .map(|lifetime| format!("{lifetime} "))
+                    .unwrap_or_default();
+                let mutability = if inner["is_mutable"] == true {
+                    "mut "
+                } else {
+                    ""
+                };
+                format!("&{lifetime}{mutability}{}", self.ty(&inner["type"]))
+            }
+            "raw_pointer" => {
+                let mutability = if inner["is_mutable"] == true {
+                    "mut"
+                } else {
+                    "const"
+                };
+                format!("*{mutability} {}", self.ty(&inner["type"]))
+            }
+            "slice" => format!("[{}]", self.ty(inner)),
+            "array" => format!(
+                "[{}; {}]",
+                self.ty(&inner["type"]),
+                inner["len"].as_str().unwrap()
+            ),
+            "tuple" => {
+                let types: Vec<_> = inner
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|ty| self.ty(ty))
+                    .collect();
+                format!("({})", types.join(", "))
+            }
+            "impl_trait" => format!("impl {}", self.bounds(inner)),
+            "dyn_trait" => {
+                let mut bounds: Vec<_> = inner["traits"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|bound| self.path(&bound["trait"]))
+                    .collect();
+                if let Some(lifetime) = inner["lifetime"].as_str() {
+                    bounds.push(lifetime.to_string());
+                }
+                format!("dyn {}", bounds.join(" + "))
+            }
+            "qualified_path" => {
+                let name = inner["name"].as_str().unwrap();
+                let self_type = self.ty(&inner["self_type"]);
+                match &inner["trait"] {
+                    Value::Null => format!("{self_type}::{name}"),
+                    bound => format!("<{self_type} as {}>::{name}", self.path(bound)),
+                }
+            }
+            "function_pointer" => {
+                let sig = &inner["sig"];
+                let inputs: Vec<_> = sig["inputs"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|input| self.ty(&input[1]))
+                    .collect();
+                let output = match &sig["output"] {
+                    Value::Null => String::new(),
+                    ty => format!(" -> {}", self.ty(ty)),
+                };
+                format!("fn({}){output}", inputs.join(", "))
+            }
+            "pat" => self.ty(&inner["type"]),
+            _ => "_".to_string(),
+        }
+    }
+}
+
+/// Lines only in `expected` (`-`) or only in `actual` (`+`).
+fn diff<'a>(expected: &'a str, actual: &'a str) -> impl Iterator<Item = String> + 'a {
+    let removed = expected
+        .lines()
+        .filter(|line| !actual.lines().any(|other| other == *line))
+        .map(|line| format!("- {line}"));
+    let added = actual
+        .lines()
+        .filter(|line| !expected.lines().any(|other| other == *line))
+        .map(|line| format!("+ {line}"));
+    removed.chain(added)
+}
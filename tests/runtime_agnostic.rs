@@ -0,0 +1,53 @@
+//! Runs the client under smol, with no Tokio runtime in sight. Built only
+//! with `--no-default-features --features runtime-agnostic,native-tls`; see
+//! `scripts/check-features.sh`.
+#![cfg(all(
+    feature = "http",
+    feature = "runtime-agnostic",
+    not(feature = "runtime-tokio")
+))]
+
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use capture_rust::{Capture, CaptureOptions, ErrorKind, RetryPolicy};
+
+/// A proxy that accepts connections and closes them at once, so every
+/// attempt fails with a transport error.
+fn refusing_proxy() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let counter = connections.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            counter.fetch_add(1, Ordering::SeqCst);
+            drop(stream);
+        }
+    });
+    (format!("http://{address}"), connections)
+}
+
+#[test]
+fn test_fetch_content_retries_under_smol() {
+    let (proxy, connections) = refusing_proxy();
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::all(proxy).unwrap())
+        .build()
+        .unwrap();
+    let options = CaptureOptions::new()
+        .with_client(client)
+        .with_retry_policy(RetryPolicy::new().with_backoff(Duration::from_millis(50)));
+    let capture = Capture::with_options("test_key".to_string(), "test_secret".to_string(), options);
+
+    let started = Instant::now();
+    let result = smol::block_on(capture.fetch_content("https://example.com", None));
+
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Transport);
+    assert_eq!(connections.load(Ordering::SeqCst), 3);
+    // 50ms + 100ms of backoff between the three attempts.
+    assert!(started.elapsed() >= Duration::from_millis(150));
+}
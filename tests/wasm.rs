@@ -0,0 +1,33 @@
+//! Smoke test of the wasm32 build. Run with `wasm-pack test --node`, or with
+//! `cargo test --target wasm32-unknown-unknown --test wasm` and
+//! `wasm-bindgen-test-runner` as the target's runner.
+#![cfg(target_arch = "wasm32")]
+
+use capture_rust::{Capture, CaptureOptions, ScreenshotOptions};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_signed_url_matches_native() {
+    let capture = Capture::with_options(
+        "test_key".to_string(),
+        "test_secret".to_string(),
+        CaptureOptions::new().with_edge(),
+    );
+    let url = capture
+        .build_screenshot_url(
+            "https://example.com",
+            Some(&ScreenshotOptions {
+                vw: Some(1440),
+                full: Some(true),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+    // md5("test_secret" + query), as signed by a native build.
+    assert_eq!(
+        url,
+        "https://edge.capture.page/test_key/16f24c3582018ed2d56280255f84639e/image\
+         ?full=true&url=https%3A%2F%2Fexample.com&vw=1440"
+    );
+}